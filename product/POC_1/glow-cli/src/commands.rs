@@ -1,27 +1,81 @@
 //! CLI command implementations
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use colored::Colorize;
+use glow_core::config::{schema, Preset, StepFormat};
 use glow_core::engine::operations::{ProcessEngine, StatusTree};
-use glow_core::model::{ParameterValue, StepStatus};
+use glow_core::engine::{Forecast, Overdue, ProcessEngineBuilder, StartOptions};
+use glow_core::GlowError;
+use glow_core::model::{Estimate, ParameterValue, StepStatus, Timebox};
+use glow_core::storage::{merge_step_files, StepDiffKind, MERGE_DRIVER, NOTES_FILE};
 
 use crate::output::{print_error, print_info, print_success, print_tree, print_warning};
 
+/// Project a command works on
+pub struct Project {
+    /// Project root directory
+    pub dir: PathBuf,
+    /// Config directory from `--config-dir` or `DEV_GLOW_CONFIG_DIR`
+    pub config_dir: Option<PathBuf>,
+}
+
+impl Project {
+    /// Start building the project's engine with the CLI's overrides
+    fn builder(&self) -> ProcessEngineBuilder {
+        let builder = ProcessEngine::builder(&self.dir);
+        match &self.config_dir {
+            Some(config_dir) => builder.config_dir(config_dir),
+            None => builder,
+        }
+    }
+
+    /// Load the project, reporting an interrupted operation it recovered
+    fn load(&self) -> glow_core::Result<ProcessEngine> {
+        let engine = self.builder().build()?;
+        if let Some(recovery) = engine.recovery() {
+            print_warning(&recovery.to_string());
+        }
+        Ok(engine)
+    }
+}
+
 /// Initialize a new project
-pub fn project_init(project_dir: &Path, name: Option<String>) -> Result<()> {
+pub fn project_init(project: &Project, name: Option<String>, preset: Option<&str>) -> Result<()> {
     print_info("Initializing glow project...");
 
-    match ProcessEngine::init_project(project_dir.to_path_buf(), name) {
+    // Fall back to the preset named in the user config
+    let preset = preset.map(str::to_string).or_else(|| {
+        glow_core::config::layers::user_config()
+            .ok()
+            .and_then(|layered| layered.config.preset)
+    });
+    let preset = match preset.as_deref().map(Preset::find).transpose() {
+        Ok(preset) => preset,
+        Err(e) => {
+            print_error(&format!("Failed to initialize project: {}", e));
+            return Err(e.into());
+        }
+    };
+
+    match project.builder().init(name, preset.as_ref()) {
         Ok(_engine) => {
             print_success(&format!(
                 "Project initialized in {}",
-                project_dir.display()
+                project.dir.display()
             ));
             print_info("Created:");
             println!("  {} Configuration and schemas", ".glow/".cyan());
             println!("  {} Process data directory", "glow/".cyan());
+            if let Some(preset) = &preset {
+                println!(
+                    "  {} Process from preset '{}' with {} template(s)",
+                    ".glow/process_config.yaml".cyan(),
+                    preset.name,
+                    preset.templates.len()
+                );
+            }
             print_info("\nNext steps:");
             println!("  1. Edit {} to configure your process", ".glow/process_config.yaml".cyan());
             println!("  2. Run {} to start your process", "glow init ROOT".cyan());
@@ -34,14 +88,345 @@ pub fn project_init(project_dir: &Path, name: Option<String>) -> Result<()> {
     }
 }
 
+/// List the available process presets
+pub fn project_presets() -> Result<()> {
+    let presets = Preset::available().context("Failed to read presets")?;
+
+    print_info("Available presets:");
+    for preset in &presets {
+        let origin = match &preset.source {
+            Some(dir) => format!(" ({})", dir.display()),
+            None => String::new(),
+        };
+        println!("  {:<16} {}{}", preset.name.cyan(), preset.description(), origin.dimmed());
+    }
+    Ok(())
+}
+
+/// Check the process definition
+pub fn config_check(project: &Project) -> Result<()> {
+    let check = project.builder().check()
+        .context("Failed to load project")?;
+
+    if check.diagnostics.is_empty() && check.findings.is_empty() {
+        print_success("Process definition is consistent: every step can be reached");
+        return Ok(());
+    }
+
+    for diagnostic in &check.diagnostics {
+        let file = diagnostic.file.strip_prefix(&project.dir).unwrap_or(&diagnostic.file);
+        print_error(&format!(
+            "{}:{}:{}: {}",
+            file.display(),
+            diagnostic.position.line,
+            diagnostic.position.column,
+            diagnostic.message
+        ));
+    }
+    let file = check.file.strip_prefix(&project.dir).unwrap_or(&check.file);
+    for finding in &check.findings {
+        let position = check.finding_position(finding);
+        let message = format!(
+            "{}:{}:{}: {}: {}",
+            file.display(),
+            position.line,
+            position.column,
+            finding.location,
+            finding.message
+        );
+        if finding.kind.is_error() {
+            print_error(&format!("[{}] {}", finding.kind, message));
+        } else {
+            print_warning(&format!("[{}] {}", finding.kind, message));
+        }
+    }
+
+    if check.has_errors() {
+        let errors = check.diagnostics.len()
+            + check.findings.iter().filter(|f| f.kind.is_error()).count();
+        anyhow::bail!("Process definition has {} problem(s)", errors);
+    }
+    Ok(())
+}
+
+/// Show the process definition, optionally resolved along with the project config
+pub fn config_show(project: &Project, resolved: bool, origin: bool) -> Result<()> {
+    if origin {
+        return config_show_origin(project);
+    }
+
+    let engine = project.load()
+        .context("Failed to load project")?;
+
+    if resolved {
+        let config = serde_yaml::to_string(engine.config())
+            .context("Failed to serialize project config")?;
+        let process_config = serde_yaml::to_string(engine.process_config())
+            .context("Failed to serialize process definition")?;
+        print!(
+            "# {}\n{}---\n# {}\n{}",
+            glow_core::config::CONFIG_FILE,
+            config,
+            engine.config().process_config,
+            process_config
+        );
+    } else {
+        let path = engine.process_config_path();
+        let content = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        print!("{}", content);
+    }
+    Ok(())
+}
+
+/// Show each config value with the layer it came from
+fn config_show_origin(project: &Project) -> Result<()> {
+    let layered = project
+        .builder()
+        .config_loader()
+        .context("Failed to find the project config")?
+        .load_layered_config()
+        .context("Failed to load config")?;
+
+    let lines: Vec<(String, String)> = layered
+        .values()
+        .into_iter()
+        .map(|(key, value, origin)| {
+            let value = serde_yaml::to_string(&value).unwrap_or_default();
+            let origin = match &origin.file {
+                Some(file) => format!(
+                    "{} ({})",
+                    origin.layer,
+                    file.strip_prefix(&project.dir).unwrap_or(file).display()
+                ),
+                None => origin.layer.to_string(),
+            };
+            (format!("{}: {}", key, value.trim_end()), origin)
+        })
+        .collect();
+
+    let width = lines.iter().map(|(line, _)| line.len()).max().unwrap_or(0);
+    for (line, origin) in lines {
+        println!("{:width$}  {}", line, format!("# {}", origin).dimmed(), width = width);
+    }
+    Ok(())
+}
+
+/// Reconcile in-flight iterations with the process definition
+pub fn config_apply(project: &Project, dry_run: bool) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let plan = if dry_run {
+        engine.plan_config_apply()
+    } else {
+        engine.apply_config()
+    }
+    .context("Failed to reconcile iterations")?;
+
+    for iteration in &plan.iterations {
+        println!(
+            "{} iteration {} (definition {} → {})",
+            iteration.fqid.cyan(),
+            iteration.iteration,
+            iteration.from_version.as_deref().unwrap_or("unversioned"),
+            iteration.to_version
+        );
+        for change in &iteration.changes {
+            println!("  {}", change);
+        }
+    }
+    if !plan.completed.is_empty() {
+        print_info(&format!("Completed, left untouched: {}", plan.completed.join(", ")));
+    }
+
+    if plan.is_empty() {
+        print_success("All in-flight iterations follow the process definition");
+    } else if dry_run {
+        print_info(&format!(
+            "Dry run: {} change(s) in {} iteration(s) not applied",
+            plan.change_count(),
+            plan.iterations.len()
+        ));
+    } else {
+        print_success(&format!(
+            "Applied {} change(s) to {} iteration(s)",
+            plan.change_count(),
+            plan.iterations.len()
+        ));
+    }
+    Ok(())
+}
+
+/// Export the generated JSON schemas
+///
+/// Inside a project the step data schema types each step's parameters.
+pub fn schema_export(project: &Project, name: Option<&str>, out: Option<&Path>) -> Result<()> {
+    let engine = project.load().ok();
+    let schemas = schema::schema_files(engine.as_ref().map(|e| e.process_config()));
+
+    if let Some(name) = name {
+        let file = format!("{}.schema.json", name.trim_end_matches(".schema.json"));
+        let Some((_, schema)) = schemas.iter().find(|(f, _)| *f == file) else {
+            let names: Vec<&str> = schemas
+                .iter()
+                .map(|(f, _)| f.trim_end_matches(".schema.json"))
+                .collect();
+            anyhow::bail!("Unknown schema '{}' (available: {})", name, names.join(", "));
+        };
+        println!("{}", schema::render(schema));
+        return Ok(());
+    }
+
+    let dir = match out {
+        Some(dir) => dir.to_path_buf(),
+        None => {
+            let config_loader = project
+                .builder()
+                .config_loader()
+                .context("Not in a glow project; use --out to choose a directory")?;
+            glow_core::config::schemas_dir(config_loader.config_dir())
+        }
+    };
+    std::fs::create_dir_all(&dir)
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    for (file, schema) in &schemas {
+        let path = dir.join(file);
+        std::fs::write(&path, schema::render(schema))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        println!("  {}", path.strip_prefix(&project.dir).unwrap_or(&path).display().to_string().cyan());
+    }
+    print_success(&format!("Exported {} schema(s)", schemas.len()));
+    Ok(())
+}
+
+/// Rebuild the status index from the step data files
+pub fn index_rebuild(project: &Project) -> Result<()> {
+    let engine = project.load()
+        .context("Failed to load project")?;
+
+    let indexed = engine.rebuild_index()
+        .context("Failed to rebuild the status index")?;
+    print_success(&format!("Indexed {} step(s)", indexed));
+    Ok(())
+}
+
+/// Convert the step data files to another format
+pub fn storage_convert(project: &Project, to: &str) -> Result<()> {
+    let to: StepFormat = to.parse().map_err(anyhow::Error::msg)?;
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let conversion = engine.convert_storage(to)
+        .context("Failed to convert the step data files")?;
+    print_success(&format!(
+        "Converted {} step file(s) to {}",
+        conversion.converted,
+        to.as_str()
+    ));
+    if conversion.notes > 0 {
+        print_info(&format!("Moved {} step body(ies) to or from {}", conversion.notes, NOTES_FILE));
+    }
+    Ok(())
+}
+
+/// Register the step file merge driver with git
+pub fn git_install(project: &Project) -> Result<()> {
+    let engine = project.load()
+        .context("Failed to load project")?;
+
+    let install = engine.storage().install_git()
+        .context("Failed to set up git")?;
+    if install.added.is_empty() {
+        print_info(&format!("{} already set up", install.attributes_path.display()));
+    } else {
+        for line in &install.added {
+            println!("  {}", line);
+        }
+        print_success(&format!("Added {} line(s) to {}", install.added.len(), install.attributes_path.display()));
+    }
+    print_success(&format!("Registered the '{}' merge driver in the git config", MERGE_DRIVER));
+    Ok(())
+}
+
+/// Merge a step file three ways, writing the result over our version
+pub fn merge_driver(base: &Path, ours: &Path, theirs: &Path, path: Option<&Path>) -> Result<()> {
+    let read = |file: &Path| {
+        std::fs::read_to_string(file).with_context(|| format!("Failed to read {}", file.display()))
+    };
+    let format = StepFormat::of_path(path.unwrap_or(ours)).unwrap_or_default();
+    let merge = merge_step_files(format, &read(base)?, &read(ours)?, &read(theirs)?)
+        .context("Failed to merge step file")?;
+    std::fs::write(ours, &merge.content)
+        .with_context(|| format!("Failed to write {}", ours.display()))?;
+
+    if !merge.is_clean() {
+        let name = path.unwrap_or(ours).display();
+        for conflict in &merge.conflicts {
+            print_warning(&format!("{}: {} changed on both sides", name, conflict));
+        }
+        anyhow::bail!("{} conflict(s) left in {}", merge.conflicts.len(), name);
+    }
+    Ok(())
+}
+
+/// Migrate config and step data files to the current schema version
+pub fn migrate(project: &Project, dry_run: bool) -> Result<()> {
+    let (plan, backup) = if dry_run {
+        let plan = project.builder().plan_migration();
+        plan.map(|plan| (plan, None))
+    } else {
+        project.builder().migrate()
+    }
+    .context("Failed to migrate project")?;
+
+    for (path, reason) in &plan.skipped {
+        let path = path.strip_prefix(&project.dir).unwrap_or(path);
+        print_warning(&format!("Skipped {}: {}", path.display(), reason));
+    }
+
+    if plan.is_empty() {
+        print_success(&format!("Project is up to date (schema version {})", plan.to));
+        return Ok(());
+    }
+
+    for file in &plan.files {
+        let path = file.path.strip_prefix(&project.dir).unwrap_or(&file.path);
+        println!("{}", path.display().to_string().cyan());
+        for change in &file.changes {
+            println!("  {}", change);
+        }
+    }
+
+    if dry_run {
+        print_info(&format!(
+            "Dry run: {} file(s) would be migrated to schema version {}",
+            plan.files.len(),
+            plan.to
+        ));
+    } else {
+        print_success(&format!(
+            "Migrated {} file(s) to schema version {}",
+            plan.files.len(),
+            plan.to
+        ));
+        if let Some(backup) = backup {
+            let backup = backup.strip_prefix(&project.dir).unwrap_or(&backup);
+            print_info(&format!("Backup of the original files: {}", backup.display()));
+        }
+    }
+    Ok(())
+}
+
 /// Initialize a step
 pub fn init_step(
-    project_dir: &Path,
+    project: &Project,
     fqid: &str,
     force_new: bool,
     params: Vec<String>,
 ) -> Result<()> {
-    let mut engine = ProcessEngine::new(project_dir.to_path_buf())
+    let mut engine = project.load()
         .context("Failed to load project")?;
 
     let scope_params = parse_params(&params)?;
@@ -70,13 +455,17 @@ pub fn init_step(
 }
 
 /// Start a step
-pub fn start_step(project_dir: &Path, fqid: &str) -> Result<()> {
-    let mut engine = ProcessEngine::new(project_dir.to_path_buf())
+pub fn start_step(project: &Project, fqid: &str, force: bool) -> Result<()> {
+    let mut engine = project.load()
         .context("Failed to load project")?;
 
     print_info(&format!("Starting step {}...", fqid.cyan()));
 
-    match engine.start_step(fqid) {
+    let options = StartOptions {
+        override_wip_limits: force,
+    };
+
+    match engine.start_step_with(fqid, options) {
         Ok(step) => {
             print_success(&format!("Step {} started", fqid.cyan()));
 
@@ -98,6 +487,12 @@ pub fn start_step(project_dir: &Path, fqid: &str) -> Result<()> {
         }
         Err(e) => {
             print_error(&format!("Failed to start step: {}", e));
+            if matches!(e, GlowError::WipLimitExceeded { .. }) {
+                print_info(&format!(
+                    "Finish work in progress first, or use {}",
+                    format!("glow start {} --force", fqid).cyan()
+                ));
+            }
             Err(e.into())
         }
     }
@@ -105,12 +500,12 @@ pub fn start_step(project_dir: &Path, fqid: &str) -> Result<()> {
 
 /// Finish a step
 pub fn finish_step(
-    project_dir: &Path,
+    project: &Project,
     fqid: &str,
     summary: Option<String>,
     params: Vec<String>,
 ) -> Result<()> {
-    let mut engine = ProcessEngine::new(project_dir.to_path_buf())
+    let mut engine = project.load()
         .context("Failed to load project")?;
 
     let output_params = parse_params(&params)?;
@@ -142,12 +537,12 @@ pub fn finish_step(
 
 /// Show project status
 pub fn show_status(
-    project_dir: &Path,
+    project: &Project,
     as_list: bool,
     _attrs: &str,
     _filter: Option<&str>,
 ) -> Result<()> {
-    let engine = ProcessEngine::new(project_dir.to_path_buf())
+    let engine = project.load()
         .context("Failed to load project")?;
 
     let status_tree = engine.get_status_tree()
@@ -159,9 +554,24 @@ pub fn show_status(
         print_tree(&status_tree);
     }
 
+    let overdue = engine.get_overdue().context("Failed to check due dates")?;
+    print_overdue(&overdue);
+
     Ok(())
 }
 
+/// Print overdue steps and iterations as warnings
+fn print_overdue(overdue: &[Overdue]) {
+    if overdue.is_empty() {
+        return;
+    }
+
+    println!();
+    for item in overdue {
+        print_warning(&item.to_string());
+    }
+}
+
 /// Print status as flat list
 fn print_status_list(tree: &StatusTree, prefix: &str) {
     let fqid = if prefix.is_empty() {
@@ -183,8 +593,8 @@ fn print_status_list(tree: &StatusTree, prefix: &str) {
 }
 
 /// Show step details
-pub fn show_step(project_dir: &Path, fqid: &str, _include_context: bool) -> Result<()> {
-    let engine = ProcessEngine::new(project_dir.to_path_buf())
+pub fn show_step(project: &Project, fqid: &str, _include_context: bool) -> Result<()> {
+    let engine = project.load()
         .context("Failed to load project")?;
 
     let step = engine.show_step(fqid)
@@ -199,6 +609,26 @@ pub fn show_step(project_dir: &Path, fqid: &str, _include_context: bool) -> Resu
         println!("{} {}", "Classification:".bold(), classification);
     }
 
+    if let Some(estimate) = &step.attr.estimate {
+        println!("{} {}", "Estimate:".bold(), estimate);
+    }
+
+    if let Some(due) = &step.attr.due {
+        println!("{} {}", "Due:".bold(), due);
+    }
+
+    if let Some(iteration) = step.attr.iteration {
+        println!("{} {}", "Iteration:".bold(), iteration);
+    }
+
+    if let Some(timebox) = &step.attr.timebox {
+        println!("{} {} .. {}", "Time box:".bold(), timebox.min, timebox.max);
+    }
+
+    if step.attr.carried_over > 0 {
+        println!("{} {} time(s)", "Carried over:".bold(), step.attr.carried_over);
+    }
+
     if let Some(purpose) = &step.attr.purpose {
         println!("\n{}", "Purpose:".bold());
         println!("  {}", purpose);
@@ -252,20 +682,32 @@ pub fn show_step(project_dir: &Path, fqid: &str, _include_context: bool) -> Resu
 }
 
 /// Show next actions
-pub fn show_next(project_dir: &Path) -> Result<()> {
-    let engine = ProcessEngine::new(project_dir.to_path_buf())
+pub fn show_next(project: &Project, limit: Option<usize>) -> Result<()> {
+    let engine = project.load()
         .context("Failed to load project")?;
 
     let next_actions = engine.get_next_actions()
         .context("Failed to get next actions")?;
 
+    let overdue = engine.get_overdue().context("Failed to check due dates")?;
+    for item in &overdue {
+        print_warning(&item.to_string());
+    }
+    let stale = engine.get_stale_work().context("Failed to check for stale work")?;
+    for item in &stale {
+        print_warning(&format!("Stale: {}", item));
+    }
+    if !overdue.is_empty() || !stale.is_empty() {
+        println!();
+    }
+
     if next_actions.is_empty() {
         print_info("No pending actions. All steps are complete or waiting.");
     } else {
         println!("{}", "Recommended Actions:".bold());
         println!();
 
-        for action in &next_actions {
+        for action in next_actions.iter().take(limit.unwrap_or(usize::MAX)) {
             let cmd = match action.action_type {
                 glow_core::engine::operations::ActionType::Start => {
                     format!("glow start {}", action.fqid)
@@ -273,14 +715,19 @@ pub fn show_next(project_dir: &Path) -> Result<()> {
                 glow_core::engine::operations::ActionType::Init => {
                     format!("glow init {}", action.fqid)
                 }
+                glow_core::engine::operations::ActionType::Finish => {
+                    format!("glow finish {}", action.fqid)
+                }
                 _ => action.fqid.clone(),
             };
 
             println!(
-                "  {} {}",
+                "  {} {} {}",
                 "→".green().bold(),
-                action.description
+                action.description,
+                format!("(score {:.1})", action.score).dimmed()
             );
+            println!("    {} {}", "why:".dimmed(), action.why);
             println!("    {}", cmd.cyan());
             println!();
         }
@@ -289,23 +736,413 @@ pub fn show_next(project_dir: &Path) -> Result<()> {
     Ok(())
 }
 
+/// Set or clear a step estimate
+pub fn set_estimate(project: &Project, fqid: &str, value: Option<&str>, clear: bool) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let estimate = match (value, clear) {
+        (Some(v), _) => Some(v.parse::<Estimate>().map_err(anyhow::Error::msg)?),
+        (None, true) => None,
+        (None, false) => {
+            let step = engine.show_step(fqid).context("Failed to get step")?;
+            match &step.attr.estimate {
+                Some(e) => println!("{} {}", fqid.cyan(), e),
+                None => println!("{} {}", fqid.cyan(), "(not estimated)".dimmed()),
+            }
+            return Ok(());
+        }
+    };
+
+    match engine.set_estimate(fqid, estimate) {
+        Ok(step) => {
+            match &step.attr.estimate {
+                Some(e) => print_success(&format!("Estimate of {} set to {}", fqid.cyan(), e)),
+                None => print_success(&format!("Estimate of {} cleared", fqid.cyan())),
+            }
+            Ok(())
+        }
+        Err(e) => {
+            print_error(&format!("Failed to set estimate: {}", e));
+            Err(e.into())
+        }
+    }
+}
+
+/// Set or clear a step due date
+pub fn set_due(project: &Project, fqid: &str, value: Option<&str>, clear: bool) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let due = match (value, clear) {
+        (Some(v), _) => Some(parse_date(v)?),
+        (None, true) => None,
+        (None, false) => {
+            let step = engine.show_step(fqid).context("Failed to get step")?;
+            match &step.attr.due {
+                Some(d) => println!("{} {}", fqid.cyan(), d),
+                None => println!("{} {}", fqid.cyan(), "(no due date)".dimmed()),
+            }
+            return Ok(());
+        }
+    };
+
+    match engine.set_due(fqid, due) {
+        Ok(step) => {
+            match &step.attr.due {
+                Some(d) => print_success(&format!("Due date of {} set to {}", fqid.cyan(), d)),
+                None => print_success(&format!("Due date of {} cleared", fqid.cyan())),
+            }
+            Ok(())
+        }
+        Err(e) => {
+            print_error(&format!("Failed to set due date: {}", e));
+            Err(e.into())
+        }
+    }
+}
+
+/// Move or rename a step
+pub fn move_step(project: &Project, from: &str, to: &str) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let moved = engine.move_step(from, to)
+        .context("Failed to move step")?;
+    for link in &moved.dropped_links {
+        print_warning(&format!("Dropped link {}", link));
+    }
+    print_success(&format!(
+        "Moved {} to {} ({} step(s))",
+        moved.from.cyan(),
+        moved.to.cyan(),
+        moved.steps
+    ));
+    if !moved.definition_moved {
+        print_warning(&format!(
+            "The definition of {} isn't written in the process config file; update it by hand",
+            moved.from
+        ));
+    }
+    Ok(())
+}
+
+/// Remove a step, and with cascade the steps linked to it
+pub fn remove_step(project: &Project, fqid: &str, cascade: bool) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let removal = engine.remove_step(fqid, cascade)
+        .context("Failed to remove step")?;
+    for removed in &removal.removed {
+        print_success(&format!("Removed {}", removed.cyan()));
+    }
+    if !removal.definitions_removed {
+        print_warning("Some definitions aren't written in the process config file; update it by hand");
+    }
+    Ok(())
+}
+
+/// Show the current iteration of a process
+pub fn iteration_show(project: &Project, fqid: &str) -> Result<()> {
+    let engine = project.load()
+        .context("Failed to load project")?;
+
+    let step = engine.show_step(fqid).context("Failed to get step")?;
+
+    let number = step.attr.iteration
+        .or_else(|| engine.storage().current_iteration(fqid));
+    match number {
+        Some(n) => println!("{} {}", "Iteration:".bold(), n),
+        None => println!("{} {}", "Iteration:".bold(), "(not started)".dimmed()),
+    }
+
+    match &step.attr.timebox {
+        Some(t) => println!("{} {} .. {} ({}d)", "Time box:".bold(), t.min, t.max, t.days()),
+        None => println!("{} {}", "Time box:".bold(), "(none)".dimmed()),
+    }
+
+    println!("{}", "Steps:".bold());
+    for own_step in &step.own_steps {
+        println!("  {} {}", format_status(own_step.status), own_step.id.cyan());
+    }
+
+    let overdue = engine.get_overdue().context("Failed to check due dates")?;
+    print_overdue(&overdue.into_iter().filter(|o| o.fqid == fqid).collect::<Vec<_>>());
+
+    Ok(())
+}
+
+/// Set or clear the time box of a process's current iteration
+pub fn iteration_timebox(
+    project: &Project,
+    fqid: &str,
+    range: Option<(&str, &str)>,
+    clear: bool,
+) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let timebox = match (range, clear) {
+        (Some((start, end)), _) => Some(Timebox::new(parse_date(start)?, parse_date(end)?)),
+        (None, true) => None,
+        (None, false) => return iteration_show(project, fqid),
+    };
+
+    match engine.set_timebox(fqid, timebox) {
+        Ok(step) => {
+            match &step.attr.timebox {
+                Some(t) => print_success(&format!(
+                    "Time box of {} set to {} .. {}",
+                    fqid.cyan(),
+                    t.min,
+                    t.max
+                )),
+                None => print_success(&format!("Time box of {} cleared", fqid.cyan())),
+            }
+            Ok(())
+        }
+        Err(e) => {
+            print_error(&format!("Failed to set time box: {}", e));
+            Err(e.into())
+        }
+    }
+}
+
+/// Close the current iteration of a process
+pub fn iteration_close(project: &Project, fqid: &str, next: Option<(&str, &str)>) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let next = match next {
+        Some((start, end)) => Some(Timebox::new(parse_date(start)?, parse_date(end)?)),
+        None => None,
+    };
+
+    match engine.close_iteration(fqid, next) {
+        Ok(close) => {
+            print_success(&format!(
+                "Closed iteration {} of {}, started iteration {}",
+                close.closed,
+                fqid.cyan(),
+                close.opened
+            ));
+            println!("  {} {}", "Done:".bold(), close.done.len());
+            println!("  {} {}", "Carried over:".bold(), close.carried_over.len());
+            for carried in &close.carried_over {
+                println!("    {} {}", "→".yellow(), carried.cyan());
+            }
+            if let Some(t) = &close.timebox {
+                println!("  {} {} .. {}", "Time box:".bold(), t.min, t.max);
+            }
+            Ok(())
+        }
+        Err(e) => {
+            print_error(&format!("Failed to close iteration: {}", e));
+            Err(e.into())
+        }
+    }
+}
+
+/// Archive a completed process iteration
+pub fn archive_iteration(project: &Project, fqid: &str, iteration: u32) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let stub = engine.archive_iteration(fqid, iteration)
+        .context("Failed to archive iteration")?;
+    print_success(&format!(
+        "Archived iteration {} of {}: {} step(s), {} file(s)",
+        iteration,
+        fqid.cyan(),
+        stub.steps.len(),
+        stub.files
+    ));
+    println!("  {} {}", "SHA-256:".bold(), stub.sha256);
+    Ok(())
+}
+
+/// Check an archived process iteration against its checksums
+pub fn archive_verify(project: &Project, fqid: &str, iteration: u32) -> Result<()> {
+    let engine = project.load()
+        .context("Failed to load project")?;
+
+    let problems = engine.verify_archive(fqid, iteration)
+        .context("Failed to verify archive")?;
+    if problems.is_empty() {
+        print_success(&format!("Archive of iteration {} of {} is intact", iteration, fqid.cyan()));
+        return Ok(());
+    }
+    for problem in &problems {
+        print_error(problem);
+    }
+    anyhow::bail!("Archive of iteration {} of {} failed verification", iteration, fqid)
+}
+
+/// Restore an archived process iteration
+pub fn restore_iteration(project: &Project, fqid: &str, iteration: u32) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let stub = engine.restore_iteration(fqid, iteration)
+        .context("Failed to restore iteration")?;
+    print_success(&format!(
+        "Restored iteration {} of {}: {} step(s), {} file(s)",
+        iteration,
+        fqid.cyan(),
+        stub.steps.len(),
+        stub.files
+    ));
+    Ok(())
+}
+
+/// Capture the data of every step in a labelled snapshot
+pub fn snapshot_create(project: &Project, label: &str) -> Result<()> {
+    let engine = project.load()
+        .context("Failed to load project")?;
+
+    let snapshot = engine.create_snapshot(label)
+        .context("Failed to create snapshot")?;
+    print_success(&format!("Created snapshot {} with {} step(s)", snapshot.id.cyan(), snapshot.steps));
+    Ok(())
+}
+
+/// List the saved snapshots
+pub fn snapshot_list(project: &Project, format: Option<&str>) -> Result<()> {
+    let engine = project.load()
+        .context("Failed to load project")?;
+    let format = format.unwrap_or(engine.config().output_format.as_str());
+
+    let snapshots = engine.list_snapshots()
+        .context("Failed to list snapshots")?;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&snapshots)?);
+        return Ok(());
+    }
+
+    if snapshots.is_empty() {
+        print_info("No snapshots yet; create one with 'glow snapshot create <LABEL>'");
+        return Ok(());
+    }
+    for snapshot in &snapshots {
+        println!(
+            "{}  {}  {} ({} step(s))",
+            snapshot.id.cyan(),
+            snapshot.created_at.format("%Y-%m-%d %H:%M"),
+            snapshot.label.bold(),
+            snapshot.steps
+        );
+    }
+    Ok(())
+}
+
+/// Put the step data back as captured in a snapshot
+pub fn snapshot_restore(project: &Project, reference: &str, force: bool) -> Result<()> {
+    let mut engine = project.load()
+        .context("Failed to load project")?;
+
+    let restore = engine.restore_snapshot(reference, force)
+        .context("Failed to restore snapshot")?;
+    print_success(&format!(
+        "Restored snapshot {}: {} step(s) changed",
+        restore.snapshot.id.cyan(),
+        restore.changes.steps.len()
+    ));
+    print_info(&format!("The data before the restore is kept as snapshot {}", restore.backup.id));
+    Ok(())
+}
+
+/// Show what changed between a snapshot and another snapshot or the current data
+pub fn diff_snapshots(project: &Project, from: &str, to: &str, format: Option<&str>) -> Result<()> {
+    let engine = project.load()
+        .context("Failed to load project")?;
+    let format = format.unwrap_or(engine.config().output_format.as_str());
+
+    let diff = engine.diff_snapshots(from, to)
+        .context("Failed to compare snapshots")?;
+    if format == "json" {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+        return Ok(());
+    }
+
+    if diff.is_empty() {
+        print_info(&format!("No changes from {} to {}", diff.from, diff.to));
+        return Ok(());
+    }
+    println!("{}", format!("Changes from {} to {}", diff.from, diff.to).bold());
+    for step in &diff.steps {
+        let status = |status: Option<StepStatus>| status.map_or_else(String::new, |s| s.to_string());
+        match step.kind {
+            StepDiffKind::Added => {
+                println!("  {} {} ({})", "+".green(), step.fqid.cyan(), status(step.status_to))
+            }
+            StepDiffKind::Removed => {
+                println!("  {} {} ({})", "-".red(), step.fqid.cyan(), status(step.status_from))
+            }
+            StepDiffKind::Changed if step.status_changed() => println!(
+                "  {} {}: {} → {}",
+                "~".yellow(),
+                step.fqid.cyan(),
+                status(step.status_from),
+                status(step.status_to)
+            ),
+            StepDiffKind::Changed => println!("  {} {}", "~".yellow(), step.fqid.cyan()),
+        }
+        for param in &step.parameters {
+            let value = |value: &Option<serde_json::Value>| {
+                value.as_ref().map_or_else(|| "(not set)".to_string(), |v| v.to_string())
+            };
+            println!(
+                "      {}.{}: {} → {}",
+                param.kind,
+                param.id,
+                value(&param.from).dimmed(),
+                value(&param.to)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Parse a YYYY-MM-DD date argument
+fn parse_date(value: &str) -> Result<chrono::NaiveDate> {
+    value
+        .parse()
+        .with_context(|| format!("Invalid date '{}' (expected YYYY-MM-DD)", value))
+}
+
 /// Show progress metrics
-pub fn show_progress(project_dir: &Path, format: &str) -> Result<()> {
-    let engine = ProcessEngine::new(project_dir.to_path_buf())
+pub fn show_progress(project: &Project, format: Option<&str>, with_forecast: bool) -> Result<()> {
+    let engine = project.load()
         .context("Failed to load project")?;
+    let format = format.unwrap_or(engine.config().output_format.as_str());
 
     let progress = engine.get_progress()
         .context("Failed to get progress")?;
 
+    let forecasts = if with_forecast {
+        engine.get_forecast().context("Failed to get forecast")?
+    } else {
+        Vec::new()
+    };
+
     if format == "json" {
-        let json = serde_json::json!({
+        let mut json = serde_json::json!({
             "total": progress.total,
             "wait": progress.wait,
             "todo": progress.todo,
             "in_progress": progress.in_progress,
             "done": progress.done,
-            "completion_percentage": progress.completion_percentage()
+            "leaves": progress.leaves,
+            "estimated": progress.estimated,
+            "total_weight": progress.total_weight,
+            "remaining_weight": progress.remaining_weight(),
+            "completion_percentage": progress.completion_percentage(),
+            "warning": progress.warning
         });
+        if with_forecast {
+            json["forecast"] = forecasts.iter().map(forecast_to_json).collect();
+        }
         println!("{}", serde_json::to_string_pretty(&json)?);
     } else {
         println!("{}", "Progress Metrics".bold());
@@ -323,6 +1160,18 @@ pub fn show_progress(project_dir: &Path, format: &str) -> Result<()> {
             pct
         );
         println!("  {}", bar);
+        if progress.estimated > 0 {
+            println!(
+                "  {}",
+                format!(
+                    "Weighted by estimate ({} of {} leaf steps estimated), {:.1} remaining",
+                    progress.estimated,
+                    progress.leaves,
+                    progress.remaining_weight()
+                )
+                .dimmed()
+            );
+        }
         println!();
 
         // Counts
@@ -331,34 +1180,95 @@ pub fn show_progress(project_dir: &Path, format: &str) -> Result<()> {
             "    {} {} ({}%)",
             format_status(StepStatus::Done),
             progress.done,
-            if progress.total > 0 { progress.done * 100 / progress.total } else { 0 }
+            progress.share(progress.done)
         );
         println!(
             "    {} {} ({}%)",
             format_status(StepStatus::InProgress),
             progress.in_progress,
-            if progress.total > 0 { progress.in_progress * 100 / progress.total } else { 0 }
+            progress.share(progress.in_progress)
         );
         println!(
             "    {} {} ({}%)",
             format_status(StepStatus::Todo),
             progress.todo,
-            if progress.total > 0 { progress.todo * 100 / progress.total } else { 0 }
+            progress.share(progress.todo)
         );
         println!(
             "    {} {} ({}%)",
             format_status(StepStatus::Wait),
             progress.wait,
-            if progress.total > 0 { progress.wait * 100 / progress.total } else { 0 }
+            progress.share(progress.wait)
         );
+
+        if let Some(warning) = &progress.warning {
+            println!();
+            print_warning(warning);
+        }
+
+        if with_forecast {
+            println!();
+            println!("{}", "Forecast".bold());
+            println!("{}", "─".repeat(40).dimmed());
+            if forecasts.is_empty() {
+                println!("  {}", "No initialized processes to forecast".dimmed());
+            }
+            for forecast in &forecasts {
+                print_forecast(forecast);
+            }
+        }
     }
 
     Ok(())
 }
 
+/// Print a single process forecast line
+fn print_forecast(forecast: &Forecast) {
+    let iteration = forecast
+        .iteration
+        .map(|i| format!(" #{}", i))
+        .unwrap_or_default();
+
+    let projection = if forecast.is_complete() {
+        "complete".green().to_string()
+    } else {
+        match (forecast.expected, forecast.earliest, forecast.latest) {
+            (Some(expected), Some(earliest), Some(latest)) => format!(
+                "{} (range {} – {})",
+                expected.format("%Y-%m-%d"),
+                earliest.format("%Y-%m-%d"),
+                latest.format("%Y-%m-%d")
+            ),
+            _ => "no throughput history yet".dimmed().to_string(),
+        }
+    };
+
+    println!(
+        "  {}{} {:.1}/{:.1} remaining → {}",
+        forecast.fqid.cyan(),
+        iteration.dimmed(),
+        forecast.remaining_weight,
+        forecast.total_weight,
+        projection
+    );
+}
+
+/// Convert a forecast to JSON
+fn forecast_to_json(forecast: &Forecast) -> serde_json::Value {
+    serde_json::json!({
+        "fqid": forecast.fqid,
+        "iteration": forecast.iteration,
+        "total_weight": forecast.total_weight,
+        "remaining_weight": forecast.remaining_weight,
+        "expected": forecast.expected.map(|d| d.to_rfc3339()),
+        "earliest": forecast.earliest.map(|d| d.to_rfc3339()),
+        "latest": forecast.latest.map(|d| d.to_rfc3339())
+    })
+}
+
 /// Validate context quality
-pub fn validate(project_dir: &Path, fqid: Option<&str>, _fix: bool) -> Result<()> {
-    let engine = ProcessEngine::new(project_dir.to_path_buf())
+pub fn validate(project: &Project, fqid: Option<&str>, _fix: bool) -> Result<()> {
+    let engine = project.load()
         .context("Failed to load project")?;
 
     let report = engine.validate(fqid)
@@ -38,6 +38,58 @@ enum Commands {
         action: ProjectAction,
     },
 
+    /// Process configuration commands
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// JSON schema commands
+    Schema {
+        #[command(subcommand)]
+        action: SchemaAction,
+    },
+
+    /// Status index commands
+    Index {
+        #[command(subcommand)]
+        action: IndexAction,
+    },
+
+    /// Step data storage commands
+    Storage {
+        #[command(subcommand)]
+        action: StorageAction,
+    },
+
+    /// Git integration commands
+    Git {
+        #[command(subcommand)]
+        action: GitAction,
+    },
+
+    /// Merge a step file three ways (run by git, see `glow git install`)
+    MergeDriver {
+        /// Common base version (%O)
+        base: PathBuf,
+
+        /// Our version, replaced with the result (%A)
+        ours: PathBuf,
+
+        /// Their version (%B)
+        theirs: PathBuf,
+
+        /// Path of the merged file in the repository, for its format (%P)
+        path: Option<PathBuf>,
+    },
+
+    /// Migrate config and step data files to the current schema version
+    Migrate {
+        /// Show what would change without writing
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Initialize a step or process iteration
     Init {
         /// Step FQID (e.g., ROOT, FEAT-001, FEAT-001.REQ-001)
@@ -56,6 +108,10 @@ enum Commands {
     Start {
         /// Step FQID
         fqid: String,
+
+        /// Start even if a work-in-progress limit would be exceeded
+        #[arg(long)]
+        force: bool,
     },
 
     /// Finish a task step
@@ -97,14 +153,117 @@ enum Commands {
         context: bool,
     },
 
-    /// Get recommended next actions
-    Next,
+    /// Get recommended next actions, best ranked first
+    Next {
+        /// Show only the top N actions
+        #[arg(short, long)]
+        limit: Option<usize>,
+    },
+
+    /// Set or clear the effort estimate of a step
+    Estimate {
+        /// Step FQID
+        fqid: String,
+
+        /// Estimate as points (e.g., 3) or duration (e.g., 4h, 2d, 1w)
+        value: Option<String>,
+
+        /// Remove the estimate
+        #[arg(long, conflicts_with = "value")]
+        clear: bool,
+    },
+
+    /// Set or clear the due date of a step
+    Due {
+        /// Step FQID
+        fqid: String,
+
+        /// Due date (YYYY-MM-DD)
+        date: Option<String>,
+
+        /// Remove the due date
+        #[arg(long, conflicts_with = "date")]
+        clear: bool,
+    },
+
+    /// Move or rename a step with its sub-steps, documents and artifacts
+    Mv {
+        /// Step FQID
+        from: String,
+
+        /// New step FQID (e.g., FEAT-001.DESIGN or FEAT-002.REQ-001)
+        to: String,
+    },
+
+    /// Remove a step with its sub-steps, documents and artifacts
+    Rm {
+        /// Step FQID
+        fqid: String,
+
+        /// Also remove the steps linked to it
+        #[arg(long)]
+        cascade: bool,
+    },
+
+    /// Process iteration commands
+    Iteration {
+        #[command(subcommand)]
+        action: IterationAction,
+    },
+
+    /// Archive a completed process iteration with the steps finished in it
+    Archive {
+        /// Process FQID
+        fqid: String,
+
+        /// Iteration number
+        #[arg(long)]
+        iteration: u32,
+
+        /// Check the archive against its checksums instead
+        #[arg(long)]
+        verify: bool,
+    },
+
+    /// Restore an archived process iteration
+    Restore {
+        /// Process FQID
+        fqid: String,
+
+        /// Iteration number
+        #[arg(long)]
+        iteration: u32,
+    },
+
+    /// Snapshots of the step data
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+
+    /// Show what changed between a snapshot and another snapshot or now
+    Diff {
+        /// Snapshot ID or label
+        from: String,
+
+        /// Snapshot ID or label, or "now" for the current data
+        #[arg(default_value = "now")]
+        to: String,
+
+        /// Output format (text or json; defaults to output_format in the config)
+        #[arg(long)]
+        format: Option<String>,
+    },
 
     /// Show progress metrics
     Progress {
-        /// Output format (text or json)
-        #[arg(long, default_value = "text")]
-        format: String,
+        /// Output format (text or json; defaults to output_format in the config)
+        #[arg(long)]
+        format: Option<String>,
+
+        /// Include completion forecast per process
+        #[arg(long)]
+        forecast: bool,
     },
 
     /// Validate context quality
@@ -125,6 +284,134 @@ enum ProjectAction {
         /// Project name
         #[arg(long)]
         name: Option<String>,
+
+        /// Start from a process preset (name or preset directory)
+        #[arg(long)]
+        preset: Option<String>,
+    },
+
+    /// List the available process presets
+    Presets,
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Check the process definition for deadlocks, unreachable steps and unsatisfiable inputs
+    Check,
+
+    /// Show the process definition
+    Show {
+        /// Show the final values of config.yaml and the definition, with imports, fragments and placeholders resolved
+        #[arg(long)]
+        resolved: bool,
+
+        /// Show each config value with the layer it came from (user, project, local)
+        #[arg(long, conflicts_with = "resolved")]
+        origin: bool,
+    },
+
+    /// Bring in-flight iterations in line with the changed process definition
+    Apply {
+        /// Show what would change without writing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SchemaAction {
+    /// Export the JSON schemas generated from this version of glow
+    Export {
+        /// Print one schema (config, process_config, step_data or mcp_config)
+        name: Option<String>,
+
+        /// Directory to write all schemas to (defaults to .glow/schemas)
+        #[arg(long, conflicts_with = "name")]
+        out: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum IndexAction {
+    /// Rebuild the status index from the step data files
+    Rebuild,
+}
+
+#[derive(Subcommand)]
+enum StorageAction {
+    /// Convert the step data files to another format (md, yaml, json or toml)
+    Convert {
+        /// Target format
+        #[arg(long)]
+        to: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum SnapshotAction {
+    /// Capture the data of every step (not artifacts) under a label
+    Create {
+        /// Label, e.g. "monday"
+        label: String,
+    },
+
+    /// List the saved snapshots
+    List {
+        /// Output format (text or json; defaults to output_format in the config)
+        #[arg(long)]
+        format: Option<String>,
+    },
+
+    /// Put the step data back as captured in a snapshot
+    Restore {
+        /// Snapshot ID or label
+        snapshot: String,
+
+        /// Remove steps created since the snapshot even if they have documents or artifacts
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum GitAction {
+    /// Register the step file merge driver in .gitattributes and the git config
+    Install,
+}
+
+#[derive(Subcommand)]
+enum IterationAction {
+    /// Show the current iteration of a process
+    Show {
+        /// Process FQID
+        fqid: String,
+    },
+
+    /// Set or clear the time box of the current iteration
+    Timebox {
+        /// Process FQID
+        fqid: String,
+
+        /// First day of the time box (YYYY-MM-DD)
+        #[arg(requires = "end")]
+        start: Option<String>,
+
+        /// Last day of the time box (YYYY-MM-DD)
+        end: Option<String>,
+
+        /// Remove the time box
+        #[arg(long, conflicts_with = "start")]
+        clear: bool,
+    },
+
+    /// Close the current iteration, carrying unfinished steps into the next one
+    Close {
+        /// Process FQID
+        fqid: String,
+
+        /// Time box of the next iteration (defaults to following the current one)
+        #[arg(long, num_args = 2, value_names = ["START", "END"])]
+        next: Option<Vec<String>>,
     },
 }
 
@@ -141,46 +428,158 @@ fn main() -> Result<()> {
         .init();
 
     // Resolve project directory
-    let project_dir = std::fs::canonicalize(&cli.project_dir)?;
+    let project = commands::Project {
+        dir: std::fs::canonicalize(&cli.project_dir)?,
+        config_dir: cli.config_dir,
+    };
 
     // Execute command
     match cli.command {
         Commands::Project { action } => match action {
-            ProjectAction::Init { name } => {
-                commands::project_init(&project_dir, name)?;
+            ProjectAction::Init { name, preset } => {
+                commands::project_init(&project, name, preset.as_deref())?;
+            }
+            ProjectAction::Presets => {
+                commands::project_presets()?;
             }
         },
 
+        Commands::Config { action } => match action {
+            ConfigAction::Check => {
+                commands::config_check(&project)?;
+            }
+            ConfigAction::Show { resolved, origin } => {
+                commands::config_show(&project, resolved, origin)?;
+            }
+            ConfigAction::Apply { dry_run } => {
+                commands::config_apply(&project, dry_run)?;
+            }
+        },
+
+        Commands::Schema { action } => match action {
+            SchemaAction::Export { name, out } => {
+                commands::schema_export(&project, name.as_deref(), out.as_deref())?;
+            }
+        },
+
+        Commands::Index { action } => match action {
+            IndexAction::Rebuild => {
+                commands::index_rebuild(&project)?;
+            }
+        },
+
+        Commands::Storage { action } => match action {
+            StorageAction::Convert { to } => {
+                commands::storage_convert(&project, &to)?;
+            }
+        },
+
+        Commands::Git { action } => match action {
+            GitAction::Install => {
+                commands::git_install(&project)?;
+            }
+        },
+
+        Commands::MergeDriver { base, ours, theirs, path } => {
+            commands::merge_driver(&base, &ours, &theirs, path.as_deref())?;
+        }
+
+        Commands::Migrate { dry_run } => {
+            commands::migrate(&project, dry_run)?;
+        }
+
         Commands::Init { fqid, new, params } => {
-            commands::init_step(&project_dir, &fqid, new, params)?;
+            commands::init_step(&project, &fqid, new, params)?;
         }
 
-        Commands::Start { fqid } => {
-            commands::start_step(&project_dir, &fqid)?;
+        Commands::Start { fqid, force } => {
+            commands::start_step(&project, &fqid, force)?;
         }
 
         Commands::Finish { fqid, summary, params } => {
-            commands::finish_step(&project_dir, &fqid, summary, params)?;
+            commands::finish_step(&project, &fqid, summary, params)?;
         }
 
         Commands::Status { list, attrs, filter } => {
-            commands::show_status(&project_dir, list, &attrs, filter.as_deref())?;
+            commands::show_status(&project, list, &attrs, filter.as_deref())?;
         }
 
         Commands::Show { fqid, context } => {
-            commands::show_step(&project_dir, &fqid, context)?;
+            commands::show_step(&project, &fqid, context)?;
+        }
+
+        Commands::Next { limit } => {
+            commands::show_next(&project, limit)?;
+        }
+
+        Commands::Estimate { fqid, value, clear } => {
+            commands::set_estimate(&project, &fqid, value.as_deref(), clear)?;
+        }
+
+        Commands::Due { fqid, date, clear } => {
+            commands::set_due(&project, &fqid, date.as_deref(), clear)?;
+        }
+
+        Commands::Mv { from, to } => {
+            commands::move_step(&project, &from, &to)?;
+        }
+
+        Commands::Rm { fqid, cascade } => {
+            commands::remove_step(&project, &fqid, cascade)?;
+        }
+
+        Commands::Iteration { action } => match action {
+            IterationAction::Show { fqid } => {
+                commands::iteration_show(&project, &fqid)?;
+            }
+            IterationAction::Timebox { fqid, start, end, clear } => {
+                commands::iteration_timebox(
+                    &project,
+                    &fqid,
+                    start.as_deref().zip(end.as_deref()),
+                    clear,
+                )?;
+            }
+            IterationAction::Close { fqid, next } => {
+                let next = next.as_deref().map(|n| (n[0].as_str(), n[1].as_str()));
+                commands::iteration_close(&project, &fqid, next)?;
+            }
+        },
+
+        Commands::Archive { fqid, iteration, verify } => {
+            if verify {
+                commands::archive_verify(&project, &fqid, iteration)?;
+            } else {
+                commands::archive_iteration(&project, &fqid, iteration)?;
+            }
+        }
+
+        Commands::Restore { fqid, iteration } => {
+            commands::restore_iteration(&project, &fqid, iteration)?;
         }
 
-        Commands::Next => {
-            commands::show_next(&project_dir)?;
+        Commands::Snapshot { action } => match action {
+            SnapshotAction::Create { label } => {
+                commands::snapshot_create(&project, &label)?;
+            }
+            SnapshotAction::List { format } => {
+                commands::snapshot_list(&project, format.as_deref())?;
+            }
+            SnapshotAction::Restore { snapshot, force } => {
+                commands::snapshot_restore(&project, &snapshot, force)?;
+            }
+        },
+
+        Commands::Diff { from, to, format } => {
+            commands::diff_snapshots(&project, &from, &to, format.as_deref())?;
         }
 
-        Commands::Progress { format } => {
-            commands::show_progress(&project_dir, &format)?;
+        Commands::Progress { format, forecast } => {
+            commands::show_progress(&project, format.as_deref(), forecast)?;
         }
 
         Commands::Validate { fqid, fix } => {
-            commands::validate(&project_dir, fqid.as_deref(), fix)?;
+            commands::validate(&project, fqid.as_deref(), fix)?;
         }
     }
 
@@ -1,6 +1,6 @@
 //! CLI Integration tests
 
-use assert_cmd::Command;
+use assert_cmd::cargo::cargo_bin_cmd;
 use predicates::prelude::*;
 use std::fs;
 use tempfile::TempDir;
@@ -15,7 +15,7 @@ fn setup_test_project() -> TempDir {
 
     // Write config
     let config = r#"
-version: "1.0"
+version: "0.1.0"
 project_id: "TEST"
 project_name: "Test Project"
 process: "simple"
@@ -25,7 +25,7 @@ glow_dir: "glow"
 
     // Write process config
     let process_config = r#"
-version: "1.0"
+version: "0.1.0"
 
 root_process:
   id: ROOT
@@ -44,7 +44,7 @@ root_process:
 
 #[test]
 fn test_cli_help() {
-    let mut cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut cmd = cargo_bin_cmd!("glow");
     cmd.arg("--help")
         .assert()
         .success()
@@ -53,7 +53,7 @@ fn test_cli_help() {
 
 #[test]
 fn test_cli_version() {
-    let mut cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut cmd = cargo_bin_cmd!("glow");
     cmd.arg("--version")
         .assert()
         .success()
@@ -64,7 +64,7 @@ fn test_cli_version() {
 fn test_cli_init_step() {
     let project = setup_test_project();
 
-    let mut cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut cmd = cargo_bin_cmd!("glow");
     cmd.current_dir(project.path())
         .args(["init", "ROOT"])
         .assert()
@@ -80,7 +80,7 @@ fn test_cli_status() {
     let project = setup_test_project();
 
     // First init ROOT
-    let mut init_cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut init_cmd = cargo_bin_cmd!("glow");
     init_cmd
         .current_dir(project.path())
         .args(["init", "ROOT"])
@@ -88,7 +88,7 @@ fn test_cli_status() {
         .success();
 
     // Then check status
-    let mut status_cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut status_cmd = cargo_bin_cmd!("glow");
     status_cmd
         .current_dir(project.path())
         .arg("status")
@@ -102,7 +102,7 @@ fn test_cli_next() {
     let project = setup_test_project();
 
     // Init ROOT
-    let mut init_cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut init_cmd = cargo_bin_cmd!("glow");
     init_cmd
         .current_dir(project.path())
         .args(["init", "ROOT"])
@@ -110,13 +110,39 @@ fn test_cli_next() {
         .success();
 
     // Check next actions
-    let mut next_cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut next_cmd = cargo_bin_cmd!("glow");
     next_cmd
         .current_dir(project.path())
         .arg("next")
         .assert()
         .success()
-        .stdout(predicate::str::contains("Recommended"));
+        .stdout(predicate::str::contains("Recommended"))
+        .stdout(predicate::str::contains("why:"));
+}
+
+#[test]
+fn test_cli_next_limit() {
+    let project = setup_test_project();
+
+    let mut init_cmd = cargo_bin_cmd!("glow");
+    init_cmd
+        .current_dir(project.path())
+        .args(["init", "ROOT"])
+        .assert()
+        .success();
+
+    let mut next_cmd = cargo_bin_cmd!("glow");
+    let output = next_cmd
+        .current_dir(project.path())
+        .args(["next", "--limit", "1"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let stdout = String::from_utf8_lossy(&output);
+    assert_eq!(stdout.matches("why:").count(), 1);
 }
 
 #[test]
@@ -124,7 +150,7 @@ fn test_cli_progress() {
     let project = setup_test_project();
 
     // Init ROOT
-    let mut init_cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut init_cmd = cargo_bin_cmd!("glow");
     init_cmd
         .current_dir(project.path())
         .args(["init", "ROOT"])
@@ -132,7 +158,7 @@ fn test_cli_progress() {
         .success();
 
     // Check progress
-    let mut progress_cmd = Command::cargo_bin("glow").expect("Failed to find binary");
+    let mut progress_cmd = cargo_bin_cmd!("glow");
     progress_cmd
         .current_dir(project.path())
         .arg("progress")
@@ -147,16 +173,14 @@ fn test_cli_workflow() {
     let project_path = project.path();
 
     // Init ROOT
-    Command::cargo_bin("glow")
-        .unwrap()
+    cargo_bin_cmd!("glow")
         .current_dir(project_path)
         .args(["init", "ROOT"])
         .assert()
         .success();
 
     // Start ROOT
-    Command::cargo_bin("glow")
-        .unwrap()
+    cargo_bin_cmd!("glow")
         .current_dir(project_path)
         .args(["start", "ROOT"])
         .assert()
@@ -164,8 +188,7 @@ fn test_cli_workflow() {
         .stdout(predicate::str::contains("started"));
 
     // Show ROOT
-    Command::cargo_bin("glow")
-        .unwrap()
+    cargo_bin_cmd!("glow")
         .current_dir(project_path)
         .args(["show", "ROOT"])
         .assert()
@@ -173,10 +196,890 @@ fn test_cli_workflow() {
         .stdout(predicate::str::contains("Step"));
 
     // Validate
-    Command::cargo_bin("glow")
-        .unwrap()
+    cargo_bin_cmd!("glow")
         .current_dir(project_path)
         .arg("validate")
         .assert()
         .success();
 }
+
+#[test]
+fn test_cli_iteration_close_and_overdue() {
+    let project = setup_test_project();
+
+    for args in [
+        vec!["init", "ROOT"],
+        vec!["start", "ROOT"],
+        vec!["iteration", "timebox", "ROOT", "2020-01-01", "2020-01-14"],
+        vec!["due", "TASK", "2020-01-10"],
+    ] {
+        let mut cmd = cargo_bin_cmd!("glow");
+        cmd.current_dir(project.path()).args(&args).assert().success();
+    }
+
+    let mut status_cmd = cargo_bin_cmd!("glow");
+    status_cmd
+        .current_dir(project.path())
+        .arg("status")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("TASK: overdue by"))
+        .stdout(predicate::str::contains("iteration 1 time box ended 2020-01-14"));
+
+    let mut close_cmd = cargo_bin_cmd!("glow");
+    close_cmd
+        .current_dir(project.path())
+        .args(["iteration", "close", "ROOT"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("started iteration 2"))
+        .stdout(predicate::str::contains("2020-01-15 .. 2020-01-28"));
+
+    let mut show_cmd = cargo_bin_cmd!("glow");
+    show_cmd
+        .current_dir(project.path())
+        .args(["show", "TASK"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Carried over:"));
+}
+
+#[test]
+fn test_cli_start_respects_wip_limit() {
+    let project = setup_test_project();
+    fs::write(
+        project.path().join(".glow/process_config.yaml"),
+        r#"
+version: "0.1.0"
+wip_limits:
+  - max: 1
+root_process:
+  id: ROOT
+  steps:
+    - id: ONE
+    - id: TWO
+"#,
+    )
+    .expect("Failed to write process config");
+
+    for args in [vec!["init", "ROOT"], vec!["start", "ROOT"], vec!["start", "ONE"]] {
+        let mut cmd = cargo_bin_cmd!("glow");
+        cmd.current_dir(project.path()).args(&args).assert().success();
+    }
+
+    let mut blocked_cmd = cargo_bin_cmd!("glow");
+    blocked_cmd
+        .current_dir(project.path())
+        .args(["start", "TWO"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("WIP limit"));
+
+    let mut next_cmd = cargo_bin_cmd!("glow");
+    next_cmd
+        .current_dir(project.path())
+        .arg("next")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("glow finish ONE"));
+
+    let mut force_cmd = cargo_bin_cmd!("glow");
+    force_cmd
+        .current_dir(project.path())
+        .args(["start", "TWO", "--force"])
+        .assert()
+        .success();
+}
+
+#[test]
+fn test_cli_reports_stale_work() {
+    let project = setup_test_project();
+    fs::write(
+        project.path().join(".glow/process_config.yaml"),
+        r#"
+version: "0.1.0"
+staleness:
+  todo_days: 0
+root_process:
+  id: ROOT
+  steps:
+    - id: ONE
+"#,
+    )
+    .expect("Failed to write process config");
+
+    let mut init_cmd = cargo_bin_cmd!("glow");
+    init_cmd.current_dir(project.path()).args(["init", "ROOT"]).assert().success();
+
+    let mut next_cmd = cargo_bin_cmd!("glow");
+    next_cmd
+        .current_dir(project.path())
+        .arg("next")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Stale: ONE: in todo for 0d"))
+        .stdout(predicate::str::contains("run 'glow start ONE'"));
+
+    let mut validate_cmd = cargo_bin_cmd!("glow");
+    validate_cmd
+        .current_dir(project.path())
+        .arg("validate")
+        .assert()
+        .stdout(predicate::str::contains("Stale: ONE"));
+}
+
+#[test]
+fn test_cli_config_check() {
+    let project = setup_test_project();
+
+    let mut ok_cmd = cargo_bin_cmd!("glow");
+    ok_cmd
+        .current_dir(project.path())
+        .args(["config", "check"])
+        .assert()
+        .success();
+
+    fs::write(
+        project.path().join(".glow/process_config.yaml"),
+        r#"
+version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: ONE
+    - id: TWO
+  links:
+    - from: TWO
+      to: MISSING
+"#,
+    )
+    .expect("Failed to write process config");
+
+    let mut check_cmd = cargo_bin_cmd!("glow");
+    check_cmd
+        .current_dir(project.path())
+        .args(["config", "check"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "process_config.yaml:9:5: root_process.links[0]: TWO depends on unknown step 'MISSING'",
+        ));
+}
+
+#[test]
+fn test_cli_reports_invalid_process_config_positions() {
+    let project = setup_test_project();
+
+    fs::write(
+        project.path().join(".glow/process_config.yaml"),
+        r#"version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: ONE
+    - id: ONE
+  links:
+    - type: blocks
+      from: ONE
+      to: ONE
+"#,
+    )
+    .expect("Failed to write process config");
+
+    let mut check_cmd = cargo_bin_cmd!("glow");
+    check_cmd
+        .current_dir(project.path())
+        .args(["config", "check"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "process_config.yaml:6:7: duplicate step ID 'ONE'",
+        ))
+        .stderr(predicate::str::contains(
+            "process_config.yaml:8:7: unknown link type 'blocks'",
+        ));
+
+    // Other commands refuse to load the invalid definition
+    let mut status_cmd = cargo_bin_cmd!("glow");
+    status_cmd
+        .current_dir(project.path())
+        .args(["status"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("duplicate step ID 'ONE'"));
+}
+
+#[test]
+fn test_cli_config_apply() {
+    let project = setup_test_project();
+
+    let mut init_cmd = cargo_bin_cmd!("glow");
+    init_cmd
+        .current_dir(project.path())
+        .args(["init", "ROOT"])
+        .assert()
+        .success();
+
+    fs::write(
+        project.path().join(".glow/process_config.yaml"),
+        r#"
+version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: TASK
+    - id: REVIEW
+  links:
+    - from: REVIEW
+      to: TASK
+"#,
+    )
+    .expect("Failed to write process config");
+
+    let mut dry_run_cmd = cargo_bin_cmd!("glow");
+    dry_run_cmd
+        .current_dir(project.path())
+        .args(["config", "apply", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("+ REVIEW: added"))
+        .stdout(predicate::str::contains("Dry run: 1 change(s) in 1 iteration(s) not applied"));
+    assert!(!project.path().join("glow/REVIEW.md").exists());
+
+    let mut apply_cmd = cargo_bin_cmd!("glow");
+    apply_cmd
+        .current_dir(project.path())
+        .args(["config", "apply"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Applied 1 change(s) to 1 iteration(s)"));
+    assert!(project.path().join("glow/REVIEW.md").exists());
+
+    let mut again_cmd = cargo_bin_cmd!("glow");
+    again_cmd
+        .current_dir(project.path())
+        .args(["config", "apply"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("All in-flight iterations follow the process definition"));
+}
+
+#[test]
+fn test_cli_config_imports_and_fragments() {
+    let project = setup_test_project();
+    let library = TempDir::new().expect("Failed to create library dir");
+
+    fs::write(
+        library.path().join("standard-feature.yaml"),
+        r#"
+fragments:
+  - id: standard-feature
+    scope:
+      - id: PRIORITY
+        default_value: low
+    steps:
+      - id: DESIGN
+      - id: BUILD
+    links:
+      - from: BUILD
+        to: DESIGN
+"#,
+    )
+    .expect("Failed to write library file");
+    fs::write(
+        project.path().join(".glow/process_config.yaml"),
+        r#"
+version: "0.1.0"
+imports:
+  - standard-feature
+root_process:
+  id: ROOT
+  steps:
+    - id: FEAT
+      uses: standard-feature
+      with:
+        PRIORITY: high
+"#,
+    )
+    .expect("Failed to write process config");
+
+    let mut show_cmd = cargo_bin_cmd!("glow");
+    show_cmd
+        .current_dir(project.path())
+        .env("DEV_GLOW_LIBRARY_DIR", library.path())
+        .args(["config", "show", "--resolved"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("id: BUILD"))
+        .stdout(predicate::str::contains("default_value: high"))
+        .stdout(predicate::str::contains("uses:").not());
+
+    let mut raw_cmd = cargo_bin_cmd!("glow");
+    raw_cmd
+        .current_dir(project.path())
+        .env("DEV_GLOW_LIBRARY_DIR", library.path())
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("uses: standard-feature"));
+
+    let mut init_cmd = cargo_bin_cmd!("glow");
+    init_cmd
+        .current_dir(project.path())
+        .env("DEV_GLOW_LIBRARY_DIR", library.path())
+        .args(["init", "FEAT"])
+        .assert()
+        .success();
+    assert!(project.path().join("glow/FEAT/DESIGN.md").exists());
+
+    // Without the library the import cannot be found
+    let empty_library = TempDir::new().expect("Failed to create library dir");
+    let mut check_cmd = cargo_bin_cmd!("glow");
+    check_cmd
+        .current_dir(project.path())
+        .env("DEV_GLOW_LIBRARY_DIR", empty_library.path())
+        .args(["config", "check"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "process_config.yaml:4:3: cannot find import 'standard-feature'",
+        ))
+        .stderr(predicate::str::contains(
+            "step 'FEAT' uses unknown fragment 'standard-feature'",
+        ));
+}
+
+#[test]
+fn test_cli_project_init_with_preset() {
+    let presets = TempDir::new().expect("Failed to create preset dir");
+    let ops = presets.path().join("ops");
+    fs::create_dir_all(ops.join("templates")).expect("Failed to create preset");
+    fs::write(
+        ops.join("process_config.yaml"),
+        r#"# Operations: runbook-driven changes
+version: "0.1.0"
+root_process:
+  id: ROOT
+  purpose: "Operations"
+  steps:
+    - id: CHANGE
+      purpose: "Change"
+      template: "runbook.md"
+"#,
+    )
+    .expect("Failed to write preset");
+    fs::write(ops.join("templates/runbook.md"), "# Runbook for {{attr.id}}\n")
+        .expect("Failed to write template");
+
+    let mut list_cmd = cargo_bin_cmd!("glow");
+    list_cmd
+        .env("DEV_GLOW_PRESET_DIR", presets.path())
+        .args(["project", "presets"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("release-train"))
+        .stdout(predicate::str::contains("Operations: runbook-driven changes"));
+
+    let project = TempDir::new().expect("Failed to create temp dir");
+    let mut unknown_cmd = cargo_bin_cmd!("glow");
+    unknown_cmd
+        .current_dir(project.path())
+        .env("DEV_GLOW_PRESET_DIR", presets.path())
+        .args(["project", "init", "--preset", "kanban"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown preset 'kanban'"));
+    assert!(!project.path().join(".glow").exists());
+
+    let mut init_cmd = cargo_bin_cmd!("glow");
+    init_cmd
+        .current_dir(project.path())
+        .env("DEV_GLOW_PRESET_DIR", presets.path())
+        .args(["project", "init", "--preset", "bugfix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("preset 'bugfix' with 2 template(s)"));
+    let process_config = fs::read_to_string(project.path().join(".glow/process_config.yaml"))
+        .expect("Failed to read process config");
+    assert!(process_config.contains("id: SEVERITY"));
+    assert!(project.path().join(".glow/templates/bug.md").exists());
+
+    let user_project = TempDir::new().expect("Failed to create temp dir");
+    let mut user_cmd = cargo_bin_cmd!("glow");
+    user_cmd
+        .current_dir(user_project.path())
+        .env("DEV_GLOW_PRESET_DIR", presets.path())
+        .args(["project", "init", "--preset", "ops"])
+        .assert()
+        .success();
+
+    let mut root_cmd = cargo_bin_cmd!("glow");
+    root_cmd
+        .current_dir(user_project.path())
+        .args(["init", "ROOT"])
+        .assert()
+        .success();
+    let mut start_cmd = cargo_bin_cmd!("glow");
+    start_cmd
+        .current_dir(user_project.path())
+        .args(["start", "CHANGE"])
+        .assert()
+        .success();
+    let description = fs::read_to_string(user_project.path().join("glow/CHANGE/description.md"))
+        .expect("Failed to read description");
+    assert_eq!(description.trim(), "# Runbook for CHANGE");
+}
+
+#[test]
+fn test_cli_migrate() {
+    let project = setup_test_project();
+    let legacy_step = "---\nattr:\n  id: TASK\n  fqid: TASK\n  status: in-progress\n  started_at: 2026-01-05T10:00:00Z\n---\n\n# TASK\n";
+    fs::write(project.path().join("glow/TASK.md"), legacy_step).expect("Failed to write step");
+    // Documents and artifacts are not step data; a broken step file is skipped
+    fs::create_dir_all(project.path().join("glow/TASK/artifacts")).expect("Failed to create artifacts");
+    fs::write(project.path().join("glow/TASK/artifacts/notes.md"), "---\nunclosed\n").expect("Failed to write artifact");
+    fs::write(project.path().join("glow/TASK/description.md"), "---\n# TASK\n").expect("Failed to write description");
+    fs::write(project.path().join("glow/BROKEN.md"), "---\nattr:\n  id: BROKEN\n").expect("Failed to write step");
+
+    let mut dry_run_cmd = cargo_bin_cmd!("glow");
+    dry_run_cmd
+        .current_dir(project.path())
+        .args(["migrate", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("renamed glow_dir to data_folder"))
+        .stdout(predicate::str::contains("set attr.status_since from started_at"))
+        .stdout(predicate::str::contains("Skipped glow/BROKEN.md"))
+        .stdout(predicate::str::contains("artifacts").not())
+        .stdout(predicate::str::contains("Dry run: 3 file(s) would be migrated to schema version 0.2.0"));
+    assert_eq!(
+        fs::read_to_string(project.path().join("glow/TASK.md")).expect("Failed to read step"),
+        legacy_step
+    );
+
+    let mut migrate_cmd = cargo_bin_cmd!("glow");
+    migrate_cmd
+        .current_dir(project.path())
+        .arg("migrate")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Migrated 3 file(s) to schema version 0.2.0"))
+        .stdout(predicate::str::contains("Backup of the original files: .glow/backups/"));
+
+    let config = fs::read_to_string(project.path().join(".glow/config.yaml")).expect("Failed to read config");
+    assert!(config.contains("data_folder: glow"));
+    assert!(!config.contains("glow_dir"));
+    let step = fs::read_to_string(project.path().join("glow/TASK.md")).expect("Failed to read step");
+    assert!(step.contains("status_since: 2026-01-05T10:00:00Z"));
+
+    let backups: Vec<_> = fs::read_dir(project.path().join(".glow/backups"))
+        .expect("Failed to read backups")
+        .collect();
+    assert_eq!(backups.len(), 1);
+    let backup = backups[0].as_ref().expect("Failed to read backup").path();
+    assert_eq!(
+        fs::read_to_string(backup.join("glow/TASK.md")).expect("Failed to read backup"),
+        legacy_step
+    );
+
+    let mut again_cmd = cargo_bin_cmd!("glow");
+    again_cmd
+        .current_dir(project.path())
+        .arg("migrate")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Project is up to date (schema version 0.2.0)"));
+
+    // A project written by a newer glow is refused
+    fs::write(project.path().join(".glow/config.yaml"), "version: \"9.0.0\"\n").expect("Failed to write config");
+    let mut status_cmd = cargo_bin_cmd!("glow");
+    status_cmd
+        .current_dir(project.path())
+        .arg("status")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("has schema version 9.0.0, but this glow supports up to 0.2.0"));
+}
+
+#[test]
+fn test_cli_schema_export() {
+    let project = setup_test_project();
+
+    let mut print_cmd = cargo_bin_cmd!("glow");
+    print_cmd
+        .current_dir(project.path())
+        .args(["schema", "export", "step_data"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"$id\": \"https://dev-glow.io/schemas/step_data.schema.json\""))
+        .stdout(predicate::str::contains("\"title\": \"Glow Step Data File\""));
+
+    let mut unknown_cmd = cargo_bin_cmd!("glow");
+    unknown_cmd
+        .current_dir(project.path())
+        .args(["schema", "export", "steps"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("available: config, process_config, step_data, mcp_config"));
+
+    let mut export_cmd = cargo_bin_cmd!("glow");
+    export_cmd
+        .current_dir(project.path())
+        .args(["schema", "export", "--out", "schemas"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Exported 4 schema(s)"));
+    for file in ["config", "process_config", "step_data", "mcp_config"] {
+        let path = project.path().join(format!("schemas/{}.schema.json", file));
+        let content = fs::read_to_string(&path).expect("Failed to read schema");
+        serde_json::from_str::<serde_json::Value>(&content).expect("Schema is not JSON");
+    }
+}
+
+#[test]
+fn test_cli_index_rebuild() {
+    let project = setup_test_project();
+
+    let mut init_cmd = cargo_bin_cmd!("glow");
+    init_cmd
+        .current_dir(project.path())
+        .args(["init", "ROOT"])
+        .assert()
+        .success();
+
+    let mut rebuild_cmd = cargo_bin_cmd!("glow");
+    rebuild_cmd
+        .current_dir(project.path())
+        .args(["index", "rebuild"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Indexed 2 step(s)"));
+    assert!(project.path().join("glow/.index.json").exists());
+}
+
+#[test]
+fn test_cli_reports_invalid_step_file() {
+    let project = setup_test_project();
+    let step = "---\nattr:\n  id: TASK\n  fqid: TASK\n  status: complete\n  owner: ana\n---\n\n# TASK\n";
+    fs::write(project.path().join("glow/TASK.md"), step).expect("Failed to write step");
+    let config_path = project.path().join(".glow/config.yaml");
+    let config = fs::read_to_string(&config_path).expect("Failed to read config");
+    fs::write(&config_path, format!("{}step_validation: strict\n", config)).expect("Failed to write config");
+
+    let mut strict_cmd = cargo_bin_cmd!("glow");
+    strict_cmd
+        .current_dir(project.path())
+        .args(["show", "TASK"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("glow/TASK.md:5:3: attr.status: \"complete\" is not one of"))
+        .stderr(predicate::str::contains("glow/TASK.md:6:3: attr.owner: unknown key"));
+
+    fs::write(&config_path, format!("{}step_validation: lenient\n", config)).expect("Failed to write config");
+    fs::write(project.path().join("glow/TASK.md"), step.replace("complete", "todo")).expect("Failed to write step");
+
+    let mut start_cmd = cargo_bin_cmd!("glow");
+    start_cmd
+        .current_dir(project.path())
+        .args(["start", "TASK"])
+        .assert()
+        .success();
+    let step = fs::read_to_string(project.path().join("glow/TASK.md")).expect("Failed to read step");
+    assert!(step.contains("status: in-progress"));
+    assert!(step.contains("owner: ana"));
+}
+
+#[test]
+fn test_cli_config_interpolation() {
+    let project = setup_test_project();
+    fs::write(
+        project.path().join(".glow/config.yaml"),
+        "version: \"0.2.0\"\nproject_name: \"${env:GLOW_CLI_TEST_PROJECT|default:Unnamed}\"\ndata_folder: glow\n",
+    )
+    .expect("Failed to write config");
+    fs::write(
+        project.path().join(".glow/process_config.yaml"),
+        r#"version: "0.2.0"
+root_process:
+  id: ROOT
+  purpose: "Deliver ${config:project_name}"
+  steps:
+    - id: TASK
+      purpose: "Publish to ${env:GLOW_CLI_TEST_ROOT}"
+"#,
+    )
+    .expect("Failed to write process config");
+
+    let mut show_cmd = cargo_bin_cmd!("glow");
+    show_cmd
+        .current_dir(project.path())
+        .env("GLOW_CLI_TEST_PROJECT", "Atlas")
+        .env("GLOW_CLI_TEST_ROOT", "/srv/artifacts")
+        .args(["config", "show", "--resolved"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("project_name: Atlas"))
+        .stdout(predicate::str::contains("purpose: Deliver Atlas"))
+        .stdout(predicate::str::contains("purpose: Publish to /srv/artifacts"));
+
+    let mut missing_cmd = cargo_bin_cmd!("glow");
+    missing_cmd
+        .current_dir(project.path())
+        .env_remove("GLOW_CLI_TEST_ROOT")
+        .args(["config", "show", "--resolved"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "process_config.yaml:7:7: environment variable 'GLOW_CLI_TEST_ROOT' is not set",
+        ));
+}
+
+#[test]
+fn test_cli_config_layers() {
+    let project = setup_test_project();
+    let user_dir = TempDir::new().expect("Failed to create user config dir");
+    fs::write(
+        user_dir.path().join("config.yaml"),
+        "actor: ana\noutput_format: json\ndefault_template: story.md\n",
+    )
+    .expect("Failed to write user config");
+    fs::write(project.path().join(".glow/local.yaml"), "actor: ana-laptop\n")
+        .expect("Failed to write local config");
+
+    let mut show_cmd = cargo_bin_cmd!("glow");
+    show_cmd
+        .current_dir(project.path())
+        .env("DEV_GLOW_USER_CONFIG_DIR", user_dir.path())
+        .args(["config", "show", "--origin"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"actor: ana-laptop\s+# local \(.glow/local.yaml\)").unwrap())
+        .stdout(predicate::str::is_match(r"output_format: json\s+# user \(").unwrap())
+        .stdout(predicate::str::is_match(r"project_name: .*\s+# project \(.glow/config.yaml\)").unwrap())
+        .stdout(predicate::str::is_match(r"step_validation: lenient\s+# default").unwrap());
+
+    let mut progress_cmd = cargo_bin_cmd!("glow");
+    progress_cmd
+        .current_dir(project.path())
+        .env("DEV_GLOW_USER_CONFIG_DIR", user_dir.path())
+        .args(["progress"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("{"));
+}
+
+#[test]
+fn test_cli_storage_convert() {
+    let project = setup_test_project();
+
+    let mut init_cmd = cargo_bin_cmd!("glow");
+    init_cmd
+        .current_dir(project.path())
+        .args(["init", "ROOT"])
+        .assert()
+        .success();
+    let root_md = project.path().join("glow/ROOT.md");
+    let root = fs::read_to_string(&root_md).expect("Failed to read step");
+    fs::write(&root_md, format!("{}\nNotes by hand.\n", root)).expect("Failed to write step");
+
+    let mut convert_cmd = cargo_bin_cmd!("glow");
+    convert_cmd
+        .current_dir(project.path())
+        .args(["storage", "convert", "--to", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Converted 2 step file(s) to json"));
+    assert!(!root_md.exists());
+    let root_json = fs::read_to_string(project.path().join("glow/ROOT.json")).expect("Failed to read step");
+    serde_json::from_str::<serde_json::Value>(&root_json).expect("Step file is not JSON");
+    let notes = fs::read_to_string(project.path().join("glow/ROOT/notes.md")).expect("Failed to read notes");
+    assert!(notes.contains("Notes by hand."));
+    let config = fs::read_to_string(project.path().join(".glow/config.yaml")).expect("Failed to read config");
+    assert!(config.contains("step_format: json"));
+
+    // JSON step files are read and written like Markdown ones
+    let mut start_cmd = cargo_bin_cmd!("glow");
+    start_cmd
+        .current_dir(project.path())
+        .args(["start", "ROOT"])
+        .assert()
+        .success();
+    let root_json = fs::read_to_string(project.path().join("glow/ROOT.json")).expect("Failed to read step");
+    assert!(root_json.contains("\"status\": \"in-progress\""));
+
+    let mut back_cmd = cargo_bin_cmd!("glow");
+    back_cmd
+        .current_dir(project.path())
+        .args(["storage", "convert", "--to", "md"])
+        .assert()
+        .success();
+    let root = fs::read_to_string(&root_md).expect("Failed to read step");
+    assert!(root.contains("Notes by hand."));
+    assert!(!project.path().join("glow/ROOT/notes.md").exists());
+    assert!(!project.path().join("glow/ROOT.json").exists());
+}
+
+#[test]
+fn test_cli_move_and_remove_step() {
+    let project = setup_test_project();
+
+    let mut init_cmd = cargo_bin_cmd!("glow");
+    init_cmd
+        .current_dir(project.path())
+        .args(["init", "ROOT"])
+        .assert()
+        .success();
+
+    let mut mv_cmd = cargo_bin_cmd!("glow");
+    mv_cmd
+        .current_dir(project.path())
+        .args(["mv", "TASK", "WORK"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved TASK to WORK (1 step(s))"));
+    assert!(project.path().join("glow/WORK.md").exists());
+    let process_config = fs::read_to_string(project.path().join(".glow/process_config.yaml"))
+        .expect("Failed to read process config");
+    assert!(process_config.contains("id: WORK"));
+
+    let mut rm_cmd = cargo_bin_cmd!("glow");
+    rm_cmd
+        .current_dir(project.path())
+        .args(["rm", "WORK"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Removed WORK"));
+    assert!(!project.path().join("glow/WORK.md").exists());
+    let root = fs::read_to_string(project.path().join("glow/ROOT.md")).expect("Failed to read step");
+    assert!(!root.contains("WORK"));
+
+    let mut missing_cmd = cargo_bin_cmd!("glow");
+    missing_cmd
+        .current_dir(project.path())
+        .args(["rm", "WORK"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_cli_archive_and_restore() {
+    let project = setup_test_project();
+    let glow = |args: &[&str]| {
+        let mut cmd = cargo_bin_cmd!("glow");
+        cmd.current_dir(project.path()).args(args).assert()
+    };
+
+    glow(&["init", "ROOT"]).success();
+    glow(&["start", "TASK"]).success();
+    glow(&["finish", "TASK"]).success();
+
+    glow(&["archive", "ROOT", "--iteration", "1"])
+        .success()
+        .stdout(predicate::str::contains("Archived iteration 1 of ROOT: 1 step(s)"));
+    assert!(!project.path().join("glow/TASK.md").exists());
+    glow(&["progress", "--format", "json"]).success().stdout(predicate::str::contains("\"done\": 2"));
+    glow(&["archive", "ROOT", "--iteration", "1", "--verify"])
+        .success()
+        .stdout(predicate::str::contains("is intact"));
+
+    glow(&["restore", "ROOT", "--iteration", "1"])
+        .success()
+        .stdout(predicate::str::contains("Restored iteration 1 of ROOT"));
+    assert!(project.path().join("glow/TASK.md").exists());
+    glow(&["restore", "ROOT", "--iteration", "1"]).failure();
+}
+
+#[test]
+fn test_cli_snapshot_and_diff() {
+    let project = setup_test_project();
+    let glow = |args: &[&str]| {
+        let mut cmd = cargo_bin_cmd!("glow");
+        cmd.current_dir(project.path()).args(args).assert()
+    };
+
+    glow(&["init", "ROOT"]).success();
+    glow(&["snapshot", "create", "monday"])
+        .success()
+        .stdout(predicate::str::contains("Created snapshot").and(predicate::str::contains("-monday")));
+    glow(&["start", "TASK"]).success();
+
+    glow(&["diff", "monday"])
+        .success()
+        .stdout(predicate::str::contains("TASK: todo → in-progress"));
+    glow(&["diff", "monday", "now", "--format", "json"])
+        .success()
+        .stdout(predicate::str::contains("\"status_to\": \"in-progress\""));
+    glow(&["diff", "tuesday"]).failure();
+
+    glow(&["snapshot", "restore", "monday"])
+        .success()
+        .stdout(predicate::str::contains("1 step(s) changed"));
+    glow(&["diff", "monday"]).success().stdout(predicate::str::contains("No changes"));
+    glow(&["snapshot", "list"])
+        .success()
+        .stdout(predicate::str::contains("monday").and(predicate::str::contains("before restore")));
+}
+
+#[test]
+fn test_cli_git_merge_driver() {
+    let project = setup_test_project();
+    let git = |args: &[&str]| {
+        let glow_dir = std::path::Path::new(env!("CARGO_BIN_EXE_glow")).parent().unwrap();
+        let path = format!("{}:{}", glow_dir.display(), std::env::var("PATH").unwrap_or_default());
+        let output = std::process::Command::new("git")
+            .current_dir(project.path())
+            .env("PATH", path)
+            .args(args)
+            .output()
+            .expect("Failed to run git");
+        assert!(output.status.success(), "git {:?}: {}", args, String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).to_string()
+    };
+    git(&["init", "-q"]);
+    git(&["config", "user.email", "test@example.com"]);
+    git(&["config", "user.name", "Test"]);
+
+    let mut install_cmd = cargo_bin_cmd!("glow");
+    install_cmd
+        .current_dir(project.path())
+        .args(["git", "install"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("glow/**/*.md merge=glow"));
+    let attributes = fs::read_to_string(project.path().join(".gitattributes")).expect("Failed to read .gitattributes");
+    assert!(attributes.contains("glow/journal.jsonl merge=union"));
+    assert!(git(&["check-attr", "merge", "--", "glow/TASK.md"]).contains("merge: glow"));
+    assert!(git(&["check-attr", "merge", "--", "glow/TASK/summary.md"]).contains("merge: unspecified"));
+    assert!(git(&["check-attr", "merge", "--", "glow/TASK/artifacts/plan.md"]).contains("merge: unspecified"));
+    assert!(git(&["config", "merge.glow.driver"]).contains("glow merge-driver %O %A %B %P"));
+
+    // Installing again adds nothing
+    let mut again_cmd = cargo_bin_cmd!("glow");
+    again_cmd
+        .current_dir(project.path())
+        .args(["git", "install"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already set up"));
+
+    // Two branches each finish a sibling of the same process, and move on C
+    let base = "---\nattr:\n  id: ROOT\n  status: in-progress\nown_steps:\n- id: A\n  status: todo\n- id: B\n  status: todo\n- id: C\n  status: wait\n---\n# ROOT\n";
+    let step_path = project.path().join("glow/ROOT.md");
+    fs::write(&step_path, base).expect("Failed to write step");
+    git(&["add", "-A"]);
+    git(&["commit", "-qm", "base"]);
+    git(&["checkout", "-qb", "other"]);
+    let ours = base.replacen("status: todo", "status: done", 1).replace("wait", "todo");
+    fs::write(&step_path, ours).expect("Failed to write step");
+    git(&["commit", "-qam", "finish A"]);
+    git(&["checkout", "-q", "-"]);
+    let theirs = base.replace("B\n  status: todo", "B\n  status: done").replace("wait", "in-progress");
+    fs::write(&step_path, theirs).expect("Failed to write step");
+    git(&["commit", "-qam", "finish B"]);
+
+    git(&["merge", "-q", "other", "-m", "merge"]);
+    let merged = fs::read_to_string(&step_path).expect("Failed to read step");
+    assert!(merged.contains("id: A\n  status: done"));
+    assert!(merged.contains("id: B\n  status: done"));
+    assert!(merged.contains("id: C\n  status: in-progress"));
+}
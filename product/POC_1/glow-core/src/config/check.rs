@@ -0,0 +1,621 @@
+//! Process definition analysis
+//!
+//! Simulates a process from ROOT to find steps that can never become `Todo`:
+//! deadlocks, unreachable steps and unsatisfiable required inputs. Also
+//! reports parameter types nothing refers to.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::model::{ParameterRef, StepDefinition};
+
+use super::ProcessConfig;
+
+/// Kind of problem found in a process definition
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FindingKind {
+    /// Steps waiting on each other
+    Deadlock,
+    /// A step that can never become `Todo`
+    Unreachable,
+    /// A required input nothing can supply
+    UnsatisfiableInput,
+    /// A parameter type no step refers to
+    OrphanedParameterType,
+}
+
+impl FindingKind {
+    /// Check if the finding prevents the process from completing
+    pub fn is_error(&self) -> bool {
+        !matches!(self, FindingKind::OrphanedParameterType)
+    }
+}
+
+impl std::fmt::Display for FindingKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FindingKind::Deadlock => write!(f, "deadlock"),
+            FindingKind::Unreachable => write!(f, "unreachable"),
+            FindingKind::UnsatisfiableInput => write!(f, "unsatisfiable input"),
+            FindingKind::OrphanedParameterType => write!(f, "orphaned parameter type"),
+        }
+    }
+}
+
+/// A problem found in a process definition
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigFinding {
+    /// Kind of problem
+    pub kind: FindingKind,
+    /// Step FQID or parameter type ID the finding is about
+    pub subject: String,
+    /// Description of the problem
+    pub message: String,
+    /// Location in the process config (e.g., "root_process.steps[1].links[0]")
+    pub location: String,
+}
+
+impl std::fmt::Display for ConfigFinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {} ({})", self.location, self.message, self.kind)
+    }
+}
+
+/// Simulation state of a step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SimState {
+    Pending,
+    Todo,
+    Done,
+}
+
+/// A required parameter that must be produced by another step
+struct Need {
+    id: String,
+    location: String,
+}
+
+/// A step in the simulation
+struct Node<'a> {
+    fqid: String,
+    location: String,
+    def: &'a StepDefinition,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// Sibling steps this step depends on
+    deps: Vec<usize>,
+    /// Unknown sibling IDs this step depends on, with the link location
+    unknown_deps: Vec<(String, String)>,
+    needs: Vec<Need>,
+}
+
+/// Check a process definition by simulating it from ROOT
+///
+/// Every task is assumed to finish once started, and a process once all of
+/// its sub-steps are done. Steps still waiting at the end can never become
+/// `Todo`.
+pub fn check_process_config(config: &ProcessConfig) -> Vec<ConfigFinding> {
+    let mut nodes = Vec::new();
+    collect_nodes(
+        config,
+        &config.root_process,
+        None,
+        "root_process".to_string(),
+        &mut nodes,
+    );
+
+    let states = simulate(&nodes);
+    let waits = wait_for_edges(&nodes, &states);
+    let producers = producers(&nodes);
+
+    let mut findings = Vec::new();
+    let mut in_deadlock = HashSet::new();
+    for cycle in strongly_connected(&waits) {
+        let is_cycle = cycle.len() > 1 || waits[cycle[0]].contains(&cycle[0]);
+        if !is_cycle {
+            continue;
+        }
+        let first = cycle[0];
+        let members: Vec<&str> = cycle.iter().map(|&i| nodes[i].fqid.as_str()).collect();
+        findings.push(ConfigFinding {
+            kind: FindingKind::Deadlock,
+            subject: nodes[first].fqid.clone(),
+            message: format!(
+                "steps wait on each other and can never finish: {}",
+                members.join(", ")
+            ),
+            location: nodes[first].location.clone(),
+        });
+        in_deadlock.extend(cycle);
+    }
+
+    for (i, node) in nodes.iter().enumerate() {
+        let created = node.parent.is_none_or(|p| states[p] != SimState::Pending);
+        if states[i] != SimState::Pending || !created || in_deadlock.contains(&i) {
+            continue;
+        }
+
+        let mut explained = false;
+        for (id, location) in &node.unknown_deps {
+            findings.push(ConfigFinding {
+                kind: FindingKind::Unreachable,
+                subject: node.fqid.clone(),
+                message: format!("{} depends on unknown step '{}'", node.fqid, id),
+                location: location.clone(),
+            });
+            explained = true;
+        }
+        for need in &node.needs {
+            if producers
+                .get(need.id.as_str())
+                .is_none_or(|p| p.iter().all(|&p| p == i))
+            {
+                findings.push(ConfigFinding {
+                    kind: FindingKind::UnsatisfiableInput,
+                    subject: node.fqid.clone(),
+                    message: format!(
+                        "required parameter '{}' of {} has no default, mapping or producing step",
+                        need.id, node.fqid
+                    ),
+                    location: need.location.clone(),
+                });
+                explained = true;
+            }
+        }
+
+        if !explained {
+            let blockers: Vec<&str> = waits[i].iter().map(|&b| nodes[b].fqid.as_str()).collect();
+            findings.push(ConfigFinding {
+                kind: FindingKind::Unreachable,
+                subject: node.fqid.clone(),
+                message: format!(
+                    "{} can never start: waits on {}, which never finishes",
+                    node.fqid,
+                    blockers.join(", ")
+                ),
+                location: node.location.clone(),
+            });
+        }
+    }
+
+    findings.extend(orphaned_parameter_types(config));
+    findings
+}
+
+/// Collect step definitions as simulation nodes (pre-order)
+fn collect_nodes<'a>(
+    config: &ProcessConfig,
+    def: &'a StepDefinition,
+    parent: Option<usize>,
+    location: String,
+    nodes: &mut Vec<Node<'a>>,
+) -> usize {
+    let fqid = match parent {
+        None | Some(0) => def.id.clone(),
+        Some(p) => format!("{}.{}", nodes[p].fqid, def.id),
+    };
+
+    let mut params: Vec<(&ParameterRef, String)> = def
+        .inputs
+        .iter()
+        .enumerate()
+        .map(|(i, r)| (r, format!("{}.inputs[{}]", location, i)))
+        .collect();
+    // Process scope is supplied with 'glow init'; tasks are created without scope
+    if def.is_task() {
+        params.extend(
+            def.scope
+                .iter()
+                .enumerate()
+                .map(|(i, r)| (r, format!("{}.scope[{}]", location, i))),
+        );
+    }
+    let needs = params
+        .into_iter()
+        .filter(|(r, _)| is_required(config, r) && !has_default(config, r))
+        .filter(|(r, _)| !ancestor_provides(nodes, parent, &r.id))
+        .map(|(r, location)| Need {
+            id: r.id.clone(),
+            location,
+        })
+        .collect();
+
+    let index = nodes.len();
+    nodes.push(Node {
+        fqid,
+        location: location.clone(),
+        def,
+        parent,
+        children: Vec::new(),
+        deps: Vec::new(),
+        unknown_deps: Vec::new(),
+        needs,
+    });
+
+    let children: Vec<usize> = def
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, sub)| {
+            collect_nodes(
+                config,
+                sub,
+                Some(index),
+                format!("{}.steps[{}]", location, i),
+                nodes,
+            )
+        })
+        .collect();
+
+    // Sub-steps wait until the targets of their links are done
+    for (i, link) in def.links.iter().enumerate() {
+        let Some(&from) = children.iter().find(|&&c| nodes[c].def.id == link.from) else {
+            continue;
+        };
+        match children.iter().find(|&&c| nodes[c].def.id == link.to) {
+            Some(&to) => nodes[from].deps.push(to),
+            None => {
+                let link_location = format!("{}.links[{}]", location, i);
+                nodes[from]
+                    .unknown_deps
+                    .push((link.to.clone(), link_location));
+            }
+        }
+    }
+    nodes[index].children = children;
+
+    index
+}
+
+fn is_required(config: &ProcessConfig, param: &ParameterRef) -> bool {
+    param.is_required.unwrap_or_else(|| {
+        param
+            .type_ref
+            .as_deref()
+            .and_then(|t| config.find_parameter_type(t))
+            .is_some_and(|t| t.is_required)
+    })
+}
+
+fn has_default(config: &ProcessConfig, param: &ParameterRef) -> bool {
+    param.default_value.is_some()
+        || param.mapping.is_some()
+        || param
+            .type_ref
+            .as_deref()
+            .and_then(|t| config.find_parameter_type(t))
+            .is_some_and(|t| t.default_value.is_some())
+}
+
+/// Check if an ancestor declares the parameter in its inputs or scope
+fn ancestor_provides(nodes: &[Node], mut parent: Option<usize>, id: &str) -> bool {
+    while let Some(p) = parent {
+        let def = nodes[p].def;
+        if def.inputs.iter().chain(&def.scope).any(|r| r.id == id) {
+            return true;
+        }
+        parent = nodes[p].parent;
+    }
+    false
+}
+
+/// Map parameter IDs to the steps producing them
+fn producers<'a>(nodes: &'a [Node]) -> HashMap<&'a str, Vec<usize>> {
+    let mut producers: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, node) in nodes.iter().enumerate() {
+        for output in &node.def.outputs {
+            producers.entry(output.id.as_str()).or_default().push(i);
+        }
+    }
+    producers
+}
+
+/// Run the process until nothing changes
+fn simulate(nodes: &[Node]) -> Vec<SimState> {
+    let mut states = vec![SimState::Pending; nodes.len()];
+    let mut available: HashSet<&str> = HashSet::new();
+
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (i, node) in nodes.iter().enumerate() {
+            match states[i] {
+                SimState::Pending => {
+                    let created = node.parent.is_none_or(|p| states[p] != SimState::Pending);
+                    let ready = created
+                        && node.unknown_deps.is_empty()
+                        && node.deps.iter().all(|&d| states[d] == SimState::Done)
+                        && node.needs.iter().all(|n| available.contains(n.id.as_str()));
+                    if ready {
+                        states[i] = SimState::Todo;
+                        changed = true;
+                    }
+                }
+                SimState::Todo => {
+                    if node.children.iter().all(|&c| states[c] == SimState::Done) {
+                        states[i] = SimState::Done;
+                        available.extend(node.def.outputs.iter().map(|o| o.id.as_str()));
+                        changed = true;
+                    }
+                }
+                SimState::Done => {}
+            }
+        }
+    }
+
+    states
+}
+
+/// Build the wait-for graph of unfinished steps
+fn wait_for_edges(nodes: &[Node], states: &[SimState]) -> Vec<Vec<usize>> {
+    let producers = producers(nodes);
+    nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| {
+            let mut waits = Vec::new();
+            match states[i] {
+                SimState::Done => {}
+                SimState::Todo => {
+                    waits.extend(
+                        node.children
+                            .iter()
+                            .filter(|&&c| states[c] != SimState::Done),
+                    );
+                }
+                SimState::Pending => match node.parent {
+                    Some(p) if states[p] == SimState::Pending => waits.push(p),
+                    _ => {
+                        waits.extend(node.deps.iter().filter(|&&d| states[d] != SimState::Done));
+                        for need in &node.needs {
+                            for &p in producers.get(need.id.as_str()).into_iter().flatten() {
+                                if states[p] != SimState::Done && !waits.contains(&p) {
+                                    waits.push(p);
+                                }
+                            }
+                        }
+                    }
+                },
+            }
+            waits
+        })
+        .collect()
+}
+
+/// Find strongly connected components (Tarjan), members in definition order
+fn strongly_connected(edges: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        edges: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, v: usize) {
+            self.index[v] = Some(self.next);
+            self.low[v] = self.next;
+            self.next += 1;
+            self.stack.push(v);
+            self.on_stack[v] = true;
+
+            for &w in &self.edges[v] {
+                match self.index[w] {
+                    None => {
+                        self.visit(w);
+                        self.low[v] = self.low[v].min(self.low[w]);
+                    }
+                    Some(index) if self.on_stack[w] => self.low[v] = self.low[v].min(index),
+                    Some(_) => {}
+                }
+            }
+
+            if Some(self.low[v]) == self.index[v] {
+                let mut component = Vec::new();
+                while let Some(w) = self.stack.pop() {
+                    self.on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                component.sort_unstable();
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        edges,
+        index: vec![None; edges.len()],
+        low: vec![0; edges.len()],
+        on_stack: vec![false; edges.len()],
+        stack: Vec::new(),
+        next: 0,
+        components: Vec::new(),
+    };
+    for v in 0..edges.len() {
+        if tarjan.index[v].is_none() {
+            tarjan.visit(v);
+        }
+    }
+
+    let mut components = tarjan.components;
+    components.sort_by_key(|c| c[0]);
+    components
+}
+
+/// Find parameter types no step or classification refers to
+fn orphaned_parameter_types(config: &ProcessConfig) -> Vec<ConfigFinding> {
+    let mut used = HashSet::new();
+    collect_type_refs(&config.root_process, &mut used);
+    for value in config.classifications.iter().flat_map(|c| &c.values) {
+        let refs = value
+            .default_inputs
+            .iter()
+            .chain(&value.default_outputs)
+            .chain(&value.default_scope);
+        used.extend(refs.filter_map(|r| r.type_ref.clone()));
+    }
+
+    config
+        .parameter_types
+        .iter()
+        .enumerate()
+        .filter(|(_, t)| !used.contains(&t.id))
+        .map(|(i, t)| ConfigFinding {
+            kind: FindingKind::OrphanedParameterType,
+            subject: t.id.clone(),
+            message: format!("parameter type '{}' is not referenced by any step", t.id),
+            location: format!("parameter_types[{}]", i),
+        })
+        .collect()
+}
+
+fn collect_type_refs(def: &StepDefinition, used: &mut HashSet<String>) {
+    let refs = def.inputs.iter().chain(&def.outputs).chain(&def.scope);
+    used.extend(refs.filter_map(|r| r.type_ref.clone()));
+    for sub in &def.steps {
+        collect_type_refs(sub, used);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(yaml: &str) -> Vec<ConfigFinding> {
+        let config: ProcessConfig = serde_yaml::from_str(yaml).unwrap();
+        check_process_config(&config)
+    }
+
+    #[test]
+    fn test_valid_process_has_no_findings() {
+        let findings = check(
+            r#"
+parameter_types:
+  - id: SPEC
+    data_type: STR
+root_process:
+  id: ROOT
+  steps:
+    - id: DESIGN
+      outputs:
+        - id: SPEC
+          type_ref: SPEC
+    - id: BUILD
+      inputs:
+        - id: SPEC
+          type_ref: SPEC
+          is_required: true
+  links:
+    - from: BUILD
+      to: DESIGN
+"#,
+        );
+        assert!(findings.is_empty(), "{:?}", findings);
+    }
+
+    #[test]
+    fn test_unknown_link_target_is_unreachable() {
+        let findings = check(
+            r#"
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+    - id: B
+  links:
+    - from: A
+      to: B
+    - from: B
+      to: MISSING
+"#,
+        );
+        assert_eq!(findings.len(), 2);
+        assert_eq!(findings[0].kind, FindingKind::Unreachable);
+        assert_eq!(findings[0].subject, "A");
+        assert!(findings[0].message.contains("waits on B"));
+        assert_eq!(findings[1].subject, "B");
+        assert!(findings[1].message.contains("unknown step 'MISSING'"));
+        assert_eq!(findings[1].location, "root_process.links[1]");
+    }
+
+    #[test]
+    fn test_unsatisfiable_required_input() {
+        let findings = check(
+            r#"
+root_process:
+  id: ROOT
+  steps:
+    - id: FEAT
+      steps:
+        - id: TASK
+          scope:
+            - id: OWNER
+              is_required: true
+"#,
+        );
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, FindingKind::UnsatisfiableInput);
+        assert_eq!(findings[0].subject, "FEAT.TASK");
+        assert_eq!(
+            findings[0].location,
+            "root_process.steps[0].steps[0].scope[0]"
+        );
+    }
+
+    #[test]
+    fn test_deadlock_across_levels() {
+        let findings = check(
+            r#"
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+      steps:
+        - id: X
+          inputs:
+            - id: REPORT
+              is_required: true
+    - id: B
+      outputs:
+        - id: REPORT
+    - id: C
+  links:
+    - from: B
+      to: A
+    - from: C
+      to: B
+"#,
+        );
+        assert_eq!(findings[0].kind, FindingKind::Deadlock);
+        assert!(
+            findings[0].message.contains("A, A.X, B"),
+            "{}",
+            findings[0].message
+        );
+        assert_eq!(findings[1].subject, "C");
+        assert_eq!(findings[1].kind, FindingKind::Unreachable);
+        assert_eq!(findings.len(), 2);
+    }
+
+    #[test]
+    fn test_orphaned_parameter_type() {
+        let findings = check(
+            r#"
+parameter_types:
+  - id: UNUSED
+    data_type: STR
+root_process:
+  id: ROOT
+"#,
+        );
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].kind, FindingKind::OrphanedParameterType);
+        assert!(!findings[0].kind.is_error());
+        assert_eq!(findings[0].location, "parameter_types[0]");
+    }
+}
@@ -0,0 +1,601 @@
+//! Config composition
+//!
+//! Resolves `imports:` of other config files and expands steps that
+//! `uses:` a reusable process fragment, producing a single process config.
+//! Definitions are merged by ID; the same ID defined differently in two
+//! files is a conflict.
+
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::model::{ParameterRef, StepDefinition};
+
+use super::diagnostics::{parse_file, semantic_problems, ConfigDiagnostic};
+use super::interpolate::Variables;
+use super::source::SourceMap;
+use super::ProcessConfig;
+
+/// Process config composed from a file and its imports
+#[derive(Debug)]
+pub struct Composition {
+    /// Composed config, if the main file could be parsed
+    pub config: Option<ProcessConfig>,
+    /// Files taking part, the main file first
+    pub files: Vec<PathBuf>,
+    /// Problems in any of the files
+    pub diagnostics: Vec<ConfigDiagnostic>,
+}
+
+/// A parsed config file
+struct ConfigFile {
+    path: PathBuf,
+    canonical: PathBuf,
+    map: SourceMap,
+    config: ProcessConfig,
+}
+
+impl ConfigFile {
+    fn diagnostic(&self, path: &str, message: String) -> ConfigDiagnostic {
+        ConfigDiagnostic {
+            file: self.path.clone(),
+            position: self.map.locate(path),
+            path: path.to_string(),
+            message,
+        }
+    }
+
+    fn describe(&self, path: &str) -> String {
+        let position = self.map.locate(path);
+        format!(
+            "{}:{}:{}",
+            self.path.display(),
+            position.line,
+            position.column
+        )
+    }
+}
+
+/// A definition merged from one of the files
+struct Merged<T> {
+    item: T,
+    file: usize,
+    path: String,
+}
+
+/// Compose a process config from its main file and everything it imports
+///
+/// Imports are looked up relative to the importing file, then in
+/// `library_dir`. Placeholders in every file are resolved from `variables`.
+pub fn compose(
+    file: &Path,
+    source: &str,
+    library_dir: Option<&Path>,
+    variables: &Variables,
+) -> Composition {
+    let mut composer = Composer {
+        files: Vec::new(),
+        order: vec![file.to_path_buf()],
+        diagnostics: Vec::new(),
+        library_dir,
+        variables,
+    };
+
+    let (config, diagnostics) = parse_file(file, source, false, variables);
+    composer.diagnostics.extend(diagnostics);
+    let Some(config) = config else {
+        return composer.finish(None);
+    };
+
+    composer.files.push(ConfigFile {
+        path: file.to_path_buf(),
+        canonical: file.canonicalize().unwrap_or_else(|_| file.to_path_buf()),
+        map: SourceMap::new(source),
+        config,
+    });
+    composer.load_imports(0, &mut Vec::new());
+
+    // Merge definitions from all files, the main file first
+    let classifications = composer.merge(
+        "classifications",
+        "classification",
+        |c| &c.classifications,
+        |c| &c.id,
+    );
+    let parameter_types = composer.merge(
+        "parameter_types",
+        "parameter type",
+        |c| &c.parameter_types,
+        |c| &c.id,
+    );
+    let link_types = composer.merge("link_types", "link type", |c| &c.link_types, |c| &c.id);
+    let fragments = composer.merge("fragments", "fragment", |c| &c.fragments, |c| &c.id);
+
+    let mut composed = composer.files[0].config.clone();
+    composed.imports.clear();
+    composed.classifications = classifications.into_iter().map(|m| m.item).collect();
+    composed.parameter_types = parameter_types.into_iter().map(|m| m.item).collect();
+    composed.link_types = link_types.into_iter().map(|m| m.item).collect();
+    composed.fragments = fragments.iter().map(|m| m.item.clone()).collect();
+
+    for file in &composer.files {
+        let problems = semantic_problems(&file.config, &composed);
+        composer.diagnostics.extend(
+            problems
+                .into_iter()
+                .map(|(path, message)| file.diagnostic(&path, message)),
+        );
+    }
+
+    let mut expander = Expander {
+        files: &composer.files,
+        fragments: &fragments,
+        stack: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    composed.root_process =
+        expander.expand(&composer.files[0].config.root_process, 0, "root_process");
+    let expansion_problems = expander.diagnostics;
+    composer.diagnostics.extend(expansion_problems);
+    composed.fragments.clear();
+
+    composer.finish(Some(composed))
+}
+
+struct Composer<'a> {
+    files: Vec<ConfigFile>,
+    /// Every file read, in order, for sorting diagnostics
+    order: Vec<PathBuf>,
+    diagnostics: Vec<ConfigDiagnostic>,
+    library_dir: Option<&'a Path>,
+    variables: &'a Variables,
+}
+
+impl Composer<'_> {
+    /// Load the imports of a file, depth first
+    fn load_imports(&mut self, index: usize, stack: &mut Vec<usize>) {
+        stack.push(index);
+        let imports = self.files[index].config.imports.clone();
+        let base = self.files[index]
+            .path
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_default();
+
+        for (i, import) in imports.iter().enumerate() {
+            let location = format!("imports[{}]", i);
+            let Some(path) = self.find_import(&base, import) else {
+                let mut message = format!(
+                    "cannot find import '{}' relative to {}",
+                    import,
+                    base.display()
+                );
+                if let Some(library) = self.library_dir {
+                    message.push_str(&format!(" or in the library {}", library.display()));
+                }
+                let diagnostic = self.files[index].diagnostic(&location, message);
+                self.diagnostics.push(diagnostic);
+                continue;
+            };
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+
+            if let Some(start) = stack
+                .iter()
+                .position(|&f| self.files[f].canonical == canonical)
+            {
+                let mut cycle: Vec<String> = stack[start..]
+                    .iter()
+                    .map(|&f| self.files[f].path.display().to_string())
+                    .collect();
+                cycle.push(path.display().to_string());
+                let message = format!("import cycle: {}", cycle.join(" -> "));
+                let diagnostic = self.files[index].diagnostic(&location, message);
+                self.diagnostics.push(diagnostic);
+                continue;
+            }
+            if self.files.iter().any(|f| f.canonical == canonical) {
+                // Already imported through another file
+                continue;
+            }
+
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) => {
+                    let message = format!("cannot read import '{}': {}", import, e);
+                    let diagnostic = self.files[index].diagnostic(&location, message);
+                    self.diagnostics.push(diagnostic);
+                    continue;
+                }
+            };
+            self.order.push(path.clone());
+
+            let (config, diagnostics) = parse_file(&path, &source, true, self.variables);
+            self.diagnostics.extend(diagnostics);
+            if let Some(config) = config {
+                self.files.push(ConfigFile {
+                    path,
+                    canonical,
+                    map: SourceMap::new(&source),
+                    config,
+                });
+                self.load_imports(self.files.len() - 1, stack);
+            }
+        }
+        stack.pop();
+    }
+
+    /// Resolve an import relative to the importing file, then in the library
+    fn find_import(&self, base: &Path, import: &str) -> Option<PathBuf> {
+        let mut candidates = vec![base.join(import)];
+        if let Some(library) = self.library_dir {
+            candidates.push(library.join(import));
+            if Path::new(import).extension().is_none() {
+                candidates.push(library.join(format!("{}.yaml", import)));
+            }
+        }
+        candidates.into_iter().find(|c| c.is_file())
+    }
+
+    /// Merge definitions by ID, reporting conflicting definitions across files
+    ///
+    /// Duplicates within one file are left to the semantic checks.
+    fn merge<T: Clone + Serialize>(
+        &mut self,
+        field: &str,
+        what: &str,
+        items: impl Fn(&ProcessConfig) -> &Vec<T>,
+        id: impl Fn(&T) -> &String,
+    ) -> Vec<Merged<T>> {
+        let mut merged: Vec<Merged<T>> = Vec::new();
+        for (f, file) in self.files.iter().enumerate() {
+            for (i, item) in items(&file.config).iter().enumerate() {
+                let path = format!("{}[{}]", field, i);
+                match merged.iter().find(|m| id(&m.item) == id(item)) {
+                    Some(existing) if existing.file == f => {}
+                    Some(existing) => {
+                        if serde_json::to_value(&existing.item).ok()
+                            != serde_json::to_value(item).ok()
+                        {
+                            let other = self.files[existing.file]
+                                .describe(&format!("{}.id", existing.path));
+                            self.diagnostics.push(file.diagnostic(
+                                &format!("{}.id", path),
+                                format!(
+                                    "{} '{}' conflicts with its definition at {}",
+                                    what,
+                                    id(item),
+                                    other
+                                ),
+                            ));
+                        }
+                    }
+                    None => merged.push(Merged {
+                        item: item.clone(),
+                        file: f,
+                        path,
+                    }),
+                }
+            }
+        }
+        merged
+    }
+
+    fn finish(mut self, config: Option<ProcessConfig>) -> Composition {
+        let order = &self.order;
+        self.diagnostics.sort_by_key(|d| {
+            let file = order
+                .iter()
+                .position(|f| *f == d.file)
+                .unwrap_or(usize::MAX);
+            (file, d.position.line, d.position.column)
+        });
+        Composition {
+            config,
+            files: self.order,
+            diagnostics: self.diagnostics,
+        }
+    }
+}
+
+/// Expands steps that use fragments
+struct Expander<'a> {
+    files: &'a [ConfigFile],
+    fragments: &'a [Merged<StepDefinition>],
+    /// Fragments being expanded, for cycle detection
+    stack: Vec<String>,
+    diagnostics: Vec<ConfigDiagnostic>,
+}
+
+impl Expander<'_> {
+    /// Expand a step definition located at `path` in file `file`
+    fn expand(&mut self, def: &StepDefinition, file: usize, path: &str) -> StepDefinition {
+        let mut local = def.clone();
+        local.steps = def
+            .steps
+            .iter()
+            .enumerate()
+            .map(|(i, s)| self.expand(s, file, &format!("{}.steps[{}]", path, i)))
+            .collect();
+        local.uses = None;
+        local.with.clear();
+
+        let Some(name) = def.uses.as_deref() else {
+            if !def.with.is_empty() {
+                self.report(
+                    file,
+                    &format!("{}.with", path),
+                    format!(
+                        "step '{}' overrides parameters but uses no fragment",
+                        def.id
+                    ),
+                );
+            }
+            return local;
+        };
+        let Some(fragment) = self.fragments.iter().find(|f| f.item.id == name) else {
+            self.report(
+                file,
+                &format!("{}.uses", path),
+                format!("step '{}' uses unknown fragment '{}'", def.id, name),
+            );
+            return local;
+        };
+        if self.stack.iter().any(|s| s == name) {
+            let mut cycle = self.stack.clone();
+            cycle.push(name.to_string());
+            self.report(
+                file,
+                &format!("{}.uses", path),
+                format!("fragment cycle: {}", cycle.join(" -> ")),
+            );
+            return local;
+        }
+
+        self.stack.push(name.to_string());
+        let mut base = self.expand(&fragment.item, fragment.file, &fragment.path);
+        self.stack.pop();
+
+        for (key, value) in &def.with {
+            let target = base
+                .scope
+                .iter_mut()
+                .chain(base.inputs.iter_mut())
+                .chain(base.outputs.iter_mut())
+                .find(|p| p.id == *key);
+            match target {
+                Some(param) => param.default_value = Some(value.clone()),
+                None => self.report(
+                    file,
+                    &format!("{}.with.{}", path, key),
+                    format!("fragment '{}' has no parameter '{}'", name, key),
+                ),
+            }
+        }
+
+        // Settings of the using step override the fragment's
+        base.id = local.id;
+        base.purpose = local.purpose.or(base.purpose);
+        base.classification = local.classification.or(base.classification);
+        base.expectations = local.expectations.or(base.expectations);
+        base.template = local.template.or(base.template);
+        base.estimate = local.estimate.or(base.estimate);
+        base.due = local.due.or(base.due);
+        base.iteration_length = local.iteration_length.or(base.iteration_length);
+        base.allow_iterations &= local.allow_iterations;
+        override_refs(&mut base.inputs, local.inputs);
+        override_refs(&mut base.outputs, local.outputs);
+        override_refs(&mut base.scope, local.scope);
+
+        for (i, step) in local.steps.into_iter().enumerate() {
+            if base.find_step(&step.id).is_some() {
+                self.report(
+                    file,
+                    &format!("{}.steps[{}].id", path, i),
+                    format!(
+                        "step '{}' is already defined by fragment '{}'",
+                        step.id, name
+                    ),
+                );
+            } else {
+                base.steps.push(step);
+            }
+        }
+        base.links.extend(local.links);
+        base
+    }
+
+    fn report(&mut self, file: usize, path: &str, message: String) {
+        self.diagnostics
+            .push(self.files[file].diagnostic(path, message));
+    }
+}
+
+/// Replace parameter references by ID, appending new ones
+fn override_refs(base: &mut Vec<ParameterRef>, overrides: Vec<ParameterRef>) {
+    for r in overrides {
+        match base.iter_mut().find(|b| b.id == r.id) {
+            Some(existing) => *existing = r,
+            None => base.push(r),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, content: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    const FRAGMENT: &str = r#"parameter_types:
+  - id: PRIORITY
+    data_type: STR
+fragments:
+  - id: standard-feature
+    purpose: "A feature"
+    scope:
+      - id: PRIORITY
+        type_ref: PRIORITY
+        default_value: low
+    steps:
+      - id: DESIGN
+      - id: BUILD
+    links:
+      - from: BUILD
+        to: DESIGN
+"#;
+
+    #[test]
+    fn test_imports_and_fragments() {
+        let project = tempfile::tempdir().unwrap();
+        let library = tempfile::tempdir().unwrap();
+        write(library.path(), "features.yaml", FRAGMENT);
+        let main = write(
+            project.path(),
+            "process_config.yaml",
+            r#"version: "0.1.0"
+imports:
+  - features
+root_process:
+  id: ROOT
+  steps:
+    - id: FEAT-001
+      uses: standard-feature
+      purpose: "Login"
+      with:
+        PRIORITY: high
+      steps:
+        - id: REVIEW
+      links:
+        - from: REVIEW
+          to: BUILD
+"#,
+        );
+
+        let source = std::fs::read_to_string(&main).unwrap();
+        let composition = compose(&main, &source, Some(library.path()), &Variables::default());
+        assert!(
+            composition.diagnostics.is_empty(),
+            "{:?}",
+            composition.diagnostics
+        );
+        assert_eq!(composition.files.len(), 2);
+
+        let config = composition.config.unwrap();
+        assert!(config.imports.is_empty() && config.fragments.is_empty());
+        assert!(config.find_parameter_type("PRIORITY").is_some());
+
+        let feature = config.find_step_definition("FEAT-001").unwrap();
+        assert_eq!(feature.purpose.as_deref(), Some("Login"));
+        assert!(feature.uses.is_none());
+        assert_eq!(feature.all_step_ids(), vec!["DESIGN", "BUILD", "REVIEW"]);
+        assert_eq!(feature.links.len(), 2);
+        assert_eq!(
+            feature.scope[0].default_value,
+            Some(serde_json::json!("high"))
+        );
+    }
+
+    #[test]
+    fn test_conflicts_and_unknown_references() {
+        let project = tempfile::tempdir().unwrap();
+        write(project.path(), "shared/features.yaml", FRAGMENT);
+        let main = write(
+            project.path(),
+            "process_config.yaml",
+            r#"version: "0.1.0"
+imports:
+  - shared/features.yaml
+  - missing.yaml
+parameter_types:
+  - id: PRIORITY
+    data_type: INT
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+      uses: standard-feature
+      with:
+        SEVERITY: high
+      steps:
+        - id: DESIGN
+    - id: B
+      uses: bugfix
+"#,
+        );
+
+        let source = std::fs::read_to_string(&main).unwrap();
+        let composition = compose(&main, &source, None, &Variables::default());
+        let messages: Vec<String> = composition
+            .diagnostics
+            .iter()
+            .map(|d| {
+                let file = d
+                    .file
+                    .strip_prefix(project.path())
+                    .unwrap()
+                    .display()
+                    .to_string();
+                format!(
+                    "{}:{}:{}: {}",
+                    file, d.position.line, d.position.column, d.message
+                )
+            })
+            .collect();
+
+        let main_path = main.display();
+        assert_eq!(
+            messages,
+            vec![
+                format!(
+                    "process_config.yaml:4:3: cannot find import 'missing.yaml' relative to {}",
+                    project.path().display()
+                ),
+                "process_config.yaml:14:9: fragment 'standard-feature' has no parameter 'SEVERITY'"
+                    .to_string(),
+                "process_config.yaml:16:11: step 'DESIGN' is already defined by fragment 'standard-feature'"
+                    .to_string(),
+                "process_config.yaml:18:7: step 'B' uses unknown fragment 'bugfix'".to_string(),
+                format!(
+                    "shared/features.yaml:2:5: parameter type 'PRIORITY' conflicts with its definition at {}:6:5",
+                    main_path
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_cycle_and_restricted_keys() {
+        let project = tempfile::tempdir().unwrap();
+        write(
+            project.path(),
+            "a.yaml",
+            "imports:\n  - b.yaml\nroot_process:\n  id: ROOT\n",
+        );
+        write(project.path(), "b.yaml", "imports:\n  - a.yaml\n");
+        let main = write(
+            project.path(),
+            "process_config.yaml",
+            "version: \"0.1.0\"\nimports:\n  - a.yaml\nroot_process:\n  id: ROOT\n",
+        );
+
+        let source = std::fs::read_to_string(&main).unwrap();
+        let composition = compose(&main, &source, None, &Variables::default());
+        let messages: Vec<&str> = composition
+            .diagnostics
+            .iter()
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(messages.len(), 2, "{:?}", messages);
+        assert_eq!(
+            messages[0],
+            "'root_process' cannot be set in an imported file"
+        );
+        assert!(messages[1].starts_with("import cycle: "), "{}", messages[1]);
+        assert!(composition.config.is_some());
+    }
+}
@@ -0,0 +1,494 @@
+//! Process config diagnostics
+//!
+//! Validates a process config on load: YAML syntax, schema and semantic
+//! problems are all reported at once with their file, line and column.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+use crate::model::{parse_classification, parse_iteration_days, ParameterRef, StepDefinition, MAX_ITERATION_DAYS};
+
+use super::check::{check_process_config, ConfigFinding};
+use super::compose::compose;
+use super::interpolate::Variables;
+use super::schema::{process_config_schema, schema_errors, PROCESS_CONFIG_SCHEMA};
+use super::source::{SourceMap, SourcePosition};
+use super::{library_dir, ProcessConfig};
+
+/// A problem in a config file with its source position
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigDiagnostic {
+    /// Config file
+    pub file: PathBuf,
+    /// Line and column (1-based)
+    pub position: SourcePosition,
+    /// Path of the offending value (e.g., "root_process.steps[1].id")
+    pub path: String,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl std::fmt::Display for ConfigDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file.display(),
+            self.position.line,
+            self.position.column,
+            self.message
+        )
+    }
+}
+
+/// Result of checking a process config file
+#[derive(Debug)]
+pub struct ConfigCheck {
+    /// Checked file
+    pub file: PathBuf,
+    /// Syntax, schema and semantic problems
+    pub diagnostics: Vec<ConfigDiagnostic>,
+    /// Process simulation findings (empty if the file could not be parsed)
+    pub findings: Vec<ConfigFinding>,
+    source_map: SourceMap,
+}
+
+impl ConfigCheck {
+    /// Check a process config file's content, including its imports
+    pub fn new(file: &Path, source: &str, variables: &Variables) -> Self {
+        let (config, diagnostics) = diagnose(file, source, variables);
+        Self {
+            file: file.to_path_buf(),
+            diagnostics,
+            findings: config
+                .as_ref()
+                .map(check_process_config)
+                .unwrap_or_default(),
+            source_map: SourceMap::new(source),
+        }
+    }
+
+    /// Source position of a simulation finding
+    pub fn finding_position(&self, finding: &ConfigFinding) -> SourcePosition {
+        self.source_map.locate(&finding.location)
+    }
+
+    /// Check if anything prevents the process from working
+    pub fn has_errors(&self) -> bool {
+        !self.diagnostics.is_empty() || self.findings.iter().any(|f| f.kind.is_error())
+    }
+}
+
+/// Parse, compose and validate a process config
+///
+/// Returns the composed config when the file could be deserialized, along
+/// with all problems found in it and in the files it imports. Placeholders
+/// are resolved from `variables`.
+pub fn diagnose(
+    file: &Path,
+    source: &str,
+    variables: &Variables,
+) -> (Option<ProcessConfig>, Vec<ConfigDiagnostic>) {
+    let composition = compose(file, source, library_dir().as_deref(), variables);
+    (composition.config, composition.diagnostics)
+}
+
+/// Keys an imported file may set
+const IMPORTABLE_KEYS: &[&str] = &[
+    "version",
+    "imports",
+    "classifications",
+    "parameter_types",
+    "link_types",
+    "fragments",
+];
+
+/// Parse a single config file, checking syntax and schema
+///
+/// Placeholders are resolved before the schema check. Imported files may
+/// only contribute definitions (see `IMPORTABLE_KEYS`).
+pub(crate) fn parse_file(
+    file: &Path,
+    source: &str,
+    imported: bool,
+    variables: &Variables,
+) -> (Option<ProcessConfig>, Vec<ConfigDiagnostic>) {
+    let map = SourceMap::new(source);
+    let diagnostic = |path: &str, position: SourcePosition, message: String| ConfigDiagnostic {
+        file: file.to_path_buf(),
+        position,
+        path: path.to_string(),
+        message,
+    };
+    let yaml_error = |e: serde_yaml::Error| {
+        let position = e
+            .location()
+            .map(|l| SourcePosition {
+                line: l.line(),
+                column: l.column(),
+            })
+            .unwrap_or(SourcePosition::START);
+        diagnostic("", position, e.to_string())
+    };
+
+    let mut value: serde_json::Value = match serde_yaml::from_str(source) {
+        Ok(value) => value,
+        Err(e) => return (None, vec![yaml_error(e)]),
+    };
+
+    let original = value.clone();
+    let mut diagnostics: Vec<ConfigDiagnostic> = variables
+        .interpolate(&mut value, &process_config_schema())
+        .into_iter()
+        .map(|(pointer, message)| diagnostic(&pointer, map.locate_pointer(&pointer), message))
+        .collect();
+    let interpolated = value != original;
+
+    if imported {
+        if let Some(object) = value.as_object_mut() {
+            let rejected: Vec<String> = object
+                .keys()
+                .filter(|k| !IMPORTABLE_KEYS.contains(&k.as_str()))
+                .cloned()
+                .collect();
+            for key in rejected {
+                object.remove(&key);
+                diagnostics.push(diagnostic(
+                    &key,
+                    map.locate(&key),
+                    format!("'{}' cannot be set in an imported file", key),
+                ));
+            }
+            let defaults = ProcessConfig::default();
+            object
+                .entry("version")
+                .or_insert_with(|| serde_json::Value::from(defaults.version));
+            object.insert(
+                "root_process".to_string(),
+                serde_json::json!({ "id": defaults.root_process.id }),
+            );
+        }
+    }
+
+    diagnostics.extend(
+        schema_errors(&value, &PROCESS_CONFIG_SCHEMA)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(pointer, message)| diagnostic(&pointer, map.locate_pointer(&pointer), message)),
+    );
+
+    let config = if imported || interpolated {
+        serde_json::from_value(value)
+            .map_err(|e| diagnostic("", SourcePosition::START, e.to_string()))
+    } else {
+        serde_yaml::from_str(source).map_err(yaml_error)
+    };
+    match config {
+        Ok(config) => (Some(config), diagnostics),
+        Err(d) => {
+            diagnostics.push(d);
+            (None, diagnostics)
+        }
+    }
+}
+
+/// Find semantic problems in a config file as (path, message) pairs
+///
+/// Checks duplicate IDs, undeclared parameter types, unknown link types,
+/// link cycles and classifications with the wrong number of dimensions.
+/// Types, link types and classifications are looked up in `context`, the
+/// definitions merged from all files.
+pub fn semantic_problems(config: &ProcessConfig, context: &ProcessConfig) -> Vec<(String, String)> {
+    let mut problems = Vec::new();
+
+    check_duplicates(
+        config.classifications.iter().map(|c| c.id.as_str()),
+        "classifications",
+        "classification",
+        &mut problems,
+    );
+    check_duplicates(
+        config.parameter_types.iter().map(|t| t.id.as_str()),
+        "parameter_types",
+        "parameter type",
+        &mut problems,
+    );
+    check_duplicates(
+        config.link_types.iter().map(|t| t.id.as_str()),
+        "link_types",
+        "link type",
+        &mut problems,
+    );
+    check_duplicates(
+        config.fragments.iter().map(|f| f.id.as_str()),
+        "fragments",
+        "fragment",
+        &mut problems,
+    );
+
+    for (i, dictionary) in config.classifications.iter().enumerate() {
+        for (j, value) in dictionary.values.iter().enumerate() {
+            let path = format!("classifications[{}].values[{}]", i, j);
+            check_type_refs(
+                context,
+                &path,
+                "default_inputs",
+                &value.default_inputs,
+                &mut problems,
+            );
+            check_type_refs(
+                context,
+                &path,
+                "default_outputs",
+                &value.default_outputs,
+                &mut problems,
+            );
+            check_type_refs(
+                context,
+                &path,
+                "default_scope",
+                &value.default_scope,
+                &mut problems,
+            );
+        }
+    }
+
+    for (i, fragment) in config.fragments.iter().enumerate() {
+        check_step(
+            context,
+            fragment,
+            &format!("fragments[{}]", i),
+            &mut problems,
+        );
+    }
+    check_step(context, &config.root_process, "root_process", &mut problems);
+    problems
+}
+
+fn check_duplicates<'a>(
+    ids: impl Iterator<Item = &'a str>,
+    path: &str,
+    what: &str,
+    problems: &mut Vec<(String, String)>,
+) {
+    let mut seen = HashSet::new();
+    for (i, id) in ids.enumerate() {
+        if !seen.insert(id) {
+            problems.push((
+                format!("{}[{}].id", path, i),
+                format!("duplicate {} ID '{}'", what, id),
+            ));
+        }
+    }
+}
+
+fn check_step(
+    config: &ProcessConfig,
+    def: &StepDefinition,
+    path: &str,
+    problems: &mut Vec<(String, String)>,
+) {
+    let dimensions = config.classifications.len();
+    if let Some(classification) = def.classification.as_deref().filter(|_| dimensions > 0) {
+        let count = parse_classification(classification).len();
+        if count != dimensions {
+            problems.push((
+                format!("{}.classification", path),
+                format!(
+                    "classification '{}' has {} dimension(s), expected {}",
+                    classification, count, dimensions
+                ),
+            ));
+        }
+    }
+
+    if let Some(length) = def.iteration_length.as_deref() {
+        if parse_iteration_days(length).is_none() {
+            problems.push((
+                format!("{}.iteration_length", path),
+                format!(
+                    "invalid iteration length '{}', expected days or weeks up to {}d (e.g. 10d, 2w)",
+                    length, MAX_ITERATION_DAYS
+                ),
+            ));
+        }
+    }
+
+    check_type_refs(config, path, "inputs", &def.inputs, problems);
+    check_type_refs(config, path, "outputs", &def.outputs, problems);
+    check_type_refs(config, path, "scope", &def.scope, problems);
+
+    check_duplicates(
+        def.steps.iter().map(|s| s.id.as_str()),
+        &format!("{}.steps", path),
+        "step",
+        problems,
+    );
+
+    for (i, link) in def.links.iter().enumerate() {
+        // Built-in link types are the blocking ones
+        let known = link.is_blocking() || config.find_link_type(&link.r#type).is_some();
+        if !known {
+            problems.push((
+                format!("{}.links[{}].type", path, i),
+                format!("unknown link type '{}'", link.r#type),
+            ));
+        }
+    }
+
+    if let Some((link, cycle)) = find_link_cycle(def) {
+        problems.push((
+            format!("{}.links[{}]", path, link),
+            format!("circular dependency: {}", cycle.join(" -> ")),
+        ));
+    }
+
+    for (i, sub) in def.steps.iter().enumerate() {
+        check_step(config, sub, &format!("{}.steps[{}]", path, i), problems);
+    }
+}
+
+fn check_type_refs(
+    config: &ProcessConfig,
+    path: &str,
+    field: &str,
+    refs: &[ParameterRef],
+    problems: &mut Vec<(String, String)>,
+) {
+    for (i, r) in refs.iter().enumerate() {
+        if let Some(type_ref) = r.type_ref.as_deref() {
+            if config.find_parameter_type(type_ref).is_none() {
+                problems.push((
+                    format!("{}.{}[{}].type_ref", path, field, i),
+                    format!(
+                        "parameter '{}' refers to undeclared parameter type '{}'",
+                        r.id, type_ref
+                    ),
+                ));
+            }
+        }
+    }
+}
+
+/// Find a cycle among a process's links, in definition order
+///
+/// Returns the index of the link closing the cycle and the step IDs on it.
+fn find_link_cycle(def: &StepDefinition) -> Option<(usize, Vec<String>)> {
+    fn visit<'a>(
+        id: &'a str,
+        def: &'a StepDefinition,
+        path: &mut Vec<&'a str>,
+        done: &mut HashSet<&'a str>,
+    ) -> Option<(usize, Vec<String>)> {
+        path.push(id);
+        for (i, link) in def.links.iter().enumerate().filter(|(_, l)| l.from == id) {
+            let to = link.to.as_str();
+            if let Some(start) = path.iter().position(|&p| p == to) {
+                let mut cycle: Vec<String> = path[start..].iter().map(|s| s.to_string()).collect();
+                cycle.push(to.to_string());
+                return Some((i, cycle));
+            }
+            if !done.contains(to) {
+                if let Some(found) = visit(to, def, path, done) {
+                    return Some(found);
+                }
+            }
+        }
+        path.pop();
+        done.insert(id);
+        None
+    }
+
+    let mut done = HashSet::new();
+    for link in &def.links {
+        if !done.contains(link.from.as_str()) {
+            if let Some(found) = visit(&link.from, def, &mut Vec::new(), &mut done) {
+                return Some(found);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"version: "0.1.0"
+classifications:
+  - id: type
+    values:
+      - key: Task
+  - id: priority
+    values:
+      - key: Must
+parameter_types:
+  - id: SPEC
+    data_type: STR
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+      classification: "Task"
+      inputs:
+        - id: SPEC
+          type_ref: UNDECLARED
+    - id: A
+    - id: B
+      classification: "Task,Must"
+      iteration_length: 9999w
+  links:
+    - type: blocks
+      from: A
+      to: B
+    - from: B
+      to: A
+"#;
+
+    #[test]
+    fn test_reports_all_problems_with_positions() {
+        let (config, diagnostics) = diagnose(Path::new("process_config.yaml"), CONFIG, &Variables::default());
+        assert!(config.is_some());
+
+        let messages: Vec<String> = diagnostics.iter().map(|d| d.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "process_config.yaml:16:7: classification 'Task' has 1 dimension(s), expected 2",
+                "process_config.yaml:19:11: parameter 'SPEC' refers to undeclared parameter type 'UNDECLARED'",
+                "process_config.yaml:20:7: duplicate step ID 'A'",
+                "process_config.yaml:23:7: invalid iteration length '9999w', expected days or weeks up to 3650d (e.g. 10d, 2w)",
+                "process_config.yaml:25:7: unknown link type 'blocks'",
+                "process_config.yaml:28:5: circular dependency: A -> B -> A",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_and_syntax_errors() {
+        let (config, diagnostics) = diagnose(
+            Path::new("p.yaml"),
+            "version: \"0.1.0\"\nroot_process:\n  id: ROOT\n  unknown_key: 1\n",
+            &Variables::default(),
+        );
+        assert!(config.is_some());
+        assert_eq!(diagnostics.len(), 1);
+        assert!(
+            diagnostics[0].to_string().starts_with("p.yaml:4:3: "),
+            "{}",
+            diagnostics[0]
+        );
+
+        let (config, diagnostics) = diagnose(Path::new("p.yaml"), "root_process: [\n", &Variables::default());
+        assert!(config.is_none());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].position.line, 2);
+    }
+
+    #[test]
+    fn test_default_config_is_clean() {
+        let source = serde_yaml::to_string(&ProcessConfig::default()).unwrap();
+        let (_, diagnostics) = diagnose(Path::new("p.yaml"), &source, &Variables::default());
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+    }
+}
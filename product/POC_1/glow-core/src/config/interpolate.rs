@@ -0,0 +1,336 @@
+//! Variable interpolation
+//!
+//! Replaces placeholders in the string values of config files:
+//!
+//! - `${env:VAR}`: environment variable
+//! - `${config:KEY}`: value from `config.yaml` (e.g., `project_name`)
+//! - `${default:TEXT}`: literal text, as the last alternative
+//!
+//! Alternatives are separated by `|` and tried in order, e.g.
+//! `${env:ARTIFACT_ROOT|default:artifacts}`. `$${` writes a literal `${`.
+//! A value that is a single placeholder takes the type of its result where
+//! the schema expects a number or a boolean, so `${env:WIP_LIMIT}` can set a
+//! number; elsewhere the result stays text.
+
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+
+/// Nesting limit for `${config:...}` values that contain placeholders
+const MAX_DEPTH: usize = 8;
+
+/// Values placeholders are resolved from
+#[derive(Debug, Clone, Default)]
+pub struct Variables {
+    config: Map<String, Value>,
+    /// Values for `${env:...}` instead of the process environment
+    env: Option<HashMap<String, String>>,
+}
+
+impl Variables {
+    /// Resolve `${config:...}` from the keys of a config file
+    pub fn new(config: &impl serde::Serialize) -> Self {
+        let config = match serde_json::to_value(config) {
+            Ok(Value::Object(map)) => map,
+            _ => Map::new(),
+        };
+        Self { config, env: None }
+    }
+
+    /// Resolve `${config:...}` from an unparsed `config.yaml` mapping
+    pub fn from_map(config: Map<String, Value>) -> Self {
+        Self { config, env: None }
+    }
+
+    /// Resolve `${env:...}` from given variables instead of the process environment
+    pub fn with_env(mut self, env: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.env = Some(env.into_iter().collect());
+        self
+    }
+
+    /// Interpolate every string in a value, in place, typed by the value's JSON schema
+    ///
+    /// Returns (JSON pointer, message) for each placeholder that could not be
+    /// resolved; the value keeps the placeholder text there.
+    pub fn interpolate(&self, value: &mut Value, schema: &Value) -> Vec<(String, String)> {
+        let mut errors = Vec::new();
+        let mut schemas = Vec::new();
+        branches(schema, schema, &mut schemas);
+        self.interpolate_at(value, String::new(), &Schemas { root: schema, nodes: schemas }, &mut errors);
+        errors
+    }
+
+    fn interpolate_at(
+        &self,
+        value: &mut Value,
+        pointer: String,
+        schemas: &Schemas,
+        errors: &mut Vec<(String, String)>,
+    ) {
+        match value {
+            Value::String(text) if text.contains("${") => match self.resolve(text, schemas.scalar(), 0) {
+                Ok(resolved) => *value = resolved,
+                Err(message) => errors.push((pointer, message)),
+            },
+            Value::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    self.interpolate_at(item, format!("{}/{}", pointer, i), &schemas.item(i), errors);
+                }
+            }
+            Value::Object(map) => {
+                for (key, item) in map.iter_mut() {
+                    self.interpolate_at(item, format!("{}/{}", pointer, key), &schemas.property(key), errors);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Resolve the placeholders in a string
+    ///
+    /// With `typed`, a string that is a single placeholder takes the type of
+    /// its result.
+    fn resolve(&self, text: &str, typed: bool, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!(
+                "placeholders nest deeper than {} levels (is a config value referring to itself?)",
+                MAX_DEPTH
+            ));
+        }
+
+        let mut out = String::new();
+        let mut rest = text;
+        let mut whole = None;
+
+        while let Some(start) = rest.find("${") {
+            if rest[..start].ends_with('$') {
+                out.push_str(&rest[..start - 1]);
+                out.push_str("${");
+                rest = &rest[start + 2..];
+                continue;
+            }
+            out.push_str(&rest[..start]);
+
+            let after = &rest[start + 2..];
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("unclosed placeholder in '{}'", text))?;
+            let resolved = self.resolve_placeholder(&after[..end], depth)?;
+            if start == 0 && end + 3 == rest.len() && out.is_empty() {
+                whole = Some(resolved.clone());
+            }
+            out.push_str(&resolved);
+            rest = &after[end + 1..];
+        }
+        out.push_str(rest);
+
+        // A single placeholder takes the type of its result
+        if let Some(resolved) = whole.filter(|_| typed) {
+            if let Ok(typed @ (Value::Number(_) | Value::Bool(_))) = serde_yaml::from_str(&resolved)
+            {
+                return Ok(typed);
+            }
+        }
+        Ok(Value::String(out))
+    }
+
+    /// Resolve one placeholder: alternatives separated by `|`
+    fn resolve_placeholder(&self, placeholder: &str, depth: usize) -> Result<String, String> {
+        let mut missing = Vec::new();
+
+        for alternative in placeholder.split('|') {
+            let (source, name) = alternative.split_once(':').ok_or_else(|| {
+                format!(
+                    "invalid placeholder '${{{}}}' (expected ${{env:VAR}}, ${{config:KEY}} or ${{default:TEXT}})",
+                    placeholder
+                )
+            })?;
+            let name = name.trim();
+
+            match source.trim() {
+                "env" => match self.env_var(name) {
+                    Some(value) => return Ok(value),
+                    None => missing.push(format!("environment variable '{}' is not set", name)),
+                },
+                "config" => match self.config.get(name) {
+                    Some(Value::String(value)) => {
+                        return Ok(self.resolve(value, false, depth + 1)?.as_str().unwrap_or_default().to_string());
+                    }
+                    Some(Value::Null) | None => {
+                        missing.push(format!("config value '{}' is not set", name))
+                    }
+                    Some(value) => return Ok(value.to_string()),
+                },
+                "default" => return Ok(name.to_string()),
+                other => {
+                    return Err(format!(
+                        "unknown placeholder source '{}' in '${{{}}}' (expected env, config or default)",
+                        other, placeholder
+                    ))
+                }
+            }
+        }
+
+        Err(missing.join("; "))
+    }
+
+    /// Get an environment variable, from the given variables if there are
+    fn env_var(&self, name: &str) -> Option<String> {
+        match &self.env {
+            Some(env) => env.get(name).cloned(),
+            None => std::env::var(name).ok(),
+        }
+    }
+}
+
+/// The schemas a value must match, found by following its JSON pointer
+struct Schemas<'a> {
+    /// Root schema, for `$ref`s
+    root: &'a Value,
+    /// Schemas of the value, with references resolved and combinations flattened
+    nodes: Vec<&'a Value>,
+}
+
+impl<'a> Schemas<'a> {
+    /// Schemas of a property of an object
+    fn property(&self, key: &str) -> Self {
+        self.child(|node| {
+            node.get("properties")
+                .and_then(|properties| properties.get(key))
+                .or_else(|| node.get("additionalProperties").filter(|s| s.is_object()))
+        })
+    }
+
+    /// Schemas of an item of an array
+    fn item(&self, index: usize) -> Self {
+        self.child(|node| match node.get("items")? {
+            Value::Array(items) => items.get(index),
+            items => Some(items),
+        })
+    }
+
+    fn child(&self, find: impl Fn(&'a Value) -> Option<&'a Value>) -> Self {
+        let mut nodes = Vec::new();
+        for child in self.nodes.iter().filter_map(|node| find(node)) {
+            branches(self.root, child, &mut nodes);
+        }
+        Self { root: self.root, nodes }
+    }
+
+    /// Whether a text value here is read as a number or a boolean
+    fn scalar(&self) -> bool {
+        let types: Vec<&str> = self
+            .nodes
+            .iter()
+            .filter_map(|node| node.get("type"))
+            .flat_map(|t| match t {
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                t => t.as_str().into_iter().collect::<Vec<_>>(),
+            })
+            .collect();
+        !types.contains(&"string") && types.iter().any(|t| matches!(*t, "integer" | "number" | "boolean"))
+    }
+}
+
+/// Resolve `$ref`s and flatten `anyOf`, `oneOf` and `allOf` into the schemas a value must match
+fn branches<'a>(root: &'a Value, schema: &'a Value, nodes: &mut Vec<&'a Value>) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        if let Some(target) = reference.strip_prefix('#').and_then(|pointer| root.pointer(pointer)) {
+            branches(root, target, nodes);
+        }
+        return;
+    }
+    let mut combined = false;
+    for key in ["anyOf", "oneOf", "allOf"] {
+        if let Some(Value::Array(items)) = schema.get(key) {
+            combined = true;
+            items.iter().for_each(|item| branches(root, item, nodes));
+        }
+    }
+    if !combined || schema.get("type").is_some() {
+        nodes.push(schema);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /// Variables with a test environment
+    fn variables(config: Value) -> Variables {
+        Variables::from_map(config.as_object().cloned().unwrap()).with_env([
+            ("ARTIFACTS".to_string(), "/srv/artifacts".to_string()),
+            ("WIP".to_string(), "3".to_string()),
+            ("YEAR".to_string(), "2024".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_interpolate_sources_and_alternatives() {
+        let variables = variables(json!({ "project_name": "Atlas", "data_folder": "${default:glow}" }));
+        let schema = json!({
+            "definitions": { "Limit": { "type": "integer" } },
+            "properties": {
+                "name": { "type": "string" },
+                "limit": { "anyOf": [{ "$ref": "#/definitions/Limit" }, { "type": "null" }] },
+                "steps": { "type": "array", "items": { "properties": { "id": { "type": "string" } } } }
+            }
+        });
+
+        let mut value = json!({
+            "purpose": "Deliver ${config:project_name} from ${config:data_folder}",
+            "root": "${env:ARTIFACTS}/out",
+            "fallback": "${env:UNSET|default:artifacts}",
+            "limit": "${env:WIP}",
+            "name": "${env:YEAR}",
+            "untyped": "${env:WIP}",
+            "escaped": "$${env:HOME}",
+            "steps": [{ "id": "${config:project_name}" }]
+        });
+        assert!(variables.interpolate(&mut value, &schema).is_empty());
+        assert_eq!(value["purpose"], "Deliver Atlas from glow");
+        assert_eq!(value["root"], "/srv/artifacts/out");
+        assert_eq!(value["fallback"], "artifacts");
+        assert_eq!(value["escaped"], "${env:HOME}");
+        assert_eq!(value["steps"][0]["id"], "Atlas");
+
+        // Only where the schema expects a number does a placeholder make one
+        assert_eq!(value["limit"], 3);
+        assert_eq!(value["name"], "2024");
+        assert_eq!(value["untyped"], "3");
+    }
+
+    #[test]
+    fn test_interpolate_errors_name_the_variable() {
+        let variables = variables(json!({ "a": "${config:b}", "b": "${config:a}" }));
+        let mut value = json!({
+            "x": "${env:MISSING}",
+            "y": ["${config:owner}"],
+            "z": "${config:a}",
+            "w": "${vault:key}"
+        });
+
+        let errors = variables.interpolate(&mut value, &json!({}));
+        assert_eq!(
+            errors[0],
+            ("/w".to_string(), "unknown placeholder source 'vault' in '${vault:key}' (expected env, config or default)".to_string())
+        );
+        assert_eq!(
+            errors[1],
+            (
+                "/x".to_string(),
+                "environment variable 'MISSING' is not set".to_string()
+            )
+        );
+        assert_eq!(
+            errors[2],
+            (
+                "/y/0".to_string(),
+                "config value 'owner' is not set".to_string()
+            )
+        );
+        assert!(errors[3].1.contains("referring to itself"));
+        assert_eq!(value["x"], "${env:MISSING}");
+    }
+}
@@ -0,0 +1,301 @@
+//! Layered configuration
+//!
+//! `config.yaml` values are merged from several files, each overriding the
+//! ones before it:
+//!
+//! 1. built-in defaults
+//! 2. the user config (e.g., `~/.config/glow/config.yaml`)
+//! 3. the project config (`.glow/config.yaml`)
+//! 4. local overrides (`.glow/local.yaml`, not committed to git)
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use serde_json::{Map, Value};
+
+use crate::error::{GlowError, Result};
+
+use super::interpolate::Variables;
+use super::loader::Config;
+use super::source::SourceMap;
+use super::{user_config_dir, CONFIG_FILE, LOCAL_CONFIG_FILE};
+
+/// Layer a configuration value comes from, lowest precedence first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigLayer {
+    /// Built into glow
+    Default,
+    /// User config
+    User,
+    /// Project `config.yaml`
+    Project,
+    /// Project `local.yaml`
+    Local,
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ConfigLayer::Default => "default",
+            ConfigLayer::User => "user",
+            ConfigLayer::Project => "project",
+            ConfigLayer::Local => "local",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Where a configuration value was set
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOrigin {
+    /// Layer that set the value
+    pub layer: ConfigLayer,
+    /// File of the layer (None for built-in defaults)
+    pub file: Option<PathBuf>,
+}
+
+impl ConfigOrigin {
+    const DEFAULT: ConfigOrigin = ConfigOrigin {
+        layer: ConfigLayer::Default,
+        file: None,
+    };
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.file {
+            Some(file) => write!(f, "{} ({})", self.layer, file.display()),
+            None => write!(f, "{}", self.layer),
+        }
+    }
+}
+
+/// Configuration merged from all layers
+#[derive(Debug, Clone)]
+pub struct LayeredConfig {
+    /// Merged configuration
+    pub config: Config,
+    /// Layer of each key set by a file
+    origins: BTreeMap<String, ConfigOrigin>,
+}
+
+impl LayeredConfig {
+    /// Get the layer a key was set by
+    pub fn origin(&self, key: &str) -> &ConfigOrigin {
+        self.origins.get(key).unwrap_or(&ConfigOrigin::DEFAULT)
+    }
+
+    /// Get every key with its final value and origin, in declaration order
+    pub fn values(&self) -> Vec<(String, serde_yaml::Value, &ConfigOrigin)> {
+        let mut values: Vec<(String, serde_yaml::Value)> = match serde_yaml::to_value(&self.config)
+        {
+            Ok(serde_yaml::Value::Mapping(mapping)) => mapping
+                .into_iter()
+                .filter_map(|(k, v)| Some((k.as_str()?.to_string(), v)))
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        // Keys without a value are not serialized
+        let schema = super::schema::config_schema();
+        if let Some(properties) = schema["properties"].as_object() {
+            for key in properties.keys() {
+                if !values.iter().any(|(k, _)| k == key) {
+                    values.push((key.clone(), serde_yaml::Value::Null));
+                }
+            }
+        }
+
+        values
+            .into_iter()
+            .map(|(key, value)| {
+                let origin = self.origin(&key);
+                (key, value, origin)
+            })
+            .collect()
+    }
+}
+
+/// Get the user config file (may not exist)
+pub fn user_config_file() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(CONFIG_FILE))
+}
+
+/// Load the user config on its own (e.g., before a project exists)
+pub fn user_config() -> Result<LayeredConfig> {
+    let layers: Vec<(ConfigLayer, PathBuf)> = user_config_file()
+        .map(|file| (ConfigLayer::User, file))
+        .into_iter()
+        .collect();
+    merge_layers(&layers)
+}
+
+/// Load the configuration of a project from all layers
+///
+/// The project `config.yaml` must exist; the user config and `local.yaml`
+/// are optional. The schema version is taken from the project config only.
+pub fn project_config(config_dir: &Path) -> Result<LayeredConfig> {
+    let project_file = config_dir.join(CONFIG_FILE);
+    if !project_file.is_file() {
+        return Err(GlowError::FileReadError {
+            path: project_file.clone(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "config file not found"),
+        });
+    }
+
+    let mut layers = Vec::new();
+    if let Some(user_file) = user_config_file() {
+        layers.push((ConfigLayer::User, user_file));
+    }
+    layers.push((ConfigLayer::Project, project_file));
+    layers.push((ConfigLayer::Local, config_dir.join(LOCAL_CONFIG_FILE)));
+    merge_layers(&layers)
+}
+
+/// Merge config files, later layers overriding earlier ones
+///
+/// Missing files are skipped. Placeholders are resolved after merging, so
+/// `${config:...}` sees the final values.
+fn merge_layers(layers: &[(ConfigLayer, PathBuf)]) -> Result<LayeredConfig> {
+    let mut merged = Map::new();
+    let mut origins = BTreeMap::new();
+    let mut sources = BTreeMap::new();
+
+    for (layer, file) in layers {
+        if !file.is_file() {
+            continue;
+        }
+        let content = std::fs::read_to_string(file).map_err(|e| GlowError::FileReadError {
+            path: file.clone(),
+            source: e,
+        })?;
+        let value: Value =
+            serde_yaml::from_str(&content).map_err(|e| GlowError::YamlParseError {
+                message: format!("Failed to parse {}: {}", file.display(), e),
+                source: e,
+            })?;
+
+        let map = match value {
+            Value::Object(map) => map,
+            Value::Null => Map::new(),
+            _ => {
+                return Err(GlowError::InvalidConfig {
+                    path: file.display().to_string(),
+                    message: "expected a mapping of config keys".to_string(),
+                })
+            }
+        };
+        for (key, value) in map {
+            // The schema version belongs to the project config
+            if key == "version" && *layer != ConfigLayer::Project {
+                continue;
+            }
+            merged.insert(key.clone(), value);
+            origins.insert(
+                key,
+                ConfigOrigin {
+                    layer: *layer,
+                    file: Some(file.clone()),
+                },
+            );
+        }
+        sources.insert(file.clone(), content);
+    }
+
+    let mut value = Value::Object(merged.clone());
+    let errors = Variables::from_map(merged).interpolate(&mut value, &super::schema::config_schema());
+    if let Some((pointer, _)) = errors.first() {
+        let file_of = |pointer: &str| {
+            let key = pointer.split('/').nth(1).unwrap_or_default();
+            origins.get(key).and_then(|o: &ConfigOrigin| o.file.clone())
+        };
+        let first_file = file_of(pointer).unwrap_or_default();
+        let message = errors
+            .iter()
+            .map(|(pointer, message)| {
+                let file = file_of(pointer).unwrap_or_default();
+                let map = SourceMap::new(sources.get(&file).map(String::as_str).unwrap_or(""));
+                let position = map.locate_pointer(pointer);
+                if file == first_file {
+                    format!("{}:{}: {}", position.line, position.column, message)
+                } else {
+                    format!(
+                        "{}:{}:{}: {}",
+                        file.display(),
+                        position.line,
+                        position.column,
+                        message
+                    )
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        return Err(GlowError::InvalidConfig {
+            path: first_file.display().to_string(),
+            message,
+        });
+    }
+
+    let config = serde_json::from_value(value).map_err(|e| GlowError::InvalidConfig {
+        path: layers
+            .iter()
+            .map(|(_, file)| file.display().to_string())
+            .collect::<Vec<_>>()
+            .join(", "),
+        message: e.to_string(),
+    })?;
+
+    Ok(LayeredConfig { config, origins })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OutputFormat;
+
+    #[test]
+    fn test_layers_override_in_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let user = dir.path().join("user.yaml");
+        let project = dir.path().join(CONFIG_FILE);
+        let local = dir.path().join(LOCAL_CONFIG_FILE);
+        std::fs::write(
+            &user,
+            "version: \"9.9.9\"\nactor: ana\noutput_format: json\ndefault_template: story.md\n",
+        )
+        .unwrap();
+        std::fs::write(
+            &project,
+            "version: \"0.2.0\"\nproject_name: Atlas\ndefault_template: task.md\n",
+        )
+        .unwrap();
+        std::fs::write(&local, "actor: \"${config:project_name} bot\"\n").unwrap();
+
+        let layered = merge_layers(&[
+            (ConfigLayer::User, user.clone()),
+            (ConfigLayer::Project, project.clone()),
+            (ConfigLayer::Local, local.clone()),
+            (ConfigLayer::Local, dir.path().join("missing.yaml")),
+        ])
+        .unwrap();
+
+        assert_eq!(layered.config.version, "0.2.0");
+        assert_eq!(layered.config.actor.as_deref(), Some("Atlas bot"));
+        assert_eq!(layered.config.output_format, OutputFormat::Json);
+        assert_eq!(layered.config.default_template, "task.md");
+        assert_eq!(layered.origin("actor").layer, ConfigLayer::Local);
+        assert_eq!(layered.origin("output_format").file.as_ref(), Some(&user));
+        assert_eq!(
+            layered.origin("default_template").layer,
+            ConfigLayer::Project
+        );
+        assert_eq!(layered.origin("data_folder"), &ConfigOrigin::DEFAULT);
+
+        let values = layered.values();
+        assert_eq!(values[0].0, "version");
+        assert!(values
+            .iter()
+            .any(|(key, value, _)| key == "preset" && value.is_null()));
+    }
+}
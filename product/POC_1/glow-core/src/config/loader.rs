@@ -7,17 +7,32 @@ use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 
 use crate::error::{GlowError, Result};
-use crate::model::{ClassificationDictionary, LinkType, ParameterType, StepDefinition};
+use crate::model::{
+    ClassificationDictionary, LinkType, ParameterType, RankingConfig, StalenessConfig,
+    StepDefinition, WipLimit,
+};
+use crate::storage::{write_atomic, StepFileManager};
 use crate::DEFAULT_DATA_DIR;
 
+use super::diagnostics::{diagnose, ConfigCheck};
+use super::interpolate::Variables;
+use super::layers::{project_config, LayeredConfig};
+use super::migrate::{
+    file_version, migrate_content, FileKind, FileMigration, MigrationPlan, SchemaVersion,
+    SCHEMA_VERSION,
+};
+
 /// Project configuration (.glow/config.yaml)
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct Config {
     /// Schema version for migration support
     #[serde(default = "default_version")]
+    #[schemars(regex(pattern = r"^\d+\.\d+\.\d+$"))]
     pub version: String,
     /// Human-readable project name
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(length(min = 1))]
     pub project_name: Option<String>,
     /// Folder name for process data storage
     #[serde(default = "default_data_folder")]
@@ -31,10 +46,129 @@ pub struct Config {
     /// Default template file name for steps
     #[serde(default = "default_template")]
     pub default_template: String,
+    /// How step data files are checked when read
+    #[serde(default)]
+    pub step_validation: StepValidation,
+    /// File format of new step data files
+    #[serde(default)]
+    pub step_format: StepFormat,
+    /// Name of the person using glow
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// Default output format of commands that support several
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    /// Preset used by `glow project init` when none is given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preset: Option<String>,
+}
+
+/// Output format of commands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// JSON
+    Json,
+}
+
+impl OutputFormat {
+    /// Get the format name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+        }
+    }
+}
+
+/// Keys a user-level config typically sets; a project config leaves them
+/// out while they have their default value
+const USER_LEVEL_KEYS: &[&str] = &[
+    "templates_folder",
+    "default_template",
+    "step_validation",
+    "output_format",
+];
+
+/// How step data files are checked when read
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StepValidation {
+    /// Unknown frontmatter keys are errors
+    Strict,
+    /// Unknown keys at the top level and in `attr` are kept as they are
+    #[default]
+    Lenient,
+}
+
+/// File format of step data files
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum StepFormat {
+    /// Markdown with YAML frontmatter
+    #[default]
+    Markdown,
+    /// YAML
+    Yaml,
+    /// JSON
+    Json,
+    /// TOML
+    Toml,
+}
+
+impl StepFormat {
+    /// Every format, the default first
+    pub const ALL: [StepFormat; 4] = [
+        StepFormat::Markdown,
+        StepFormat::Yaml,
+        StepFormat::Json,
+        StepFormat::Toml,
+    ];
+
+    /// Get the format name
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StepFormat::Markdown => "markdown",
+            StepFormat::Yaml => "yaml",
+            StepFormat::Json => "json",
+            StepFormat::Toml => "toml",
+        }
+    }
+
+    /// Get the extension of step data files in this format
+    pub fn extension(&self) -> &'static str {
+        match self {
+            StepFormat::Markdown => "md",
+            StepFormat::Yaml => "yaml",
+            StepFormat::Json => "json",
+            StepFormat::Toml => "toml",
+        }
+    }
+
+    /// Get the format of a step data file from its extension
+    pub fn of_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?;
+        Self::ALL.into_iter().find(|f| f.extension() == extension)
+    }
+}
+
+impl std::str::FromStr for StepFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let s = s.trim().to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|f| f.as_str() == s || f.extension() == s)
+            .ok_or_else(|| format!("Unknown step format: {} (expected markdown, yaml, json or toml)", s))
+    }
 }
 
 fn default_version() -> String {
-    "0.1.0".to_string()
+    SCHEMA_VERSION.to_string()
 }
 
 fn default_data_folder() -> String {
@@ -62,6 +196,11 @@ impl Default for Config {
             process_config: default_process_config(),
             templates_folder: default_templates_folder(),
             default_template: default_template(),
+            step_validation: StepValidation::default(),
+            step_format: StepFormat::default(),
+            actor: None,
+            output_format: OutputFormat::default(),
+            preset: None,
         }
     }
 }
@@ -83,10 +222,15 @@ impl Config {
 
 /// Process configuration (.glow/process_config.yaml)
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ProcessConfig {
     /// Schema version for migration support
     #[serde(default = "default_version")]
+    #[schemars(regex(pattern = r"^\d+\.\d+\.\d+$"))]
     pub version: String,
+    /// Other config files to take definitions from (relative or from the user library)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub imports: Vec<String>,
     /// Classification dimensions
     #[serde(default)]
     pub classifications: Vec<ClassificationDictionary>,
@@ -96,6 +240,18 @@ pub struct ProcessConfig {
     /// Custom link type definitions
     #[serde(default)]
     pub link_types: Vec<LinkType>,
+    /// Next action ranking (defaults apply when omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ranking: Option<RankingConfig>,
+    /// Work-in-progress limits
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub wip_limits: Vec<WipLimit>,
+    /// Stale work thresholds (defaults apply when omitted)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staleness: Option<StalenessConfig>,
+    /// Reusable process fragments for steps to `uses`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fragments: Vec<StepDefinition>,
     /// Root process step definition
     pub root_process: StepDefinition,
 }
@@ -104,9 +260,14 @@ impl Default for ProcessConfig {
     fn default() -> Self {
         Self {
             version: default_version(),
+            imports: Vec::new(),
             classifications: Vec::new(),
             parameter_types: Vec::new(),
             link_types: Vec::new(),
+            ranking: None,
+            wip_limits: Vec::new(),
+            staleness: None,
+            fragments: Vec::new(),
             root_process: StepDefinition::new_root(),
         }
     }
@@ -123,6 +284,11 @@ impl ProcessConfig {
         self.parameter_types.iter().find(|p| p.id == id)
     }
 
+    /// Find a process fragment by ID
+    pub fn find_fragment(&self, id: &str) -> Option<&StepDefinition> {
+        self.fragments.iter().find(|f| f.id == id)
+    }
+
     /// Find a link type by ID
     pub fn find_link_type(&self, id: &str) -> Option<&LinkType> {
         self.link_types.iter().find(|l| l.id == id)
@@ -165,35 +331,279 @@ impl ConfigLoader {
         }
     }
 
-    /// Load the main config file
+    /// Load the main config file, merged with the user config and `local.yaml`
+    ///
+    /// Placeholders are resolved; `${config:...}` refers to the merged
+    /// keys. Fails if the file was written for a newer schema version.
     pub fn load_config(&self) -> Result<Config> {
+        Ok(self.load_layered_config()?.config)
+    }
+
+    /// Load the main config file with the layer each value came from
+    pub fn load_layered_config(&self) -> Result<LayeredConfig> {
         let config_path = self.config_dir.join(super::CONFIG_FILE);
-        self.load_yaml(&config_path)
+        let layered = project_config(&self.config_dir)?;
+        let config = &layered.config;
+        let version = config.version.parse().map_err(|message| GlowError::InvalidConfig {
+            path: config_path.display().to_string(),
+            message,
+        })?;
+        self.check_version(&config_path, version)?;
+        Ok(layered)
     }
 
-    /// Load the process config file
+    /// Load and validate the process config file
+    ///
+    /// Fails with every syntax, schema and semantic problem found, each with
+    /// its line and column.
     pub fn load_process_config(&self, config: &Config) -> Result<ProcessConfig> {
         let process_config_path = self.config_dir.join(&config.process_config);
-        self.load_yaml(&process_config_path)
+        let content = self.read_file(&process_config_path)?;
+        self.check_file_version(&process_config_path, &content)?;
+
+        match diagnose(&process_config_path, &content, &Variables::new(config)) {
+            (Some(process_config), diagnostics) if diagnostics.is_empty() => Ok(process_config),
+            (_, diagnostics) => Err(GlowError::InvalidProcessConfig {
+                diagnostics: diagnostics.iter().map(|d| d.to_string()).collect(),
+            }),
+        }
     }
 
-    /// Load a YAML file
-    fn load_yaml<T: serde::de::DeserializeOwned>(&self, path: &Path) -> Result<T> {
-        let content = std::fs::read_to_string(path).map_err(|e| GlowError::FileReadError {
-            path: path.to_path_buf(),
-            source: e,
+    /// Check the process config file without failing on problems
+    pub fn check_process_config(&self, config: &Config) -> Result<ConfigCheck> {
+        let process_config_path = self.config_dir.join(&config.process_config);
+        let content = self.read_file(&process_config_path)?;
+        self.check_file_version(&process_config_path, &content)?;
+        Ok(ConfigCheck::new(
+            &process_config_path,
+            &content,
+            &Variables::new(config),
+        ))
+    }
+
+    /// Fail if a file was written for a newer schema version than supported
+    fn check_version(&self, path: &Path, version: SchemaVersion) -> Result<()> {
+        let supported = SchemaVersion::current();
+        if version > supported {
+            return Err(GlowError::UnsupportedVersion {
+                path: path.to_path_buf(),
+                version: version.to_string(),
+                supported: supported.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Check the version of a config file that parses; syntax errors are
+    /// left to the file's own diagnostics
+    fn check_file_version(&self, path: &Path, content: &str) -> Result<()> {
+        match file_version(content) {
+            Ok(version) => self.check_version(path, version),
+            Err(_) => Ok(()),
+        }
+    }
+
+    /// Plan migrating the config files and step data files to the current
+    /// schema version
+    pub fn plan_migration(&self) -> Result<MigrationPlan> {
+        let mut files = Vec::new();
+
+        let config_path = self.config_dir.join(super::CONFIG_FILE);
+        let content = self.read_file(&config_path)?;
+        let from = self.migration_version(&config_path, &content)?;
+        let content = self.plan_file(FileKind::Config, from, config_path, content, &mut files)?;
+        let config: Config = serde_yaml::from_str(&content)?;
+
+        let process_config_path = self.config_dir.join(&config.process_config);
+        let content = self.read_file(&process_config_path)?;
+        let version = self.migration_version(&process_config_path, &content)?;
+        self.plan_file(FileKind::ProcessConfig, version, process_config_path, content, &mut files)?;
+
+        // Step data files follow config.yaml's version; documents, artifacts
+        // and iteration folders are left out
+        let data_dir = config.data_dir(&self.project_root);
+        let step_files = StepFileManager::new(data_dir.clone());
+        let mut step_paths: Vec<PathBuf> = walkdir::WalkDir::new(&data_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.into_path())
+            .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == "md"))
+            .filter(|path| step_files.step_fqid(path).is_some())
+            .collect();
+        step_paths.sort();
+        let mut skipped = Vec::new();
+        for path in step_paths {
+            let content = self.read_file(&path)?;
+            if let Err(e) = self.plan_file(FileKind::StepData, from, path.clone(), content, &mut files) {
+                skipped.push((path, e.to_string()));
+            }
+        }
+
+        Ok(MigrationPlan {
+            from,
+            to: SchemaVersion::current(),
+            files,
+            skipped,
+        })
+    }
+
+    /// Read a file's version for migration, refusing newer versions
+    fn migration_version(&self, path: &Path, content: &str) -> Result<SchemaVersion> {
+        let version = file_version(content).map_err(|e| GlowError::InvalidConfig {
+            path: path.display().to_string(),
+            message: e.to_string(),
         })?;
+        self.check_version(path, version)?;
+        Ok(version)
+    }
+
+    /// Add a file's migration to the plan; returns the migrated content
+    fn plan_file(
+        &self,
+        kind: FileKind,
+        version: SchemaVersion,
+        path: PathBuf,
+        content: String,
+        files: &mut Vec<FileMigration>,
+    ) -> Result<String> {
+        let migrated = migrate_content(kind, version, &content).map_err(|e| GlowError::InvalidConfig {
+            path: path.display().to_string(),
+            message: e.to_string(),
+        })?;
+
+        match migrated {
+            Some((migrated, changes)) => {
+                files.push(FileMigration {
+                    path,
+                    changes,
+                    content: migrated.clone(),
+                });
+                Ok(migrated)
+            }
+            None => Ok(content),
+        }
+    }
+
+    /// Write a migration plan, backing up every file it changes first
+    ///
+    /// Backups go to `.glow/backups/<timestamp>-v<version>/`, keeping paths
+    /// relative to the project root. Returns the backup folder, or None if
+    /// the project was up to date.
+    pub fn migrate(&self, plan: &MigrationPlan) -> Result<Option<PathBuf>> {
+        if plan.is_empty() {
+            return Ok(None);
+        }
 
-        serde_yaml::from_str(&content).map_err(|e| GlowError::YamlParseError {
-            message: format!("Failed to parse {}: {}", path.display(), e),
+        let stamp = chrono::Utc::now().format("%Y%m%dT%H%M%S");
+        let backup_dir = self
+            .config_dir
+            .join(super::BACKUPS_FOLDER)
+            .join(format!("{}-v{}", stamp, plan.from));
+
+        for file in &plan.files {
+            let relative = match file.path.strip_prefix(&self.project_root) {
+                Ok(relative) => relative.to_path_buf(),
+                Err(_) => PathBuf::from(file.path.file_name().unwrap_or_default()),
+            };
+            let backup = backup_dir.join(relative);
+            if let Some(parent) = backup.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(&file.path, &backup).map_err(|e| GlowError::FileWriteError {
+                path: backup.clone(),
+                source: e,
+            })?;
+        }
+
+        for file in &plan.files {
+            write_atomic(&file.path, file.content.as_bytes())?;
+        }
+
+        Ok(Some(backup_dir))
+    }
+
+    /// Read a config file
+    fn read_file(&self, path: &Path) -> Result<String> {
+        std::fs::read_to_string(path).map_err(|e| GlowError::FileReadError {
+            path: path.to_path_buf(),
             source: e,
         })
     }
 
     /// Save a config file
+    ///
+    /// User-level keys at their default value are left out, so they do not
+    /// override the user config.
     pub fn save_config(&self, config: &Config) -> Result<()> {
         let config_path = self.config_dir.join(super::CONFIG_FILE);
-        self.save_yaml(&config_path, config)
+        let mut value = serde_yaml::to_value(config)?;
+        let defaults = serde_yaml::to_value(Config::default())?;
+        if let Some(mapping) = value.as_mapping_mut() {
+            for key in USER_LEVEL_KEYS {
+                if mapping.get(*key) == defaults.get(*key) {
+                    mapping.remove(*key);
+                }
+            }
+        }
+        self.save_yaml(&config_path, &value)
+    }
+
+    /// Set one key of the project config file, keeping the others as written
+    ///
+    /// Unlike `save_config`, values coming from the user config are not
+    /// written into the project config.
+    pub fn save_config_key(&self, key: &str, value: impl serde::Serialize) -> Result<()> {
+        let config_path = self.config_dir.join(super::CONFIG_FILE);
+        let mut config = match std::fs::read_to_string(&config_path) {
+            Ok(content) => serde_yaml::from_str::<serde_yaml::Value>(&content)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => serde_yaml::Value::Null,
+            Err(e) => {
+                return Err(GlowError::FileReadError {
+                    path: config_path,
+                    source: e,
+                })
+            }
+        };
+        if !config.is_mapping() {
+            config = serde_yaml::Value::Mapping(Default::default());
+        }
+        if let Some(mapping) = config.as_mapping_mut() {
+            mapping.insert(key.into(), serde_yaml::to_value(value)?);
+        }
+        self.save_yaml(&config_path, &config)
+    }
+
+    /// Edit the text of the process config file as written, with imports, fragments and variables unresolved
+    ///
+    /// The edit returns the new text, or None to leave the file alone. The
+    /// file is saved only if the edit changed it; returns whether it did.
+    pub fn edit_process_config(
+        &self,
+        config: &Config,
+        edit: impl FnOnce(&str) -> Result<Option<String>>,
+    ) -> Result<bool> {
+        let process_config_path = self.config_dir.join(&config.process_config);
+        let content = self.read_file(&process_config_path)?;
+        // A file that isn't valid YAML is not edited
+        serde_yaml::from_str::<serde_yaml::Value>(&content)?;
+
+        match edit(&content)? {
+            Some(edited) if edited != content => {
+                write_atomic(&process_config_path, edited.as_bytes())?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    /// Read the process config file as written
+    pub fn read_process_config_file(&self, config: &Config) -> Result<String> {
+        self.read_file(&self.config_dir.join(&config.process_config))
+    }
+
+    /// Put back the process config file as read by `read_process_config_file`
+    pub fn restore_process_config_file(&self, config: &Config, content: &str) -> Result<()> {
+        write_atomic(&self.config_dir.join(&config.process_config), content.as_bytes())
     }
 
     /// Save the process config file
@@ -210,18 +620,7 @@ impl ConfigLoader {
         let schema_comment = self.get_schema_comment(path);
         let content = format!("{}{}", schema_comment, content);
 
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| GlowError::FileWriteError {
-                path: parent.to_path_buf(),
-                source: e,
-            })?;
-        }
-
-        std::fs::write(path, content).map_err(|e| GlowError::FileWriteError {
-            path: path.to_path_buf(),
-            source: e,
-        })
+        write_atomic(path, content.as_bytes())
     }
 
     /// Get schema comment for a config file
@@ -257,7 +656,7 @@ mod tests {
     #[test]
     fn test_config_default() {
         let config = Config::default();
-        assert_eq!(config.version, "0.1.0");
+        assert_eq!(config.version, SCHEMA_VERSION);
         assert_eq!(config.data_folder, "glow");
     }
 
@@ -294,6 +693,9 @@ mod tests {
             classification: None,
             expectations: None,
             template: None,
+            estimate: None,
+            due: None,
+            iteration_length: None,
             inputs: Vec::new(),
             outputs: Vec::new(),
             scope: Vec::new(),
@@ -303,15 +705,22 @@ mod tests {
                 classification: None,
                 expectations: None,
                 template: None,
+                estimate: None,
+                due: None,
+                iteration_length: None,
                 inputs: Vec::new(),
                 outputs: Vec::new(),
                 scope: Vec::new(),
                 steps: Vec::new(),
                 links: Vec::new(),
                 allow_iterations: true,
+                uses: None,
+                with: Default::default(),
             }],
             links: Vec::new(),
             allow_iterations: true,
+            uses: None,
+            with: Default::default(),
         });
 
         assert!(process_config.find_step_definition("ROOT").is_some());
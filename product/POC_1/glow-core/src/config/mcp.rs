@@ -0,0 +1,149 @@
+//! MCP server configuration
+//!
+//! Optional settings for the glow-mcp server (.glow/mcp_config.yaml).
+
+use serde::{Deserialize, Serialize};
+
+/// Glow MCP server configuration (.glow/mcp_config.yaml)
+#[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct McpConfig {
+    /// Schema version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(regex(pattern = r"^\d+\.\d+\.\d+$"))]
+    pub version: Option<String>,
+    /// Server identification
+    #[serde(default)]
+    pub server: McpServerSettings,
+    /// Resource exposure settings
+    #[serde(default)]
+    pub resources: McpResourceSettings,
+    /// Tool behavior settings
+    #[serde(default)]
+    pub tools: McpToolSettings,
+    /// Prompt template settings
+    #[serde(default)]
+    pub prompts: McpPromptSettings,
+}
+
+/// Server identification
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct McpServerSettings {
+    /// Server name for MCP protocol
+    #[serde(default = "default_server_name")]
+    pub name: String,
+    /// Server version to report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+impl Default for McpServerSettings {
+    fn default() -> Self {
+        Self {
+            name: default_server_name(),
+            version: None,
+        }
+    }
+}
+
+fn default_server_name() -> String {
+    "glow-mcp".to_string()
+}
+
+/// Resource exposure settings
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct McpResourceSettings {
+    /// Include artifact file contents in resources
+    #[serde(default = "default_true")]
+    pub expose_artifacts: bool,
+    /// Maximum depth for context tree traversal
+    #[serde(default = "default_max_context_depth")]
+    #[schemars(range(min = 1, max = 20))]
+    pub max_context_depth: u32,
+    /// Maximum artifact file size to include (KB)
+    #[serde(default = "default_max_file_size_kb")]
+    #[schemars(range(min = 1))]
+    pub max_file_size_kb: u32,
+}
+
+impl Default for McpResourceSettings {
+    fn default() -> Self {
+        Self {
+            expose_artifacts: true,
+            max_context_depth: default_max_context_depth(),
+            max_file_size_kb: default_max_file_size_kb(),
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_context_depth() -> u32 {
+    5
+}
+
+fn default_max_file_size_kb() -> u32 {
+    512
+}
+
+/// Tool behavior settings
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct McpToolSettings {
+    /// Tools requiring user confirmation before execution
+    #[serde(default = "default_require_confirmation")]
+    pub require_confirmation: Vec<String>,
+    /// Disable all write operations
+    #[serde(default)]
+    pub read_only_mode: bool,
+    /// Whitelist of allowed tools (empty = all allowed)
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Blacklist of denied tools
+    #[serde(default)]
+    pub denied_tools: Vec<String>,
+}
+
+impl Default for McpToolSettings {
+    fn default() -> Self {
+        Self {
+            require_confirmation: default_require_confirmation(),
+            read_only_mode: false,
+            allowed_tools: Vec::new(),
+            denied_tools: Vec::new(),
+        }
+    }
+}
+
+fn default_require_confirmation() -> Vec<String> {
+    vec!["glow_finish_step".to_string()]
+}
+
+/// Prompt template settings
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct McpPromptSettings {
+    /// Folder for custom prompt templates
+    #[serde(default = "default_custom_prompts_folder")]
+    pub custom_prompts_folder: String,
+    /// List of enabled prompt names (empty = all)
+    #[serde(default)]
+    pub enabled_prompts: Vec<String>,
+}
+
+impl Default for McpPromptSettings {
+    fn default() -> Self {
+        Self {
+            custom_prompts_folder: default_custom_prompts_folder(),
+            enabled_prompts: Vec::new(),
+        }
+    }
+}
+
+fn default_custom_prompts_folder() -> String {
+    "prompts/".to_string()
+}
@@ -0,0 +1,381 @@
+//! Schema migrations
+//!
+//! Brings config files and step data files written by older versions of glow
+//! up to the current schema version, one ordered migration at a time. Step
+//! data files carry no version of their own and follow `config.yaml`.
+
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use serde_json::{Map, Value};
+
+use crate::error::{GlowError, Result};
+use crate::storage::{parse_frontmatter, render_frontmatter};
+
+/// Schema version this build of glow reads and writes
+pub const SCHEMA_VERSION: &str = "0.2.0";
+
+/// Version assumed for files that do not state one
+const UNVERSIONED: SchemaVersion = SchemaVersion::new(0, 1, 0);
+
+/// Schema version (MAJOR.MINOR.PATCH)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SchemaVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl SchemaVersion {
+    /// Create a version
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Get the version this build supports
+    pub fn current() -> Self {
+        SCHEMA_VERSION
+            .parse()
+            .expect("SCHEMA_VERSION is a valid version")
+    }
+}
+
+impl fmt::Display for SchemaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for SchemaVersion {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('.').collect();
+        let number = |part: &str| part.parse::<u32>().ok();
+        match parts.as_slice() {
+            [major, minor, patch] => match (number(major), number(minor), number(patch)) {
+                (Some(major), Some(minor), Some(patch)) => Ok(Self::new(major, minor, patch)),
+                _ => Err(format!(
+                    "invalid schema version '{}' (expected MAJOR.MINOR.PATCH)",
+                    s
+                )),
+            },
+            _ => Err(format!(
+                "invalid schema version '{}' (expected MAJOR.MINOR.PATCH)",
+                s
+            )),
+        }
+    }
+}
+
+/// Kind of file a migration changes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+    /// `config.yaml`
+    Config,
+    /// `process_config.yaml`
+    ProcessConfig,
+    /// Step data file (frontmatter)
+    StepData,
+}
+
+/// Changes a mapping in place and describes each change
+type Transform = fn(&mut Map<String, Value>) -> Vec<String>;
+
+/// Migration from the previous schema version to `to`
+pub struct Migration {
+    /// Version the migration brings files to
+    pub to: SchemaVersion,
+    /// What changed in this version
+    pub description: &'static str,
+    config: Option<Transform>,
+    process_config: Option<Transform>,
+    step_data: Option<Transform>,
+}
+
+impl Migration {
+    fn transform(&self, kind: FileKind) -> Option<Transform> {
+        match kind {
+            FileKind::Config => self.config,
+            FileKind::ProcessConfig => self.process_config,
+            FileKind::StepData => self.step_data,
+        }
+    }
+}
+
+/// All migrations, oldest first
+pub const MIGRATIONS: &[Migration] = &[Migration {
+    to: SchemaVersion::new(0, 2, 0),
+    description: "Legacy config keys and status timestamps",
+    config: Some(legacy_config_keys),
+    process_config: None,
+    step_data: Some(backfill_status_since),
+}];
+
+/// Get the migrations needed to bring a file at `version` up to date
+pub fn pending(version: SchemaVersion) -> impl Iterator<Item = &'static Migration> {
+    MIGRATIONS.iter().filter(move |m| m.to > version)
+}
+
+/// Read the schema version stated in a config file
+///
+/// Files without a version predate versioning and count as 0.1.0.
+pub fn file_version(content: &str) -> Result<SchemaVersion> {
+    let value: Value = serde_yaml::from_str(content)?;
+    match value.get("version") {
+        None | Some(Value::Null) => Ok(UNVERSIONED),
+        Some(Value::String(version)) => version
+            .parse()
+            .map_err(|message| GlowError::ValidationError { message }),
+        Some(other) => Err(GlowError::ValidationError {
+            message: format!(
+                "invalid schema version '{}' (expected MAJOR.MINOR.PATCH)",
+                other
+            ),
+        }),
+    }
+}
+
+/// Migration of one file
+#[derive(Debug, Clone)]
+pub struct FileMigration {
+    /// File to rewrite
+    pub path: PathBuf,
+    /// Description of each change
+    pub changes: Vec<String>,
+    /// Migrated content
+    pub content: String,
+}
+
+/// Plan for migrating a project to the current schema version
+#[derive(Debug, Clone)]
+pub struct MigrationPlan {
+    /// Version of the project (from `config.yaml`)
+    pub from: SchemaVersion,
+    /// Version the project is migrated to
+    pub to: SchemaVersion,
+    /// Files that change
+    pub files: Vec<FileMigration>,
+    /// Step data files left as they are because they don't parse, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+impl MigrationPlan {
+    /// Check if the project is already up to date
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Apply pending migrations to a file's content
+///
+/// Config files are brought to the current version; their comments are kept
+/// unless a migration changes more than the version. Returns None if the
+/// file needs no change.
+pub fn migrate_content(
+    kind: FileKind,
+    version: SchemaVersion,
+    content: &str,
+) -> Result<Option<(String, Vec<String>)>> {
+    let current = SchemaVersion::current();
+    if version >= current {
+        return Ok(None);
+    }
+
+    if kind == FileKind::StepData {
+        let (mut frontmatter, body) = parse_frontmatter(content)?;
+        let Some(map) = frontmatter
+            .as_object_mut()
+            .filter(|m| m.contains_key("attr"))
+        else {
+            return Ok(None);
+        };
+        let changes = apply(kind, version, map);
+        if changes.is_empty() {
+            return Ok(None);
+        }
+        return Ok(Some((render_frontmatter(&frontmatter, &body)?, changes)));
+    }
+
+    let mut value: Value = serde_yaml::from_str(content)?;
+    let Some(map) = value.as_object_mut() else {
+        return Ok(None);
+    };
+    let mut changes = apply(kind, version, map);
+
+    let migrated = if changes.is_empty() {
+        set_version_line(content, &current)
+    } else {
+        map.insert("version".to_string(), Value::String(current.to_string()));
+        format!(
+            "{}{}",
+            leading_comments(content),
+            serde_yaml::to_string(&value)?
+        )
+    };
+    changes.push(format!("version {} -> {}", version, current));
+
+    Ok(Some((migrated, changes)))
+}
+
+fn apply(kind: FileKind, version: SchemaVersion, map: &mut Map<String, Value>) -> Vec<String> {
+    pending(version)
+        .filter_map(|m| m.transform(kind))
+        .flat_map(|transform| transform(map))
+        .collect()
+}
+
+/// Replace the top-level `version:` line, or add one after the leading comments
+fn set_version_line(content: &str, version: &SchemaVersion) -> String {
+    let line = format!("version: \"{}\"", version);
+    let mut replaced = false;
+    let mut lines: Vec<String> = content
+        .lines()
+        .map(|l| {
+            if !replaced && l.starts_with("version:") {
+                replaced = true;
+                line.clone()
+            } else {
+                l.to_string()
+            }
+        })
+        .collect();
+
+    if !replaced {
+        let at = lines.iter().take_while(|l| l.starts_with('#')).count();
+        lines.insert(at, line);
+    }
+
+    let mut migrated = lines.join("\n");
+    if content.ends_with('\n') {
+        migrated.push('\n');
+    }
+    migrated
+}
+
+fn leading_comments(content: &str) -> String {
+    content
+        .lines()
+        .take_while(|l| l.starts_with('#'))
+        .map(|l| format!("{}\n", l))
+        .collect()
+}
+
+/// 0.2.0: `glow_dir` became `data_folder`; `project_id` and `process` were dropped
+fn legacy_config_keys(config: &mut Map<String, Value>) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if let Some(glow_dir) = config.remove("glow_dir") {
+        if config.contains_key("data_folder") {
+            changes.push("removed glow_dir (data_folder is set)".to_string());
+        } else {
+            config.insert("data_folder".to_string(), glow_dir);
+            changes.push("renamed glow_dir to data_folder".to_string());
+        }
+    }
+    if let Some(project_id) = config.remove("project_id") {
+        if config.contains_key("project_name") {
+            changes.push("removed project_id (project_name is set)".to_string());
+        } else {
+            config.insert("project_name".to_string(), project_id);
+            changes.push("renamed project_id to project_name".to_string());
+        }
+    }
+    if config.remove("process").is_some() {
+        changes.push("removed process (the process is defined in process_config)".to_string());
+    }
+
+    changes
+}
+
+/// 0.2.0: steps record when they entered their status
+fn backfill_status_since(step: &mut Map<String, Value>) -> Vec<String> {
+    let Some(attr) = step.get_mut("attr").and_then(Value::as_object_mut) else {
+        return Vec::new();
+    };
+    if attr.get("status_since").is_some_and(|v| !v.is_null()) {
+        return Vec::new();
+    }
+
+    let source = match attr.get("status").and_then(Value::as_str) {
+        Some("done") => "finished_at",
+        Some("in-progress") => "started_at",
+        _ => return Vec::new(),
+    };
+    match attr.get(source).filter(|v| !v.is_null()).cloned() {
+        Some(at) => {
+            attr.insert("status_since".to_string(), at);
+            vec![format!("set attr.status_since from {}", source)]
+        }
+        None => Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_version_order() {
+        let v: SchemaVersion = "0.10.0".parse().unwrap();
+        assert!(v > "0.9.3".parse().unwrap());
+        assert_eq!(v.to_string(), "0.10.0");
+        assert!("1.0".parse::<SchemaVersion>().is_err());
+        assert_eq!(MIGRATIONS.last().unwrap().to, SchemaVersion::current());
+    }
+
+    #[test]
+    fn test_migrate_config_keeps_comments_when_only_version_changes() {
+        let content = "# My process\nversion: \"0.1.0\"\nroot_process:\n  id: ROOT\n";
+        let (migrated, changes) = migrate_content(FileKind::ProcessConfig, UNVERSIONED, content)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            migrated,
+            "# My process\nversion: \"0.2.0\"\nroot_process:\n  id: ROOT\n"
+        );
+        assert_eq!(changes, vec!["version 0.1.0 -> 0.2.0"]);
+
+        let current = SchemaVersion::current();
+        assert!(migrate_content(FileKind::ProcessConfig, current, &migrated)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_migrate_legacy_config_keys() {
+        let content =
+            "version: \"0.1.0\"\nproject_id: \"TEST\"\nprocess: \"simple\"\nglow_dir: \"data\"\n";
+        let (migrated, changes) = migrate_content(FileKind::Config, UNVERSIONED, content)
+            .unwrap()
+            .unwrap();
+        let config: crate::config::Config = serde_yaml::from_str(&migrated).unwrap();
+        assert_eq!(config.data_folder, "data");
+        assert_eq!(config.project_name.as_deref(), Some("TEST"));
+        assert_eq!(config.version, SCHEMA_VERSION);
+        assert!(!migrated.contains("process:"));
+        assert_eq!(changes.len(), 4);
+    }
+
+    #[test]
+    fn test_migrate_step_data_backfills_status_since() {
+        let content = "---\nattr:\n  id: A\n  status: done\n  finished_at: 2026-01-05T10:00:00Z\n---\n\n# A\n";
+        let (migrated, changes) = migrate_content(FileKind::StepData, UNVERSIONED, content)
+            .unwrap()
+            .unwrap();
+        assert_eq!(changes, vec!["set attr.status_since from finished_at"]);
+        let (frontmatter, body) = parse_frontmatter(&migrated).unwrap();
+        assert_eq!(frontmatter["attr"]["status_since"], "2026-01-05T10:00:00Z");
+        assert_eq!(body.trim(), "# A");
+
+        let waiting = "---\nattr:\n  id: B\n  status: wait\n---\n";
+        assert!(migrate_content(FileKind::StepData, UNVERSIONED, waiting)
+            .unwrap()
+            .is_none());
+    }
+}
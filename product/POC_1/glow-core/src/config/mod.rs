@@ -2,23 +2,46 @@
 //!
 //! Handles loading and validation of configuration files.
 
+pub mod check;
+pub mod compose;
+pub mod diagnostics;
+pub mod interpolate;
+pub mod layers;
 mod loader;
+pub mod mcp;
+pub mod migrate;
+pub mod preset;
 pub mod schema;
+pub mod source;
 
-pub use loader::{Config, ConfigLoader, ProcessConfig};
+pub use check::{check_process_config, ConfigFinding, FindingKind};
+pub use diagnostics::{ConfigCheck, ConfigDiagnostic};
+pub use interpolate::Variables;
+pub use layers::{ConfigLayer, ConfigOrigin, LayeredConfig};
+pub use loader::{Config, ConfigLoader, OutputFormat, ProcessConfig, StepFormat, StepValidation};
+pub use mcp::McpConfig;
+pub use migrate::{MigrationPlan, SchemaVersion, SCHEMA_VERSION};
+pub use preset::Preset;
+pub use source::{SourceMap, SourcePosition};
 pub use schema::{validate_config, CONFIG_SCHEMA, MCP_CONFIG_SCHEMA, PROCESS_CONFIG_SCHEMA, STEP_DATA_SCHEMA};
 
 use std::path::{Path, PathBuf};
 
 use crate::error::{GlowError, Result};
-use crate::{CONFIG_DIR_ENV, DEFAULT_CONFIG_DIR};
+use crate::{
+    CONFIG_DIR_ENV, DEFAULT_CONFIG_DIR, LIBRARY_DIR_ENV, PRESET_DIR_ENV, USER_CONFIG_DIR_ENV,
+};
 
 /// Default configuration file names
 pub const CONFIG_FILE: &str = "config.yaml";
+pub const LOCAL_CONFIG_FILE: &str = "local.yaml";
 pub const PROCESS_CONFIG_FILE: &str = "process_config.yaml";
 pub const TEMPLATES_FOLDER: &str = "templates";
 pub const SCHEMAS_FOLDER: &str = "schemas";
 pub const DEFAULT_TEMPLATE: &str = "any-step.md";
+pub const LIBRARY_FOLDER: &str = "library";
+pub const PRESETS_FOLDER: &str = "presets";
+pub const BACKUPS_FOLDER: &str = "backups";
 
 /// Find the configuration directory
 pub fn find_config_dir(project_root: &Path, override_path: Option<&Path>) -> Result<PathBuf> {
@@ -65,3 +88,42 @@ pub fn schemas_dir(config_dir: &Path) -> PathBuf {
 pub fn templates_dir(config_dir: &Path) -> PathBuf {
     config_dir.join(TEMPLATES_FOLDER)
 }
+
+/// Get the user-level config directory
+///
+/// `DEV_GLOW_USER_CONFIG_DIR` overrides the platform location
+/// (e.g., `~/.config/glow` on Linux).
+pub fn user_config_dir() -> Option<PathBuf> {
+    if let Ok(env_path) = std::env::var(USER_CONFIG_DIR_ENV) {
+        return Some(PathBuf::from(env_path));
+    }
+    directories::ProjectDirs::from("", "", "glow").map(|dirs| dirs.config_dir().to_path_buf())
+}
+
+/// Get the user-level library of shared process config files
+///
+/// `DEV_GLOW_LIBRARY_DIR` overrides the location in the user config
+/// directory (e.g., `~/.config/glow/library` on Linux).
+pub fn library_dir() -> Option<PathBuf> {
+    if let Ok(env_path) = std::env::var(LIBRARY_DIR_ENV) {
+        return Some(PathBuf::from(env_path));
+    }
+    user_config_dir().map(|dir| dir.join(LIBRARY_FOLDER))
+}
+
+/// Get the user-level directory of process presets
+///
+/// `DEV_GLOW_PRESET_DIR` overrides the location in the user config
+/// directory (e.g., `~/.config/glow/presets` on Linux).
+pub fn preset_dir() -> Option<PathBuf> {
+    if let Ok(env_path) = std::env::var(PRESET_DIR_ENV) {
+        return Some(PathBuf::from(env_path));
+    }
+    user_config_dir().map(|dir| dir.join(PRESETS_FOLDER))
+}
+
+/// Get the user-level templates directory, used when a template is not
+/// found in the project
+pub fn user_templates_dir() -> Option<PathBuf> {
+    user_config_dir().map(|dir| dir.join(TEMPLATES_FOLDER))
+}
@@ -0,0 +1,290 @@
+//! Process presets
+//!
+//! Ready-made process definitions, with matching classifications, parameter
+//! types and templates, for `glow project init --preset`. Built-in presets are
+//! embedded in the binary; teams add their own as directories in the user
+//! preset directory.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::{GlowError, Result};
+
+use super::diagnostics::diagnose;
+use super::interpolate::Variables;
+use super::{preset_dir, Config, ProcessConfig, PROCESS_CONFIG_FILE, TEMPLATES_FOLDER};
+
+/// Built-in preset embedded in the binary
+struct BuiltIn {
+    name: &'static str,
+    process_config: &'static str,
+    templates: &'static [(&'static str, &'static str)],
+}
+
+macro_rules! built_in {
+    ($name:literal, [$($template:literal),* $(,)?]) => {
+        BuiltIn {
+            name: $name,
+            process_config: include_str!(concat!("../../presets/", $name, "/process_config.yaml")),
+            templates: &[$((
+                $template,
+                include_str!(concat!("../../presets/", $name, "/templates/", $template)),
+            )),*],
+        }
+    };
+}
+
+const BUILT_IN: &[BuiltIn] = &[
+    built_in!("feature", ["feature.md", "requirement.md", "task.md"]),
+    built_in!("bugfix", ["bug.md", "task.md"]),
+    built_in!("release-train", ["release.md", "milestone.md"]),
+    built_in!("research-spike", ["spike.md", "task.md"]),
+];
+
+/// A process definition with its templates
+#[derive(Debug, Clone)]
+pub struct Preset {
+    /// Preset name (directory name for user presets)
+    pub name: String,
+    /// Directory the preset was read from (None for built-in presets)
+    pub source: Option<PathBuf>,
+    /// Content of `process_config.yaml`
+    pub process_config: String,
+    /// Other YAML files next to `process_config.yaml` (e.g., imported files)
+    pub config_files: Vec<(String, String)>,
+    /// Template files by name
+    pub templates: Vec<(String, String)>,
+}
+
+impl Preset {
+    /// Get the presets embedded in the binary
+    pub fn built_in() -> Vec<Preset> {
+        BUILT_IN
+            .iter()
+            .map(|preset| Preset {
+                name: preset.name.to_string(),
+                source: None,
+                process_config: preset.process_config.to_string(),
+                config_files: Vec::new(),
+                templates: preset
+                    .templates
+                    .iter()
+                    .map(|(name, content)| (name.to_string(), content.to_string()))
+                    .collect(),
+            })
+            .collect()
+    }
+
+    /// Read a preset from a directory holding `process_config.yaml` and
+    /// optionally `templates/*.md`
+    pub fn from_dir(dir: &Path) -> Result<Preset> {
+        let process_config_path = dir.join(PROCESS_CONFIG_FILE);
+        if !process_config_path.is_file() {
+            return Err(GlowError::ConfigNotFound {
+                path: process_config_path,
+            });
+        }
+
+        let name = dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(Preset {
+            name,
+            source: Some(dir.to_path_buf()),
+            process_config: read_file(&process_config_path)?,
+            config_files: read_files(dir, "yaml")?
+                .into_iter()
+                .filter(|(file, _)| file != PROCESS_CONFIG_FILE)
+                .collect(),
+            templates: read_files(&dir.join(TEMPLATES_FOLDER), "md")?,
+        })
+    }
+
+    /// Get all available presets: built-in ones, then user presets
+    ///
+    /// A user preset replaces the built-in preset of the same name.
+    pub fn available() -> Result<Vec<Preset>> {
+        let mut presets = Self::built_in();
+
+        if let Some(dir) = preset_dir().filter(|d| d.is_dir()) {
+            let mut dirs: Vec<PathBuf> = std::fs::read_dir(&dir)?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|path| path.join(PROCESS_CONFIG_FILE).is_file())
+                .collect();
+            dirs.sort();
+
+            for path in dirs {
+                let preset = Self::from_dir(&path)?;
+                match presets.iter_mut().find(|p| p.name == preset.name) {
+                    Some(existing) => *existing = preset,
+                    None => presets.push(preset),
+                }
+            }
+        }
+
+        Ok(presets)
+    }
+
+    /// Find a preset by name, or read it from a directory path
+    ///
+    /// The argument is taken as a path only when it contains a path
+    /// separator, so a folder in the working directory never shadows a
+    /// preset name.
+    pub fn find(name: &str) -> Result<Preset> {
+        if name.contains(std::path::is_separator) {
+            return Self::from_dir(Path::new(name));
+        }
+
+        let presets = Self::available()?;
+        let available = presets.iter().map(|p| p.name.clone()).collect();
+        presets
+            .into_iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| GlowError::PresetNotFound {
+                name: name.to_string(),
+                available,
+            })
+    }
+
+    /// One-line description: the first comment line of `process_config.yaml`
+    pub fn description(&self) -> &str {
+        self.process_config
+            .lines()
+            .find_map(|line| line.strip_prefix('#'))
+            .map(str::trim)
+            .unwrap_or_default()
+    }
+
+    /// Validate the preset's process definition
+    pub fn validate(&self) -> Result<ProcessConfig> {
+        self.validate_for(&Config::default())
+    }
+
+    /// Validate the preset's process definition for a project's config
+    ///
+    /// `${config:...}` placeholders are resolved from `config`.
+    pub fn validate_for(&self, config: &Config) -> Result<ProcessConfig> {
+        let file = match &self.source {
+            Some(dir) => dir.join(PROCESS_CONFIG_FILE),
+            None => Path::new("presets")
+                .join(&self.name)
+                .join(PROCESS_CONFIG_FILE),
+        };
+
+        match diagnose(&file, &self.process_config, &Variables::new(config)) {
+            (Some(process_config), diagnostics) if diagnostics.is_empty() => Ok(process_config),
+            (_, diagnostics) => Err(GlowError::InvalidProcessConfig {
+                diagnostics: diagnostics.iter().map(|d| d.to_string()).collect(),
+            }),
+        }
+    }
+}
+
+fn read_file(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path).map_err(|e| GlowError::FileReadError {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+/// Read the files with an extension in a directory, sorted by name
+fn read_files(dir: &Path, extension: &str) -> Result<Vec<(String, String)>> {
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut paths: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == extension))
+        .collect();
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| {
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .into_owned();
+            Ok((name, read_file(path)?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::check_process_config;
+
+    #[test]
+    fn test_built_in_presets_are_consistent() {
+        for preset in Preset::built_in() {
+            let config = preset
+                .validate()
+                .unwrap_or_else(|e| panic!("preset '{}': {}", preset.name, e));
+            let findings = check_process_config(&config);
+            assert!(
+                findings.is_empty(),
+                "preset '{}': {:?}",
+                preset.name,
+                findings
+            );
+            assert!(
+                !config.classifications.is_empty(),
+                "preset '{}'",
+                preset.name
+            );
+            assert!(
+                !config.parameter_types.is_empty(),
+                "preset '{}'",
+                preset.name
+            );
+            assert!(!preset.description().is_empty(), "preset '{}'", preset.name);
+
+            let mut templates = Vec::new();
+            collect_templates(&config.root_process, &mut templates);
+            for template in templates {
+                assert!(
+                    preset.templates.iter().any(|(name, _)| *name == template),
+                    "preset '{}' lacks template '{}'",
+                    preset.name,
+                    template
+                );
+            }
+        }
+    }
+
+    fn collect_templates(def: &crate::model::StepDefinition, templates: &mut Vec<String>) {
+        templates.extend(def.template.clone());
+        for step in &def.steps {
+            collect_templates(step, templates);
+        }
+    }
+
+    #[test]
+    fn test_preset_from_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let preset_path = dir.path().join("ops");
+        std::fs::create_dir_all(preset_path.join(TEMPLATES_FOLDER)).unwrap();
+        std::fs::write(
+            preset_path.join(PROCESS_CONFIG_FILE),
+            "# Operations runbook\nversion: \"0.1.0\"\nroot_process:\n  id: ROOT\n",
+        )
+        .unwrap();
+        std::fs::write(preset_path.join("shared.yaml"), "version: \"0.1.0\"\n").unwrap();
+        std::fs::write(preset_path.join("templates/runbook.md"), "# {{attr.id}}\n").unwrap();
+
+        let preset = Preset::find(preset_path.to_str().unwrap()).unwrap();
+        assert_eq!(preset.name, "ops");
+        assert_eq!(preset.description(), "Operations runbook");
+        assert_eq!(preset.config_files.len(), 1);
+        assert_eq!(preset.templates[0].0, "runbook.md");
+        assert!(preset.validate().is_ok());
+
+        assert_eq!(Preset::find("bugfix").unwrap().source, None);
+        let err = Preset::find("no-such-preset").unwrap_err();
+        assert!(err.to_string().contains("available: feature, bugfix"));
+    }
+}
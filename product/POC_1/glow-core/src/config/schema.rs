@@ -1,16 +1,279 @@
-//! Schema validation
+//! Schema generation and validation
 //!
-//! Validates configuration files against JSON schemas.
+//! JSON schemas are generated from the configuration and step data types;
+//! their doc comments become the schema descriptions.
 
 use std::path::Path;
+use std::sync::LazyLock;
+
+use schemars::gen::SchemaSettings;
+use schemars::JsonSchema;
+use serde_json::{json, Map, Value};
 
 use crate::error::{GlowError, Result};
+use crate::model::{DataType, ParameterRef, ParameterType, Step, StepDefinition};
+
+use super::mcp::McpConfig;
+use super::{Config, ProcessConfig};
+
+/// Schema file names
+pub const CONFIG_SCHEMA_FILE: &str = "config.schema.json";
+pub const PROCESS_CONFIG_SCHEMA_FILE: &str = "process_config.schema.json";
+pub const STEP_DATA_SCHEMA_FILE: &str = "step_data.schema.json";
+pub const MCP_CONFIG_SCHEMA_FILE: &str = "mcp_config.schema.json";
+
+/// Generated schemas
+pub static CONFIG_SCHEMA: LazyLock<String> = LazyLock::new(|| render(&config_schema()));
+pub static PROCESS_CONFIG_SCHEMA: LazyLock<String> =
+    LazyLock::new(|| render(&process_config_schema()));
+pub static STEP_DATA_SCHEMA: LazyLock<String> = LazyLock::new(|| render(&step_data_schema()));
+pub static MCP_CONFIG_SCHEMA: LazyLock<String> = LazyLock::new(|| render(&mcp_config_schema()));
+
+/// Generate the schema of `.glow/config.yaml`
+pub fn config_schema() -> Value {
+    generate::<Config>(CONFIG_SCHEMA_FILE, "Glow Project Configuration")
+}
+
+/// Generate the schema of `.glow/process_config.yaml`
+pub fn process_config_schema() -> Value {
+    generate::<ProcessConfig>(PROCESS_CONFIG_SCHEMA_FILE, "Glow Process Configuration")
+}
+
+/// Generate the schema of step data file frontmatter
+pub fn step_data_schema() -> Value {
+    generate::<Step>(STEP_DATA_SCHEMA_FILE, "Glow Step Data File")
+}
+
+/// Generate the schema of `.glow/mcp_config.yaml`
+pub fn mcp_config_schema() -> Value {
+    generate::<McpConfig>(MCP_CONFIG_SCHEMA_FILE, "Glow MCP Server Configuration")
+}
+
+/// Get all schemas as (file name, schema)
+///
+/// With a process config, the step data schema types each step's parameter
+/// values from their parameter types.
+pub fn schema_files(process_config: Option<&ProcessConfig>) -> Vec<(&'static str, Value)> {
+    let step_data = match process_config {
+        Some(process_config) => project_step_data_schema(process_config),
+        None => step_data_schema(),
+    };
+    vec![
+        (CONFIG_SCHEMA_FILE, config_schema()),
+        (PROCESS_CONFIG_SCHEMA_FILE, process_config_schema()),
+        (STEP_DATA_SCHEMA_FILE, step_data),
+        (MCP_CONFIG_SCHEMA_FILE, mcp_config_schema()),
+    ]
+}
+
+/// Render a schema as pretty-printed JSON
+pub fn render(schema: &Value) -> String {
+    serde_json::to_string_pretty(schema).expect("schemas serialize to JSON")
+}
+
+fn generate<T: JsonSchema>(file_name: &str, title: &str) -> Value {
+    let root = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    let mut schema = serde_json::to_value(root).expect("schemas serialize to JSON");
+
+    schema["$id"] = Value::from(format!("https://dev-glow.io/schemas/{}", file_name));
+    schema["title"] = Value::from(title);
+    simplify_enums(&mut schema);
+    schema
+}
+
+/// Turn `oneOf` lists of documented constants into a plain `enum`
+///
+/// Documented unit enums generate one branch per variant, which validators
+/// report as "not valid under any of the schemas". A plain `enum` reports the
+/// expected values; the variant docs move into the description.
+fn simplify_enums(schema: &mut Value) {
+    match schema {
+        Value::Object(object) => {
+            let constants = object.get("oneOf").and_then(Value::as_array).and_then(|branches| {
+                branches
+                    .iter()
+                    .map(|branch| match branch.get("enum").and_then(Value::as_array) {
+                        Some(values) if values.len() == 1 => {
+                            Some((values[0].clone(), branch.get("description").cloned()))
+                        }
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()
+            });
+
+            if let Some(constants) = constants {
+                object.remove("oneOf");
+                let mut lines: Vec<String> = object
+                    .get("description")
+                    .and_then(Value::as_str)
+                    .map(|d| vec![d.to_string(), String::new()])
+                    .unwrap_or_default();
+                for (value, description) in &constants {
+                    if let Some(description) = description.as_ref().and_then(Value::as_str) {
+                        lines.push(format!("- {}: {}", value, description));
+                    }
+                }
+                if !lines.is_empty() {
+                    object.insert("description".to_string(), Value::from(lines.join("\n")));
+                }
+                object.insert(
+                    "enum".to_string(),
+                    Value::Array(constants.into_iter().map(|(value, _)| value).collect()),
+                );
+            }
+
+            for value in object.values_mut() {
+                simplify_enums(value);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(simplify_enums),
+        _ => {}
+    }
+}
+
+/// Generate the step data schema for a project
+///
+/// Parameter values of every step in the process definition are typed from
+/// their parameter types. Values may also be given as strings, as written by
+/// the CLI, and may be empty (null).
+pub fn project_step_data_schema(process_config: &ProcessConfig) -> Value {
+    let mut schema = step_data_schema();
+
+    let mut rules = Vec::new();
+    collect_step_rules(process_config, &process_config.root_process, None, &mut rules);
+    if !rules.is_empty() {
+        schema["allOf"] = Value::Array(rules);
+    }
+    schema
+}
+
+fn collect_step_rules(
+    process_config: &ProcessConfig,
+    def: &StepDefinition,
+    parent_fqid: Option<&str>,
+    rules: &mut Vec<Value>,
+) {
+    let fqid = match parent_fqid {
+        None => def.id.clone(),
+        Some("ROOT") => def.id.clone(),
+        Some(parent) => format!("{}.{}", parent, def.id),
+    };
+
+    let mut properties = Map::new();
+    for (section, params) in [("input", &def.inputs), ("scope", &def.scope), ("output", &def.outputs)] {
+        let typed: Vec<Value> = params
+            .iter()
+            .filter_map(|param| parameter_rule(process_config, param))
+            .collect();
+        if !typed.is_empty() {
+            properties.insert(section.to_string(), json!({ "items": { "allOf": typed } }));
+        }
+    }
+    if !properties.is_empty() {
+        rules.push(json!({
+            "if": {
+                "properties": { "attr": { "properties": { "fqid": { "const": fqid } }, "required": ["fqid"] } },
+                "required": ["attr"]
+            },
+            "then": { "properties": properties }
+        }));
+    }
+
+    for step in &def.steps {
+        collect_step_rules(process_config, step, Some(&fqid), rules);
+    }
+}
+
+/// Type the value of a parameter by its ID
+fn parameter_rule(process_config: &ProcessConfig, param: &ParameterRef) -> Option<Value> {
+    let type_id = param.type_ref.as_deref().unwrap_or(&param.id);
+    let param_type = process_config.parameter_types.iter().find(|t| t.id == type_id)?;
+
+    let mut value = value_schema(param_type);
+    let purpose = param.purpose.as_ref().or(param_type.purpose.as_ref());
+    let description = match purpose {
+        Some(purpose) => format!("{} ({})", purpose, param_type.data_type),
+        None => param_type.data_type.to_string(),
+    };
+    value["description"] = Value::from(description);
+
+    Some(json!({
+        "if": { "properties": { "id": { "const": param.id } }, "required": ["id"] },
+        "then": { "properties": { "value": value } }
+    }))
+}
+
+/// Schema for the value of a parameter type
+fn value_schema(param_type: &ParameterType) -> Value {
+    let validation = param_type.validation.clone().unwrap_or_default();
+    let range = |item: Value| {
+        json!({
+            "type": ["object", "null"],
+            "properties": { "min": item.clone(), "max": item },
+            "required": ["min", "max"],
+            "additionalProperties": false
+        })
+    };
 
-/// Embedded schemas
-pub const CONFIG_SCHEMA: &str = include_str!("../../schemas/config.schema.json");
-pub const PROCESS_CONFIG_SCHEMA: &str = include_str!("../../schemas/process_config.schema.json");
-pub const STEP_DATA_SCHEMA: &str = include_str!("../../schemas/step_data.schema.json");
-pub const MCP_CONFIG_SCHEMA: &str = include_str!("../../schemas/mcp_config.schema.json");
+    let mut schema = match param_type.data_type {
+        DataType::Str | DataType::Template => json!({ "type": ["string", "null"] }),
+        DataType::Int => json!({ "type": ["integer", "string", "null"], "pattern": r"^\s*-?\d+\s*$" }),
+        DataType::Dec => {
+            json!({ "type": ["number", "string", "null"], "pattern": r"^\s*-?\d+(\.\d+)?\s*$" })
+        }
+        DataType::Bool => json!({ "enum": [true, false, "true", "false", null] }),
+        DataType::Date => json!({ "type": ["string", "null"], "format": "date" }),
+        DataType::RangeInt => range(json!({ "type": "integer" })),
+        DataType::RangeDec => range(json!({ "type": "number" })),
+        DataType::RangeDate => range(json!({ "type": "string", "format": "date" })),
+        DataType::Set => json!({
+            "type": ["array", "string", "null"],
+            "items": {
+                "anyOf": [
+                    { "type": "string" },
+                    {
+                        "type": "object",
+                        "properties": { "code": { "type": "string" }, "value": { "type": "string" } },
+                        "required": ["code", "value"],
+                        "additionalProperties": false
+                    }
+                ]
+            }
+        }),
+        DataType::Content => {
+            let mut mime = json!({ "type": "string" });
+            if let Some(mime_types) = &validation.mime_types {
+                mime["enum"] = json!(mime_types);
+            }
+            json!({
+                "type": ["object", "string", "null"],
+                "properties": { "uri": { "type": "string" }, "mime": mime, "title": { "type": "string" } },
+                "required": ["uri"],
+                "additionalProperties": false
+            })
+        }
+    };
+
+    if let Some(pattern) = &validation.pattern {
+        if param_type.data_type == DataType::Str {
+            schema["pattern"] = Value::from(pattern.clone());
+        }
+    }
+    if let Some(min) = validation.min {
+        schema["minimum"] = json!(min);
+    }
+    if let Some(max) = validation.max {
+        schema["maximum"] = json!(max);
+    }
+    if let Some(values) = &validation.r#enum {
+        let mut values = values.clone();
+        values.push(Value::Null);
+        schema["enum"] = Value::Array(values);
+    }
+    schema
+}
 
 /// Validate a configuration value against its schema
 pub fn validate_config<T: serde::Serialize>(value: &T, schema_json: &str) -> Result<()> {
@@ -55,7 +318,28 @@ pub fn validate_yaml_file(file_path: &Path, schema_json: &str) -> Result<()> {
     })?;
 
     let value: serde_json::Value = serde_yaml::from_str(&content)?;
+    let errors = schema_errors(&value, schema_json)?;
+
+    if !errors.is_empty() {
+        let error_messages: Vec<String> = errors
+            .into_iter()
+            .map(|(path, message)| format!("{}: {}", path, message))
+            .collect();
 
+        return Err(GlowError::SchemaValidationError {
+            path: file_path.display().to_string(),
+            errors: error_messages,
+        });
+    }
+
+    Ok(())
+}
+
+/// Validate a value against a schema, returning (JSON pointer, message) per violation
+///
+/// Unexpected properties point at the first unexpected key rather than the
+/// enclosing object.
+pub fn schema_errors(value: &serde_json::Value, schema_json: &str) -> Result<Vec<(String, String)>> {
     let schema: serde_json::Value =
         serde_json::from_str(schema_json).map_err(|e| GlowError::ConfigError {
             message: format!("Invalid schema: {}", e),
@@ -68,20 +352,23 @@ pub fn validate_yaml_file(file_path: &Path, schema_json: &str) -> Result<()> {
             source: None,
         })?;
 
-    let result = compiled.validate(&value);
-
-    if let Err(errors) = result {
-        let error_messages: Vec<String> = errors
-            .map(|e| format!("{}: {}", e.instance_path, e))
-            .collect();
-
-        return Err(GlowError::SchemaValidationError {
-            path: file_path.display().to_string(),
-            errors: error_messages,
-        });
-    }
-
-    Ok(())
+    let errors = match compiled.validate(value) {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors
+            .map(|e| {
+                let mut pointer = e.instance_path.to_string();
+                if let jsonschema::error::ValidationErrorKind::AdditionalProperties { unexpected } =
+                    &e.kind
+                {
+                    if let Some(key) = unexpected.first() {
+                        pointer = format!("{}/{}", pointer, key);
+                    }
+                }
+                (pointer, e.to_string())
+            })
+            .collect(),
+    };
+    Ok(errors)
 }
 
 #[cfg(test)]
@@ -92,7 +379,7 @@ mod tests {
     #[test]
     fn test_validate_default_config() {
         let config = Config::default();
-        let result = validate_config(&config, CONFIG_SCHEMA);
+        let result = validate_config(&config, &CONFIG_SCHEMA);
         if let Err(e) = &result {
             eprintln!("Validation error: {:?}", e);
         }
@@ -102,10 +389,56 @@ mod tests {
     #[test]
     fn test_validate_default_process_config() {
         let config = ProcessConfig::default();
-        let result = validate_config(&config, PROCESS_CONFIG_SCHEMA);
+        let result = validate_config(&config, &PROCESS_CONFIG_SCHEMA);
         if let Err(e) = &result {
             eprintln!("Validation error: {:?}", e);
         }
         assert!(result.is_ok());
     }
+
+    #[test]
+    fn test_generated_schemas_carry_doc_comments() {
+        let schema = process_config_schema();
+        assert_eq!(schema["$id"], "https://dev-glow.io/schemas/process_config.schema.json");
+        assert!(schema["definitions"]["StepDefinition"]["properties"]["id"]["description"]
+            .is_string());
+
+        let yaml = "version: \"0.2.0\"\nstaleness:\n  in_progress_days: 5\n  classifications:\n    - classification: \"Bug,*\"\n      wait_days: 2\nroot_process:\n  id: ROOT\n";
+        let value: Value = serde_yaml::from_str(yaml).unwrap();
+        assert!(schema_errors(&value, &PROCESS_CONFIG_SCHEMA).unwrap().is_empty());
+
+        let value: Value = serde_yaml::from_str("version: \"0.2.0\"\nroot_proces: {}\n").unwrap();
+        let errors = schema_errors(&value, &PROCESS_CONFIG_SCHEMA).unwrap();
+        assert!(errors.iter().any(|(path, _)| path == "/root_proces"));
+    }
+
+    #[test]
+    fn test_project_step_data_schema_types_parameters() {
+        let yaml = r#"
+version: "0.2.0"
+parameter_types:
+  - id: EFFORT
+    data_type: INT
+root_process:
+  id: ROOT
+  steps:
+    - id: TASK
+      outputs:
+        - id: EFFORT
+"#;
+        let process_config: ProcessConfig = serde_yaml::from_str(yaml).unwrap();
+        let schema = render(&project_step_data_schema(&process_config));
+
+        let step = |value: Value| {
+            json!({
+                "attr": { "id": "TASK", "fqid": "TASK", "status": "todo" },
+                "output": [{ "id": "EFFORT", "value": value }]
+            })
+        };
+        assert!(schema_errors(&step(json!(3)), &schema).unwrap().is_empty());
+        assert!(schema_errors(&step(json!("3")), &schema).unwrap().is_empty());
+        assert!(schema_errors(&step(Value::Null), &schema).unwrap().is_empty());
+        let errors = schema_errors(&step(json!("three")), &schema).unwrap();
+        assert_eq!(errors[0].0, "/output/0/value");
+    }
 }
@@ -0,0 +1,304 @@
+//! YAML source positions
+//!
+//! Maps paths like `root_process.steps[1].links[0]` or JSON pointers like
+//! `/root_process/steps/1` to lines and columns of a block-style YAML file.
+//! Flow-style collections are treated as scalars; paths into them resolve to
+//! the enclosing key.
+
+/// Position in a source file (1-based)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl SourcePosition {
+    /// Start of the file
+    pub const START: SourcePosition = SourcePosition { line: 1, column: 1 };
+}
+
+/// A node in the outline of a YAML document
+#[derive(Debug)]
+enum Outline {
+    Scalar,
+    Mapping(Vec<(String, SourcePosition, Outline)>),
+    Sequence(Vec<(SourcePosition, Outline)>),
+}
+
+/// A significant line; sequence dashes are split into their own lines
+#[derive(Debug)]
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    dash: bool,
+    text: &'a str,
+}
+
+impl Line<'_> {
+    fn position(&self) -> SourcePosition {
+        SourcePosition {
+            line: self.number,
+            column: self.indent + 1,
+        }
+    }
+}
+
+/// One step of a path
+enum Segment<'a> {
+    Key(&'a str),
+    Index(usize),
+}
+
+/// Outline of a YAML document for looking up source positions
+#[derive(Debug)]
+pub struct SourceMap {
+    root: Outline,
+}
+
+impl SourceMap {
+    /// Build the outline of a YAML document
+    pub fn new(source: &str) -> Self {
+        let lines = significant_lines(source);
+        let mut pos = 0;
+        let root = match lines.first() {
+            Some(first) => parse_node(&lines, &mut pos, first.indent),
+            None => Outline::Scalar,
+        };
+        Self { root }
+    }
+
+    /// Locate a dotted path (e.g., "root_process.steps[1].id")
+    pub fn locate(&self, path: &str) -> SourcePosition {
+        let mut segments = Vec::new();
+        for part in path.split('.').filter(|p| !p.is_empty()) {
+            let mut pieces = part.split('[');
+            if let Some(key) = pieces.next().filter(|k| !k.is_empty()) {
+                segments.push(Segment::Key(key));
+            }
+            for index in pieces {
+                if let Ok(i) = index.trim_end_matches(']').parse() {
+                    segments.push(Segment::Index(i));
+                }
+            }
+        }
+        self.locate_segments(&segments)
+    }
+
+    /// Locate a JSON pointer (e.g., "/root_process/steps/1/id")
+    pub fn locate_pointer(&self, pointer: &str) -> SourcePosition {
+        let segments: Vec<Segment> = pointer
+            .split('/')
+            .filter(|p| !p.is_empty())
+            .map(|p| match p.parse() {
+                Ok(i) => Segment::Index(i),
+                Err(_) => Segment::Key(p),
+            })
+            .collect();
+        self.locate_segments(&segments)
+    }
+
+    /// Resolve as much of the path as possible, returning the deepest position found
+    fn locate_segments(&self, segments: &[Segment]) -> SourcePosition {
+        let mut node = &self.root;
+        let mut position = SourcePosition::START;
+
+        for segment in segments {
+            let next = match (segment, node) {
+                (Segment::Key(key), Outline::Mapping(entries)) => entries
+                    .iter()
+                    .find(|(k, _, _)| k == key)
+                    .map(|(_, p, n)| (*p, n)),
+                (Segment::Index(i), Outline::Sequence(items)) => {
+                    items.get(*i).map(|(p, n)| (*p, n))
+                }
+                _ => None,
+            };
+            match next {
+                Some((p, n)) => {
+                    position = p;
+                    node = n;
+                }
+                None => break,
+            }
+        }
+
+        position
+    }
+}
+
+/// Collect non-empty, non-comment lines, splitting off sequence dashes
+fn significant_lines(source: &str) -> Vec<Line<'_>> {
+    let mut lines = Vec::new();
+    for (i, raw) in source.lines().enumerate() {
+        let trimmed = raw.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed == "---" || trimmed == "..." {
+            continue;
+        }
+
+        let mut indent = raw.len() - trimmed.len();
+        let mut text = trimmed.trim_end();
+        while text == "-" || text.starts_with("- ") {
+            lines.push(Line {
+                number: i + 1,
+                indent,
+                dash: true,
+                text: "",
+            });
+            let rest = text[1..].trim_start();
+            indent += text.len() - rest.len();
+            text = rest;
+        }
+        if !text.is_empty() {
+            lines.push(Line {
+                number: i + 1,
+                indent,
+                dash: false,
+                text,
+            });
+        }
+    }
+    lines
+}
+
+/// Split a mapping line into its key and whether a value follows on the same line
+fn split_key(text: &str) -> Option<(String, bool)> {
+    if text.starts_with(['{', '[']) {
+        return None;
+    }
+
+    let key_end = match text.chars().next() {
+        Some(quote @ ('"' | '\'')) => text[1..].find(quote).map(|i| i + 2)?,
+        _ => 0,
+    };
+    let colon = text[key_end..]
+        .char_indices()
+        .find(|&(i, c)| {
+            c == ':'
+                && text[key_end + i + 1..]
+                    .chars()
+                    .next()
+                    .is_none_or(|n| n == ' ')
+        })
+        .map(|(i, _)| key_end + i)?;
+
+    let key = text[..colon].trim().trim_matches(['"', '\'']).to_string();
+    let rest = text[colon + 1..].trim();
+    Some((key, !rest.is_empty() && !rest.starts_with('#')))
+}
+
+fn parse_node(lines: &[Line], pos: &mut usize, indent: usize) -> Outline {
+    let first = &lines[*pos];
+    if first.dash {
+        parse_sequence(lines, pos, indent)
+    } else if split_key(first.text).is_some() {
+        parse_mapping(lines, pos, indent)
+    } else {
+        *pos += 1;
+        skip_deeper(lines, pos, indent);
+        Outline::Scalar
+    }
+}
+
+fn parse_sequence(lines: &[Line], pos: &mut usize, indent: usize) -> Outline {
+    let mut items = Vec::new();
+    while let Some(line) = lines.get(*pos).filter(|l| l.dash && l.indent == indent) {
+        let position = line.position();
+        *pos += 1;
+        let item = match lines.get(*pos) {
+            Some(next) if next.indent > indent => parse_node(lines, pos, next.indent),
+            _ => Outline::Scalar,
+        };
+        items.push((position, item));
+    }
+    Outline::Sequence(items)
+}
+
+fn parse_mapping(lines: &[Line], pos: &mut usize, indent: usize) -> Outline {
+    let mut entries = Vec::new();
+    while let Some(line) = lines.get(*pos).filter(|l| !l.dash && l.indent == indent) {
+        let Some((key, inline_value)) = split_key(line.text) else {
+            break;
+        };
+        let position = line.position();
+        *pos += 1;
+
+        let value = if inline_value {
+            // Block scalars and multi-line values continue on deeper lines
+            skip_deeper(lines, pos, indent);
+            Outline::Scalar
+        } else {
+            match lines.get(*pos) {
+                Some(next) if next.indent > indent => parse_node(lines, pos, next.indent),
+                Some(next) if next.dash && next.indent == indent => {
+                    parse_sequence(lines, pos, indent)
+                }
+                _ => Outline::Scalar,
+            }
+        };
+        entries.push((key, position, value));
+    }
+    Outline::Mapping(entries)
+}
+
+fn skip_deeper(lines: &[Line], pos: &mut usize, indent: usize) {
+    while lines.get(*pos).is_some_and(|l| l.indent > indent) {
+        *pos += 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"# comment
+version: "0.1.0"
+parameter_types:
+  - id: SPEC
+    data_type: STR
+root_process:
+  id: ROOT
+  purpose: |
+    Multi-line
+    - not a list
+  steps:
+    - id: A
+      inputs: [{ id: X }]
+    - id: B
+      classification: "Task"
+  links:
+  - from: B
+    to: A
+"#;
+
+    fn at(line: usize, column: usize) -> SourcePosition {
+        SourcePosition { line, column }
+    }
+
+    #[test]
+    fn test_locate_dotted_paths() {
+        let map = SourceMap::new(SOURCE);
+        assert_eq!(map.locate("version"), at(2, 1));
+        assert_eq!(map.locate("parameter_types[0].data_type"), at(5, 5));
+        assert_eq!(map.locate("root_process.steps[1]"), at(14, 5));
+        assert_eq!(
+            map.locate("root_process.steps[1].classification"),
+            at(15, 7)
+        );
+        assert_eq!(map.locate("root_process.links[0].to"), at(18, 5));
+    }
+
+    #[test]
+    fn test_locate_falls_back_to_deepest_match() {
+        let map = SourceMap::new(SOURCE);
+        assert_eq!(map.locate("root_process.steps[0].inputs[0].id"), at(13, 7));
+        assert_eq!(map.locate("root_process.steps[7]"), at(11, 3));
+        assert_eq!(map.locate("unknown"), SourcePosition::START);
+    }
+
+    #[test]
+    fn test_locate_json_pointer() {
+        let map = SourceMap::new(SOURCE);
+        assert_eq!(map.locate_pointer("/root_process/steps/0/id"), at(12, 7));
+        assert_eq!(map.locate_pointer(""), SourcePosition::START);
+    }
+}
@@ -0,0 +1,245 @@
+//! Process engine builder
+//!
+//! Assembles a `ProcessEngine` from the project's config, letting embedding
+//! tools replace the config and data directories, template directories,
+//! clock, actor and storage.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use crate::config::{Config, ConfigCheck, ConfigLoader, MigrationPlan, Preset, ProcessConfig};
+use crate::error::{GlowError, Result};
+use crate::storage::{StepStore, Storage};
+use crate::template::TemplateEngine;
+
+use super::clock::{Clock, SystemClock};
+use super::operations::ProcessEngine;
+use super::state::StateManager;
+use super::validation::Validator;
+
+/// Builds a `ProcessEngine`, taking defaults for anything not set
+pub struct ProcessEngineBuilder {
+    project_root: PathBuf,
+    config_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+    templates_dir: Option<PathBuf>,
+    user_templates_dir: Option<PathBuf>,
+    clock: Arc<dyn Clock>,
+    actor: Option<String>,
+    store: Option<Box<dyn StepStore>>,
+}
+
+impl ProcessEngineBuilder {
+    /// Start building an engine for a project
+    pub fn new(project_root: impl Into<PathBuf>) -> Self {
+        Self {
+            project_root: project_root.into(),
+            config_dir: None,
+            data_dir: None,
+            templates_dir: None,
+            user_templates_dir: crate::config::user_templates_dir(),
+            clock: Arc::new(SystemClock),
+            actor: None,
+            store: None,
+        }
+    }
+
+    /// Use a config directory instead of `DEV_GLOW_CONFIG_DIR` or `.glow/`
+    pub fn config_dir(mut self, config_dir: impl Into<PathBuf>) -> Self {
+        self.config_dir = Some(config_dir.into());
+        self
+    }
+
+    /// Keep step data files in a directory instead of `data_folder`
+    pub fn data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+        self.data_dir = Some(data_dir.into());
+        self
+    }
+
+    /// Load templates from a directory instead of the config's `templates_folder`
+    pub fn templates_dir(mut self, templates_dir: impl Into<PathBuf>) -> Self {
+        self.templates_dir = Some(templates_dir.into());
+        self
+    }
+
+    /// Look up templates missing from the templates directory here (None to disable)
+    pub fn user_templates_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.user_templates_dir = dir;
+        self
+    }
+
+    /// Read the current time from a clock instead of the system time
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Record changes as made by an actor, overriding `actor` in the config
+    pub fn actor(mut self, actor: impl Into<String>) -> Self {
+        self.actor = Some(actor.into());
+        self
+    }
+
+    /// Keep step data in a store instead of files in the data directory
+    pub fn storage(mut self, store: impl StepStore + 'static) -> Self {
+        self.store = Some(Box::new(store));
+        self
+    }
+
+    /// Get a loader for the project's config files
+    pub fn config_loader(&self) -> Result<ConfigLoader> {
+        let config_dir =
+            crate::config::find_config_dir(&self.project_root, self.config_dir.as_deref())?;
+        Ok(ConfigLoader::new(config_dir, self.project_root.clone()))
+    }
+
+    /// Load an existing project
+    ///
+    /// Completes or undoes an operation an earlier process was interrupted
+    /// in; see `ProcessEngine::recovery`.
+    pub fn build(self) -> Result<ProcessEngine> {
+        let config_loader = self.config_loader()?;
+        let config = config_loader.load_config()?;
+        let process_config = config_loader.load_process_config(&config)?;
+        let mut engine = self.assemble(config_loader, config, process_config);
+        engine.recovery = engine.storage.recover()?;
+        Ok(engine)
+    }
+
+    /// Check the project's process config without loading the project
+    pub fn check(self) -> Result<ConfigCheck> {
+        let config_loader = self.config_loader()?;
+        let config = config_loader.load_config()?;
+        config_loader.check_process_config(&config)
+    }
+
+    /// Plan migrating the project to the current schema version
+    pub fn plan_migration(self) -> Result<MigrationPlan> {
+        self.config_loader()?.plan_migration()
+    }
+
+    /// Migrate the project to the current schema version
+    ///
+    /// Returns the migration carried out and the folder holding the backups
+    /// of the changed files (None if the project was up to date).
+    pub fn migrate(self) -> Result<(MigrationPlan, Option<PathBuf>)> {
+        let config_loader = self.config_loader()?;
+        let plan = config_loader.plan_migration()?;
+        let backup = config_loader.migrate(&plan)?;
+        Ok((plan, backup))
+    }
+
+    /// Initialize a new project, optionally from a process preset
+    pub fn init(
+        self,
+        project_name: Option<String>,
+        preset: Option<&Preset>,
+    ) -> Result<ProcessEngine> {
+        let config_dir = self
+            .config_dir
+            .clone()
+            .unwrap_or_else(|| crate::config::default_config_dir(&self.project_root));
+
+        // Check if already initialized
+        if config_dir.exists() {
+            return Err(GlowError::ProjectAlreadyExists { path: config_dir });
+        }
+
+        let config = if let Some(name) = project_name {
+            Config::new(name)
+        } else {
+            Config::default()
+        };
+        let preset_config = preset.map(|p| p.validate_for(&config)).transpose()?;
+
+        // Create config directory
+        std::fs::create_dir_all(&config_dir)?;
+
+        // Create templates directory
+        let templates_dir = crate::config::templates_dir(&config_dir);
+        std::fs::create_dir_all(&templates_dir)?;
+        ProcessEngine::write_default_template(&templates_dir)?;
+        if let Some(preset) = preset {
+            for (name, content) in &preset.templates {
+                std::fs::write(templates_dir.join(name), content)?;
+            }
+        }
+
+        // Keep local overrides out of version control
+        std::fs::write(
+            config_dir.join(".gitignore"),
+            format!("{}\n", crate::config::LOCAL_CONFIG_FILE),
+        )?;
+
+        // Create config file
+        let config_loader = ConfigLoader::new(config_dir.clone(), self.project_root.clone());
+        config_loader.save_config(&config)?;
+
+        // Create process config
+        let process_config = match (preset, preset_config) {
+            (Some(preset), Some(process_config)) => {
+                std::fs::write(
+                    config_dir.join(&config.process_config),
+                    &preset.process_config,
+                )?;
+                for (name, content) in &preset.config_files {
+                    std::fs::write(config_dir.join(name), content)?;
+                }
+                process_config
+            }
+            _ => {
+                let process_config = ProcessConfig::default();
+                config_loader.save_process_config(&config, &process_config)?;
+                process_config
+            }
+        };
+
+        // Create schemas directory and write the generated schemas
+        let schemas_dir = crate::config::schemas_dir(&config_dir);
+        std::fs::create_dir_all(&schemas_dir)?;
+        ProcessEngine::write_schema_files(&schemas_dir, &process_config)?;
+
+        let engine = self.assemble(config_loader, config, process_config);
+        engine.storage.init_data_dir()?;
+        Ok(engine)
+    }
+
+    /// Create the engine's components from the loaded config
+    fn assemble(
+        self,
+        config_loader: ConfigLoader,
+        config: Config,
+        process_config: ProcessConfig,
+    ) -> ProcessEngine {
+        let config_dir = config_loader.config_dir().to_path_buf();
+
+        let mut storage = Storage::new(self.project_root.clone(), &config, config_dir.clone())
+            .with_process_config(&process_config);
+        if let Some(data_dir) = self.data_dir {
+            storage = storage.with_data_dir(data_dir);
+        }
+        if let Some(store) = self.store {
+            storage = storage.with_store(store);
+        }
+
+        let templates_dir = self
+            .templates_dir
+            .unwrap_or_else(|| crate::config::templates_dir(&config_dir));
+        let template_engine =
+            TemplateEngine::new(templates_dir).with_fallback_dir(self.user_templates_dir);
+
+        ProcessEngine {
+            project_root: self.project_root,
+            config_loader,
+            config,
+            process_config,
+            storage,
+            template_engine,
+            state_manager: StateManager::new(),
+            validator: Validator::new(),
+            clock: self.clock,
+            actor: self.actor,
+            recovery: None,
+        }
+    }
+}
@@ -0,0 +1,244 @@
+//! Completion forecasting
+//!
+//! Projects completion dates from the historical throughput of finished steps.
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Minimum observation window for throughput calculation (days)
+const MIN_WINDOW_DAYS: f64 = 1.0;
+
+/// Length of a throughput sampling bucket (days)
+const BUCKET_DAYS: i64 = 7;
+
+/// Lower bound for the pessimistic rate as a fraction of the mean rate
+const MIN_RATE_FRACTION: f64 = 0.1;
+
+/// Expected, earliest and latest completion dates; a date too far out to represent is `None`
+pub type Projection = (Option<DateTime<Utc>>, Option<DateTime<Utc>>, Option<DateTime<Utc>>);
+
+/// A unit of finished work used as throughput history
+#[derive(Debug, Clone)]
+pub struct CompletedWork {
+    /// When the work was finished
+    pub finished_at: DateTime<Utc>,
+    /// Estimate weight of the finished work
+    pub weight: f64,
+}
+
+/// Historical throughput in estimate weight per day
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throughput {
+    /// Mean weight finished per day
+    pub mean: f64,
+    /// Pessimistic weight finished per day
+    pub low: f64,
+    /// Optimistic weight finished per day
+    pub high: f64,
+}
+
+impl Throughput {
+    /// Compute throughput from finished work history
+    ///
+    /// History is split into weekly buckets ending at `now`; the spread of
+    /// bucket rates gives the confidence range. Returns `None` without history.
+    pub fn from_history(history: &[CompletedWork], now: DateTime<Utc>) -> Option<Self> {
+        let first = history.iter().map(|w| w.finished_at).min()?;
+        let total: f64 = history.iter().map(|w| w.weight).sum();
+        if total <= 0.0 {
+            return None;
+        }
+
+        let window_days = ((now - first).num_seconds() as f64 / 86_400.0).max(MIN_WINDOW_DAYS);
+        let mean = total / window_days;
+
+        // Weekly bucket rates for spread estimation
+        let bucket_count = ((window_days / BUCKET_DAYS as f64).ceil() as usize).max(1);
+        let mut buckets = vec![0.0; bucket_count];
+        for work in history {
+            let age_days = (now - work.finished_at).num_days().max(0);
+            let idx = ((age_days / BUCKET_DAYS) as usize).min(bucket_count - 1);
+            buckets[idx] += work.weight / BUCKET_DAYS as f64;
+        }
+
+        let deviation = if bucket_count > 1 {
+            let bucket_mean = buckets.iter().sum::<f64>() / bucket_count as f64;
+            let variance = buckets
+                .iter()
+                .map(|b| (b - bucket_mean).powi(2))
+                .sum::<f64>()
+                / (bucket_count - 1) as f64;
+            variance.sqrt()
+        } else {
+            // Single bucket: assume +/- 50% until more history is available
+            mean * 0.5
+        };
+
+        Some(Self {
+            mean,
+            low: (mean - deviation).max(mean * MIN_RATE_FRACTION),
+            high: mean + deviation,
+        })
+    }
+
+    /// Project completion dates for the remaining weight
+    pub fn project(&self, remaining: f64, now: DateTime<Utc>) -> Projection {
+        let at_rate = |rate: f64| {
+            let seconds = remaining / rate * 86_400.0;
+            if !seconds.is_finite() {
+                return None;
+            }
+            Duration::try_seconds(seconds as i64).and_then(|duration| now.checked_add_signed(duration))
+        };
+        (at_rate(self.mean), at_rate(self.high), at_rate(self.low))
+    }
+}
+
+/// Completion forecast for a process
+#[derive(Debug, Clone)]
+pub struct Forecast {
+    /// Process FQID
+    pub fqid: String,
+    /// Current iteration number of the process, if started
+    pub iteration: Option<u32>,
+    /// Total estimate weight in the process
+    pub total_weight: f64,
+    /// Estimate weight not yet done
+    pub remaining_weight: f64,
+    /// Expected completion date at mean throughput
+    pub expected: Option<DateTime<Utc>>,
+    /// Earliest completion date (optimistic throughput)
+    pub earliest: Option<DateTime<Utc>>,
+    /// Latest completion date (pessimistic throughput)
+    pub latest: Option<DateTime<Utc>>,
+}
+
+impl Forecast {
+    /// Build a forecast from remaining work and throughput
+    pub fn new(
+        fqid: impl Into<String>,
+        iteration: Option<u32>,
+        total_weight: f64,
+        remaining_weight: f64,
+        throughput: Option<&Throughput>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let (expected, earliest, latest) = match throughput {
+            Some(_) if remaining_weight <= 0.0 => (Some(now), Some(now), Some(now)),
+            Some(t) => t.project(remaining_weight, now),
+            None => (None, None, None),
+        };
+
+        Self {
+            fqid: fqid.into(),
+            iteration,
+            total_weight,
+            remaining_weight,
+            expected,
+            earliest,
+            latest,
+        }
+    }
+
+    /// Check if the process is complete
+    pub fn is_complete(&self) -> bool {
+        self.remaining_weight <= 0.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn work(days_ago: i64, weight: f64, now: DateTime<Utc>) -> CompletedWork {
+        CompletedWork {
+            finished_at: now - Duration::days(days_ago),
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_throughput_no_history() {
+        assert!(Throughput::from_history(&[], Utc::now()).is_none());
+    }
+
+    #[test]
+    fn test_throughput_zero_weight() {
+        let now = Utc::now();
+        assert!(Throughput::from_history(&[work(3, 0.0, now)], now).is_none());
+    }
+
+    #[test]
+    fn test_throughput_mean() {
+        let now = Utc::now();
+        let history = vec![work(10, 5.0, now), work(5, 5.0, now)];
+        let t = Throughput::from_history(&history, now).unwrap();
+
+        assert!((t.mean - 1.0).abs() < 1e-9);
+        assert!(t.low <= t.mean);
+        assert!(t.high >= t.mean);
+        assert!(t.low > 0.0);
+    }
+
+    #[test]
+    fn test_throughput_short_window() {
+        let now = Utc::now();
+        // Finished a moment ago: window is clamped to one day
+        let t = Throughput::from_history(&[work(0, 2.0, now)], now).unwrap();
+        assert!((t.mean - 2.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_forecast_projection_range() {
+        let now = Utc::now();
+        let t = Throughput {
+            mean: 1.0,
+            low: 0.5,
+            high: 2.0,
+        };
+        let f = Forecast::new("ROOT", Some(1), 10.0, 4.0, Some(&t), now);
+
+        assert_eq!(f.expected.unwrap(), now + Duration::days(4));
+        assert_eq!(f.earliest.unwrap(), now + Duration::days(2));
+        assert_eq!(f.latest.unwrap(), now + Duration::days(8));
+        assert!(!f.is_complete());
+    }
+
+    #[test]
+    fn test_forecast_beyond_representable_dates() {
+        let now = Utc::now();
+        let t = Throughput {
+            mean: 1e-6,
+            low: 1e-300,
+            high: 1.0,
+        };
+        let f = Forecast::new("ROOT", Some(1), 1e9, 1e9, Some(&t), now);
+        assert!(f.expected.is_none());
+        assert!(f.latest.is_none());
+        assert!(f.earliest.is_none());
+
+        let f = Forecast::new("ROOT", Some(1), 10.0, 4.0, Some(&t), now);
+        assert_eq!(f.earliest.unwrap(), now + Duration::days(4));
+        assert!(f.latest.is_none());
+    }
+
+    #[test]
+    fn test_forecast_without_throughput() {
+        let f = Forecast::new("ROOT", None, 10.0, 4.0, None, Utc::now());
+        assert!(f.expected.is_none());
+        assert!(f.earliest.is_none());
+        assert!(f.latest.is_none());
+    }
+
+    #[test]
+    fn test_forecast_complete() {
+        let now = Utc::now();
+        let t = Throughput {
+            mean: 1.0,
+            low: 0.5,
+            high: 2.0,
+        };
+        let f = Forecast::new("ROOT", Some(1), 10.0, 0.0, Some(&t), now);
+        assert!(f.is_complete());
+        assert_eq!(f.expected, Some(now));
+    }
+}
@@ -2,12 +2,27 @@
 //!
 //! Orchestrates the development process execution.
 
+pub mod builder;
+pub mod clock;
 mod context;
+pub mod forecast;
 pub mod operations;
+pub mod ranking;
+pub mod reconcile;
+pub mod restructure;
+pub mod schedule;
+pub mod staleness;
 mod state;
 mod validation;
 
+pub use builder::ProcessEngineBuilder;
+pub use clock::{Clock, FixedClock, SystemClock};
 pub use context::ContextBuilder;
-pub use operations::{IssueType, ProcessEngine, ValidationReport};
+pub use forecast::{Forecast, Throughput};
+pub use operations::{IssueType, ProcessEngine, StartOptions, ValidationReport};
+pub use reconcile::{ChangeKind, DefinitionChange, IterationReconcile, ReconcilePlan};
+pub use restructure::{StepMove, StepRemoval};
+pub use schedule::{IterationClose, Overdue, OverdueKind};
+pub use staleness::{StaleKind, StaleWork};
 pub use state::StateManager;
 pub use validation::Validator;
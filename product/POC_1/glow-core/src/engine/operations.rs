@@ -2,150 +2,120 @@
 //!
 //! Main process engine implementation.
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
-use crate::config::{Config, ConfigLoader, ProcessConfig};
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::config::{
+    check_process_config, Config, ConfigCheck, ConfigFinding, ConfigLoader, MigrationPlan,
+    Preset, ProcessConfig, StepFormat,
+};
 use crate::error::{GlowError, Result};
 use crate::model::{
-    LinkGraph, ParameterValue, Step, StepDefinition, StepRef, StepStatus,
+    fill_weights, parse_iteration_days, Estimate, EstimateUnit, JournalEntry, LinkGraph, LinkRef, ParameterValue,
+    Step, StepChange, StepDefinition, StepRef, StepStatus, Timebox, WipLimit,
+};
+use crate::storage::{
+    ArchiveStub, Conversion, Recovery, Snapshot, SnapshotDiff, SnapshotInfo, SnapshotRestore, Storage,
+    CURRENT_DATA, NOTES_FILE,
 };
-use crate::storage::Storage;
 use crate::template::TemplateEngine;
 
+use super::builder::ProcessEngineBuilder;
+use super::clock::Clock;
+use super::forecast::{CompletedWork, Forecast, Throughput};
+use super::reconcile::{diff_iteration, ChangeKind, IterationReconcile, ReconcilePlan};
+use super::restructure::{move_definition, remove_definition, split_fqid, StepMove, StepRemoval};
+use super::ranking::{analyze_siblings, GraphPosition, RankFactors, Sibling};
+use super::schedule::{find_overdue, IterationClose, Overdue};
+use super::staleness::{assess, StaleCandidate, StaleWork};
 use super::state::StateManager;
 use super::validation::Validator;
 
 /// Main process engine
 pub struct ProcessEngine {
     /// Project root path
-    project_root: PathBuf,
+    pub(super) project_root: PathBuf,
     /// Configuration loader
-    #[allow(dead_code)]
-    config_loader: ConfigLoader,
+    pub(super) config_loader: ConfigLoader,
     /// Project configuration
-    config: Config,
+    pub(super) config: Config,
     /// Process configuration
-    process_config: ProcessConfig,
+    pub(super) process_config: ProcessConfig,
     /// Storage layer
-    storage: Storage,
+    pub(super) storage: Storage,
     /// Template engine
-    template_engine: TemplateEngine,
+    pub(super) template_engine: TemplateEngine,
     /// State manager
-    #[allow(dead_code)]
-    state_manager: StateManager,
+    pub(super) state_manager: StateManager,
     /// Validator
-    validator: Validator,
+    pub(super) validator: Validator,
+    /// Source of the current time
+    pub(super) clock: Arc<dyn Clock>,
+    /// Who makes the changes (None if unknown)
+    pub(super) actor: Option<String>,
+    /// Interrupted operation recovered when loading (None if there was none)
+    pub(super) recovery: Option<Recovery>,
 }
 
 impl ProcessEngine {
+    /// Start building an engine for a project with non-default components
+    pub fn builder(project_root: impl Into<PathBuf>) -> ProcessEngineBuilder {
+        ProcessEngineBuilder::new(project_root)
+    }
+
     /// Create a new process engine for an existing project
     pub fn new(project_root: PathBuf) -> Result<Self> {
-        let config_dir = crate::config::find_config_dir(&project_root, None)?;
-        let config_loader = ConfigLoader::new(config_dir.clone(), project_root.clone());
-
-        let config = config_loader.load_config()?;
-        let process_config = config_loader.load_process_config(&config)?;
-
-        let storage = Storage::new(project_root.clone(), &config, config_dir.clone());
-        let templates_dir = crate::config::templates_dir(&config_dir);
-        let template_engine = TemplateEngine::new(templates_dir);
-
-        let state_manager = StateManager::new();
-        let validator = Validator::new();
-
-        Ok(Self {
-            project_root,
-            config_loader,
-            config,
-            process_config,
-            storage,
-            template_engine,
-            state_manager,
-            validator,
-        })
+        Self::builder(project_root).build()
     }
 
-    /// Initialize a new project
-    pub fn init_project(project_root: PathBuf, project_name: Option<String>) -> Result<Self> {
-        let config_dir = crate::config::default_config_dir(&project_root);
-
-        // Check if already initialized
-        if config_dir.exists() {
-            return Err(GlowError::ProjectAlreadyExists {
-                path: config_dir,
-            });
-        }
-
-        // Create config directory
-        std::fs::create_dir_all(&config_dir)?;
+    /// Check a project's process config without loading the project
+    ///
+    /// Reports syntax, schema and semantic problems even when they would
+    /// prevent `new` from succeeding.
+    pub fn check_project(project_root: PathBuf) -> Result<ConfigCheck> {
+        Self::builder(project_root).check()
+    }
 
-        // Create schemas directory and copy schemas
-        let schemas_dir = crate::config::schemas_dir(&config_dir);
-        std::fs::create_dir_all(&schemas_dir)?;
-        Self::write_schema_files(&schemas_dir)?;
+    /// Plan migrating a project to the current schema version without loading it
+    pub fn plan_migration(project_root: PathBuf) -> Result<MigrationPlan> {
+        Self::builder(project_root).plan_migration()
+    }
 
-        // Create templates directory
-        let templates_dir = crate::config::templates_dir(&config_dir);
-        std::fs::create_dir_all(&templates_dir)?;
-        Self::write_default_template(&templates_dir)?;
+    /// Migrate a project to the current schema version
+    ///
+    /// Returns the migration carried out and the folder holding the backups
+    /// of the changed files (None if the project was up to date).
+    pub fn migrate_project(project_root: PathBuf) -> Result<(MigrationPlan, Option<PathBuf>)> {
+        Self::builder(project_root).migrate()
+    }
 
-        // Create config file
-        let config = if let Some(name) = project_name {
-            Config::new(name)
-        } else {
-            Config::default()
-        };
+    /// Initialize a new project
+    pub fn init_project(project_root: PathBuf, project_name: Option<String>) -> Result<Self> {
+        Self::builder(project_root).init(project_name, None)
+    }
 
-        let config_loader = ConfigLoader::new(config_dir.clone(), project_root.clone());
-        config_loader.save_config(&config)?;
-
-        // Create process config
-        let process_config = ProcessConfig::default();
-        config_loader.save_process_config(&config, &process_config)?;
-
-        // Create storage
-        let storage = Storage::new(project_root.clone(), &config, config_dir.clone());
-        storage.init_data_dir()?;
-
-        let template_engine = TemplateEngine::new(templates_dir);
-        let state_manager = StateManager::new();
-        let validator = Validator::new();
-
-        Ok(Self {
-            project_root,
-            config_loader,
-            config,
-            process_config,
-            storage,
-            template_engine,
-            state_manager,
-            validator,
-        })
+    /// Initialize a new project from a process preset
+    pub fn init_project_with_preset(
+        project_root: PathBuf,
+        project_name: Option<String>,
+        preset: Option<&Preset>,
+    ) -> Result<Self> {
+        Self::builder(project_root).init(project_name, preset)
     }
 
     /// Write schema files to schemas directory
-    fn write_schema_files(schemas_dir: &Path) -> Result<()> {
-        std::fs::write(
-            schemas_dir.join("config.schema.json"),
-            crate::config::schema::CONFIG_SCHEMA,
-        )?;
-        std::fs::write(
-            schemas_dir.join("process_config.schema.json"),
-            crate::config::schema::PROCESS_CONFIG_SCHEMA,
-        )?;
-        std::fs::write(
-            schemas_dir.join("step_data.schema.json"),
-            crate::config::schema::STEP_DATA_SCHEMA,
-        )?;
-        std::fs::write(
-            schemas_dir.join("mcp_config.schema.json"),
-            crate::config::schema::MCP_CONFIG_SCHEMA,
-        )?;
+    pub(super) fn write_schema_files(schemas_dir: &Path, process_config: &ProcessConfig) -> Result<()> {
+        for (name, schema) in crate::config::schema::schema_files(Some(process_config)) {
+            std::fs::write(schemas_dir.join(name), crate::config::schema::render(&schema))?;
+        }
         Ok(())
     }
 
     /// Write default template
-    fn write_default_template(templates_dir: &Path) -> Result<()> {
+    pub(super) fn write_default_template(templates_dir: &Path) -> Result<()> {
         std::fs::write(
             templates_dir.join("any-step.md"),
             crate::template::DEFAULT_TEMPLATE,
@@ -168,11 +138,31 @@ impl ProcessEngine {
         &self.process_config
     }
 
+    /// Get the path of the process config file
+    pub fn process_config_path(&self) -> PathBuf {
+        self.config_loader.config_dir().join(&self.config.process_config)
+    }
+
     /// Get storage
     pub fn storage(&self) -> &Storage {
         &self.storage
     }
 
+    /// Get the clock the engine reads the current time from
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Get who makes the changes: the builder's actor, else `actor` in the config
+    pub fn actor(&self) -> Option<&str> {
+        self.actor.as_deref().or(self.config.actor.as_deref())
+    }
+
+    /// Get the operation an earlier process was interrupted in, recovered when loading
+    pub fn recovery(&self) -> Option<&Recovery> {
+        self.recovery.as_ref()
+    }
+
     /// Initialize a process step
     pub fn init_step(
         &mut self,
@@ -180,54 +170,89 @@ impl ProcessEngine {
         scope_params: Vec<ParameterValue>,
         force_new_iteration: bool,
     ) -> Result<Step> {
-        let step_def = self.process_config.find_step_definition(fqid)
-            .ok_or_else(|| GlowError::StepNotFound { fqid: fqid.to_string() })?
-            .clone();
+        self.operation(|engine| {
+            let step_def = engine.process_config.find_step_definition(fqid)
+                .ok_or_else(|| GlowError::StepNotFound { fqid: fqid.to_string() })?
+                .clone();
 
-        // Check if step already exists and is in progress
-        if self.storage.step_files().step_exists(fqid) {
-            let existing = self.storage.read_step(fqid)?;
-            if existing.status() == StepStatus::InProgress && !force_new_iteration {
-                // Return existing step
-                return Ok(existing);
+            // Check if step already exists and is in progress
+            if engine.storage.step_exists(fqid) {
+                let existing = engine.storage.read_step(fqid)?;
+                if existing.status() == StepStatus::InProgress && !force_new_iteration {
+                    // Return existing step
+                    return Ok(existing);
+                }
             }
-        }
 
-        // Validate dependencies are satisfied
-        if fqid != "ROOT" {
-            self.validate_dependencies_for_step(fqid)?;
-        }
+            // Validate dependencies are satisfied
+            if fqid != "ROOT" {
+                engine.validate_dependencies_for_step(fqid)?;
+            }
 
-        // Create or update step
-        let parent_fqid = self.get_parent_fqid(fqid);
-        let mut step = Step::from_definition(&step_def, parent_fqid.as_deref());
+            // Create or update step
+            let parent_fqid = engine.get_parent_fqid(fqid);
+            let mut step = Step::from_definition(&step_def, parent_fqid.as_deref());
 
-        // Set scope parameters
-        for param in scope_params {
-            step.scope.push(param);
-        }
+            // Set scope parameters
+            for param in scope_params {
+                step.scope.push(param);
+            }
 
-        // Determine initial status
-        if self.can_step_start(&step, &step_def)? {
-            step.attr.status = StepStatus::Todo;
-        } else {
-            step.attr.status = StepStatus::Wait;
-        }
+            // Determine initial status
+            let initial_status = if engine.can_step_start(&step, &step_def)? {
+                StepStatus::Todo
+            } else {
+                StepStatus::Wait
+            };
+            step.attr.set_status(initial_status, engine.clock.now());
+
+            // For process steps, create iteration
+            if step_def.is_process() {
+                engine.init_process_iteration(&mut step, &step_def)?;
+            }
 
-        // For process steps, create iteration
-        if step_def.is_process() {
-            self.init_process_iteration(&mut step, &step_def)?;
-        }
+            // Write step file
+            engine.storage.write_step(&mut step)?;
+            engine.record_status(&step)?;
+
+            // Render description file if todo
+            if step.status() == StepStatus::Todo {
+                engine.render_description_file(&step)?;
+            }
 
-        // Write step file
-        self.storage.write_step(&step)?;
+            Ok(step)
+        })
+    }
 
-        // Render description file if todo
-        if step.status() == StepStatus::Todo {
-            self.render_description_file(&step)?;
+    /// Run a change to the project's data as one operation
+    ///
+    /// Holds the project lock against other glow processes, and applies the
+    /// change's writes together once it succeeds, or none of them if it fails.
+    fn operation<T>(&mut self, change: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        self.storage.begin()?;
+        match change(self) {
+            Ok(value) => self.storage.commit().map(|()| value),
+            Err(e) => {
+                self.storage.abort();
+                Err(e)
+            }
         }
+    }
 
-        Ok(step)
+    /// Record a step's current status in the journal
+    fn record_status(&self, step: &Step) -> Result<()> {
+        self.storage.store().append_journal(&JournalEntry {
+            at: step.attr.status_since.unwrap_or_else(|| self.clock.now()),
+            fqid: step.fqid().to_string(),
+            status: step.status(),
+            actor: self.actor().map(String::from),
+            change: None,
+        })
+    }
+
+    /// Get the journal of status changes, oldest first
+    pub fn get_journal(&self) -> Result<Vec<JournalEntry>> {
+        self.storage.store().read_journal()
     }
 
     /// Get parent FQID from a FQID
@@ -252,7 +277,7 @@ impl ProcessEngine {
             let parent_def = self.process_config.find_step_definition(&parent_fqid)
                 .ok_or_else(|| GlowError::StepNotFound { fqid: parent_fqid.clone() })?;
 
-            let step_id = fqid.split('.').last().unwrap_or(fqid);
+            let step_id = fqid.split('.').next_back().unwrap_or(fqid);
             let blocking_deps: Vec<String> = parent_def.links.iter()
                 .filter(|l| l.from == step_id && l.is_blocking())
                 .filter_map(|l| {
@@ -262,15 +287,11 @@ impl ProcessEngine {
                         format!("{}.{}", parent_fqid, l.to)
                     };
                     
-                    // Check if dependency is done
-                    if let Ok(dep_step) = self.storage.read_step(&dep_fqid) {
-                        if dep_step.status() != StepStatus::Done {
-                            return Some(l.to.clone());
-                        }
-                    } else {
-                        return Some(l.to.clone());
+                    // Check if dependency is done, reading archived steps from their stub
+                    match self.storage.read_summary(&dep_fqid) {
+                        Ok(dep) if dep.status() == StepStatus::Done => None,
+                        _ => Some(l.to.clone()),
                     }
-                    None
                 })
                 .collect();
 
@@ -289,11 +310,10 @@ impl ProcessEngine {
     fn can_step_start(&self, step: &Step, def: &StepDefinition) -> Result<bool> {
         // Check required scope parameters
         for scope_ref in &def.scope {
-            if scope_ref.is_required.unwrap_or(false) {
-                if !step.scope.iter().any(|p| p.id == scope_ref.id && p.value.is_some()) {
+            if scope_ref.is_required.unwrap_or(false)
+                && !step.scope.iter().any(|p| p.id == scope_ref.id && p.value.is_some()) {
                     return Ok(false);
                 }
-            }
         }
 
         Ok(true)
@@ -301,23 +321,33 @@ impl ProcessEngine {
 
     /// Initialize process iteration
     fn init_process_iteration(&mut self, step: &mut Step, def: &StepDefinition) -> Result<()> {
-        // Create iteration folder
-        let iteration_num = 1; // TODO: Track iteration numbers
-        let _iteration_path = self.storage.create_iteration_folder(step.fqid(), iteration_num)?;
+        // Create iteration folder, after those of earlier initializations
+        let iteration_num = self.storage.current_iteration(step.fqid()).map_or(1, |n| n + 1);
+        self.storage.create_iteration(step.fqid(), iteration_num)?;
+        step.attr.iteration = Some(iteration_num);
+        step.attr.definition_version = Some(def.version());
+        step.attr.timebox = def
+            .iteration_length
+            .as_deref()
+            .and_then(parse_iteration_days)
+            .and_then(|days| Timebox::starting(self.clock.today(), days));
 
         // Create sub-steps
         for sub_def in &def.steps {
             let _sub_fqid = format!("{}.{}", step.fqid(), sub_def.id);
-            let sub_step = Step::from_definition(sub_def, Some(step.fqid()));
-            
+            let mut sub_step = Step::from_definition(sub_def, Some(step.fqid()));
+            sub_step.attr.set_status(StepStatus::Wait, self.clock.now());
+            sub_step.links = def.link_refs(&sub_def.id);
+
             // Add to own_steps
             step.own_steps.push(StepRef {
                 id: sub_def.id.clone(),
                 status: StepStatus::Wait,
+                iteration: None,
             });
 
             // Write sub-step file
-            self.storage.write_step(&sub_step)?;
+            self.storage.write_step(&mut sub_step)?;
         }
 
         // Update sub-step statuses based on dependencies
@@ -370,8 +400,9 @@ impl ProcessEngine {
             // Update the actual step file
             if let Ok(mut sub_step) = self.storage.read_step(&sub_fqid) {
                 if sub_step.attr.status == StepStatus::Wait {
-                    sub_step.attr.status = StepStatus::Todo;
-                    self.storage.write_step(&sub_step)?;
+                    sub_step.attr.set_status(StepStatus::Todo, self.clock.now());
+                    self.storage.write_step(&mut sub_step)?;
+                    self.record_status(&sub_step)?;
                 }
             }
         }
@@ -381,21 +412,19 @@ impl ProcessEngine {
 
     /// Render description file for a step
     fn render_description_file(&mut self, step: &Step) -> Result<()> {
-        let content = self.template_engine.render_step(step, None)?;
-        
-        let folder_path = self.storage.step_files().step_folder_path(step.fqid());
-        std::fs::create_dir_all(&folder_path)?;
-        
-        let desc_path = folder_path.join("description.md");
+        let template = self
+            .process_config
+            .find_step_definition(step.fqid())
+            .and_then(|def| def.template.clone())
+            .unwrap_or_else(|| self.config.default_template.clone());
+        let content = self.template_engine.render_step(step, Some(&template))?;
         
         // Extract body from rendered content and resolve references
         let body = self.extract_body_from_rendered(&content);
         let context = crate::template::renderer::TemplateContext::from_step(step);
         let resolved = self.template_engine.render_content_template(&body, &context)?;
         
-        std::fs::write(&desc_path, resolved)?;
-        
-        Ok(())
+        self.storage.store().write_document(step.fqid(), "description.md", &resolved)
     }
 
     /// Extract body from rendered template
@@ -409,27 +438,49 @@ impl ProcessEngine {
 
     /// Start a task step
     pub fn start_step(&mut self, fqid: &str) -> Result<Step> {
-        let mut step = self.storage.read_step(fqid)?;
-
-        // Validate state transition
-        if step.status() != StepStatus::Todo {
-            return Err(GlowError::InvalidStateTransition {
-                step_id: fqid.to_string(),
-                current: step.status().to_string(),
-                target: StepStatus::InProgress.to_string(),
-            });
-        }
+        self.start_step_with(fqid, StartOptions::default())
+    }
+
+    /// Start a task step with options
+    pub fn start_step_with(&mut self, fqid: &str, options: StartOptions) -> Result<Step> {
+        self.operation(|engine| {
+            let mut step = engine.storage.read_step(fqid)?;
+
+            // Validate state transition
+            if step.status() != StepStatus::Todo {
+                return Err(GlowError::InvalidStateTransition {
+                    step_id: fqid.to_string(),
+                    current: step.status().to_string(),
+                    target: StepStatus::InProgress.to_string(),
+                });
+            }
 
-        // Update status
-        step.attr.status = StepStatus::InProgress;
+            // Enforce work-in-progress limits
+            if !options.override_wip_limits {
+                engine.sync_active_steps()?;
+                let exceeded = engine.exceeded_wip_limits(fqid, engine.state_manager.active_steps());
+                if let Some((limit, active)) = exceeded.into_iter().next() {
+                    return Err(GlowError::WipLimitExceeded {
+                        step_id: fqid.to_string(),
+                        limit: limit.to_string(),
+                        active,
+                    });
+                }
+            }
+
+            // Update status
+            step.attr.set_status(StepStatus::InProgress, engine.clock.now());
 
-        // Re-render step file
-        self.storage.write_step(&step)?;
+            // Re-render step file
+            engine.storage.write_step(&mut step)?;
+            engine.record_status(&step)?;
+            engine.state_manager.mark_active(fqid);
         
-        // Render description file
-        self.render_description_file(&step)?;
+            // Render description file
+            engine.render_description_file(&step)?;
 
-        Ok(step)
+            Ok(step)
+        })
     }
 
     /// Finish a task step
@@ -439,39 +490,45 @@ impl ProcessEngine {
         outputs: Vec<ParameterValue>,
         summary: Option<String>,
     ) -> Result<Step> {
-        let mut step = self.storage.read_step(fqid)?;
-
-        // Validate state transition
-        if step.status() != StepStatus::InProgress {
-            return Err(GlowError::InvalidStateTransition {
-                step_id: fqid.to_string(),
-                current: step.status().to_string(),
-                target: StepStatus::Done.to_string(),
-            });
-        }
+        self.operation(|engine| {
+            let mut step = engine.storage.read_step(fqid)?;
 
-        // Set outputs
-        for output in outputs {
-            step.output.push(output);
-        }
-
-        // Update status
-        step.attr.status = StepStatus::Done;
+            // Validate state transition
+            if step.status() != StepStatus::InProgress {
+                return Err(GlowError::InvalidStateTransition {
+                    step_id: fqid.to_string(),
+                    current: step.status().to_string(),
+                    target: StepStatus::Done.to_string(),
+                });
+            }
 
-        // Write step file
-        self.storage.write_step(&step)?;
+            // Set outputs
+            for output in outputs {
+                step.output.push(output);
+            }
 
-        // Update summary if provided
-        if let Some(summary_text) = summary {
-            let folder_path = self.storage.step_files().step_folder_path(fqid);
-            let summary_path = folder_path.join("summary.md");
-            std::fs::write(&summary_path, format!("# Summary\n\n{}\n", summary_text))?;
-        }
+            // Update status
+            step.attr.set_status(StepStatus::Done, engine.clock.now());
+
+            // Write step file
+            engine.storage.write_step(&mut step)?;
+            engine.record_status(&step)?;
+            engine.state_manager.mark_inactive(fqid);
+
+            // Update summary if provided
+            if let Some(summary_text) = summary {
+                engine.storage.store().write_document(
+                    fqid,
+                    "summary.md",
+                    &format!("# Summary\n\n{}\n", summary_text),
+                )?;
+            }
 
-        // Update parent process
-        self.update_parent_after_step_done(&step)?;
+            // Update parent process
+            engine.update_parent_after_step_done(&step)?;
 
-        Ok(step)
+            Ok(step)
+        })
     }
 
     /// Update parent process after a step is done
@@ -482,6 +539,7 @@ impl ProcessEngine {
                 .clone();
 
             let mut parent_step = self.storage.read_step(&parent_fqid)?;
+            self.refresh_derived(&mut parent_step);
 
             // Update own_steps status
             for own_step in &mut parent_step.own_steps {
@@ -494,25 +552,853 @@ impl ProcessEngine {
             self.update_sub_step_statuses(&mut parent_step, &parent_def)?;
 
             // Check if all sub-steps are done
-            if parent_step.own_steps.iter().all(|s| s.status == StepStatus::Done) {
-                parent_step.attr.status = StepStatus::Done;
+            let all_done = parent_step.own_steps.iter().all(|s| s.status == StepStatus::Done);
+            if all_done {
+                parent_step.attr.set_status(StepStatus::Done, self.clock.now());
+                self.state_manager.mark_inactive(&parent_fqid);
             }
 
-            self.storage.write_step(&parent_step)?;
+            self.storage.write_step(&mut parent_step)?;
+            if all_done {
+                self.record_status(&parent_step)?;
+            }
         }
 
         Ok(())
     }
 
+    /// Refresh the state manager's active steps from step files
+    fn sync_active_steps(&mut self) -> Result<()> {
+        let status_tree = self.get_status_tree()?;
+        for node in status_tree.flatten() {
+            if node.status == StepStatus::InProgress {
+                self.state_manager.mark_active(&node.fqid);
+            } else {
+                self.state_manager.mark_inactive(&node.fqid);
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the steps currently in progress
+    pub fn active_steps(&mut self) -> Result<Vec<String>> {
+        self.sync_active_steps()?;
+        Ok(self.state_manager.active_steps().to_vec())
+    }
+
+    /// Get the WIP limits a step would exceed if started, with the steps counted against each
+    pub fn exceeded_wip_limits(&self, fqid: &str, active: &[String]) -> Vec<(&WipLimit, Vec<String>)> {
+        self.process_config
+            .wip_limits
+            .iter()
+            .filter(|limit| self.wip_limit_applies(limit, fqid))
+            .filter_map(|limit| {
+                let counted: Vec<String> = active
+                    .iter()
+                    .filter(|a| *a != fqid && self.wip_limit_applies(limit, a))
+                    .cloned()
+                    .collect();
+                (counted.len() >= limit.max).then_some((limit, counted))
+            })
+            .collect()
+    }
+
+    /// Check if a WIP limit applies to a step
+    fn wip_limit_applies(&self, limit: &WipLimit, fqid: &str) -> bool {
+        let def = self.process_config.find_step_definition(fqid);
+        limit.applies_to(
+            self.get_parent_fqid(fqid).as_deref(),
+            def.and_then(|d| d.classification.as_deref()),
+            def.map(|d| d.is_task()).unwrap_or(true),
+        )
+    }
+
     /// Show step details
+    ///
+    /// Steps archived with an iteration are shown from the archive's stub,
+    /// without their parameter values.
     pub fn show_step(&self, fqid: &str) -> Result<Step> {
+        let mut step = match self.storage.read_step(fqid) {
+            Err(GlowError::StepNotFound { .. }) => self.storage.read_summary(fqid)?.to_step(),
+            result => result?,
+        };
+        self.refresh_derived(&mut step);
+        Ok(step)
+    }
+
+    /// Recompute the statuses a step copies from its sub-steps and linked steps
+    ///
+    /// The copies are derived data that a git merge may leave stale; the
+    /// files of the steps themselves are authoritative. Link statuses are
+    /// refreshed only where a step file records them.
+    fn refresh_derived(&self, step: &mut Step) {
+        let fqid = step.fqid().to_string();
+        for own_step in &mut step.own_steps {
+            if let Ok(summary) = self.storage.read_summary(&sub_step_fqid(&fqid, &own_step.id)) {
+                own_step.status = summary.status();
+            }
+        }
+
+        let parent_fqid = self.get_parent_fqid(&fqid).unwrap_or_else(|| "ROOT".to_string());
+        for link in step.links.iter_mut().filter(|link| link.step_status.is_some()) {
+            if let Ok(summary) = self.storage.read_summary(&sub_step_fqid(&parent_fqid, &link.step_id)) {
+                link.step_status = Some(summary.status());
+            }
+        }
+    }
+
+    /// Set or clear the estimate of an initialized step
+    ///
+    /// Refused if other steps are estimated in the other unit (points or
+    /// durations), as the two can't be added up.
+    pub fn set_estimate(&mut self, fqid: &str, estimate: Option<Estimate>) -> Result<Step> {
+        self.operation(|engine| {
+            if let Some(estimate) = &estimate {
+                let tree = engine.get_status_tree()?;
+                let other = tree.flatten().into_iter().find_map(|node| {
+                    let unit = node.estimate.as_ref().map(Estimate::unit)?;
+                    (node.fqid != fqid && unit != estimate.unit()).then_some((node, unit))
+                });
+                if let Some((other, other_unit)) = other {
+                    return Err(mixed_units(fqid, estimate.unit(), &other.fqid, other_unit));
+                }
+            }
+
+            let mut step = engine.storage.read_step(fqid)?;
+            step.attr.estimate = estimate;
+            engine.storage.write_step(&mut step)?;
+            Ok(step)
+        })
+    }
+
+    /// Set or clear the due date of an initialized step
+    pub fn set_due(&mut self, fqid: &str, due: Option<NaiveDate>) -> Result<Step> {
+        self.operation(|engine| {
+            let mut step = engine.storage.read_step(fqid)?;
+            step.attr.due = due;
+            engine.storage.write_step(&mut step)?;
+            Ok(step)
+        })
+    }
+
+    /// Set or clear the time box of a process's current iteration
+    pub fn set_timebox(&mut self, fqid: &str, timebox: Option<Timebox>) -> Result<Step> {
+        self.operation(|engine| {
+            if let Some(t) = &timebox {
+                if t.max < t.min {
+                    return Err(GlowError::ValidationError {
+                        message: format!("Time box ends before it starts: {} .. {}", t.min, t.max),
+                    });
+                }
+            }
+
+            let mut step = engine.read_process_step(fqid)?;
+            step.attr.timebox = timebox;
+            engine.storage.write_step(&mut step)?;
+            Ok(step)
+        })
+    }
+
+    /// Close the current iteration of a process and open the next one
+    ///
+    /// Unfinished sub-steps are carried over with their data intact; the
+    /// closed iteration's summary records what was done and what moved on,
+    /// and the process's `own_steps` record the iteration each finished
+    /// sub-step was done in.
+    /// Without an explicit time box, the next one follows the previous one
+    /// with the definition's iteration length (or the same length).
+    pub fn close_iteration(&mut self, fqid: &str, next: Option<Timebox>) -> Result<IterationClose> {
+        self.operation(|engine| {
+            let mut step = engine.read_process_step(fqid)?;
+            if step.status() == StepStatus::Done {
+                return Err(GlowError::ValidationError {
+                    message: format!("Process '{}' is done; no iteration to close", fqid),
+                });
+            }
+
+            let def_length = engine
+                .process_config
+                .find_step_definition(fqid)
+                .and_then(|d| d.iteration_length.as_deref())
+                .and_then(parse_iteration_days);
+
+            let closed = step
+                .attr
+                .iteration
+                .or_else(|| engine.storage.current_iteration(fqid))
+                .unwrap_or(1);
+            let opened = closed + 1;
+
+            let mut done = Vec::new();
+            let mut carried_over = Vec::new();
+            for own_step in &mut step.own_steps {
+                let sub_fqid = sub_step_fqid(fqid, &own_step.id);
+                let finished = engine
+                    .storage
+                    .read_summary(&sub_fqid)
+                    .is_ok_and(|summary| summary.status() == StepStatus::Done);
+
+                if finished {
+                    // Steps done in an earlier iteration belong to it
+                    if own_step.iteration.is_none() {
+                        own_step.iteration = Some(closed);
+                        done.push(sub_fqid);
+                    }
+                    continue;
+                }
+                own_step.iteration = None;
+                if let Ok(mut sub_step) = engine.storage.read_step(&sub_fqid) {
+                    sub_step.attr.carried_over += 1;
+                    engine.storage.write_step(&mut sub_step)?;
+                }
+                carried_over.push(sub_fqid);
+            }
+
+            // Record the closed iteration
+            engine.storage.create_iteration(fqid, closed)?;
+            engine.storage.store().write_iteration_document(
+                fqid,
+                closed,
+                "summary.md",
+                &render_iteration_summary(
+                    closed,
+                    step.attr.timebox.as_ref(),
+                    engine.clock.today(),
+                    &done,
+                    &carried_over,
+                ),
+            )?;
+
+            let timebox = match (next, step.attr.timebox.as_ref()) {
+                (Some(next), _) => Some(next),
+                (None, Some(previous)) => Some(previous.following(def_length).ok_or_else(|| {
+                    GlowError::ValidationError {
+                        message: format!(
+                            "No time box follows {} .. {}; set the next one explicitly",
+                            previous.min, previous.max
+                        ),
+                    }
+                })?),
+                (None, None) => def_length.and_then(|days| Timebox::starting(engine.clock.today(), days)),
+            };
+
+            engine.storage.create_iteration(fqid, opened)?;
+            step.attr.iteration = Some(opened);
+            step.attr.timebox = timebox.clone();
+            engine.storage.write_step(&mut step)?;
+
+            Ok(IterationClose {
+                fqid: fqid.to_string(),
+                closed,
+                opened,
+                done,
+                carried_over,
+                timebox,
+            })
+        })
+    }
+
+    /// Read an initialized process step
+    fn read_process_step(&self, fqid: &str) -> Result<Step> {
+        let is_process = self
+            .process_config
+            .find_step_definition(fqid)
+            .ok_or_else(|| GlowError::StepNotFound { fqid: fqid.to_string() })?
+            .is_process();
+        if !is_process {
+            return Err(GlowError::ValidationError {
+                message: format!("Step '{}' is not a process and has no iterations", fqid),
+            });
+        }
         self.storage.read_step(fqid)
     }
 
+    /// Archive a completed iteration of a process
+    ///
+    /// Packs the iteration folder and the data of the sub-steps finished in
+    /// it into a compressed archive, leaving a stub through which they still
+    /// count in status and progress. An iteration is completed once closed,
+    /// or when its process is done.
+    pub fn archive_iteration(&mut self, fqid: &str, iteration: u32) -> Result<ArchiveStub> {
+        self.operation(|engine| {
+            let step = engine.read_process_step(fqid)?;
+            let current = step
+                .attr
+                .iteration
+                .or_else(|| engine.storage.current_iteration(fqid))
+                .unwrap_or(1);
+
+            let finished = if iteration < current {
+                let summary = engine
+                    .storage
+                    .store()
+                    .read_iteration_document(fqid, iteration, "summary.md")?
+                    .unwrap_or_default();
+                parse_iteration_done(&summary)
+            } else if iteration == current && step.status() == StepStatus::Done {
+                step.own_steps.iter().map(|s| sub_step_fqid(fqid, &s.id)).collect()
+            } else if iteration == current {
+                return Err(GlowError::ValidationError {
+                    message: format!("Iteration {} of '{}' is still open; close it first", iteration, fqid),
+                });
+            } else {
+                return Err(GlowError::IterationNotFound {
+                    fqid: fqid.to_string(),
+                    iteration,
+                });
+            };
+
+            // Steps archived with an earlier iteration, or reopened since, stay
+            let steps: Vec<String> = finished
+                .into_iter()
+                .filter(|sub_fqid| {
+                    engine
+                        .storage
+                        .read_step(sub_fqid)
+                        .is_ok_and(|sub_step| sub_step.status() == StepStatus::Done)
+                })
+                .collect();
+            engine
+                .storage
+                .store()
+                .archive_iteration(fqid, iteration, &steps, engine.clock.now())
+        })
+    }
+
+    /// Restore an archived iteration of a process, after verifying the archive
+    pub fn restore_iteration(&mut self, fqid: &str, iteration: u32) -> Result<ArchiveStub> {
+        self.operation(|engine| engine.storage.store().restore_iteration(fqid, iteration))
+    }
+
+    /// Check the archive of an iteration against its checksums, returning the problems found
+    pub fn verify_archive(&self, fqid: &str, iteration: u32) -> Result<Vec<String>> {
+        self.storage.store().verify_archive(fqid, iteration)
+    }
+
+    /// List the archived iterations of a process, oldest first
+    pub fn archived_iterations(&self, fqid: &str) -> Result<Vec<ArchiveStub>> {
+        self.storage.store().archived_iterations(fqid)
+    }
+
+    /// Get overdue steps and iterations
+    pub fn get_overdue(&self) -> Result<Vec<Overdue>> {
+        let status_tree = self.get_status_tree()?;
+        Ok(find_overdue(&status_tree, self.clock.today()))
+    }
+
+    /// Get stale steps with suggested remediations
+    ///
+    /// Activity is the latest status change or change to the step's own
+    /// documents and artifacts.
+    pub fn get_stale_work(&self) -> Result<Vec<StaleWork>> {
+        let status_tree = self.get_status_tree()?;
+        Ok(self.stale_work_in(&status_tree))
+    }
+
+    /// Get stale steps of a status tree
+    pub(super) fn stale_work_in(&self, status_tree: &StatusTree) -> Vec<StaleWork> {
+        let staleness = self.process_config.staleness.clone().unwrap_or_default();
+        let now = self.clock.now();
+
+        status_tree
+            .flatten()
+            .into_iter()
+            .filter_map(|node| {
+                let modified = self
+                    .storage
+                    .store()
+                    .last_modified(&node.fqid)
+                    .map(DateTime::<Utc>::from);
+                let candidate = StaleCandidate {
+                    fqid: node.fqid.clone(),
+                    status: node.status,
+                    last_activity: node.status_since.max(modified),
+                    uninitialized_dependencies: match node.status {
+                        StepStatus::Wait => self.uninitialized_dependencies(&node.fqid),
+                        _ => Vec::new(),
+                    },
+                };
+                let thresholds = staleness.thresholds_for(node.classification.as_deref());
+                assess(&candidate, thresholds, now)
+            })
+            .collect()
+    }
+
+    /// Get blocking dependencies of a step that were never initialized
+    ///
+    /// A dependency is uninitialized when it has no step file, or when it is a
+    /// process without an iteration.
+    fn uninitialized_dependencies(&self, fqid: &str) -> Vec<String> {
+        let Some(parent_fqid) = self.get_parent_fqid(fqid) else {
+            return Vec::new();
+        };
+        let Some(parent_def) = self.process_config.find_step_definition(&parent_fqid) else {
+            return Vec::new();
+        };
+
+        let step_id = fqid.split('.').next_back().unwrap_or(fqid);
+        parent_def
+            .links
+            .iter()
+            .filter(|l| l.from == step_id && l.is_blocking())
+            .map(|l| match parent_fqid.as_str() {
+                "ROOT" => l.to.clone(),
+                parent => format!("{}.{}", parent, l.to),
+            })
+            .filter(|dep_fqid| {
+                let is_process = self
+                    .process_config
+                    .find_step_definition(dep_fqid)
+                    .is_some_and(|def| def.is_process());
+                match self.storage.read_summary(dep_fqid) {
+                    Ok(dep) => {
+                        is_process
+                            && dep.status() != StepStatus::Done
+                            && dep.attr.iteration.is_none()
+                            && dep.own_steps.is_empty()
+                    }
+                    Err(_) => true,
+                }
+            })
+            .collect()
+    }
+
     /// Get current project status tree
     pub fn get_status_tree(&self) -> Result<StatusTree> {
+        let store = self.storage.store();
+        store.lock(false)?;
         let root_def = &self.process_config.root_process;
-        self.build_status_tree(root_def, None)
+        let tree = self.build_status_tree(root_def, None);
+        // The status index is a cache; failing to persist it only costs speed
+        let _ = store.flush();
+        store.unlock();
+        tree
+    }
+
+    /// Rebuild the status index of the step data, returning the number of indexed steps
+    pub fn rebuild_index(&self) -> Result<usize> {
+        let store = self.storage.store();
+        store.lock(true)?;
+        let indexed = store.rebuild_index();
+        store.unlock();
+        indexed
+    }
+
+    /// Convert the step data files to another format and make it the project's
+    ///
+    /// Returns what was converted; the project config keeps the new format.
+    pub fn convert_storage(&mut self, to: StepFormat) -> Result<Conversion> {
+        let conversion = self.operation(|engine| engine.storage.store().convert_steps(to))?;
+        self.storage.set_step_format(to);
+        self.config.step_format = to;
+        self.config_loader.save_config_key("step_format", to)?;
+        Ok(conversion)
+    }
+
+    /// Move or rename a step with its sub-steps, documents and artifacts
+    ///
+    /// Links to the step from its siblings follow a rename; a step moved to
+    /// another parent loses the links with its former siblings. The parents'
+    /// `own_steps` and the process definition are updated, and the move is
+    /// journaled.
+    pub fn move_step(&mut self, from: &str, to: &str) -> Result<StepMove> {
+        let (from_parent, from_id) = split_fqid(from);
+        let (to_parent, to_id) = split_fqid(to);
+        if from == "ROOT" || to == "ROOT" {
+            return Err(GlowError::ValidationError {
+                message: "The ROOT step can't be moved".to_string(),
+            });
+        }
+        if !is_step_id(to_id) {
+            return Err(GlowError::ValidationError {
+                message: format!("Invalid step ID '{}': use letters, digits, '_' and '-'", to_id),
+            });
+        }
+        if to.starts_with(&format!("{}.", from)) {
+            return Err(GlowError::ValidationError {
+                message: format!("Can't move '{}' into itself", from),
+            });
+        }
+
+        let mut versions = self.ancestor_versions(&[from_parent, to_parent]);
+        self.definition_operation(|engine| {
+            if !engine.storage.step_exists(from) {
+                return Err(GlowError::StepNotFound { fqid: from.to_string() });
+            }
+            if engine.storage.step_exists(to) {
+                return Err(GlowError::StepAlreadyExists { fqid: to.to_string() });
+            }
+            if !engine.storage.step_exists(to_parent) {
+                return Err(GlowError::StepNotFound { fqid: to_parent.to_string() });
+            }
+
+            // The sub-steps and siblings are listed before the files move
+            let sub_steps = engine.list_sub_steps(from)?;
+            let siblings = engine.list_sub_steps_of(from_parent)?;
+            for old_fqid in std::iter::once(from).chain(sub_steps.iter().map(String::as_str)) {
+                if let Some(def) = engine.process_config.find_step_definition(old_fqid) {
+                    versions.insert(format!("{}{}", to, &old_fqid[from.len()..]), def.version());
+                }
+            }
+            engine.storage.store().move_step(from, to)?;
+
+            let mut step = engine.storage.read_step(to)?;
+            step.attr.id = to_id.to_string();
+            step.attr.fqid = Some(to.to_string());
+            let mut dropped_links = Vec::new();
+            if from_parent != to_parent {
+                dropped_links.extend(step.links.drain(..).map(|l| format!("{} -> {}", from_id, l.step_id)));
+            }
+            engine.storage.write_step(&mut step)?;
+            for sub_fqid in &sub_steps {
+                let new_fqid = format!("{}{}", to, &sub_fqid[from.len()..]);
+                let mut sub_step = engine.storage.read_step(&new_fqid)?;
+                sub_step.attr.fqid = Some(new_fqid);
+                engine.storage.write_step(&mut sub_step)?;
+            }
+
+            for sibling_fqid in siblings.iter().filter(|s| *s != from) {
+                let mut sibling = engine.storage.read_step(sibling_fqid)?;
+                let before = sibling.links.len();
+                if from_parent == to_parent {
+                    let renamed = sibling.links.iter_mut().filter(|l| l.step_id == from_id);
+                    let count = renamed.map(|l| l.step_id = to_id.to_string()).count();
+                    if count == 0 {
+                        continue;
+                    }
+                } else {
+                    sibling.links.retain(|l| l.step_id != from_id);
+                    if sibling.links.len() == before {
+                        continue;
+                    }
+                    dropped_links.push(format!("{} -> {}", sibling.attr.id, from_id));
+                }
+                engine.storage.write_step(&mut sibling)?;
+            }
+
+            let mut parent = engine.storage.read_step(from_parent)?;
+            if from_parent == to_parent {
+                if let Some(own) = parent.own_steps.iter_mut().find(|s| s.id == from_id) {
+                    own.id = to_id.to_string();
+                }
+            } else {
+                parent.own_steps.retain(|s| s.id != from_id);
+                engine.storage.write_step(&mut parent)?;
+                parent = engine.storage.read_step(to_parent)?;
+                parent.own_steps.push(StepRef {
+                    id: to_id.to_string(),
+                    status: step.status(),
+                    iteration: None,
+                });
+            }
+            engine.storage.write_step(&mut parent)?;
+
+            engine.storage.store().append_journal(&JournalEntry {
+                at: engine.clock.now(),
+                fqid: from.to_string(),
+                status: step.status(),
+                actor: engine.actor().map(String::from),
+                change: Some(StepChange::Moved { to: to.to_string() }),
+            })?;
+
+            let definition_moved = engine.edit_definitions(&versions, |content| move_definition(content, from, to))?;
+            Ok(StepMove {
+                from: from.to_string(),
+                to: to.to_string(),
+                steps: sub_steps.len() + 1,
+                dropped_links,
+                definition_moved,
+            })
+        })
+    }
+
+    /// Remove a step with its sub-steps, documents and artifacts
+    ///
+    /// Refused while sibling steps link to it, unless `cascade` removes them
+    /// too, and the steps linking to those. The parent's `own_steps` and the
+    /// process definition are updated, and each removed step is journaled.
+    pub fn remove_step(&mut self, fqid: &str, cascade: bool) -> Result<StepRemoval> {
+        if fqid == "ROOT" {
+            return Err(GlowError::ValidationError {
+                message: "The ROOT step can't be removed".to_string(),
+            });
+        }
+        let (parent_fqid, id) = split_fqid(fqid);
+
+        let versions = self.ancestor_versions(&[parent_fqid]);
+        self.definition_operation(|engine| {
+            if !engine.storage.step_exists(fqid) {
+                return Err(GlowError::StepNotFound { fqid: fqid.to_string() });
+            }
+
+            // Steps linking to a removed step go with it, transitively
+            let siblings: Vec<Step> = engine
+                .list_sub_steps_of(parent_fqid)?
+                .iter()
+                .map(|sibling| engine.storage.read_step(sibling))
+                .collect::<Result<_>>()?;
+            let mut removed = vec![id.to_string()];
+            let mut next = 0;
+            while next < removed.len() {
+                for sibling in &siblings {
+                    let sibling_id = &sibling.attr.id;
+                    if !removed.contains(sibling_id)
+                        && sibling.links.iter().any(|l| l.step_id == removed[next])
+                    {
+                        removed.push(sibling_id.clone());
+                    }
+                }
+                next += 1;
+            }
+            let removed: Vec<String> = removed.iter().map(|id| sub_step_fqid(parent_fqid, id)).collect();
+            if removed.len() > 1 && !cascade {
+                return Err(GlowError::StepHasDependents {
+                    fqid: fqid.to_string(),
+                    dependents: removed[1..].to_vec(),
+                });
+            }
+
+            for removed_fqid in &removed {
+                let mut journaled = vec![removed_fqid.clone()];
+                journaled.extend(engine.list_sub_steps(removed_fqid)?);
+                let statuses = journaled
+                    .iter()
+                    .map(|fqid| engine.storage.read_summary(fqid).map(|s| s.status()))
+                    .collect::<Result<Vec<_>>>()?;
+                engine.storage.store().delete_step(removed_fqid)?;
+                for (fqid, status) in journaled.into_iter().zip(statuses) {
+                    engine.storage.store().append_journal(&JournalEntry {
+                        at: engine.clock.now(),
+                        fqid,
+                        status,
+                        actor: engine.actor().map(String::from),
+                        change: Some(StepChange::Removed),
+                    })?;
+                }
+            }
+
+            if engine.storage.step_exists(parent_fqid) {
+                let mut parent = engine.storage.read_step(parent_fqid)?;
+                parent.own_steps.retain(|s| !removed.contains(&sub_step_fqid(parent_fqid, &s.id)));
+                engine.storage.write_step(&mut parent)?;
+            }
+
+            let mut definitions_removed = true;
+            engine.edit_definitions(&versions, |content| {
+                let mut edited = content.to_string();
+                for removed_fqid in &removed {
+                    match remove_definition(&edited, removed_fqid) {
+                        Some(content) => edited = content,
+                        None => definitions_removed = false,
+                    }
+                }
+                Ok(Some(edited))
+            })?;
+            Ok(StepRemoval {
+                removed,
+                definitions_removed,
+            })
+        })
+    }
+
+    /// Capture the data of every step under a label and save it as a snapshot
+    ///
+    /// Documents and artifacts are not captured.
+    pub fn create_snapshot(&self, label: &str) -> Result<SnapshotInfo> {
+        let mut snapshot = self.current_snapshot(label)?;
+        self.storage.save_snapshot(&mut snapshot)?;
+        Ok(snapshot.info())
+    }
+
+    /// List the saved snapshots, oldest first
+    pub fn list_snapshots(&self) -> Result<Vec<SnapshotInfo>> {
+        Ok(self.storage.snapshots()?.iter().map(Snapshot::info).collect())
+    }
+
+    /// Compare two snapshots, by ID or label, or a snapshot and `now`
+    pub fn diff_snapshots(&self, from: &str, to: &str) -> Result<SnapshotDiff> {
+        let from = self.resolve_snapshot(from)?;
+        let to = self.resolve_snapshot(to)?;
+        Ok(SnapshotDiff::between(&from, &to))
+    }
+
+    /// Put the step data back as captured in a snapshot
+    ///
+    /// The current data is saved as a snapshot first, so the step data of a
+    /// restore can be undone. Steps created since the snapshot are removed;
+    /// snapshots don't hold documents or artifacts, so the restore is refused
+    /// while such a step has a summary, notes or artifacts, unless forced.
+    /// The documents and artifacts of the other steps are left as they are.
+    pub fn restore_snapshot(&mut self, reference: &str, force: bool) -> Result<SnapshotRestore> {
+        let mut snapshot = self.storage.find_snapshot(reference)?;
+
+        self.operation(|engine| {
+            let mut backup = engine.current_snapshot("before restore")?;
+            let gone: Vec<String> = backup
+                .steps
+                .keys()
+                .filter(|fqid| !snapshot.steps.contains_key(*fqid))
+                .cloned()
+                .collect();
+
+            if !force {
+                let mut with_work = Vec::new();
+                for fqid in &gone {
+                    if engine.has_work(fqid)? {
+                        with_work.push(fqid.clone());
+                    }
+                }
+                if !with_work.is_empty() {
+                    return Err(GlowError::RestoreRemovesWork {
+                        snapshot: snapshot.id.clone(),
+                        steps: with_work,
+                    });
+                }
+            }
+
+            let changes = SnapshotDiff::between(&backup, &snapshot);
+            engine.storage.save_snapshot(&mut backup)?;
+
+            let mut removed: Vec<&str> = Vec::new();
+            for fqid in &gone {
+                let parent_gone = removed.iter().any(|r| fqid.starts_with(&format!("{}.", r)));
+                if !parent_gone {
+                    engine.storage.store().delete_step(fqid)?;
+                    removed.push(fqid);
+                }
+            }
+            for step in snapshot.steps.values_mut() {
+                engine.storage.write_step(step)?;
+            }
+
+            Ok(SnapshotRestore {
+                snapshot: snapshot.info(),
+                backup: backup.info(),
+                changes,
+            })
+        })
+    }
+
+    /// Check whether a step has a summary, notes or artifacts
+    ///
+    /// The description is left out: it is rendered again from the template.
+    fn has_work(&self, fqid: &str) -> Result<bool> {
+        let store = self.storage.store();
+        for name in ["summary.md", NOTES_FILE] {
+            if store.read_document(fqid, name)?.is_some() {
+                return Ok(true);
+            }
+        }
+        Ok(!store.list_artifacts(fqid)?.is_empty())
+    }
+
+    /// Get a saved snapshot, or the current data for `now`
+    fn resolve_snapshot(&self, reference: &str) -> Result<Snapshot> {
+        if reference == CURRENT_DATA {
+            return self.current_snapshot(CURRENT_DATA).map(|mut snapshot| {
+                snapshot.id = CURRENT_DATA.to_string();
+                snapshot
+            });
+        }
+        self.storage.find_snapshot(reference)
+    }
+
+    /// Capture the data of every step, unsaved
+    fn current_snapshot(&self, label: &str) -> Result<Snapshot> {
+        let mut fqids = Vec::new();
+        if self.storage.step_exists("ROOT") {
+            fqids.push("ROOT".to_string());
+        }
+        fqids.extend(self.list_sub_steps("ROOT")?);
+        let steps = fqids
+            .iter()
+            .map(|fqid| self.storage.read_step(fqid))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Snapshot::new(label, self.clock.now(), steps))
+    }
+
+    /// List the FQIDs of the sub-steps of a step, at all depths
+    fn list_sub_steps(&self, fqid: &str) -> Result<Vec<String>> {
+        let mut sub_steps = Vec::new();
+        for sub_fqid in self.list_sub_steps_of(fqid)? {
+            let nested = self.list_sub_steps(&sub_fqid)?;
+            sub_steps.push(sub_fqid);
+            sub_steps.extend(nested);
+        }
+        Ok(sub_steps)
+    }
+
+    /// List the FQIDs of the steps directly under a step
+    fn list_sub_steps_of(&self, fqid: &str) -> Result<Vec<String>> {
+        let store = self.storage.store();
+        let steps = if fqid == "ROOT" {
+            store.list_steps(None)?.into_iter().filter(|s| s != "ROOT").collect()
+        } else {
+            store.list_steps(Some(fqid))?
+        };
+        Ok(steps)
+    }
+
+    /// Get the definition versions of steps and their ancestors, by FQID
+    fn ancestor_versions(&self, fqids: &[&str]) -> HashMap<String, String> {
+        let mut versions = HashMap::new();
+        for fqid in fqids {
+            let mut current = Some(fqid.to_string());
+            while let Some(fqid) = current {
+                if let Some(def) = self.process_config.find_step_definition(&fqid) {
+                    versions.insert(fqid.clone(), def.version());
+                }
+                current = self.get_parent_fqid(&fqid);
+            }
+        }
+        versions
+    }
+
+    /// Run an operation that edits the process definition with `edit_definitions`
+    ///
+    /// If the operation fails, the process config file is put back as it
+    /// was and the definitions are loaded from it again.
+    fn definition_operation<T>(&mut self, change: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        let original = self.config_loader.read_process_config_file(&self.config)?;
+        let result = self.operation(change);
+        if result.is_err() && self.config_loader.read_process_config_file(&self.config)? != original {
+            self.config_loader.restore_process_config_file(&self.config, &original)?;
+            self.process_config = self.config_loader.load_process_config(&self.config)?;
+            self.storage.set_process_config(&self.process_config);
+        }
+        result
+    }
+
+    /// Edit the process definition within an operation, and reload it if the edit says so
+    ///
+    /// Iterations that were current with the definition of their process
+    /// before the change, as given by `versions`, are stamped with its new
+    /// version.
+    fn edit_definitions(
+        &mut self,
+        versions: &HashMap<String, String>,
+        edit: impl FnOnce(&str) -> Result<Option<String>>,
+    ) -> Result<bool> {
+        if !self.config_loader.edit_process_config(&self.config, edit)? {
+            return Ok(false);
+        }
+        self.process_config = self.config_loader.load_process_config(&self.config)?;
+        self.storage.set_process_config(&self.process_config);
+
+        for (fqid, version) in versions {
+            let Some(def) = self.process_config.find_step_definition(fqid) else {
+                continue;
+            };
+            if !self.storage.step_exists(fqid) {
+                continue;
+            }
+            let mut step = self.storage.read_step(fqid)?;
+            if step.attr.definition_version.as_ref() == Some(version) {
+                step.attr.definition_version = Some(def.version());
+                self.storage.write_step(&mut step)?;
+            }
+        }
+        Ok(true)
     }
 
     /// Build status tree recursively
@@ -527,11 +1413,17 @@ impl ProcessEngine {
             None => "ROOT".to_string(),
         };
 
-        let status = if let Ok(step) = self.storage.read_step(&fqid) {
-            step.status()
-        } else {
-            StepStatus::Wait
-        };
+        let step = self.storage.read_summary(&fqid).ok();
+        let status = step.as_ref().map(|s| s.status()).unwrap_or(StepStatus::Wait);
+        let estimate = step
+            .as_ref()
+            .and_then(|s| s.attr.estimate.clone())
+            .or_else(|| def.estimate.clone());
+        let due = step.as_ref().and_then(|s| s.attr.due).or(def.due);
+        let iteration = step.as_ref().and_then(|s| s.attr.iteration);
+        let timebox = step.as_ref().and_then(|s| s.attr.timebox.clone());
+        let status_since = step.as_ref().and_then(|s| s.attr.status_since);
+        let finished_at = step.as_ref().and_then(|s| s.attr.finished_at);
 
         let children: Vec<StatusTree> = def.steps.iter()
             .filter_map(|sub_def| {
@@ -543,88 +1435,471 @@ impl ProcessEngine {
             id: def.id.clone(),
             fqid,
             purpose: def.purpose.clone(),
+            classification: def.classification.clone(),
             status,
+            estimate,
+            due,
+            iteration,
+            timebox,
+            status_since,
+            finished_at,
             children,
         })
     }
 
-    /// Get recommended next actions
+    /// Get recommended next actions, best ranked first
+    ///
+    /// When a WIP limit is reached, finishing steps counted against it comes
+    /// first and starts that would exceed a limit come last.
     pub fn get_next_actions(&self) -> Result<Vec<NextAction>> {
         let mut actions = Vec::new();
         let status_tree = self.get_status_tree()?;
-        self.collect_next_actions(&status_tree, &mut actions);
-        Ok(actions)
+        self.collect_next_actions(&status_tree, GraphPosition::default(), &mut actions);
+
+        let nodes = status_tree.flatten();
+        let active: Vec<String> = nodes
+            .iter()
+            .filter(|n| n.status == StepStatus::InProgress)
+            .map(|n| n.fqid.clone())
+            .collect();
+
+        // Tier 0: finish work held by a reached limit, 1: start, 2: start over a limit
+        let mut ranked: Vec<(u8, NextAction)> = Vec::new();
+        let mut to_finish: Vec<(String, &WipLimit)> = Vec::new();
+        for mut action in actions {
+            let exceeded = self.exceeded_wip_limits(&action.fqid, &active);
+            match exceeded.into_iter().next() {
+                Some((limit, counted)) => {
+                    action.why = format!("WIP limit reached for {}; {}", limit, action.why);
+                    for fqid in counted {
+                        if !to_finish.iter().any(|(f, _)| *f == fqid) {
+                            to_finish.push((fqid, limit));
+                        }
+                    }
+                    ranked.push((2, action));
+                }
+                None => ranked.push((1, action)),
+            }
+        }
+
+        for (fqid, limit) in to_finish {
+            if let Some(node) = nodes.iter().find(|n| n.fqid == fqid) {
+                let (score, why) = self.rank_node(node, GraphPosition::default(), None);
+                let why = match why.as_str() {
+                    "ready to start" => format!("WIP limit reached for {}", limit),
+                    _ => format!("WIP limit reached for {}, {}", limit, why),
+                };
+                ranked.push((
+                    0,
+                    NextAction {
+                        fqid: node.fqid.clone(),
+                        action_type: ActionType::Finish,
+                        description: format!(
+                            "Finish: {}",
+                            node.purpose.as_deref().unwrap_or(&node.id)
+                        ),
+                        score,
+                        why,
+                    },
+                ));
+            }
+        }
+
+        // Stable sort keeps tree order for equal scores
+        ranked.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.score.total_cmp(&a.1.score)));
+        Ok(ranked.into_iter().map(|(_, action)| action).collect())
+    }
+
+    /// Score a status tree node for ranking
+    fn rank_node(
+        &self,
+        node: &StatusTree,
+        position: GraphPosition,
+        todo_since: Option<DateTime<Utc>>,
+    ) -> (f64, String) {
+        let ranking = self.process_config.ranking.clone().unwrap_or_default();
+        let factors = RankFactors {
+            priority: node.classification.as_deref().and_then(|c| {
+                ranking.priority_of(c, &self.process_config.classifications)
+            }),
+            position,
+            todo_since,
+            due: node.due,
+        };
+        factors.evaluate(&ranking, self.clock.now())
     }
 
     /// Collect next actions recursively
-    fn collect_next_actions(&self, tree: &StatusTree, actions: &mut Vec<NextAction>) {
+    fn collect_next_actions(
+        &self,
+        tree: &StatusTree,
+        position: GraphPosition,
+        actions: &mut Vec<NextAction>,
+    ) {
         match tree.status {
             StepStatus::Todo => {
+                let (score, why) = self.rank_node(tree, position, tree.status_since);
+
                 actions.push(NextAction {
                     fqid: tree.fqid.clone(),
                     action_type: ActionType::Start,
                     description: format!("Start: {}", tree.purpose.as_deref().unwrap_or(&tree.id)),
+                    score,
+                    why,
                 });
             }
-            StepStatus::InProgress => {
-                // Check children for available work
-                for child in &tree.children {
-                    self.collect_next_actions(child, actions);
-                }
-            }
             _ => {
+                // Check children for available work
+                let positions = self.sibling_positions(tree);
                 for child in &tree.children {
-                    self.collect_next_actions(child, actions);
+                    let position = positions.get(&child.id).copied().unwrap_or_default();
+                    self.collect_next_actions(child, position, actions);
                 }
             }
         }
     }
 
+    /// Analyze the dependency graph positions of a node's children
+    fn sibling_positions(&self, tree: &StatusTree) -> std::collections::HashMap<String, GraphPosition> {
+        let Some(def) = self.process_config.find_step_definition(&tree.fqid) else {
+            return Default::default();
+        };
+
+        let weights = estimate_weights(&tree.children.iter().collect::<Vec<_>>());
+        let siblings: Vec<Sibling> = tree
+            .children
+            .iter()
+            .zip(weights)
+            .map(|(child, weight)| Sibling {
+                id: child.id.clone(),
+                status: child.status,
+                weight,
+            })
+            .collect();
+
+        analyze_siblings(&siblings, &def.links)
+    }
+
     /// Get progress metrics
     pub fn get_progress(&self) -> Result<ProgressMetrics> {
         let status_tree = self.get_status_tree()?;
-        let mut metrics = ProgressMetrics::default();
-        self.collect_progress_metrics(&status_tree, &mut metrics);
-        Ok(metrics)
+        Ok(ProgressMetrics::from_tree(&status_tree))
     }
 
-    /// Collect progress metrics recursively
-    fn collect_progress_metrics(&self, tree: &StatusTree, metrics: &mut ProgressMetrics) {
-        metrics.total += 1;
-        match tree.status {
-            StepStatus::Wait => metrics.wait += 1,
-            StepStatus::Todo => metrics.todo += 1,
-            StepStatus::InProgress => metrics.in_progress += 1,
-            StepStatus::Done => metrics.done += 1,
+    /// Get completion forecasts for every process in the project
+    ///
+    /// Throughput is derived from all finished steps in the project. If leaf
+    /// steps are estimated in different units, every step weighs the same
+    /// (see [`ProgressMetrics::warning`]).
+    pub fn get_forecast(&self) -> Result<Vec<Forecast>> {
+        let status_tree = self.get_status_tree()?;
+        let now = self.clock.now();
+
+        let nodes = status_tree.leaves();
+        let weights = estimate_weights(&nodes);
+
+        let history: Vec<CompletedWork> = nodes
+            .iter()
+            .zip(&weights)
+            .filter_map(|(node, weight)| {
+                node.finished_at.map(|finished_at| CompletedWork {
+                    finished_at,
+                    weight: *weight,
+                })
+            })
+            .collect();
+        let throughput = Throughput::from_history(&history, now);
+
+        let mut forecasts = Vec::new();
+        self.collect_forecasts(&status_tree, throughput.as_ref(), now, &mut forecasts);
+        Ok(forecasts)
+    }
+
+    /// Collect forecasts for process nodes recursively
+    fn collect_forecasts(
+        &self,
+        tree: &StatusTree,
+        throughput: Option<&Throughput>,
+        now: DateTime<Utc>,
+        forecasts: &mut Vec<Forecast>,
+    ) {
+        if tree.children.is_empty() {
+            return;
         }
 
+        let progress = ProgressMetrics::from_tree(tree);
+        forecasts.push(Forecast::new(
+            tree.fqid.clone(),
+            self.storage.current_iteration(&tree.fqid),
+            progress.total_weight,
+            progress.remaining_weight(),
+            throughput,
+            now,
+        ));
+
         for child in &tree.children {
-            self.collect_progress_metrics(child, metrics);
+            self.collect_forecasts(child, throughput, now, forecasts);
         }
     }
 
+    /// Check the process definition for steps that can never start
+    pub fn check_config(&self) -> Vec<ConfigFinding> {
+        check_process_config(&self.process_config)
+    }
+
+    /// Plan how to bring in-flight iterations in line with the current definitions
+    ///
+    /// Completed processes are listed but never changed.
+    pub fn plan_config_apply(&self) -> Result<ReconcilePlan> {
+        let mut plan = ReconcilePlan::default();
+        let root = self.process_config.root_process.clone();
+        self.collect_reconcile(&root, "ROOT", &mut plan)?;
+        Ok(plan)
+    }
+
+    fn collect_reconcile(&self, def: &StepDefinition, fqid: &str, plan: &mut ReconcilePlan) -> Result<()> {
+        if !def.is_process() || !self.storage.step_exists(fqid) {
+            return Ok(());
+        }
+
+        let step = self.storage.read_step(fqid)?;
+        if step.status() == StepStatus::Done {
+            plan.completed.push(fqid.to_string());
+            return Ok(());
+        }
+        if step.attr.iteration.is_none() && step.own_steps.is_empty() {
+            return Ok(());
+        }
+
+        let recorded_links: HashMap<String, Vec<LinkRef>> = step
+            .own_steps
+            .iter()
+            .filter_map(|s| {
+                let sub_step = self.storage.read_summary(&sub_step_fqid(fqid, &s.id)).ok()?;
+                Some((s.id.clone(), sub_step.links))
+            })
+            .collect();
+
+        let iteration = IterationReconcile {
+            fqid: fqid.to_string(),
+            iteration: step
+                .attr
+                .iteration
+                .or_else(|| self.storage.current_iteration(fqid))
+                .unwrap_or(1),
+            from_version: step.attr.definition_version.clone(),
+            to_version: def.version(),
+            changes: diff_iteration(&step, def, &recorded_links)
+                .into_iter()
+                // Sub-steps archived with an earlier iteration keep the links they were archived with
+                .filter(|change| {
+                    !matches!(change.kind, ChangeKind::Relinked { .. })
+                        || self.storage.step_exists(&sub_step_fqid(fqid, &change.step_id))
+                })
+                .collect(),
+        };
+        if !iteration.is_current() {
+            plan.iterations.push(iteration);
+        }
+
+        for sub_def in &def.steps {
+            self.collect_reconcile(sub_def, &sub_step_fqid(fqid, &sub_def.id), plan)?;
+        }
+        Ok(())
+    }
+
+    /// Bring in-flight iterations in line with the current definitions
+    ///
+    /// Added sub-steps are created waiting, orphaned sub-steps leave the
+    /// iteration with their files kept, and relinked sub-steps get their new
+    /// links. Each iteration is stamped with its definition version.
+    pub fn apply_config(&mut self) -> Result<ReconcilePlan> {
+        self.operation(|engine| {
+            let plan = engine.plan_config_apply()?;
+
+            for iteration in &plan.iterations {
+                let def = engine
+                    .process_config
+                    .find_step_definition(&iteration.fqid)
+                    .ok_or_else(|| GlowError::StepNotFound { fqid: iteration.fqid.clone() })?
+                    .clone();
+                let mut step = engine.storage.read_step(&iteration.fqid)?;
+
+                for change in &iteration.changes {
+                    let sub_fqid = sub_step_fqid(&iteration.fqid, &change.step_id);
+                    match &change.kind {
+                        ChangeKind::Added => {
+                            let sub_def = def.find_step(&change.step_id).ok_or_else(|| {
+                                GlowError::StepNotFound { fqid: sub_fqid.clone() }
+                            })?;
+                            let mut sub_step = Step::from_definition(sub_def, Some(&iteration.fqid));
+                            sub_step.attr.set_status(StepStatus::Wait, engine.clock.now());
+                            sub_step.links = def.link_refs(&change.step_id);
+                            engine.storage.write_step(&mut sub_step)?;
+                            step.own_steps.push(StepRef {
+                                id: change.step_id.clone(),
+                                status: StepStatus::Wait,
+                                iteration: None,
+                            });
+                        }
+                        ChangeKind::Orphaned => {
+                            step.own_steps.retain(|s| s.id != change.step_id);
+                        }
+                        ChangeKind::Relinked { to, .. } => {
+                            let mut sub_step = engine.storage.read_step(&sub_fqid)?;
+                            sub_step.links = to.clone();
+
+                            // A step that has not started waits again on new unfinished dependencies
+                            let blocked = def
+                                .links
+                                .iter()
+                                .filter(|l| l.from == change.step_id && l.is_blocking())
+                                .any(|l| {
+                                    step.own_steps
+                                        .iter()
+                                        .find(|s| s.id == l.to)
+                                        .is_none_or(|s| s.status != StepStatus::Done)
+                                });
+                            if blocked && sub_step.status() == StepStatus::Todo {
+                                sub_step.attr.set_status(StepStatus::Wait, engine.clock.now());
+                                if let Some(own) = step.own_steps.iter_mut().find(|s| s.id == change.step_id) {
+                                    own.status = StepStatus::Wait;
+                                }
+                            }
+                            engine.storage.write_step(&mut sub_step)?;
+                        }
+                    }
+                }
+
+                engine.update_sub_step_statuses(&mut step, &def)?;
+                step.attr.definition_version = Some(iteration.to_version.clone());
+                engine.storage.write_step(&mut step)?;
+            }
+
+            Ok(plan)
+        })
+    }
+
     /// Validate context quality
     pub fn validate(&self, fqid: Option<&str>) -> Result<ValidationReport> {
         self.validator.validate_project(self, fqid)
     }
 }
 
+/// FQID of a sub-step; children of ROOT don't include ROOT
+fn sub_step_fqid(parent_fqid: &str, id: &str) -> String {
+    if parent_fqid == "ROOT" {
+        id.to_string()
+    } else {
+        format!("{}.{}", parent_fqid, id)
+    }
+}
+
+/// Check if a step ID can be used in an FQID and as a file name
+fn is_step_id(id: &str) -> bool {
+    !id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// Get the sub-steps listed as done in the summary of a closed iteration
+fn parse_iteration_done(summary: &str) -> Vec<String> {
+    summary
+        .lines()
+        .skip_while(|line| *line != "## Done")
+        .skip(1)
+        .take_while(|line| !line.starts_with("## "))
+        .filter_map(|line| line.strip_prefix("- "))
+        .map(String::from)
+        .collect()
+}
+
+/// Render the summary of a closed iteration
+fn render_iteration_summary(
+    number: u32,
+    timebox: Option<&Timebox>,
+    closed_on: NaiveDate,
+    done: &[String],
+    carried_over: &[String],
+) -> String {
+    let mut summary = format!("# Iteration {} Summary\n\n", number);
+    if let Some(t) = timebox {
+        summary.push_str(&format!("Time box: {} .. {}\n", t.min, t.max));
+    }
+    summary.push_str(&format!("Closed: {}\n", closed_on));
+
+    summary.push_str("\n## Done\n\n");
+    if done.is_empty() {
+        summary.push_str("None.\n");
+    }
+    for fqid in done {
+        summary.push_str(&format!("- {}\n", fqid));
+    }
+
+    summary.push_str(&format!("\n## Carried over to iteration {}\n\n", number + 1));
+    if carried_over.is_empty() {
+        summary.push_str("None.\n");
+    }
+    for fqid in carried_over {
+        summary.push_str(&format!("- {}\n", fqid));
+    }
+
+    summary
+}
+
+/// Options for starting a step
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StartOptions {
+    /// Start even if a work-in-progress limit would be exceeded
+    pub override_wip_limits: bool,
+}
+
 /// Status tree for displaying project state
 #[derive(Debug, Clone)]
 pub struct StatusTree {
     pub id: String,
     pub fqid: String,
     pub purpose: Option<String>,
+    pub classification: Option<String>,
     pub status: StepStatus,
+    pub estimate: Option<Estimate>,
+    pub due: Option<NaiveDate>,
+    pub iteration: Option<u32>,
+    pub timebox: Option<Timebox>,
+    pub status_since: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
     pub children: Vec<StatusTree>,
 }
 
+impl StatusTree {
+    /// Get the estimate weight of this node, if estimated
+    pub fn estimate_weight(&self) -> Option<f64> {
+        self.estimate.as_ref().and_then(|e| e.weight())
+    }
+
+    /// Get the steps without sub-steps, which carry the estimate weight (pre-order)
+    pub fn leaves(&self) -> Vec<&StatusTree> {
+        self.flatten().into_iter().filter(|node| node.children.is_empty()).collect()
+    }
+
+    /// Flatten the tree into a list of nodes (pre-order)
+    pub fn flatten(&self) -> Vec<&StatusTree> {
+        let mut nodes = vec![self];
+        for child in &self.children {
+            nodes.extend(child.flatten());
+        }
+        nodes
+    }
+}
+
 /// Next action recommendation
 #[derive(Debug, Clone)]
 pub struct NextAction {
     pub fqid: String,
     pub action_type: ActionType,
     pub description: String,
+    /// Ranking score (higher is more urgent)
+    pub score: f64,
+    /// Short explanation of the ranking
+    pub why: String,
 }
 
 /// Action type
@@ -644,16 +1919,123 @@ pub struct ProgressMetrics {
     pub todo: usize,
     pub in_progress: usize,
     pub done: usize,
+    /// Number of steps without sub-steps, which carry the estimate weight
+    pub leaves: usize,
+    /// Estimate weight of the leaf steps (unestimated steps weigh the mean estimate)
+    pub total_weight: f64,
+    /// Estimate weight of done leaf steps
+    pub done_weight: f64,
+    /// Number of leaf steps with an explicit estimate
+    pub estimated: usize,
+    /// Set when leaf steps are estimated in different units, in which case
+    /// every leaf step weighs the same
+    pub warning: Option<String>,
 }
 
 impl ProgressMetrics {
+    /// Collect metrics from a status tree
+    ///
+    /// Every step is counted, but only the steps without sub-steps carry
+    /// weight: a process weighs what its sub-steps weigh, so its own
+    /// estimate is only used while it has none.
+    pub fn from_tree(tree: &StatusTree) -> Self {
+        let leaves = tree.leaves();
+        let weights = estimate_weights(&leaves);
+
+        let mut metrics = Self {
+            leaves: leaves.len(),
+            estimated: leaves.iter().filter(|n| n.estimate_weight().is_some()).count(),
+            warning: check_estimate_units(&leaves),
+            ..Self::default()
+        };
+
+        for (node, weight) in leaves.iter().zip(weights) {
+            metrics.total_weight += weight;
+            if node.status == StepStatus::Done {
+                metrics.done_weight += weight;
+            }
+        }
+        for node in tree.flatten() {
+            metrics.total += 1;
+            match node.status {
+                StepStatus::Wait => metrics.wait += 1,
+                StepStatus::Todo => metrics.todo += 1,
+                StepStatus::InProgress => metrics.in_progress += 1,
+                StepStatus::Done => metrics.done += 1,
+            }
+        }
+
+        metrics
+    }
+
+    /// Completion percentage weighted by estimate
     pub fn completion_percentage(&self) -> f64 {
-        if self.total == 0 {
+        if self.total_weight > 0.0 {
+            (self.done_weight / self.total_weight) * 100.0
+        } else if self.total == 0 {
             0.0
         } else {
             (self.done as f64 / self.total as f64) * 100.0
         }
     }
+
+    /// Estimate weight not yet done
+    pub fn remaining_weight(&self) -> f64 {
+        (self.total_weight - self.done_weight).max(0.0)
+    }
+
+    /// Share of steps in a status count, as a whole percentage
+    pub fn share(&self, count: usize) -> usize {
+        (count * 100).checked_div(self.total).unwrap_or(0)
+    }
+}
+
+/// Resolve the estimate weights of steps
+///
+/// Steps estimated in points and in durations can't be weighed together;
+/// if they are mixed, all steps weigh the same.
+fn estimate_weights(nodes: &[&StatusTree]) -> Vec<f64> {
+    let mut units = nodes.iter().filter_map(|n| n.estimate.as_ref().map(Estimate::unit));
+    let first = units.next();
+    if units.any(|unit| Some(unit) != first) {
+        return vec![1.0; nodes.len()];
+    }
+    fill_weights(&nodes.iter().map(|n| n.estimate_weight()).collect::<Vec<_>>())
+}
+
+/// Check that leaf steps are estimated in one unit, points or durations
+///
+/// Only leaf steps carry weight, so a process estimated in the other unit
+/// doesn't matter once it has sub-steps. Returns a warning if they are mixed.
+fn check_estimate_units(leaves: &[&StatusTree]) -> Option<String> {
+    let mut first: Option<(&str, EstimateUnit)> = None;
+    for node in leaves {
+        let Some(unit) = node.estimate.as_ref().map(Estimate::unit) else {
+            continue;
+        };
+        match first {
+            None => first = Some((&node.fqid, unit)),
+            Some((fqid, first_unit)) if first_unit != unit => {
+                return Some(format!(
+                    "'{}' is estimated in {} but '{}' in {}, so every step weighs the same; \
+                     estimate every step in points or every step as a duration",
+                    node.fqid, unit, fqid, first_unit
+                ));
+            }
+            Some(_) => {}
+        }
+    }
+    None
+}
+
+/// Error for steps estimated in different units
+fn mixed_units(fqid: &str, unit: EstimateUnit, other: &str, other_unit: EstimateUnit) -> GlowError {
+    GlowError::ValidationError {
+        message: format!(
+            "'{}' is estimated in {} but '{}' in {}; estimate every step in points or every step as a duration",
+            fqid, unit, other, other_unit
+        ),
+    }
 }
 
 /// Validation report
@@ -681,3 +2063,85 @@ pub enum IssueType {
     InconsistentState,
     CircularDependency,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(id: &str, status: StepStatus, estimate: Option<f64>) -> StatusTree {
+        StatusTree {
+            id: id.to_string(),
+            fqid: id.to_string(),
+            purpose: None,
+            classification: None,
+            status,
+            estimate: estimate.map(Estimate::Points),
+            due: None,
+            iteration: None,
+            timebox: None,
+            status_since: None,
+            finished_at: None,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_progress_unestimated_counts_steps() {
+        let mut root = node("ROOT", StepStatus::InProgress, None);
+        root.children.push(node("A", StepStatus::Done, None));
+        root.children.push(node("B", StepStatus::Todo, None));
+        root.children.push(node("C", StepStatus::Wait, None));
+
+        let metrics = ProgressMetrics::from_tree(&root);
+        assert_eq!(metrics.total, 4);
+        assert_eq!(metrics.done, 1);
+        assert_eq!(metrics.estimated, 0);
+        // The process itself weighs nothing: 1 of 3 leaf steps is done
+        assert!((metrics.completion_percentage() - 100.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_progress_weighted_by_estimate() {
+        // A process weighs what its sub-steps weigh, not its own estimate on top
+        let mut root = node("ROOT", StepStatus::InProgress, Some(100.0));
+        root.children.push(node("BIG", StepStatus::Done, Some(8.0)));
+        root.children.push(node("SMALL", StepStatus::Todo, Some(2.0)));
+
+        let metrics = ProgressMetrics::from_tree(&root);
+        assert_eq!(metrics.leaves, 2);
+        assert_eq!(metrics.estimated, 2);
+        assert_eq!(metrics.total_weight, 10.0);
+        assert_eq!(metrics.done_weight, 8.0);
+        assert_eq!(metrics.remaining_weight(), 2.0);
+        assert_eq!(metrics.completion_percentage(), 80.0);
+    }
+
+    #[test]
+    fn test_mixed_estimate_units_weigh_the_same() {
+        // The process's own estimate in hours doesn't count once it has sub-steps
+        let mut root = node("ROOT", StepStatus::InProgress, None);
+        root.estimate = Some(Estimate::Duration("1w".to_string()));
+        root.children.push(node("A", StepStatus::Done, Some(3.0)));
+        root.children.push(node("B", StepStatus::Todo, Some(1.0)));
+        let metrics = ProgressMetrics::from_tree(&root);
+        assert!(metrics.warning.is_none());
+        assert_eq!(metrics.completion_percentage(), 75.0);
+
+        root.children[1].estimate = Some(Estimate::Duration("2d".to_string()));
+        let metrics = ProgressMetrics::from_tree(&root);
+        assert!(metrics.warning.as_deref().is_some_and(|w| w.contains("'B' is estimated in")));
+        assert_eq!(metrics.total_weight, 2.0);
+        assert_eq!(metrics.completion_percentage(), 50.0);
+    }
+
+    #[test]
+    fn test_progress_share() {
+        let metrics = ProgressMetrics {
+            total: 4,
+            done: 1,
+            ..Default::default()
+        };
+        assert_eq!(metrics.share(metrics.done), 25);
+        assert_eq!(ProgressMetrics::default().share(0), 0);
+    }
+}
@@ -0,0 +1,337 @@
+//! Next action ranking
+//!
+//! Scores candidate steps by priority, critical path position, age,
+//! due date and the number of dependents they unblock.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::model::{LinkDefinition, RankingConfig, StepStatus};
+
+/// Number of unblocked dependents at which the unblocks factor is saturated
+const UNBLOCKS_SATURATION: f64 = 3.0;
+
+/// A sibling step as seen by the ranking
+#[derive(Debug, Clone)]
+pub struct Sibling {
+    /// Step ID within the parent process
+    pub id: String,
+    /// Current status
+    pub status: StepStatus,
+    /// Estimate weight (unestimated steps use a filled weight)
+    pub weight: f64,
+}
+
+/// Position of a step within its parent's dependency graph
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GraphPosition {
+    /// Remaining work downstream of the step relative to the longest chain (0..=1)
+    pub critical_path: f64,
+    /// Whether the step starts the longest chain of remaining work
+    pub on_critical_path: bool,
+    /// Number of dependents that become ready once the step is done
+    pub unblocks: usize,
+}
+
+/// Analyze the dependency graph of sibling steps
+///
+/// Only blocking links are considered; done steps carry no remaining weight.
+pub fn analyze_siblings(
+    siblings: &[Sibling],
+    links: &[LinkDefinition],
+) -> HashMap<String, GraphPosition> {
+    let blocking: Vec<&LinkDefinition> = links.iter().filter(|l| l.is_blocking()).collect();
+    let by_id: HashMap<&str, &Sibling> = siblings.iter().map(|s| (s.id.as_str(), s)).collect();
+    let is_done = |id: &str| {
+        by_id
+            .get(id)
+            .map(|s| s.status == StepStatus::Done)
+            .unwrap_or(false)
+    };
+
+    let mut memo = HashMap::new();
+    let downstream: HashMap<&str, f64> = siblings
+        .iter()
+        .map(|s| {
+            let mut visiting = HashSet::new();
+            let value = downstream_weight(&s.id, &by_id, &blocking, &mut memo, &mut visiting);
+            (s.id.as_str(), value)
+        })
+        .collect();
+
+    let open: Vec<&Sibling> = siblings
+        .iter()
+        .filter(|s| s.status != StepStatus::Done)
+        .collect();
+    let longest = open
+        .iter()
+        .map(|s| downstream[s.id.as_str()])
+        .fold(0.0, f64::max);
+
+    siblings
+        .iter()
+        .map(|s| {
+            let critical_path = if longest > 0.0 && s.status != StepStatus::Done {
+                downstream[s.id.as_str()] / longest
+            } else {
+                0.0
+            };
+
+            let unblocks = blocking
+                .iter()
+                .filter(|l| l.to == s.id && !is_done(&l.from))
+                .filter(|l| {
+                    blocking
+                        .iter()
+                        .filter(|other| other.from == l.from && other.to != s.id)
+                        .all(|other| is_done(&other.to))
+                })
+                .map(|l| l.from.as_str())
+                .collect::<HashSet<_>>()
+                .len();
+
+            let position = GraphPosition {
+                critical_path,
+                on_critical_path: open.len() > 1 && (critical_path - 1.0).abs() < 1e-9,
+                unblocks,
+            };
+            (s.id.clone(), position)
+        })
+        .collect()
+}
+
+/// Longest chain of remaining weight starting at a step and following its dependents
+fn downstream_weight<'a>(
+    id: &'a str,
+    by_id: &HashMap<&str, &Sibling>,
+    links: &[&'a LinkDefinition],
+    memo: &mut HashMap<&'a str, f64>,
+    visiting: &mut HashSet<&'a str>,
+) -> f64 {
+    if let Some(value) = memo.get(id) {
+        return *value;
+    }
+    // Cycles are reported by validation; break them here
+    if !visiting.insert(id) {
+        return 0.0;
+    }
+
+    let own = by_id
+        .get(id)
+        .filter(|s| s.status != StepStatus::Done)
+        .map(|s| s.weight)
+        .unwrap_or(0.0);
+
+    let mut longest_dependent: f64 = 0.0;
+    for link in links.iter().filter(|l| l.to == id) {
+        let value = downstream_weight(&link.from, by_id, links, memo, visiting);
+        longest_dependent = longest_dependent.max(value);
+    }
+
+    visiting.remove(id);
+    let value = own + longest_dependent;
+    memo.insert(id, value);
+    value
+}
+
+/// Ranking inputs for a single candidate step
+#[derive(Debug, Clone, Default)]
+pub struct RankFactors {
+    /// Matched priority key and its normalized value
+    pub priority: Option<(String, f64)>,
+    /// Position in the parent's dependency graph
+    pub position: GraphPosition,
+    /// When the step became todo
+    pub todo_since: Option<DateTime<Utc>>,
+    /// Due date of the step
+    pub due: Option<NaiveDate>,
+}
+
+impl RankFactors {
+    /// Compute the score and a short explanation
+    pub fn evaluate(&self, config: &RankingConfig, now: DateTime<Utc>) -> (f64, String) {
+        let weights = &config.weights;
+        let mut parts: Vec<(f64, String)> = Vec::new();
+
+        if let Some((key, value)) = &self.priority {
+            parts.push((weights.priority * value, format!("{} priority", key)));
+        }
+
+        if self.position.critical_path > 0.0 {
+            let label = if self.position.on_critical_path {
+                "on critical path".to_string()
+            } else {
+                format!("{:.0}% of critical path", self.position.critical_path * 100.0)
+            };
+            parts.push((weights.critical_path * self.position.critical_path, label));
+        }
+
+        if self.position.unblocks > 0 {
+            let factor = (self.position.unblocks as f64 / UNBLOCKS_SATURATION).min(1.0);
+            parts.push((
+                weights.unblocks * factor,
+                format!("unblocks {}", self.position.unblocks),
+            ));
+        }
+
+        if let Some(due) = self.due {
+            let days_left = (due - now.date_naive()).num_days();
+            let horizon = config.due_horizon_days.max(1) as f64;
+            let factor = if days_left <= 0 {
+                1.0
+            } else {
+                (1.0 - days_left as f64 / horizon).max(0.0)
+            };
+            let label = match days_left {
+                d if d < 0 => format!("overdue by {}d", -d),
+                0 => "due today".to_string(),
+                d => format!("due in {}d", d),
+            };
+            parts.push((weights.due * factor, label));
+        }
+
+        if let Some(since) = self.todo_since {
+            let days = (now - since).num_seconds().max(0) as f64 / 86_400.0;
+            let horizon = config.age_horizon_days.max(1) as f64;
+            if days >= 1.0 {
+                parts.push((
+                    weights.age * (days / horizon).min(1.0),
+                    format!("todo for {}d", days.floor()),
+                ));
+            }
+        }
+
+        let score = parts.iter().map(|(contribution, _)| contribution).sum();
+
+        parts.retain(|(contribution, _)| *contribution > 0.0);
+        parts.sort_by(|a, b| b.0.total_cmp(&a.0));
+        let why = if parts.is_empty() {
+            "ready to start".to_string()
+        } else {
+            parts
+                .into_iter()
+                .map(|(_, label)| label)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        (score, why)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn sibling(id: &str, status: StepStatus, weight: f64) -> Sibling {
+        Sibling {
+            id: id.to_string(),
+            status,
+            weight,
+        }
+    }
+
+    #[test]
+    fn test_critical_path_follows_longest_chain() {
+        // C depends on A, D depends on C; B is independent
+        let siblings = vec![
+            sibling("A", StepStatus::Todo, 1.0),
+            sibling("B", StepStatus::Todo, 2.0),
+            sibling("C", StepStatus::Wait, 1.0),
+            sibling("D", StepStatus::Wait, 1.0),
+        ];
+        let links = vec![
+            LinkDefinition::dependency("C", "A"),
+            LinkDefinition::dependency("D", "C"),
+        ];
+
+        let analysis = analyze_siblings(&siblings, &links);
+        assert!(analysis["A"].on_critical_path);
+        assert_eq!(analysis["A"].critical_path, 1.0);
+        assert!(!analysis["B"].on_critical_path);
+        assert!((analysis["B"].critical_path - 2.0 / 3.0).abs() < 1e-9);
+        assert_eq!(analysis["A"].unblocks, 1);
+        assert_eq!(analysis["B"].unblocks, 0);
+    }
+
+    #[test]
+    fn test_unblocks_requires_other_dependencies_done() {
+        // C depends on A and B
+        let siblings = vec![
+            sibling("A", StepStatus::Todo, 1.0),
+            sibling("B", StepStatus::Done, 1.0),
+            sibling("C", StepStatus::Wait, 1.0),
+            sibling("D", StepStatus::Todo, 1.0),
+        ];
+        let links = vec![
+            LinkDefinition::dependency("C", "A"),
+            LinkDefinition::dependency("C", "B"),
+            LinkDefinition::dependency("C", "D"),
+        ];
+
+        let analysis = analyze_siblings(&siblings, &links);
+        // D is still open, so finishing A alone does not unblock C
+        assert_eq!(analysis["A"].unblocks, 0);
+        assert_eq!(analysis["B"].critical_path, 0.0);
+    }
+
+    #[test]
+    fn test_analysis_survives_cycles() {
+        let siblings = vec![
+            sibling("A", StepStatus::Todo, 1.0),
+            sibling("B", StepStatus::Todo, 1.0),
+        ];
+        let links = vec![
+            LinkDefinition::dependency("A", "B"),
+            LinkDefinition::dependency("B", "A"),
+        ];
+
+        let analysis = analyze_siblings(&siblings, &links);
+        assert_eq!(analysis.len(), 2);
+    }
+
+    #[test]
+    fn test_score_prefers_priority_and_explains() {
+        let config = RankingConfig::default();
+        let now = Utc::now();
+
+        let must = RankFactors {
+            priority: Some(("Must".to_string(), 1.0)),
+            ..Default::default()
+        };
+        let could = RankFactors {
+            priority: Some(("Could".to_string(), 1.0 / 3.0)),
+            ..Default::default()
+        };
+
+        let (must_score, why) = must.evaluate(&config, now);
+        let (could_score, _) = could.evaluate(&config, now);
+        assert!(must_score > could_score);
+        assert_eq!(why, "Must priority");
+    }
+
+    #[test]
+    fn test_score_due_and_age() {
+        let config = RankingConfig::default();
+        let now = Utc::now();
+
+        let overdue = RankFactors {
+            due: Some(now.date_naive() - Duration::days(2)),
+            todo_since: Some(now - Duration::days(3)),
+            ..Default::default()
+        };
+        let (score, why) = overdue.evaluate(&config, now);
+        assert!(score > config.weights.due);
+        assert_eq!(why, "overdue by 2d, todo for 3d");
+
+        let far = RankFactors {
+            due: Some(now.date_naive() + Duration::days(60)),
+            ..Default::default()
+        };
+        let (score, why) = far.evaluate(&config, now);
+        assert_eq!(score, 0.0);
+        assert_eq!(why, "ready to start");
+    }
+}
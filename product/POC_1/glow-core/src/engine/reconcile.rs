@@ -0,0 +1,223 @@
+//! Reconciling iterations with changed definitions
+//!
+//! Compares a process's in-flight iteration with its current definition:
+//! sub-steps added to the definition, sub-steps no longer defined (orphaned)
+//! and sub-steps whose links changed.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::model::{LinkRef, Step, StepDefinition};
+
+/// Change needed to bring an iteration in line with its definition
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeKind {
+    /// Sub-step is defined but not part of the iteration
+    Added,
+    /// Sub-step is part of the iteration but no longer defined; its data is kept
+    Orphaned,
+    /// Sub-step's links changed
+    Relinked {
+        from: Vec<LinkRef>,
+        to: Vec<LinkRef>,
+    },
+}
+
+/// A change to one sub-step of an iteration
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionChange {
+    pub step_id: String,
+    pub kind: ChangeKind,
+}
+
+impl fmt::Display for DefinitionChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ChangeKind::Added => write!(f, "+ {}: added", self.step_id),
+            ChangeKind::Orphaned => write!(f, "- {}: orphaned (data kept)", self.step_id),
+            ChangeKind::Relinked { from, to } => write!(
+                f,
+                "~ {}: relinked from {} to {}",
+                self.step_id,
+                format_links(from),
+                format_links(to)
+            ),
+        }
+    }
+}
+
+fn format_links(links: &[LinkRef]) -> String {
+    if links.is_empty() {
+        return "no links".to_string();
+    }
+    links
+        .iter()
+        .map(|l| format!("{} ({})", l.step_id, l.link_type))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Changes to one in-flight iteration
+#[derive(Debug, Clone)]
+pub struct IterationReconcile {
+    /// Process FQID
+    pub fqid: String,
+    /// Iteration number
+    pub iteration: u32,
+    /// Definition version the iteration was stamped with
+    pub from_version: Option<String>,
+    /// Current definition version
+    pub to_version: String,
+    /// Sub-step changes
+    pub changes: Vec<DefinitionChange>,
+}
+
+impl IterationReconcile {
+    /// Check if the iteration already follows the current definition
+    pub fn is_current(&self) -> bool {
+        self.changes.is_empty() && self.from_version.as_deref() == Some(self.to_version.as_str())
+    }
+}
+
+/// Plan for reconciling all in-flight iterations
+#[derive(Debug, Clone, Default)]
+pub struct ReconcilePlan {
+    /// Iterations needing changes or a new definition version
+    pub iterations: Vec<IterationReconcile>,
+    /// Completed processes, left untouched
+    pub completed: Vec<String>,
+}
+
+impl ReconcilePlan {
+    /// Number of sub-step changes across all iterations
+    pub fn change_count(&self) -> usize {
+        self.iterations.iter().map(|i| i.changes.len()).sum()
+    }
+
+    /// Check if every in-flight iteration follows its definition
+    pub fn is_empty(&self) -> bool {
+        self.iterations.is_empty()
+    }
+}
+
+/// Diff an iteration against its process's current definition
+///
+/// `recorded_links` maps sub-step IDs to the links stored in their step files.
+pub fn diff_iteration(
+    process: &Step,
+    def: &StepDefinition,
+    recorded_links: &HashMap<String, Vec<LinkRef>>,
+) -> Vec<DefinitionChange> {
+    let mut changes = Vec::new();
+
+    for sub_def in &def.steps {
+        if !process.own_steps.iter().any(|s| s.id == sub_def.id) {
+            changes.push(DefinitionChange {
+                step_id: sub_def.id.clone(),
+                kind: ChangeKind::Added,
+            });
+            continue;
+        }
+
+        let from = recorded_links.get(&sub_def.id).cloned().unwrap_or_default();
+        let to = def.link_refs(&sub_def.id);
+        if link_keys(&from) != link_keys(&to) {
+            changes.push(DefinitionChange {
+                step_id: sub_def.id.clone(),
+                kind: ChangeKind::Relinked { from, to },
+            });
+        }
+    }
+
+    for own_step in &process.own_steps {
+        if def.find_step(&own_step.id).is_none() {
+            changes.push(DefinitionChange {
+                step_id: own_step.id.clone(),
+                kind: ChangeKind::Orphaned,
+            });
+        }
+    }
+
+    changes
+}
+
+/// Links compared by target and type, ignoring order and recorded status
+fn link_keys(links: &[LinkRef]) -> Vec<(&str, &str)> {
+    let mut keys: Vec<(&str, &str)> = links
+        .iter()
+        .map(|l| (l.step_id.as_str(), l.link_type.as_str()))
+        .collect();
+    keys.sort_unstable();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{LinkDefinition, StepRef, StepStatus};
+
+    fn definition(ids: &[&str], links: Vec<LinkDefinition>) -> StepDefinition {
+        let mut def = StepDefinition::new_root();
+        for id in ids {
+            let mut sub = StepDefinition::new_root();
+            sub.id = id.to_string();
+            def.steps.push(sub);
+        }
+        def.links = links;
+        def
+    }
+
+    fn iteration(ids: &[&str]) -> Step {
+        let mut step = Step::from_definition(&StepDefinition::new_root(), None);
+        step.own_steps = ids
+            .iter()
+            .map(|id| StepRef {
+                id: id.to_string(),
+                status: StepStatus::Wait,
+                iteration: None,
+            })
+            .collect();
+        step
+    }
+
+    #[test]
+    fn test_diff_added_orphaned_and_relinked() {
+        let old = definition(&["A", "B", "C"], vec![LinkDefinition::dependency("B", "A")]);
+        let new = definition(&["A", "B", "D"], vec![LinkDefinition::dependency("B", "D")]);
+
+        let recorded: HashMap<String, Vec<LinkRef>> = ["A", "B", "C"]
+            .iter()
+            .map(|id| (id.to_string(), old.link_refs(id)))
+            .collect();
+        let changes = diff_iteration(&iteration(&["A", "B", "C"]), &new, &recorded);
+
+        let lines: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "~ B: relinked from A (dependency) to D (dependency)",
+                "+ D: added",
+                "- C: orphaned (data kept)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_unchanged_iteration() {
+        let def = definition(
+            &["A", "B"],
+            vec![
+                LinkDefinition::dependency("B", "A"),
+                LinkDefinition::predecessor("B", "A"),
+            ],
+        );
+        let mut recorded: HashMap<String, Vec<LinkRef>> = HashMap::new();
+        let mut links = def.link_refs("B");
+        links.reverse();
+        recorded.insert("B".to_string(), links);
+
+        assert!(diff_iteration(&iteration(&["A", "B"]), &def, &recorded).is_empty());
+        assert_ne!(def.version(), definition(&["A"], Vec::new()).version());
+        assert_eq!(def.version().len(), 12);
+    }
+}
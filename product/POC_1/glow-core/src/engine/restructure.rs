@@ -0,0 +1,510 @@
+//! Moving and removing steps
+//!
+//! Besides the step data, a step moved or removed with `glow mv` or `glow rm`
+//! changes in the process definition, so reconciling with `glow config apply`
+//! doesn't bring the old step back. The definition is edited as written in
+//! the process config file, line by line so comments, key order and quoting
+//! stay: steps defined through imports, fragments or flow-style YAML are left
+//! for the user to change.
+
+use serde_yaml::Value;
+
+use crate::error::{GlowError, Result};
+
+/// Outcome of moving a step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepMove {
+    /// FQID the step had
+    pub from: String,
+    /// FQID the step has now
+    pub to: String,
+    /// Number of steps moved, the step and its sub-steps
+    pub steps: usize,
+    /// Links between the step and its former siblings, dropped when it moved to another parent
+    pub dropped_links: Vec<String>,
+    /// Whether the step's definition moved in the process config file
+    pub definition_moved: bool,
+}
+
+/// Outcome of removing a step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepRemoval {
+    /// FQIDs of the removed steps: the step, then the steps linked to it (with cascade)
+    pub removed: Vec<String>,
+    /// Whether the definitions were removed from the process config file
+    pub definitions_removed: bool,
+}
+
+/// Split an FQID into the FQID of its parent and its ID (children of ROOT omit ROOT)
+pub fn split_fqid(fqid: &str) -> (&str, &str) {
+    fqid.rsplit_once('.').unwrap_or(("ROOT", fqid))
+}
+
+/// Move a step definition in a process config file as written
+///
+/// Returns the edited file, or None if the definition or its new parent
+/// isn't written there in block style. A step renamed within its parent
+/// keeps its place, and the links and parameter mappings of its former
+/// siblings follow it; links to a step moved to another parent are removed.
+pub fn move_definition(content: &str, from: &str, to: &str) -> Result<Option<String>> {
+    let (from_parent, from_id) = split_fqid(from);
+    let (to_parent, to_id) = split_fqid(to);
+    let mut doc = Document::new(content);
+
+    if from_parent == to_parent {
+        let renamed = doc
+            .rename_step(from_parent, from_id, to_id)
+            .and_then(|()| doc.rename_references(from_parent, from_id, to_id));
+        return Ok(renamed.map(|()| doc.into_string()));
+    }
+
+    if doc.step(to_parent).is_none() {
+        return Ok(None);
+    }
+    let Some((definition, indent)) = doc.take_step(from_parent, from_id, to_id) else {
+        return Ok(None);
+    };
+    if doc.remove_links(from_parent, from_id).is_none() {
+        return Ok(None);
+    }
+    let Some(parent) = doc.step(to_parent) else {
+        return Err(GlowError::ValidationError {
+            message: format!(
+                "The definition of '{}' wasn't found after taking out '{}'",
+                to_parent, from
+            ),
+        });
+    };
+    Ok(doc.insert_step(&parent, definition, indent).map(|()| doc.into_string()))
+}
+
+/// Remove a step definition and the links to it from a process config file as written
+///
+/// Returns the edited file, or None if the definition isn't written there in block style.
+pub fn remove_definition(content: &str, fqid: &str) -> Option<String> {
+    let (parent_fqid, id) = split_fqid(fqid);
+    let mut doc = Document::new(content);
+    doc.take_step(parent_fqid, id, id)?;
+    doc.remove_links(parent_fqid, id)?;
+    Some(doc.into_string())
+}
+
+/// A YAML file as lines, edited in place
+struct Document {
+    lines: Vec<String>,
+    trailing_newline: bool,
+}
+
+/// Lines of a mapping: `root_process` or an item of a sequence
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    /// First line, including the comment lines right before an item
+    lead: usize,
+    /// Line of the item marker, or the first line of `root_process`'s value
+    start: usize,
+    /// Line after the last content line
+    end: usize,
+    /// Column of the mapping's keys
+    column: usize,
+}
+
+/// Sequence written under a key of a block
+struct Sequence {
+    key_line: usize,
+    value_column: usize,
+    /// Value written on the key line, like `[]` for a flow-style sequence
+    inline: String,
+    items: Vec<Block>,
+    /// Line after the last item
+    end: usize,
+}
+
+impl Document {
+    fn new(content: &str) -> Self {
+        Self {
+            lines: content.lines().map(String::from).collect(),
+            trailing_newline: content.ends_with('\n'),
+        }
+    }
+
+    fn into_string(self) -> String {
+        let mut content = self.lines.join("\n");
+        if self.trailing_newline {
+            content.push('\n');
+        }
+        content
+    }
+
+    /// Find the block of a step definition
+    fn step(&self, fqid: &str) -> Option<Block> {
+        let mut block = self.root()?;
+        if fqid == "ROOT" {
+            return Some(block);
+        }
+        for id in fqid.split('.') {
+            block = self
+                .sequence(&block, "steps")?
+                .items
+                .into_iter()
+                .find(|item| self.field(item, "id").as_deref() == Some(id))?;
+        }
+        Some(block)
+    }
+
+    /// Block of `root_process`
+    fn root(&self) -> Option<Block> {
+        let key_line = self
+            .lines
+            .iter()
+            .position(|line| key_at(line, 0, false).is_some_and(|(key, _)| key == "root_process"))?;
+        let start = key_line + 1;
+        let column = self.lines[start..].iter().find_map(|line| indent(line))?;
+        (column > 0).then(|| Block {
+            lead: start,
+            start,
+            end: self.extent(start, 0, false),
+            column,
+        })
+    }
+
+    /// Find a `key:` line of a block, with the column after the colon
+    fn key_line(&self, block: &Block, key: &str) -> Option<(usize, usize)> {
+        (block.start..block.end).find_map(|line| {
+            let (found, value_column) = key_at(&self.lines[line], block.column, line == block.start)?;
+            (found == key).then_some((line, value_column))
+        })
+    }
+
+    /// Find the sequence under a key of a block
+    fn sequence(&self, block: &Block, key: &str) -> Option<Sequence> {
+        let (key_line, value_column) = self.key_line(block, key)?;
+        let inline = value_text(&self.lines[key_line][value_column..]).to_string();
+        let end = self.extent(key_line + 1, block.column, true);
+
+        let mut items = Vec::new();
+        let dash = (key_line + 1..end).find_map(|line| indent(&self.lines[line]));
+        let mut boundary = key_line + 1;
+        for start in key_line + 1..end {
+            let line = &self.lines[start];
+            if indent(line) != dash || !is_item(line) {
+                continue;
+            }
+            let mut lead = start;
+            while lead > boundary && is_comment(&self.lines[lead - 1]) {
+                lead -= 1;
+            }
+            let marker = line.len() - line.trim_start().len();
+            let after = &line[marker + 1..];
+            let column = if after.trim().is_empty() {
+                self.lines[start + 1..].iter().find_map(|line| indent(line))?
+            } else {
+                marker + 1 + after.len() - after.trim_start().len()
+            };
+            let item_end = self.extent(start + 1, marker, false);
+            items.push(Block {
+                lead,
+                start,
+                end: item_end,
+                column,
+            });
+            boundary = item_end;
+        }
+
+        Some(Sequence {
+            key_line,
+            value_column,
+            inline,
+            items,
+            end,
+        })
+    }
+
+    /// Line after the last content line from `from` indented more than
+    /// `column`, or at `column` for item markers when `items` is set
+    fn extent(&self, from: usize, column: usize, items: bool) -> usize {
+        let mut end = from;
+        for (number, line) in self.lines.iter().enumerate().skip(from) {
+            match indent(line) {
+                None => continue,
+                Some(n) if n > column || (items && n == column && is_item(line)) => end = number + 1,
+                Some(_) => break,
+            }
+        }
+        end
+    }
+
+    /// Read a scalar field of a sequence item, whatever style it is written in
+    fn field(&self, item: &Block, key: &str) -> Option<String> {
+        let mut text = self.lines[item.start..item.end].join("\n");
+        let marker = text.find('-')?;
+        text.replace_range(marker..=marker, " ");
+        match serde_yaml::from_str::<Value>(&text).ok()?.get(key)? {
+            Value::String(value) => Some(value.clone()),
+            Value::Number(value) => Some(value.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Replace the value of a `key: value` line, keeping its quotes and comment
+    fn set_value(&mut self, line: usize, value_column: usize, value: &str) {
+        let text = &self.lines[line];
+        let rest = &text[value_column..];
+        let comment = rest.find(" #").unwrap_or(rest.len());
+        let old = rest[..comment].trim();
+        let padding = &rest[..comment][rest[..comment].trim_end().len()..];
+        let value = match old.chars().next().filter(|c| *c == '"' || *c == '\'') {
+            Some(quote) => format!(" {}{}{}", quote, value, quote),
+            None if value.is_empty() => String::new(),
+            None => format!(" {}", value),
+        };
+        self.lines[line] = format!("{}{}{}{}", &text[..value_column], value, padding, &rest[comment..]);
+    }
+
+    /// Rename a sub-step definition where it is written
+    fn rename_step(&mut self, parent_fqid: &str, id: &str, new_id: &str) -> Option<()> {
+        let parent = self.step(parent_fqid)?;
+        let steps = self.sequence(&parent, "steps")?;
+        let item = steps
+            .items
+            .iter()
+            .find(|item| self.field(item, "id").as_deref() == Some(id))?;
+        let (line, value_column) = self.key_line(item, "id")?;
+        self.set_value(line, value_column, new_id);
+        Some(())
+    }
+
+    /// Take a sub-step definition out of its parent's `steps`, renamed
+    ///
+    /// Returns its lines, comments before it included, and the column of its item marker.
+    fn take_step(&mut self, parent_fqid: &str, id: &str, new_id: &str) -> Option<(Vec<String>, usize)> {
+        self.rename_step(parent_fqid, id, new_id)?;
+        let parent = self.step(parent_fqid)?;
+        let steps = self.sequence(&parent, "steps")?;
+        let item = steps
+            .items
+            .iter()
+            .find(|item| self.field(item, "id").as_deref() == Some(new_id))?;
+        let marker = indent(&self.lines[item.start])?;
+        let lines = self.lines.drain(item.lead..item.end).collect();
+        if steps.items.len() == 1 {
+            self.set_value(steps.key_line, steps.value_column, "[]");
+        }
+        Some((lines, marker))
+    }
+
+    /// Add a sub-step definition at the end of a parent's `steps`
+    fn insert_step(&mut self, parent: &Block, definition: Vec<String>, marker: usize) -> Option<()> {
+        let (at, column) = match self.sequence(parent, "steps") {
+            Some(steps) if !steps.inline.is_empty() && steps.inline != "[]" => return None,
+            Some(steps) => {
+                if !steps.inline.is_empty() {
+                    self.set_value(steps.key_line, steps.value_column, "");
+                }
+                let column = steps
+                    .items
+                    .first()
+                    .and_then(|item| indent(&self.lines[item.start]))
+                    .unwrap_or(parent.column + 2);
+                (steps.end, column)
+            }
+            None => {
+                self.lines
+                    .insert(parent.end, format!("{}steps:", " ".repeat(parent.column)));
+                (parent.end + 1, parent.column + 2)
+            }
+        };
+        let definition = definition.into_iter().map(|line| reindent(line, marker, column));
+        self.lines.splice(at..at, definition);
+        Some(())
+    }
+
+    /// Remove the links from or to a sub-step from a parent definition
+    fn remove_links(&mut self, parent_fqid: &str, id: &str) -> Option<()> {
+        let parent = self.step(parent_fqid)?;
+        let Some(links) = self.sequence(&parent, "links") else {
+            return Some(());
+        };
+        if links.inline.contains(id) {
+            return None;
+        }
+        let linked: Vec<&Block> = links
+            .items
+            .iter()
+            .filter(|link| ["from", "to"].iter().any(|end| self.field(link, end).as_deref() == Some(id)))
+            .collect();
+        for link in linked.iter().rev() {
+            self.lines.drain(link.lead..link.end);
+        }
+        if !linked.is_empty() && linked.len() == links.items.len() {
+            self.set_value(links.key_line, links.value_column, "[]");
+        }
+        Some(())
+    }
+
+    /// Rename a sub-step in the links and parameter mappings of a parent definition
+    fn rename_references(&mut self, parent_fqid: &str, from: &str, to: &str) -> Option<()> {
+        let parent = self.step(parent_fqid)?;
+        if let Some(links) = self.sequence(&parent, "links") {
+            if links.inline.contains(from) {
+                return None;
+            }
+            for link in &links.items {
+                for end in ["from", "to"] {
+                    if self.field(link, end).as_deref() == Some(from) {
+                        let (line, value_column) = self.key_line(link, end)?;
+                        self.set_value(line, value_column, to);
+                    }
+                }
+            }
+        }
+
+        // Mappings like "links.REQ-001.output.CRITERIA" name the linked step
+        let old_prefix = format!("links.{}.", from);
+        let steps = self.sequence(&parent, "steps").map(|s| s.items).unwrap_or_default();
+        for step in &steps {
+            for kind in ["inputs", "outputs", "scope"] {
+                let Some(params) = self.sequence(step, kind) else {
+                    continue;
+                };
+                if params.inline.contains(&old_prefix) {
+                    return None;
+                }
+                for param in &params.items {
+                    let Some(rest) = self
+                        .field(param, "mapping")
+                        .and_then(|mapping| mapping.strip_prefix(&old_prefix).map(String::from))
+                    else {
+                        continue;
+                    };
+                    let (line, value_column) = self.key_line(param, "mapping")?;
+                    self.set_value(line, value_column, &format!("links.{}.{}", to, rest));
+                }
+            }
+        }
+        Some(())
+    }
+}
+
+/// Indentation of a line, or None for blank and comment lines
+fn indent(line: &str) -> Option<usize> {
+    let content = line.trim_start();
+    (!content.is_empty() && !content.starts_with('#')).then(|| line.len() - content.len())
+}
+
+/// Check if a line holds only a comment
+fn is_comment(line: &str) -> bool {
+    line.trim_start().starts_with('#')
+}
+
+/// Check if a line starts a sequence item
+fn is_item(line: &str) -> bool {
+    let content = line.trim_start();
+    content == "-" || content.starts_with("- ")
+}
+
+/// Key of a `key: value` line whose key starts at `column`, with the column after the colon
+///
+/// On the first line of an item, the item marker comes before the key.
+fn key_at(line: &str, column: usize, first: bool) -> Option<(&str, usize)> {
+    let prefix = line.get(..column)?.trim();
+    if !(prefix.is_empty() || (first && prefix == "-")) {
+        return None;
+    }
+    let rest = &line[column..];
+    let length = rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '-'))?;
+    let after = rest[length..].strip_prefix(':')?;
+    (length > 0 && (after.is_empty() || after.starts_with(' '))).then(|| (&rest[..length], column + length + 1))
+}
+
+/// Value written after a colon, without its comment
+fn value_text(rest: &str) -> &str {
+    rest[..rest.find(" #").unwrap_or(rest.len())].trim()
+}
+
+/// Move a line from under an item marker at one column to another
+fn reindent(line: String, from: usize, to: usize) -> String {
+    if line.trim().is_empty() {
+        line
+    } else if to >= from {
+        format!("{}{}", " ".repeat(to - from), line)
+    } else {
+        let spaces = line.len() - line.trim_start_matches(' ').len();
+        line[spaces.min(from - to)..].to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# Feature process
+root_process:
+  id: ROOT
+  steps:
+    - id: FEAT
+      steps:
+        # Write down what to build
+        - id: SPEC   # first
+          outputs:
+            - id: CRITERIA
+        - id: BUILD
+          inputs:
+            - id: CRITERIA
+              mapping: "links.SPEC.output.CRITERIA"
+      links:
+        - from: BUILD
+          to: SPEC
+    - id: OPS
+"#;
+
+    #[test]
+    fn test_rename_rewrites_links_and_mappings() {
+        let config = move_definition(CONFIG, "FEAT.SPEC", "FEAT.DESIGN").unwrap().unwrap();
+        assert_eq!(
+            config,
+            CONFIG
+                .replace("- id: SPEC   # first", "- id: DESIGN   # first")
+                .replace("\"links.SPEC.", "\"links.DESIGN.")
+                .replace("to: SPEC", "to: DESIGN")
+        );
+    }
+
+    #[test]
+    fn test_move_to_other_parent_and_remove() {
+        let config = move_definition(CONFIG, "FEAT.SPEC", "OPS.SPEC").unwrap().unwrap();
+        assert!(config.starts_with("# Feature process\nroot_process:\n"));
+        assert!(config.contains("      links: []\n"), "{}", config);
+        assert!(config.ends_with(
+            "    - id: OPS\n      steps:\n        # Write down what to build\n        - id: SPEC   # first\n          outputs:\n            - id: CRITERIA\n"
+        ));
+        let mut doc = Document::new(&config);
+        assert!(doc.step("OPS.SPEC").is_some());
+        assert!(doc.rename_step("FEAT", "SPEC", "OTHER").is_none());
+
+        // Definitions not written in the file are left alone
+        assert_eq!(move_definition(&config, "FEAT.MISSING", "FEAT.OTHER").unwrap(), None);
+        assert_eq!(remove_definition(&config, "FEAT.SPEC"), None);
+
+        let config = remove_definition(&config, "OPS").unwrap();
+        assert!(config.ends_with("            mapping: \"links.SPEC.output.CRITERIA\"\n      links: []\n"));
+        assert!(Document::new(&config).step("OPS").is_none());
+    }
+
+    #[test]
+    fn test_flow_style_is_left_alone() {
+        let config = "root_process:\n  id: ROOT\n  steps: [{id: A}, {id: B}]\n";
+        assert_eq!(move_definition(config, "A", "C").unwrap(), None);
+        assert_eq!(remove_definition(config, "A"), None);
+
+        let config = "root_process:\n  id: ROOT\n  steps:\n  - id: A\n  - {id: B}\n  links: [{from: B, to: A}]\n";
+        assert_eq!(move_definition(config, "A", "C").unwrap(), None);
+        assert_eq!(move_definition(config, "B", "A.B").unwrap(), None);
+
+        // Items written at the key's column are moved under a new `steps:`
+        let config = "root_process:\n  id: ROOT\n  steps:\n  - id: A\n  - id: B\n    purpose: Build\n";
+        assert_eq!(
+            move_definition(config, "B", "A.B").unwrap().unwrap(),
+            "root_process:\n  id: ROOT\n  steps:\n  - id: A\n    steps:\n      - id: B\n        purpose: Build\n"
+        );
+    }
+}
@@ -0,0 +1,160 @@
+//! Due dates and iteration time boxes
+//!
+//! Detects overdue steps and iterations whose time box has ended.
+
+use chrono::NaiveDate;
+
+use crate::model::{StepStatus, Timebox};
+
+use super::operations::StatusTree;
+
+/// What is overdue
+#[derive(Debug, Clone, PartialEq)]
+pub enum OverdueKind {
+    /// A step past its due date
+    Step { due: NaiveDate },
+    /// A process iteration past the end of its time box
+    Iteration { number: Option<u32>, timebox: Timebox },
+}
+
+/// An overdue step or iteration
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overdue {
+    /// Step or process FQID
+    pub fqid: String,
+    /// What is overdue
+    pub kind: OverdueKind,
+    /// Days past the deadline
+    pub days: i64,
+}
+
+impl std::fmt::Display for Overdue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            OverdueKind::Step { due } => write!(
+                f,
+                "{}: overdue by {}d (due {})",
+                self.fqid, self.days, due
+            ),
+            OverdueKind::Iteration { number, timebox } => {
+                let number = number.map(|n| format!(" {}", n)).unwrap_or_default();
+                write!(
+                    f,
+                    "{}: iteration{} time box ended {} ({}d ago), run 'glow iteration close {}'",
+                    self.fqid, number, timebox.max, self.days, self.fqid
+                )
+            }
+        }
+    }
+}
+
+/// Find overdue steps and iterations in a status tree
+///
+/// Done steps are never overdue.
+pub fn find_overdue(tree: &StatusTree, today: NaiveDate) -> Vec<Overdue> {
+    let mut overdue = Vec::new();
+    collect_overdue(tree, today, &mut overdue);
+    overdue
+}
+
+fn collect_overdue(tree: &StatusTree, today: NaiveDate, overdue: &mut Vec<Overdue>) {
+    if tree.status != StepStatus::Done {
+        if let Some(due) = tree.due.filter(|due| *due < today) {
+            overdue.push(Overdue {
+                fqid: tree.fqid.clone(),
+                kind: OverdueKind::Step { due },
+                days: (today - due).num_days(),
+            });
+        }
+
+        if let Some(timebox) = tree.timebox.as_ref().filter(|t| t.max < today) {
+            overdue.push(Overdue {
+                fqid: tree.fqid.clone(),
+                kind: OverdueKind::Iteration {
+                    number: tree.iteration,
+                    timebox: timebox.clone(),
+                },
+                days: (today - timebox.max).num_days(),
+            });
+        }
+    }
+
+    for child in &tree.children {
+        collect_overdue(child, today, overdue);
+    }
+}
+
+/// Result of closing a process iteration
+#[derive(Debug, Clone)]
+pub struct IterationClose {
+    /// Process FQID
+    pub fqid: String,
+    /// Closed iteration number
+    pub closed: u32,
+    /// Newly opened iteration number
+    pub opened: u32,
+    /// Sub-steps finished in the closed iteration
+    pub done: Vec<String>,
+    /// Unfinished sub-steps carried into the new iteration
+    pub carried_over: Vec<String>,
+    /// Time box of the new iteration
+    pub timebox: Option<Timebox>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn node(fqid: &str, status: StepStatus) -> StatusTree {
+        StatusTree {
+            id: fqid.to_string(),
+            fqid: fqid.to_string(),
+            purpose: None,
+            classification: None,
+            status,
+            estimate: None,
+            due: None,
+            iteration: None,
+            timebox: None,
+            status_since: None,
+            finished_at: None,
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_find_overdue_steps() {
+        let mut root = node("ROOT", StepStatus::InProgress);
+        let mut late = node("LATE", StepStatus::Todo);
+        late.due = Some(date("2026-03-01"));
+        let mut done = node("DONE", StepStatus::Done);
+        done.due = Some(date("2026-03-01"));
+        let mut future = node("FUTURE", StepStatus::Todo);
+        future.due = Some(date("2026-04-01"));
+        root.children = vec![late, done, future];
+
+        let overdue = find_overdue(&root, date("2026-03-04"));
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].fqid, "LATE");
+        assert_eq!(overdue[0].days, 3);
+        assert_eq!(overdue[0].to_string(), "LATE: overdue by 3d (due 2026-03-01)");
+    }
+
+    #[test]
+    fn test_find_overdue_iteration() {
+        let mut root = node("ROOT", StepStatus::InProgress);
+        root.iteration = Some(2);
+        root.timebox = Some(Timebox::new(date("2026-03-01"), date("2026-03-14")));
+
+        assert!(find_overdue(&root, date("2026-03-14")).is_empty());
+
+        let overdue = find_overdue(&root, date("2026-03-16"));
+        assert_eq!(overdue.len(), 1);
+        assert_eq!(overdue[0].days, 2);
+        assert!(overdue[0].to_string().contains("iteration 2 time box ended 2026-03-14"));
+    }
+}
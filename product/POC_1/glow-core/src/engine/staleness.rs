@@ -0,0 +1,178 @@
+//! Stale work detection
+//!
+//! Finds steps stuck in progress, waiting on steps that were never
+//! initialized, or sitting in todo without being picked up.
+
+use chrono::{DateTime, Utc};
+
+use crate::model::{ResolvedThresholds, StepStatus};
+
+/// Why a step is stale
+#[derive(Debug, Clone, PartialEq)]
+pub enum StaleKind {
+    /// In progress without any activity
+    InProgressIdle,
+    /// Waiting on dependencies that were never initialized
+    WaitingOnUninitialized { dependencies: Vec<String> },
+    /// In todo without being started
+    TodoUntouched,
+}
+
+/// A stale step with a suggested remediation
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaleWork {
+    /// Step FQID
+    pub fqid: String,
+    /// Why the step is stale
+    pub kind: StaleKind,
+    /// Days since the last activity
+    pub days: i64,
+    /// Threshold in days that was reached
+    pub threshold: u32,
+    /// Suggested remediation
+    pub remediation: String,
+}
+
+impl std::fmt::Display for StaleWork {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let reason = match &self.kind {
+            StaleKind::InProgressIdle => format!("in progress with no activity for {}d", self.days),
+            StaleKind::WaitingOnUninitialized { dependencies } => format!(
+                "waiting {}d on uninitialized {}",
+                self.days,
+                dependencies.join(", ")
+            ),
+            StaleKind::TodoUntouched => format!("in todo for {}d", self.days),
+        };
+        write!(f, "{}: {}, {}", self.fqid, reason, self.remediation)
+    }
+}
+
+/// A step checked for staleness
+#[derive(Debug, Clone)]
+pub struct StaleCandidate {
+    /// Step FQID
+    pub fqid: String,
+    /// Current status
+    pub status: StepStatus,
+    /// Latest status change or file modification
+    pub last_activity: Option<DateTime<Utc>>,
+    /// Blocking dependencies (FQIDs) that were never initialized
+    pub uninitialized_dependencies: Vec<String>,
+}
+
+/// Assess whether a step is stale
+pub fn assess(
+    candidate: &StaleCandidate,
+    thresholds: ResolvedThresholds,
+    now: DateTime<Utc>,
+) -> Option<StaleWork> {
+    let days = (now - candidate.last_activity?).num_days();
+    let fqid = &candidate.fqid;
+
+    let (kind, threshold, remediation) = match candidate.status {
+        StepStatus::InProgress => (
+            StaleKind::InProgressIdle,
+            thresholds.in_progress_days,
+            format!(
+                "run 'glow finish {}' if it is done, otherwise record progress or split it",
+                fqid
+            ),
+        ),
+        StepStatus::Wait if !candidate.uninitialized_dependencies.is_empty() => {
+            let commands: Vec<String> = candidate
+                .uninitialized_dependencies
+                .iter()
+                .map(|dep| format!("'glow init {}'", dep))
+                .collect();
+            (
+                StaleKind::WaitingOnUninitialized {
+                    dependencies: candidate.uninitialized_dependencies.clone(),
+                },
+                thresholds.wait_days,
+                format!("run {} or remove the dependency", commands.join(", ")),
+            )
+        }
+        StepStatus::Todo => (
+            StaleKind::TodoUntouched,
+            thresholds.todo_days,
+            format!("run 'glow start {}' or lower its priority", fqid),
+        ),
+        _ => return None,
+    };
+
+    (days >= i64::from(threshold)).then(|| StaleWork {
+        fqid: fqid.clone(),
+        kind,
+        days,
+        threshold,
+        remediation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    const THRESHOLDS: ResolvedThresholds = ResolvedThresholds {
+        in_progress_days: 7,
+        wait_days: 5,
+        todo_days: 14,
+    };
+
+    fn now() -> DateTime<Utc> {
+        "2026-03-20T12:00:00Z".parse().unwrap()
+    }
+
+    fn candidate(status: StepStatus, days_ago: i64) -> StaleCandidate {
+        StaleCandidate {
+            fqid: "FEAT.TASK".to_string(),
+            status,
+            last_activity: Some(now() - Duration::days(days_ago)),
+            uninitialized_dependencies: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_in_progress_idle() {
+        let now = now();
+        assert!(assess(&candidate(StepStatus::InProgress, 6), THRESHOLDS, now).is_none());
+
+        let stale = assess(&candidate(StepStatus::InProgress, 8), THRESHOLDS, now).unwrap();
+        assert_eq!(stale.kind, StaleKind::InProgressIdle);
+        assert_eq!(stale.days, 8);
+        assert!(stale.to_string().contains("no activity for 8d"));
+        assert!(stale.remediation.contains("glow finish FEAT.TASK"));
+    }
+
+    #[test]
+    fn test_waiting_on_uninitialized() {
+        let now = now();
+        let mut waiting = candidate(StepStatus::Wait, 6);
+        assert!(assess(&waiting, THRESHOLDS, now).is_none());
+
+        waiting.uninitialized_dependencies = vec!["FEAT.DESIGN".to_string()];
+        let stale = assess(&waiting, THRESHOLDS, now).unwrap();
+        assert_eq!(
+            stale.kind,
+            StaleKind::WaitingOnUninitialized {
+                dependencies: vec!["FEAT.DESIGN".to_string()]
+            }
+        );
+        assert!(stale.remediation.contains("glow init FEAT.DESIGN"));
+    }
+
+    #[test]
+    fn test_todo_untouched_and_done_ignored() {
+        let now = now();
+        let stale = assess(&candidate(StepStatus::Todo, 20), THRESHOLDS, now).unwrap();
+        assert_eq!(stale.kind, StaleKind::TodoUntouched);
+        assert!(assess(&candidate(StepStatus::Todo, 10), THRESHOLDS, now).is_none());
+        assert!(assess(&candidate(StepStatus::Done, 100), THRESHOLDS, now).is_none());
+
+        let mut unknown = candidate(StepStatus::Todo, 0);
+        unknown.last_activity = None;
+        assert!(assess(&unknown, THRESHOLDS, now).is_none());
+    }
+}
@@ -5,7 +5,9 @@
 use crate::error::Result;
 use crate::model::{LinkGraph, StepStatus};
 
-use super::operations::{IssueType, ProcessEngine, ValidationIssue, ValidationReport};
+use super::operations::{
+    IssueType, ProcessEngine, ProgressMetrics, ValidationIssue, ValidationReport,
+};
 
 /// Context quality validator
 #[derive(Debug, Default)]
@@ -49,7 +51,7 @@ impl Validator {
             warnings: Vec::new(),
         };
 
-        // Get status tree
+        // Get status tree, shared by every check below
         let status_tree = engine.get_status_tree()?;
 
         // Validate root or specific step
@@ -59,8 +61,22 @@ impl Validator {
             self.validate_tree(engine, &status_tree, &mut report)?;
         }
 
+        // Report overdue steps and iterations
+        for overdue in super::schedule::find_overdue(&status_tree, engine.clock().today()) {
+            if fqid.is_none_or(|target| target == overdue.fqid) {
+                report.warnings.push(overdue.to_string());
+            }
+        }
+
+        // Report stale work
+        for stale in engine.stale_work_in(&status_tree) {
+            if fqid.is_none_or(|target| target == stale.fqid) {
+                report.warnings.push(format!("Stale: {}", stale));
+            }
+        }
+
         // Calculate completeness
-        report.completeness = self.calculate_completeness(engine)?;
+        report.completeness = ProgressMetrics::from_tree(&status_tree).completion_percentage();
         report.is_valid = report.issues.is_empty();
 
         Ok(report)
@@ -73,7 +89,7 @@ impl Validator {
         fqid: &str,
         report: &mut ValidationReport,
     ) -> Result<()> {
-        let step = match engine.storage().read_step(fqid) {
+        let step = match engine.storage().read_summary(fqid) {
             Ok(s) => s,
             Err(_) => {
                 report.issues.push(ValidationIssue {
@@ -100,17 +116,12 @@ impl Validator {
         // Check required inputs for in-progress/done steps
         if step.status() == StepStatus::InProgress || step.status() == StepStatus::Done {
             for input_ref in &step_def.inputs {
-                if input_ref.is_required.unwrap_or(false) {
-                    let has_value = step.input.iter()
-                        .any(|p| p.id == input_ref.id && p.value.is_some());
-
-                    if !has_value {
-                        report.issues.push(ValidationIssue {
-                            fqid: fqid.to_string(),
-                            issue_type: IssueType::MissingParameter,
-                            message: format!("Missing required input: {}", input_ref.id),
-                        });
-                    }
+                if input_ref.is_required.unwrap_or(false) && !step.has_input(&input_ref.id) {
+                    report.issues.push(ValidationIssue {
+                        fqid: fqid.to_string(),
+                        issue_type: IssueType::MissingParameter,
+                        message: format!("Missing required input: {}", input_ref.id),
+                    });
                 }
             }
         }
@@ -118,17 +129,12 @@ impl Validator {
         // Check required outputs for done steps
         if step.status() == StepStatus::Done {
             for output_ref in &step_def.outputs {
-                if output_ref.is_required.unwrap_or(false) {
-                    let has_value = step.output.iter()
-                        .any(|p| p.id == output_ref.id && p.value.is_some());
-
-                    if !has_value {
-                        report.issues.push(ValidationIssue {
-                            fqid: fqid.to_string(),
-                            issue_type: IssueType::MissingParameter,
-                            message: format!("Missing required output: {}", output_ref.id),
-                        });
-                    }
+                if output_ref.is_required.unwrap_or(false) && !step.has_output(&output_ref.id) {
+                    report.issues.push(ValidationIssue {
+                        fqid: fqid.to_string(),
+                        issue_type: IssueType::MissingParameter,
+                        message: format!("Missing required output: {}", output_ref.id),
+                    });
                 }
             }
         }
@@ -186,12 +192,6 @@ impl Validator {
 
         Ok(())
     }
-
-    /// Calculate completeness percentage
-    fn calculate_completeness(&self, engine: &ProcessEngine) -> Result<f64> {
-        let progress = engine.get_progress()?;
-        Ok(progress.completion_percentage())
-    }
 }
 
 #[cfg(test)]
@@ -23,6 +23,16 @@ pub enum GlowError {
     #[error("Invalid configuration at {path}: {message}")]
     InvalidConfig { path: String, message: String },
 
+    #[error("{path} has schema version {version}, but this glow supports up to {supported}; upgrade glow")]
+    UnsupportedVersion {
+        path: PathBuf,
+        version: String,
+        supported: String,
+    },
+
+    #[error("Invalid process configuration:\n{}", .diagnostics.join("\n"))]
+    InvalidProcessConfig { diagnostics: Vec<String> },
+
     // Validation errors
     #[error("Validation error: {message}")]
     ValidationError { message: String },
@@ -30,6 +40,9 @@ pub enum GlowError {
     #[error("Schema validation failed at {path}: {errors:?}")]
     SchemaValidationError { path: String, errors: Vec<String> },
 
+    #[error("Archive {path} failed verification:\n{}", .problems.join("\n"))]
+    ArchiveCorrupt { path: PathBuf, problems: Vec<String> },
+
     // State errors
     #[error("Invalid state transition: cannot move step '{step_id}' from '{current}' to '{target}'")]
     InvalidStateTransition {
@@ -44,16 +57,53 @@ pub enum GlowError {
         dependencies: Vec<String>,
     },
 
+    #[error("Starting '{step_id}' would exceed the WIP limit for {limit}; in progress: {active:?} (override to start anyway)")]
+    WipLimitExceeded {
+        step_id: String,
+        limit: String,
+        active: Vec<String>,
+    },
+
     #[error("Cannot start new iteration: current iteration not complete")]
     IterationNotComplete { step_id: String },
 
+    #[error("Project data is locked by another glow process: {path}")]
+    ProjectLocked { path: PathBuf },
+
+    #[error("Project data is locked for reading, and changes need the exclusive lock from the start: {path}")]
+    LockNotExclusive { path: PathBuf },
+
+    #[error("Step '{fqid}' was changed in {path} since it was read; run the command again to apply it to the new content")]
+    StepConflict { fqid: String, path: PathBuf },
+
+    #[error("Step already exists: {fqid}")]
+    StepAlreadyExists { fqid: String },
+
+    #[error("Step '{fqid}' is linked from {dependents:?} (cascade to remove them too)")]
+    StepHasDependents { fqid: String, dependents: Vec<String> },
+
+    #[error("Restoring snapshot '{snapshot}' would remove the documents or artifacts of {steps:?} (force to remove them too)")]
+    RestoreRemovesWork { snapshot: String, steps: Vec<String> },
+
     // Not found errors
     #[error("Step not found: {fqid}")]
     StepNotFound { fqid: String },
 
+    #[error("Iteration {iteration} of '{fqid}' not found")]
+    IterationNotFound { fqid: String, iteration: u32 },
+
+    #[error("Snapshot not found: {reference}")]
+    SnapshotNotFound { reference: String },
+
+    #[error("Artifact not found: {name} of step {fqid}")]
+    ArtifactNotFound { fqid: String, name: String },
+
     #[error("Parameter not found: {param_id} in step {step_id}")]
     ParameterNotFound { step_id: String, param_id: String },
 
+    #[error("Unknown preset '{name}'; available: {}", .available.join(", "))]
+    PresetNotFound { name: String, available: Vec<String> },
+
     #[error("Template not found: {template}")]
     TemplateNotFound { template: String },
 
@@ -68,6 +118,9 @@ pub enum GlowError {
         source: std::io::Error,
     },
 
+    #[error("git failed: {message}")]
+    GitError { message: String },
+
     #[error("Failed to read file: {path}")]
     FileReadError {
         path: PathBuf,
@@ -105,9 +158,15 @@ pub enum GlowError {
         source: serde_json::Error,
     },
 
+    #[error("TOML error: {message}")]
+    TomlParseError { message: String },
+
     #[error("Invalid frontmatter in file: {path}")]
     InvalidFrontmatter { path: PathBuf },
 
+    #[error("Invalid step data file:\n{}", .diagnostics.join("\n"))]
+    InvalidStepFile { diagnostics: Vec<String> },
+
     // Process errors
     #[error("Missing required parameter: {param_id}")]
     MissingRequiredParameter { param_id: String },
@@ -135,27 +194,44 @@ impl GlowError {
         match self {
             GlowError::ConfigError { .. }
             | GlowError::ConfigNotFound { .. }
-            | GlowError::InvalidConfig { .. } => ErrorCategory::Config,
+            | GlowError::InvalidConfig { .. }
+            | GlowError::InvalidProcessConfig { .. }
+            | GlowError::UnsupportedVersion { .. } => ErrorCategory::Config,
 
             GlowError::ValidationError { .. }
-            | GlowError::SchemaValidationError { .. } => ErrorCategory::Validation,
+            | GlowError::SchemaValidationError { .. }
+            | GlowError::ArchiveCorrupt { .. }
+            | GlowError::InvalidStepFile { .. } => ErrorCategory::Validation,
 
             GlowError::InvalidStateTransition { .. }
             | GlowError::BlockedByDependencies { .. }
-            | GlowError::IterationNotComplete { .. } => ErrorCategory::State,
+            | GlowError::WipLimitExceeded { .. }
+            | GlowError::IterationNotComplete { .. }
+            | GlowError::ProjectLocked { .. }
+            | GlowError::LockNotExclusive { .. }
+            | GlowError::StepConflict { .. }
+            | GlowError::StepAlreadyExists { .. }
+            | GlowError::StepHasDependents { .. }
+            | GlowError::RestoreRemovesWork { .. } => ErrorCategory::State,
 
             GlowError::StepNotFound { .. }
+            | GlowError::IterationNotFound { .. }
+            | GlowError::SnapshotNotFound { .. }
+            | GlowError::ArtifactNotFound { .. }
             | GlowError::ParameterNotFound { .. }
+            | GlowError::PresetNotFound { .. }
             | GlowError::TemplateNotFound { .. }
             | GlowError::LinkTargetNotFound { .. } => ErrorCategory::NotFound,
 
             GlowError::IoError { .. }
+            | GlowError::GitError { .. }
             | GlowError::FileReadError { .. }
             | GlowError::FileWriteError { .. } => ErrorCategory::Io,
 
             GlowError::TemplateError { .. }
             | GlowError::YamlParseError { .. }
             | GlowError::JsonParseError { .. }
+            | GlowError::TomlParseError { .. }
             | GlowError::InvalidFrontmatter { .. } => ErrorCategory::Parse,
 
             GlowError::MissingRequiredParameter { .. }
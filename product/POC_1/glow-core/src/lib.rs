@@ -8,7 +8,7 @@
 //! - `config`: Configuration loading and validation
 //! - `model`: Data model structs (Step, Process, Parameter, Link)
 //! - `engine`: Process engine (state machine, context management)
-//! - `storage`: File system operations
+//! - `storage`: Step data storage (files or memory)
 //! - `template`: Templating engine wrapper
 //! - `quality`: Context quality assessment
 
@@ -38,3 +38,12 @@ pub const DEFAULT_DATA_DIR: &str = "glow";
 
 /// Environment variable for custom config directory
 pub const CONFIG_DIR_ENV: &str = "DEV_GLOW_CONFIG_DIR";
+
+/// Environment variable for the user-level config directory
+pub const USER_CONFIG_DIR_ENV: &str = "DEV_GLOW_USER_CONFIG_DIR";
+
+/// Environment variable for the user-level library of shared config files
+pub const LIBRARY_DIR_ENV: &str = "DEV_GLOW_LIBRARY_DIR";
+
+/// Environment variable for the user-level directory of process presets
+pub const PRESET_DIR_ENV: &str = "DEV_GLOW_PRESET_DIR";
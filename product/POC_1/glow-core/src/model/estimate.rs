@@ -0,0 +1,196 @@
+//! Estimate data model
+//!
+//! Estimates express the expected effort of a step, either as abstract
+//! points or as a duration expression.
+
+use serde::{Deserialize, Serialize};
+
+/// Working hours in a day used to normalize duration estimates
+pub const HOURS_PER_DAY: f64 = 8.0;
+
+/// Working days in a week used to normalize duration estimates
+pub const DAYS_PER_WEEK: f64 = 5.0;
+
+/// Effort estimate for a step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[serde(untagged)]
+pub enum Estimate {
+    /// Abstract effort points (e.g., story points)
+    Points(#[schemars(range(min = 0))] f64),
+    /// Duration expression such as "4h", "2d" or "1.5w"
+    Duration(#[schemars(regex(pattern = r"^\s*\d+(\.\d+)?\s*[mhdwMHDW]\s*$"))] String),
+}
+
+/// Unit of an estimate weight
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EstimateUnit {
+    /// Abstract effort points
+    Points,
+    /// Hours, from a duration expression
+    Hours,
+}
+
+impl std::fmt::Display for EstimateUnit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EstimateUnit::Points => write!(f, "points"),
+            EstimateUnit::Hours => write!(f, "durations"),
+        }
+    }
+}
+
+impl Estimate {
+    /// Get the unit of the estimate weight
+    pub fn unit(&self) -> EstimateUnit {
+        match self {
+            Estimate::Points(_) => EstimateUnit::Points,
+            Estimate::Duration(_) => EstimateUnit::Hours,
+        }
+    }
+
+    /// Get the estimate weight used for progress and forecasting
+    ///
+    /// Points are used as-is, durations are normalized to hours; the two
+    /// can't be added up, see `unit`. Returns `None` for unparsable
+    /// duration expressions.
+    pub fn weight(&self) -> Option<f64> {
+        match self {
+            Estimate::Points(points) if points.is_finite() && *points >= 0.0 => Some(*points),
+            Estimate::Points(_) => None,
+            Estimate::Duration(expr) => parse_duration_hours(expr),
+        }
+    }
+}
+
+impl std::fmt::Display for Estimate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Estimate::Points(points) => write!(f, "{}", points),
+            Estimate::Duration(expr) => write!(f, "{}", expr),
+        }
+    }
+}
+
+impl std::str::FromStr for Estimate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Ok(points) = s.parse::<f64>() {
+            if !points.is_finite() {
+                return Err(format!("Estimate must be a finite number: {}", s));
+            }
+            if points < 0.0 {
+                return Err(format!("Estimate must not be negative: {}", s));
+            }
+            return Ok(Estimate::Points(points));
+        }
+
+        if parse_duration_hours(s).is_some() {
+            Ok(Estimate::Duration(s.to_string()))
+        } else {
+            Err(format!(
+                "Invalid estimate: {} (expected points like '3' or duration like '4h', '2d', '1w')",
+                s
+            ))
+        }
+    }
+}
+
+/// Parse a duration expression ("30m", "4h", "2d", "1.5w") into hours
+pub fn parse_duration_hours(expr: &str) -> Option<f64> {
+    let expr = expr.trim();
+    let unit = expr.chars().last()?;
+    let number: f64 = expr[..expr.len() - unit.len_utf8()].trim().parse().ok()?;
+
+    if !number.is_finite() || number < 0.0 {
+        return None;
+    }
+
+    let hours = match unit.to_ascii_lowercase() {
+        'm' => number / 60.0,
+        'h' => number,
+        'd' => number * HOURS_PER_DAY,
+        'w' => number * HOURS_PER_DAY * DAYS_PER_WEEK,
+        _ => return None,
+    };
+
+    Some(hours)
+}
+
+/// Resolve weights for a set of steps with optional estimates
+///
+/// Unestimated steps are weighted with the mean of estimated ones, so a
+/// project without any estimates is weighted purely by step count.
+pub fn fill_weights(weights: &[Option<f64>]) -> Vec<f64> {
+    let estimated: Vec<f64> = weights.iter().filter_map(|w| *w).collect();
+
+    let fallback = if estimated.is_empty() {
+        1.0
+    } else {
+        estimated.iter().sum::<f64>() / estimated.len() as f64
+    };
+
+    weights.iter().map(|w| w.unwrap_or(fallback)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_hours() {
+        assert_eq!(parse_duration_hours("4h"), Some(4.0));
+        assert_eq!(parse_duration_hours("2d"), Some(16.0));
+        assert_eq!(parse_duration_hours("1w"), Some(40.0));
+        assert_eq!(parse_duration_hours("1.5d"), Some(12.0));
+        assert_eq!(parse_duration_hours("30m"), Some(0.5));
+        assert_eq!(parse_duration_hours("2x"), None);
+        assert_eq!(parse_duration_hours("h"), None);
+        assert_eq!(parse_duration_hours(""), None);
+    }
+
+    #[test]
+    fn test_estimate_weight() {
+        assert_eq!(Estimate::Points(3.0).weight(), Some(3.0));
+        assert_eq!(Estimate::Points(-1.0).weight(), None);
+        assert_eq!(Estimate::Points(f64::INFINITY).weight(), None);
+        assert_eq!(Estimate::Duration("2d".to_string()).weight(), Some(16.0));
+        assert_eq!(Estimate::Duration("soon".to_string()).weight(), None);
+        assert_eq!(Estimate::Points(3.0).unit(), EstimateUnit::Points);
+        assert_eq!(Estimate::Duration("2d".to_string()).unit(), EstimateUnit::Hours);
+    }
+
+    #[test]
+    fn test_estimate_parse() {
+        assert_eq!("5".parse::<Estimate>().unwrap(), Estimate::Points(5.0));
+        assert_eq!(
+            "3d".parse::<Estimate>().unwrap(),
+            Estimate::Duration("3d".to_string())
+        );
+        assert!("-2".parse::<Estimate>().is_err());
+        assert!("inf".parse::<Estimate>().is_err());
+        assert!("NaN".parse::<Estimate>().is_err());
+        assert!("infh".parse::<Estimate>().is_err());
+        assert!("later".parse::<Estimate>().is_err());
+    }
+
+    #[test]
+    fn test_estimate_serde_untagged() {
+        let points: Estimate = serde_yaml::from_str("8").unwrap();
+        assert_eq!(points, Estimate::Points(8.0));
+
+        let duration: Estimate = serde_yaml::from_str("\"4h\"").unwrap();
+        assert_eq!(duration, Estimate::Duration("4h".to_string()));
+    }
+
+    #[test]
+    fn test_fill_weights() {
+        assert_eq!(fill_weights(&[None, None]), vec![1.0, 1.0]);
+        assert_eq!(
+            fill_weights(&[Some(2.0), Some(4.0), None]),
+            vec![2.0, 4.0, 3.0]
+        );
+        assert!(fill_weights(&[]).is_empty());
+    }
+}
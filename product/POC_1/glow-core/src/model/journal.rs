@@ -0,0 +1,51 @@
+//! Journal
+//!
+//! Records status changes of steps as they happen, and steps being moved or
+//! removed.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::StepStatus;
+
+/// A status change of a step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// When the change happened
+    pub at: DateTime<Utc>,
+    /// FQID of the changed step
+    pub fqid: String,
+    /// Status the step moved to
+    pub status: StepStatus,
+    /// Who made the change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actor: Option<String>,
+    /// Change to the step itself (None for a status change)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub change: Option<StepChange>,
+}
+
+/// A change to a step other than its status
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StepChange {
+    /// The step moved to another FQID
+    Moved { to: String },
+    /// The step was removed, in the status it had
+    Removed,
+}
+
+impl std::fmt::Display for JournalEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let at = self.at.format("%Y-%m-%d %H:%M");
+        match &self.change {
+            None => write!(f, "{} {} -> {}", at, self.fqid, self.status)?,
+            Some(StepChange::Moved { to }) => write!(f, "{} {} moved to {}", at, self.fqid, to)?,
+            Some(StepChange::Removed) => write!(f, "{} {} removed", at, self.fqid)?,
+        }
+        if let Some(actor) = &self.actor {
+            write!(f, " ({})", actor)?;
+        }
+        Ok(())
+    }
+}
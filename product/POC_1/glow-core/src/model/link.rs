@@ -10,8 +10,10 @@ pub const LINK_TYPE_PREDECESSOR: &str = "predecessor";
 
 /// Link type definition
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct LinkType {
     /// Unique link type identifier
+    #[schemars(regex(pattern = r"^[a-z_][a-z0-9_-]*$"))]
     pub id: String,
     /// Human-readable name
     #[serde(default)]
@@ -48,6 +50,7 @@ impl LinkType {
 
 /// Link definition in process configuration
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct LinkDefinition {
     /// Link type (dependency, predecessor, or custom)
     #[serde(default = "default_link_type")]
@@ -3,35 +3,50 @@
 //! This module contains all the core data structures representing
 //! steps, processes, parameters, links, and classifications.
 
+mod estimate;
+mod journal;
 mod link;
 mod parameter;
 mod process;
+mod ranking;
+mod staleness;
 mod step;
+mod wip;
 
+pub use estimate::{fill_weights, parse_duration_hours, Estimate, EstimateUnit};
+pub use journal::{JournalEntry, StepChange};
 pub use link::{Link, LinkDefinition, LinkGraph, LinkType};
 pub use parameter::{
     ContentValue, DataType, Parameter, ParameterRef, ParameterType, ParameterValue, RangeValue,
     SetValue,
 };
-pub use process::{Process, ProcessDefinition};
+pub use process::{parse_iteration_days, Process, ProcessDefinition, Timebox, MAX_ITERATION_DAYS};
+pub use ranking::{RankingConfig, RankingWeights};
+pub use staleness::{ClassificationThresholds, ResolvedThresholds, StalenessConfig, StalenessThresholds};
 pub use step::{LinkRef, ParentRef, Step, StepAttributes, StepDefinition, StepRef, StepStatus};
+pub use wip::WipLimit;
 
 /// Classification dimension for multi-dimensional grouping
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ClassificationDictionary {
     /// Unique identifier for this classification dimension
+    #[schemars(regex(pattern = r"^[A-Za-z_][A-Za-z0-9_]*$"))]
     pub id: String,
     /// Human-readable name
     #[serde(default)]
     pub name: Option<String>,
     /// Available classification values in this dimension
+    #[schemars(length(min = 1))]
     pub values: Vec<ClassificationValue>,
 }
 
 /// A single classification value with optional defaults
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ClassificationValue {
     /// Classification key used in expressions
+    #[schemars(regex(pattern = r"^[A-Za-z][A-Za-z0-9_-]*$"))]
     pub key: String,
     /// Human-readable name
     #[serde(default)]
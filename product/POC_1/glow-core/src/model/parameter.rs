@@ -7,8 +7,10 @@ use serde::{Deserialize, Serialize};
 /// Data type for parameters
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[derive(Default)]
 pub enum DataType {
     /// String value
+    #[default]
     Str,
     /// Integer number
     Int,
@@ -32,11 +34,6 @@ pub enum DataType {
     Template,
 }
 
-impl Default for DataType {
-    fn default() -> Self {
-        DataType::Str
-    }
-}
 
 impl std::fmt::Display for DataType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -58,8 +55,10 @@ impl std::fmt::Display for DataType {
 
 /// Parameter type definition
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ParameterType {
     /// Unique identifier for this parameter type
+    #[schemars(regex(pattern = r"^[A-Z_][A-Z0-9_]*$"))]
     pub id: String,
     /// Description of what this parameter represents
     #[serde(default)]
@@ -82,6 +81,7 @@ pub struct ParameterType {
 
 /// Validation rules for parameter values
 #[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ParameterValidation {
     /// Regex pattern for STR type
     #[serde(default)]
@@ -102,8 +102,10 @@ pub struct ParameterValidation {
 
 /// Reference to a parameter type with optional overrides
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ParameterRef {
     /// Parameter identifier in step scope
+    #[schemars(regex(pattern = r"^[A-Z_][A-Z0-9_]*$"))]
     pub id: String,
     /// Reference to parameter_types definition
     #[serde(default)]
@@ -145,6 +147,7 @@ impl Parameter {
 
 /// Parameter value in step data
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ParameterValue {
     /// Parameter identifier
     pub id: String,
@@ -193,6 +196,7 @@ impl ParameterValue {
 
 /// CONTENT type parameter value
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ContentValue {
     /// Relative path or absolute URL to artifact
     pub uri: String,
@@ -235,6 +239,7 @@ impl ContentValue {
 
 /// SET type parameter value (hierarchical coded list)
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct SetValue {
     /// Hierarchical code (dot-separated)
     pub code: String,
@@ -268,7 +273,8 @@ impl SetValue {
 }
 
 /// Range type parameter value [min, max]
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct RangeValue<T> {
     /// Minimum value
     pub min: T,
@@ -2,13 +2,63 @@
 //!
 //! Processes are collections of steps with iterations.
 
+use chrono::{Days, NaiveDate};
 use serde::{Deserialize, Serialize};
 
-use super::{Step, StepDefinition, StepStatus};
+use super::{RangeValue, Step, StepDefinition, StepStatus};
 
 /// Process definition (step-as-process)
 pub type ProcessDefinition = StepDefinition;
 
+/// Iteration time box as an inclusive date range (RANGE_DATE)
+pub type Timebox = RangeValue<NaiveDate>;
+
+/// Longest iteration length in calendar days (ten years)
+pub const MAX_ITERATION_DAYS: i64 = 3650;
+
+impl RangeValue<NaiveDate> {
+    /// Create a time box of `days` calendar days starting at `start`
+    ///
+    /// None if the time box would end beyond the representable dates.
+    pub fn starting(start: NaiveDate, days: i64) -> Option<Self> {
+        let end = start.checked_add_days(Days::new(days.max(1).unsigned_abs() - 1))?;
+        Some(Self::new(start, end))
+    }
+
+    /// Length of the time box in calendar days
+    pub fn days(&self) -> i64 {
+        (self.max - self.min).num_days() + 1
+    }
+
+    /// The time box that follows this one, with the given or the same length
+    ///
+    /// None if it would end beyond the representable dates.
+    pub fn following(&self, days: Option<i64>) -> Option<Self> {
+        let start = self.max.checked_add_days(Days::new(1))?;
+        Self::starting(start, days.unwrap_or_else(|| self.days()))
+    }
+}
+
+/// Parse an iteration length ("10d", "2w") into calendar days
+///
+/// None unless it is a positive length of at most [`MAX_ITERATION_DAYS`].
+pub fn parse_iteration_days(expr: &str) -> Option<i64> {
+    let expr = expr.trim();
+    let unit = expr.chars().last()?;
+    let number: i64 = expr[..expr.len() - unit.len_utf8()].trim().parse().ok()?;
+
+    if number <= 0 {
+        return None;
+    }
+
+    let days = match unit.to_ascii_lowercase() {
+        'd' => number,
+        'w' => number.checked_mul(7)?,
+        _ => return None,
+    };
+    (days <= MAX_ITERATION_DAYS).then_some(days)
+}
+
 /// Runtime process with iterations
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Process {
@@ -200,14 +250,49 @@ mod tests {
 
     #[test]
     fn test_status_counts() {
-        let mut counts = StatusCounts::default();
-        counts.done = 3;
-        counts.wait = 2;
+        let counts = StatusCounts {
+            done: 3,
+            wait: 2,
+            ..Default::default()
+        };
 
         assert_eq!(counts.total(), 5);
         assert_eq!(counts.completion_percentage(), 60.0);
     }
 
+    #[test]
+    fn test_parse_iteration_days() {
+        assert_eq!(parse_iteration_days("10d"), Some(10));
+        assert_eq!(parse_iteration_days("2w"), Some(14));
+        assert_eq!(parse_iteration_days("0d"), None);
+        assert_eq!(parse_iteration_days("3h"), None);
+        assert_eq!(parse_iteration_days("w"), None);
+        assert_eq!(parse_iteration_days("3650d"), Some(MAX_ITERATION_DAYS));
+        assert_eq!(parse_iteration_days("3651d"), None);
+        assert_eq!(parse_iteration_days("9223372036854775807w"), None);
+    }
+
+    #[test]
+    fn test_timebox_following() {
+        let start = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        let timebox = Timebox::starting(start, 14).unwrap();
+        assert_eq!(timebox.max, NaiveDate::from_ymd_opt(2026, 3, 15).unwrap());
+        assert_eq!(timebox.days(), 14);
+        assert!(timebox.contains(&start));
+
+        let next = timebox.following(None).unwrap();
+        assert_eq!(next.min, NaiveDate::from_ymd_opt(2026, 3, 16).unwrap());
+        assert_eq!(next.days(), 14);
+
+        let short = timebox.following(Some(7)).unwrap();
+        assert_eq!(short.max, NaiveDate::from_ymd_opt(2026, 3, 22).unwrap());
+
+        // Nothing follows the last representable date
+        let last = Timebox::new(NaiveDate::MAX, NaiveDate::MAX);
+        assert!(last.following(None).is_none());
+        assert!(Timebox::starting(NaiveDate::MAX, MAX_ITERATION_DAYS).is_none());
+    }
+
     #[test]
     fn test_process_from_definition() {
         let def = ProcessDefinition::new_root();
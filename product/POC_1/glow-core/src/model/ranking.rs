@@ -0,0 +1,214 @@
+//! Ranking configuration
+//!
+//! Declares how recommended next actions are prioritized.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::ClassificationDictionary;
+
+/// Ranking configuration for next action recommendations
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct RankingConfig {
+    /// Classification dimension holding priority values (e.g., "priority")
+    #[serde(default = "default_priority_dimension")]
+    pub priority_dimension: String,
+    /// Explicit priority per classification key (higher ranks first).
+    /// When empty, the order of values in the dimension is used (first = highest).
+    #[serde(default)]
+    pub priorities: HashMap<String, f64>,
+    /// Factor weights
+    #[serde(default)]
+    pub weights: RankingWeights,
+    /// Days in todo after which the age factor is saturated
+    #[serde(default = "default_horizon_days")]
+    pub age_horizon_days: u32,
+    /// Days before the due date at which the due factor starts to grow
+    #[serde(default = "default_horizon_days")]
+    pub due_horizon_days: u32,
+}
+
+/// Weights of individual ranking factors
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct RankingWeights {
+    /// Classification priority weight
+    #[serde(default = "default_priority_weight")]
+    pub priority: f64,
+    /// Critical path position weight
+    #[serde(default = "default_critical_path_weight")]
+    pub critical_path: f64,
+    /// Age in todo weight
+    #[serde(default = "default_age_weight")]
+    pub age: f64,
+    /// Due date proximity weight
+    #[serde(default = "default_due_weight")]
+    pub due: f64,
+    /// Unblocked dependents weight
+    #[serde(default = "default_unblocks_weight")]
+    pub unblocks: f64,
+}
+
+fn default_priority_dimension() -> String {
+    "priority".to_string()
+}
+
+fn default_horizon_days() -> u32 {
+    14
+}
+
+fn default_priority_weight() -> f64 {
+    10.0
+}
+
+fn default_critical_path_weight() -> f64 {
+    5.0
+}
+
+fn default_age_weight() -> f64 {
+    2.0
+}
+
+fn default_due_weight() -> f64 {
+    8.0
+}
+
+fn default_unblocks_weight() -> f64 {
+    3.0
+}
+
+impl Default for RankingWeights {
+    fn default() -> Self {
+        Self {
+            priority: default_priority_weight(),
+            critical_path: default_critical_path_weight(),
+            age: default_age_weight(),
+            due: default_due_weight(),
+            unblocks: default_unblocks_weight(),
+        }
+    }
+}
+
+impl Default for RankingConfig {
+    fn default() -> Self {
+        Self {
+            priority_dimension: default_priority_dimension(),
+            priorities: HashMap::new(),
+            weights: RankingWeights::default(),
+            age_horizon_days: default_horizon_days(),
+            due_horizon_days: default_horizon_days(),
+        }
+    }
+}
+
+impl RankingConfig {
+    /// Resolve the normalized priority (0..=1) of a classification expression
+    ///
+    /// Returns the matched priority key with its normalized value.
+    pub fn priority_of(
+        &self,
+        classification: &str,
+        dictionaries: &[ClassificationDictionary],
+    ) -> Option<(String, f64)> {
+        let dimension = dictionaries
+            .iter()
+            .find(|d| d.id == self.priority_dimension)?;
+
+        let key = super::parse_classification(classification)
+            .into_iter()
+            .find(|part| dimension.values.iter().any(|v| v.key == *part))?;
+
+        let value = if self.priorities.is_empty() {
+            let count = dimension.values.len();
+            let idx = dimension.values.iter().position(|v| v.key == key)?;
+            (count - idx) as f64 / count as f64
+        } else {
+            let max = self.priorities.values().cloned().fold(0.0, f64::max);
+            let raw = self.priorities.get(&key).copied().unwrap_or(0.0);
+            if max > 0.0 {
+                raw / max
+            } else {
+                0.0
+            }
+        };
+
+        Some((key, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ClassificationValue;
+
+    fn value(key: &str) -> ClassificationValue {
+        ClassificationValue {
+            key: key.to_string(),
+            name: None,
+            template: None,
+            default_attributes: None,
+            default_inputs: Vec::new(),
+            default_outputs: Vec::new(),
+            default_scope: Vec::new(),
+        }
+    }
+
+    fn dictionaries() -> Vec<ClassificationDictionary> {
+        vec![
+            ClassificationDictionary {
+                id: "stage".to_string(),
+                name: None,
+                values: vec![value("Feature"), value("Task")],
+            },
+            ClassificationDictionary {
+                id: "priority".to_string(),
+                name: None,
+                values: vec![value("Must"), value("Should"), value("Could")],
+            },
+        ]
+    }
+
+    #[test]
+    fn test_priority_from_dimension_order() {
+        let config = RankingConfig::default();
+        let dicts = dictionaries();
+
+        let (key, must) = config.priority_of("Task,Must", &dicts).unwrap();
+        assert_eq!(key, "Must");
+        assert_eq!(must, 1.0);
+
+        let (_, could) = config.priority_of("Task,Could", &dicts).unwrap();
+        assert!((could - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_priority_explicit_map() {
+        let mut config = RankingConfig::default();
+        config.priorities.insert("Must".to_string(), 4.0);
+        config.priorities.insert("Should".to_string(), 2.0);
+        let dicts = dictionaries();
+
+        assert_eq!(config.priority_of("Must", &dicts).unwrap().1, 1.0);
+        assert_eq!(config.priority_of("Should", &dicts).unwrap().1, 0.5);
+        assert_eq!(config.priority_of("Could", &dicts).unwrap().1, 0.0);
+    }
+
+    #[test]
+    fn test_priority_missing() {
+        let config = RankingConfig::default();
+        let dicts = dictionaries();
+
+        assert!(config.priority_of("Feature", &dicts).is_none());
+        assert!(config.priority_of("Must", &[]).is_none());
+    }
+
+    #[test]
+    fn test_ranking_config_defaults_from_yaml() {
+        let config: RankingConfig = serde_yaml::from_str("weights:\n  age: 0\n").unwrap();
+        assert_eq!(config.priority_dimension, "priority");
+        assert_eq!(config.weights.age, 0.0);
+        assert_eq!(config.weights.priority, 10.0);
+    }
+}
@@ -0,0 +1,121 @@
+//! Staleness configuration
+//!
+//! Declares after how many days steps are considered stale.
+
+use serde::{Deserialize, Serialize};
+
+use super::match_classification;
+
+/// Default days without activity before an in-progress step is stale
+pub const DEFAULT_IN_PROGRESS_DAYS: u32 = 7;
+
+/// Default days waiting on uninitialized steps before a step is stale
+pub const DEFAULT_WAIT_DAYS: u32 = 7;
+
+/// Default days in todo before an unclaimed step is stale
+pub const DEFAULT_TODO_DAYS: u32 = 14;
+
+/// Staleness thresholds in days (unset values fall back to the defaults)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+pub struct StalenessThresholds {
+    /// Days without activity before an in-progress step is stale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub in_progress_days: Option<u32>,
+    /// Days waiting on uninitialized steps before a step is stale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wait_days: Option<u32>,
+    /// Days in todo before an unclaimed step is stale
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub todo_days: Option<u32>,
+}
+
+/// Thresholds for steps matching a classification pattern
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct ClassificationThresholds {
+    /// Classification pattern (e.g., "Bug,*")
+    pub classification: String,
+    /// Thresholds for matching steps
+    #[serde(flatten)]
+    pub thresholds: StalenessThresholds,
+}
+
+/// Staleness configuration
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct StalenessConfig {
+    /// Project-wide thresholds
+    #[serde(flatten)]
+    pub thresholds: StalenessThresholds,
+    /// Thresholds per classification pattern (first match wins)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub classifications: Vec<ClassificationThresholds>,
+}
+
+/// Resolved staleness thresholds in days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedThresholds {
+    pub in_progress_days: u32,
+    pub wait_days: u32,
+    pub todo_days: u32,
+}
+
+impl StalenessConfig {
+    /// Resolve the thresholds for a step classification
+    pub fn thresholds_for(&self, classification: Option<&str>) -> ResolvedThresholds {
+        let specific = classification.and_then(|c| {
+            self.classifications
+                .iter()
+                .find(|t| match_classification(c, &t.classification))
+                .map(|t| &t.thresholds)
+        });
+
+        let pick = |get: fn(&StalenessThresholds) -> Option<u32>, default: u32| {
+            specific
+                .and_then(get)
+                .or_else(|| get(&self.thresholds))
+                .unwrap_or(default)
+        };
+
+        ResolvedThresholds {
+            in_progress_days: pick(|t| t.in_progress_days, DEFAULT_IN_PROGRESS_DAYS),
+            wait_days: pick(|t| t.wait_days, DEFAULT_WAIT_DAYS),
+            todo_days: pick(|t| t.todo_days, DEFAULT_TODO_DAYS),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thresholds_defaults() {
+        let config = StalenessConfig::default();
+        let resolved = config.thresholds_for(Some("Task"));
+        assert_eq!(resolved.in_progress_days, DEFAULT_IN_PROGRESS_DAYS);
+        assert_eq!(resolved.wait_days, DEFAULT_WAIT_DAYS);
+        assert_eq!(resolved.todo_days, DEFAULT_TODO_DAYS);
+    }
+
+    #[test]
+    fn test_thresholds_per_classification() {
+        let config: StalenessConfig = serde_yaml::from_str(
+            r#"
+todo_days: 10
+classifications:
+  - classification: "Bug"
+    in_progress_days: 2
+"#,
+        )
+        .unwrap();
+
+        let bug = config.thresholds_for(Some("Bug"));
+        assert_eq!(bug.in_progress_days, 2);
+        assert_eq!(bug.todo_days, 10);
+
+        let task = config.thresholds_for(Some("Task"));
+        assert_eq!(task.in_progress_days, DEFAULT_IN_PROGRESS_DAYS);
+        assert_eq!(config.thresholds_for(None).todo_days, 10);
+    }
+}
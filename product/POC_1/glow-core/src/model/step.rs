@@ -2,16 +2,20 @@
 //!
 //! Steps represent tasks or sub-processes in the development workflow.
 
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
 
-use super::{LinkDefinition, ParameterRef};
+use super::{Estimate, LinkDefinition, ParameterRef, Timebox};
 
 /// Step status in the workflow
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, schemars::JsonSchema)]
 #[serde(rename_all = "kebab-case")]
+#[derive(Default)]
 pub enum StepStatus {
     /// Waiting for dependencies to complete
+    #[default]
     Wait,
     /// Ready to start (all dependencies done)
     Todo,
@@ -59,11 +63,6 @@ impl StepStatus {
     }
 }
 
-impl Default for StepStatus {
-    fn default() -> Self {
-        StepStatus::Wait
-    }
-}
 
 impl fmt::Display for StepStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -92,8 +91,10 @@ impl std::str::FromStr for StepStatus {
 
 /// Step definition in process configuration
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct StepDefinition {
     /// Step identifier (ROOT for root process)
+    #[schemars(regex(pattern = r"^[A-Za-z][A-Za-z0-9_-]*$"))]
     pub id: String,
     /// Description of step's purpose or destiny
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -107,6 +108,16 @@ pub struct StepDefinition {
     /// Template file name override
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub template: Option<String>,
+    /// Effort estimate (points or duration like "2d")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    /// Due date (YYYY-MM-DD)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// Iteration time box length for processes ("10d", "2w")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(regex(pattern = r"^\s*\d+\s*[dwDW]\s*$"))]
+    pub iteration_length: Option<String>,
     /// Input parameters for this step
     #[serde(default)]
     pub inputs: Vec<ParameterRef>,
@@ -125,6 +136,12 @@ pub struct StepDefinition {
     /// Whether multiple iterations are allowed
     #[serde(default = "default_allow_iterations")]
     pub allow_iterations: bool,
+    /// Reusable process fragment this step is based on
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uses: Option<String>,
+    /// Parameter default overrides for the fragment in `uses`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub with: BTreeMap<String, serde_json::Value>,
 }
 
 fn default_allow_iterations() -> bool {
@@ -140,12 +157,17 @@ impl StepDefinition {
             classification: None,
             expectations: None,
             template: None,
+            estimate: None,
+            due: None,
+            iteration_length: None,
             inputs: Vec::new(),
             outputs: Vec::new(),
             scope: Vec::new(),
             steps: Vec::new(),
             links: Vec::new(),
             allow_iterations: true,
+            uses: None,
+            with: BTreeMap::new(),
         }
     }
 
@@ -168,15 +190,43 @@ impl StepDefinition {
     pub fn all_step_ids(&self) -> Vec<&str> {
         self.steps.iter().map(|s| s.id.as_str()).collect()
     }
+
+    /// Get the links recorded for a sub-step (links it is the source of)
+    pub fn link_refs(&self, step_id: &str) -> Vec<LinkRef> {
+        self.links
+            .iter()
+            .filter(|l| l.from == step_id)
+            .map(|l| LinkRef {
+                step_id: l.to.clone(),
+                link_type: l.r#type.clone(),
+                step_status: None,
+            })
+            .collect()
+    }
+
+    /// Get the version of this definition, a short hash of its content
+    pub fn version(&self) -> String {
+        use sha2::{Digest, Sha256};
+
+        let content = serde_json::to_vec(self).unwrap_or_default();
+        Sha256::digest(&content)
+            .iter()
+            .take(6)
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
 }
 
 /// Step attributes stored in data files
 #[derive(Debug, Clone, Default, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct StepAttributes {
     /// Step identifier within parent scope
+    #[schemars(regex(pattern = r"^[A-Za-z0-9_-]+$"))]
     pub id: String,
     /// Fully qualified identifier (dot-separated path from root)
     #[serde(default)]
+    #[schemars(regex(pattern = r"^[A-Za-z0-9_.-]+$"))]
     pub fqid: Option<String>,
     /// Classification expression
     #[serde(default)]
@@ -190,6 +240,38 @@ pub struct StepAttributes {
     /// Current step status
     #[serde(default)]
     pub status: StepStatus,
+    /// Effort estimate (defaults to the definition's estimate)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimate: Option<Estimate>,
+    /// Due date (defaults to the definition's due date)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>,
+    /// Current iteration number (processes only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schemars(range(min = 1))]
+    pub iteration: Option<u32>,
+    /// Time box of the current iteration (processes only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timebox: Option<Timebox>,
+    /// Version of the definition the current iteration follows (processes only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub definition_version: Option<String>,
+    /// Number of times the step was carried over into a next iteration
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub carried_over: u32,
+    /// When the step entered its current status
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status_since: Option<DateTime<Utc>>,
+    /// When the step was started
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the step was finished
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Unknown keys kept from the data file (lenient step validation)
+    #[serde(flatten)]
+    #[schemars(skip)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl StepAttributes {
@@ -214,12 +296,44 @@ impl StepAttributes {
             purpose: def.purpose.clone(),
             expectations: def.expectations.clone(),
             status: StepStatus::Wait,
+            estimate: def.estimate.clone(),
+            due: def.due,
+            iteration: None,
+            timebox: None,
+            definition_version: None,
+            carried_over: 0,
+            status_since: None,
+            started_at: None,
+            finished_at: None,
+            extra: serde_json::Map::new(),
         }
     }
+
+    /// Change the status, recording when the change happened
+    ///
+    /// Also records the start and finish times of the step.
+    pub fn set_status(&mut self, status: StepStatus, at: DateTime<Utc>) {
+        if self.status == status && self.status_since.is_some() {
+            return;
+        }
+
+        self.status = status;
+        self.status_since = Some(at);
+        match status {
+            StepStatus::InProgress => self.started_at = Some(at),
+            StepStatus::Done => self.finished_at = Some(at),
+            _ => {}
+        }
+    }
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
 }
 
 /// Runtime step instance with data
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct Step {
     /// Step attributes
     pub attr: StepAttributes,
@@ -241,6 +355,16 @@ pub struct Step {
     /// Links to other steps
     #[serde(default)]
     pub links: Vec<LinkRef>,
+    /// Unknown keys kept from the data file (lenient step validation)
+    #[serde(flatten)]
+    #[schemars(skip)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+    /// Content hash of the data file the step was read from (None if not read from a file)
+    ///
+    /// Writing the step fails with a conflict if the file changed since.
+    #[serde(skip)]
+    #[schemars(skip)]
+    pub revision: Option<String>,
 }
 
 impl Step {
@@ -254,6 +378,8 @@ impl Step {
             parent: Vec::new(),
             own_steps: Vec::new(),
             links: Vec::new(),
+            extra: serde_json::Map::new(),
+            revision: None,
         }
     }
 
@@ -286,10 +412,16 @@ impl Step {
     pub fn get_output(&self, id: &str) -> Option<&super::ParameterValue> {
         self.output.iter().find(|p| p.id == id)
     }
+
+    /// Get the estimate weight of this step, if estimated
+    pub fn estimate_weight(&self) -> Option<f64> {
+        self.attr.estimate.as_ref().and_then(|e| e.weight())
+    }
 }
 
 /// Reference to parent step in execution stack
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct ParentRef {
     /// Parent step ID
     pub id: String,
@@ -300,22 +432,27 @@ pub struct ParentRef {
 
 /// Reference to a step with status
 #[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct StepRef {
     /// Step ID
     pub id: String,
     /// Step status
     pub status: StepStatus,
+    /// Iteration the step was finished in, once that iteration is closed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iteration: Option<u32>,
 }
 
 /// Reference to a linked step
-#[derive(Debug, Clone, Serialize, Deserialize, schemars::JsonSchema)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct LinkRef {
     /// Linked step ID
     pub step_id: String,
     /// Type of link (dependency, predecessor, or custom)
     pub link_type: String,
     /// Current status of linked step
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub step_status: Option<StepStatus>,
 }
 
@@ -359,12 +496,17 @@ mod tests {
             classification: None,
             expectations: None,
             template: None,
+            estimate: None,
+            due: None,
+            iteration_length: None,
             inputs: Vec::new(),
             outputs: Vec::new(),
             scope: Vec::new(),
             steps: Vec::new(),
             links: Vec::new(),
             allow_iterations: true,
+            uses: None,
+            with: BTreeMap::new(),
         });
         assert!(def.is_process());
     }
@@ -377,12 +519,17 @@ mod tests {
             classification: None,
             expectations: None,
             template: None,
+            estimate: None,
+            due: None,
+            iteration_length: None,
             inputs: Vec::new(),
             outputs: Vec::new(),
             scope: Vec::new(),
             steps: Vec::new(),
             links: Vec::new(),
             allow_iterations: true,
+            uses: None,
+            with: BTreeMap::new(),
         };
 
         // Child of ROOT
@@ -393,4 +540,27 @@ mod tests {
         let attrs = StepAttributes::from_definition(&def, Some("FEAT-001"));
         assert_eq!(attrs.fqid, Some("FEAT-001.FEAT-001".to_string()));
     }
+
+    #[test]
+    fn test_set_status_records_times() {
+        let mut attrs = StepAttributes::default();
+        let t0 = Utc::now();
+
+        attrs.set_status(StepStatus::Todo, t0);
+        assert_eq!(attrs.status_since, Some(t0));
+        assert!(attrs.started_at.is_none());
+
+        let t1 = t0 + chrono::Duration::hours(1);
+        attrs.set_status(StepStatus::InProgress, t1);
+        assert_eq!(attrs.started_at, Some(t1));
+
+        let t2 = t1 + chrono::Duration::hours(1);
+        attrs.set_status(StepStatus::Done, t2);
+        assert_eq!(attrs.finished_at, Some(t2));
+        assert_eq!(attrs.status_since, Some(t2));
+
+        // Same status keeps the original timestamp
+        attrs.set_status(StepStatus::Done, t2 + chrono::Duration::hours(1));
+        assert_eq!(attrs.status_since, Some(t2));
+    }
 }
@@ -0,0 +1,98 @@
+//! Work-in-progress limits
+//!
+//! Limits the number of steps in progress at the same time.
+
+use serde::{Deserialize, Serialize};
+
+use super::match_classification;
+
+/// Work-in-progress limit
+///
+/// A limit applies to steps matching all of its selectors. A limit without
+/// selectors applies to all task steps in the project.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, schemars::JsonSchema)]
+#[schemars(deny_unknown_fields)]
+pub struct WipLimit {
+    /// Parent process FQID whose direct sub-steps are limited (e.g., "ROOT", "FEAT-001")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub process: Option<String>,
+    /// Classification pattern of limited steps (e.g., "Task,*")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub classification: Option<String>,
+    /// Maximum number of steps in progress
+    pub max: usize,
+}
+
+impl WipLimit {
+    /// Check if the limit applies to a step
+    pub fn applies_to(&self, parent: Option<&str>, classification: Option<&str>, is_task: bool) -> bool {
+        if self.process.is_none() && self.classification.is_none() {
+            return is_task;
+        }
+
+        let process_matches = match &self.process {
+            Some(process) => parent == Some(process.as_str()),
+            None => true,
+        };
+        let classification_matches = match &self.classification {
+            Some(pattern) => classification.is_some_and(|c| match_classification(c, pattern)),
+            None => true,
+        };
+
+        process_matches && classification_matches
+    }
+}
+
+impl std::fmt::Display for WipLimit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let scope = match (&self.process, &self.classification) {
+            (Some(p), Some(c)) => format!("{} steps of {}", c, p),
+            (Some(p), None) => format!("steps of {}", p),
+            (None, Some(c)) => format!("{} steps", c),
+            (None, None) => "tasks".to_string(),
+        };
+        write!(f, "{} (max {})", scope, self.max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limit(process: Option<&str>, classification: Option<&str>) -> WipLimit {
+        WipLimit {
+            process: process.map(String::from),
+            classification: classification.map(String::from),
+            max: 2,
+        }
+    }
+
+    #[test]
+    fn test_process_limit() {
+        let l = limit(Some("FEAT"), None);
+        assert!(l.applies_to(Some("FEAT"), None, true));
+        assert!(l.applies_to(Some("FEAT"), Some("Task"), false));
+        assert!(!l.applies_to(Some("ROOT"), None, true));
+        assert_eq!(l.to_string(), "steps of FEAT (max 2)");
+    }
+
+    #[test]
+    fn test_classification_limit() {
+        let l = limit(None, Some("Task|Bug"));
+        assert!(l.applies_to(Some("FEAT"), Some("Bug"), true));
+        assert!(!l.applies_to(Some("FEAT"), Some("Feature"), true));
+        assert!(!l.applies_to(Some("FEAT"), None, true));
+    }
+
+    #[test]
+    fn test_combined_and_global_limits() {
+        let l = limit(Some("FEAT"), Some("Task"));
+        assert!(l.applies_to(Some("FEAT"), Some("Task"), true));
+        assert!(!l.applies_to(Some("OTHER"), Some("Task"), true));
+
+        let global = limit(None, None);
+        assert!(global.applies_to(Some("FEAT"), None, true));
+        assert!(!global.applies_to(None, None, false));
+        assert_eq!(global.to_string(), "tasks (max 2)");
+    }
+}
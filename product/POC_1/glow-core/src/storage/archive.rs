@@ -0,0 +1,228 @@
+//! Iteration archives
+//!
+//! A completed process iteration is packed, with the data of the sub-steps
+//! finished in it, into a compressed archive in the iteration folder. A
+//! manifest inside the archive lists every file with its checksum; a stub
+//! next to it keeps the checksum of the archive and the summaries of the
+//! archived steps, so status queries still count them.
+
+use std::io::Read;
+use std::path::Component;
+
+use chrono::{DateTime, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GlowError, Result};
+
+use super::index::{content_hash, StepSummary};
+
+/// Archive file in an archived iteration folder
+pub const ARCHIVE_FILE: &str = "archive.tar.gz";
+
+/// Stub file left in an archived iteration folder
+pub const ARCHIVE_STUB: &str = "archived.json";
+
+/// Manifest file inside an archive
+const MANIFEST_FILE: &str = "manifest.json";
+
+/// Files by path relative to the data directory, with their content
+pub type PackedFiles = Vec<(String, Vec<u8>)>;
+
+/// File packed in an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    /// Path relative to the data directory, with `/` separators
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+    /// SHA-256 of the content
+    pub sha256: String,
+}
+
+/// Manifest packed in an archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchiveManifest {
+    /// FQID of the process
+    pub process: String,
+    /// Iteration number
+    pub iteration: u32,
+    /// When the iteration was archived
+    pub archived_at: DateTime<Utc>,
+    /// FQIDs of the archived sub-steps
+    pub steps: Vec<String>,
+    /// Packed files
+    pub files: Vec<ArchivedFile>,
+}
+
+/// Stub left in place of an archived iteration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveStub {
+    /// FQID of the process
+    pub process: String,
+    /// Iteration number
+    pub iteration: u32,
+    /// When the iteration was archived
+    pub archived_at: DateTime<Utc>,
+    /// Archive file name in the iteration folder
+    pub archive: String,
+    /// SHA-256 of the archive file
+    pub sha256: String,
+    /// Number of packed files
+    pub files: usize,
+    /// Summaries of the archived sub-steps, as they were archived
+    pub steps: Vec<StepSummary>,
+}
+
+impl ArchiveStub {
+    /// Get the summary of an archived step
+    pub fn step(&self, fqid: &str) -> Option<&StepSummary> {
+        self.steps.iter().find(|s| s.attr.fqid.as_deref().unwrap_or(&s.attr.id) == fqid)
+    }
+}
+
+/// Pack files, given by path relative to the data directory, into a compressed archive
+///
+/// Returns the archive and its manifest.
+pub fn pack(
+    process: &str,
+    iteration: u32,
+    archived_at: DateTime<Utc>,
+    steps: Vec<String>,
+    files: &[(String, Vec<u8>)],
+) -> Result<(Vec<u8>, ArchiveManifest)> {
+    let manifest = ArchiveManifest {
+        process: process.to_string(),
+        iteration,
+        archived_at,
+        steps,
+        files: files
+            .iter()
+            .map(|(path, content)| ArchivedFile {
+                path: path.clone(),
+                size: content.len() as u64,
+                sha256: content_hash(content),
+            })
+            .collect(),
+    };
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    let manifest_json = serde_json::to_vec_pretty(&manifest)?;
+    append(&mut builder, MANIFEST_FILE, &manifest_json)?;
+    for (path, content) in files {
+        append(&mut builder, path, content)?;
+    }
+    let archive = builder.into_inner()?.finish()?;
+    Ok((archive, manifest))
+}
+
+/// Unpack an archive into its manifest and files, by path relative to the data directory
+pub fn unpack(archive: &[u8]) -> Result<(ArchiveManifest, PackedFiles)> {
+    let mut manifest = None;
+    let mut files = Vec::new();
+    for entry in tar::Archive::new(GzDecoder::new(archive)).entries()? {
+        let mut entry = entry?;
+        let entry_path = entry.path()?;
+        // Files are unpacked into the data directory, never outside it
+        if !entry_path.components().all(|c| matches!(c, Component::Normal(_))) {
+            return Err(GlowError::ValidationError {
+                message: format!("Archive entry {} leaves the data directory", entry_path.display()),
+            });
+        }
+        let path = entry_path.to_string_lossy().replace('\\', "/");
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        if path == MANIFEST_FILE {
+            manifest = Some(serde_json::from_slice(&content)?);
+        } else {
+            files.push((path, content));
+        }
+    }
+
+    let manifest = manifest.ok_or_else(|| GlowError::ValidationError {
+        message: format!("Archive has no {}", MANIFEST_FILE),
+    })?;
+    Ok((manifest, files))
+}
+
+/// Check an archive against its stub and the checksums in its manifest
+///
+/// Returns the problems found; none if the archive is intact.
+pub fn verify(stub: &ArchiveStub, archive: &[u8]) -> Vec<String> {
+    let mut problems = Vec::new();
+    let sha256 = content_hash(archive);
+    if sha256 != stub.sha256 {
+        problems.push(format!("{} checksum is {}, expected {}", stub.archive, sha256, stub.sha256));
+    }
+
+    let (manifest, files) = match unpack(archive) {
+        Ok(unpacked) => unpacked,
+        Err(e) => {
+            problems.push(format!("{} can't be unpacked: {}", stub.archive, e));
+            return problems;
+        }
+    };
+    for expected in &manifest.files {
+        match files.iter().find(|(path, _)| *path == expected.path) {
+            None => problems.push(format!("{} is missing", expected.path)),
+            Some((_, content)) if content_hash(content) != expected.sha256 => {
+                problems.push(format!("{} checksum doesn't match the manifest", expected.path))
+            }
+            Some(_) => {}
+        }
+    }
+    for (path, _) in &files {
+        if !manifest.files.iter().any(|f| f.path == *path) {
+            problems.push(format!("{} is not in the manifest", path));
+        }
+    }
+    problems
+}
+
+/// Append a file to an archive
+fn append<W: std::io::Write>(builder: &mut tar::Builder<W>, path: &str, content: &[u8]) -> Result<()> {
+    let mut header = tar::Header::new_gnu();
+    header.set_size(content.len() as u64);
+    header.set_mode(0o644);
+    header.set_cksum();
+    builder.append_data(&mut header, path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pack_unpack_and_verify() {
+        let files = vec![
+            ("FEAT/REQ.md".to_string(), b"---\nattr:\n  id: REQ\n---\n".to_vec()),
+            ("FEAT/REQ/artifacts/logo.png".to_string(), vec![0x89, 0x50, 0x00, 0xff]),
+        ];
+        let (archive, manifest) = pack("FEAT", 1, Utc::now(), vec!["FEAT.REQ".to_string()], &files).unwrap();
+        assert_eq!(manifest.files[1].size, 4);
+
+        let (unpacked_manifest, unpacked) = unpack(&archive).unwrap();
+        assert_eq!(unpacked_manifest, manifest);
+        assert_eq!(unpacked, files);
+
+        let mut stub = ArchiveStub {
+            process: "FEAT".to_string(),
+            iteration: 1,
+            archived_at: manifest.archived_at,
+            archive: ARCHIVE_FILE.to_string(),
+            sha256: content_hash(&archive),
+            files: files.len(),
+            steps: Vec::new(),
+        };
+        assert!(verify(&stub, &archive).is_empty());
+
+        stub.sha256 = "0".repeat(64);
+        assert_eq!(verify(&stub, &archive).len(), 1);
+        let mut damaged = archive.clone();
+        damaged.truncate(archive.len() / 2);
+        assert!(verify(&stub, &damaged).len() >= 2);
+    }
+}
@@ -0,0 +1,227 @@
+//! Atomic writes and the project lock
+//!
+//! Data files are written aside and renamed into place, so readers and
+//! crashes never leave a half-written file. The project lock keeps glow
+//! processes working on the same data folder (e.g. the CLI and the MCP
+//! server) from interleaving their operations.
+
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::error::{GlowError, Result};
+
+/// Lock file in the data directory
+pub const LOCK_FILE: &str = ".lock";
+
+/// How long to wait for another process to release the project lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often to check whether the project lock was released
+const LOCK_RETRY: Duration = Duration::from_millis(20);
+
+/// Write a file by writing a temporary file next to it and renaming it
+///
+/// Creates the parent directory if needed. Readers see either the old or the
+/// new content, never a mix.
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let write_error = |source| GlowError::FileWriteError {
+        path: path.to_path_buf(),
+        source,
+    };
+    let dir = path.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir).map_err(write_error)?;
+
+    // Unique per process and call, so concurrent writers never share a temporary file
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("file");
+    let temp = dir.join(format!(
+        ".{}.{}.{}.tmp",
+        name,
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let result = File::create(&temp)
+        .and_then(|mut file| {
+            file.write_all(content)?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result.map_err(write_error)
+}
+
+/// Advisory lock on a project's data folder, shared by readers and exclusive to writers
+///
+/// The lock is reentrant within a process: nested acquisitions only count,
+/// and the file lock is released with the outermost one. A shared lock is
+/// not upgraded: changes take the exclusive lock from the start. The
+/// operating system releases it when the process exits, so a crash never
+/// leaves the project locked.
+pub struct ProjectLock {
+    path: PathBuf,
+    state: Mutex<LockState>,
+}
+
+/// Lock file held, how, and number of nested acquisitions
+#[derive(Default)]
+struct LockState {
+    file: Option<File>,
+    exclusive: bool,
+    depth: usize,
+}
+
+impl ProjectLock {
+    /// Create the lock of a data directory, without acquiring it
+    pub fn new(data_dir: &Path) -> Self {
+        Self {
+            path: data_dir.join(LOCK_FILE),
+            state: Mutex::new(LockState::default()),
+        }
+    }
+
+    /// Acquire the lock, waiting for other processes to release it
+    ///
+    /// Only the exclusive lock creates the data directory and lock file; a
+    /// shared lock on a project without them holds nothing, as there is no
+    /// data to read yet.
+    pub fn acquire(&self, exclusive: bool) -> Result<()> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if state.depth > 0 {
+            if exclusive && !state.exclusive {
+                return Err(GlowError::LockNotExclusive {
+                    path: self.path.clone(),
+                });
+            }
+            state.depth += 1;
+            return Ok(());
+        }
+
+        let open_error = |source| GlowError::FileWriteError {
+            path: self.path.clone(),
+            source,
+        };
+        let opened = if exclusive {
+            if let Some(dir) = self.path.parent() {
+                std::fs::create_dir_all(dir).map_err(open_error)?;
+            }
+            OpenOptions::new()
+                .create(true)
+                .truncate(false)
+                .write(true)
+                .open(&self.path)
+        } else {
+            File::open(&self.path)
+        };
+        let file = match opened {
+            Ok(file) => file,
+            Err(e) if !exclusive && e.kind() == std::io::ErrorKind::NotFound => {
+                state.exclusive = false;
+                state.depth = 1;
+                return Ok(());
+            }
+            Err(e) => return Err(open_error(e)),
+        };
+
+        let deadline = Instant::now() + LOCK_TIMEOUT;
+        loop {
+            let attempt = if exclusive {
+                file.try_lock()
+            } else {
+                file.try_lock_shared()
+            };
+            match attempt {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) if Instant::now() < deadline => {
+                    std::thread::sleep(LOCK_RETRY)
+                }
+                Err(TryLockError::WouldBlock) => {
+                    return Err(GlowError::ProjectLocked {
+                        path: self.path.clone(),
+                    })
+                }
+                Err(TryLockError::Error(e)) => return Err(open_error(e)),
+            }
+        }
+
+        state.file = Some(file);
+        state.exclusive = exclusive;
+        state.depth = 1;
+        Ok(())
+    }
+
+    /// Release one acquisition of the lock
+    pub fn release(&self) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.depth = state.depth.saturating_sub(1);
+        if state.depth == 0 {
+            if let Some(file) = state.file.take() {
+                let _ = file.unlock();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_write_atomic_replaces_content() {
+        let temp = tempdir().unwrap();
+        let path = temp.path().join("nested").join("A.md");
+
+        write_atomic(&path, b"first").unwrap();
+        write_atomic(&path, b"second").unwrap();
+
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "second");
+        // No temporary files are left behind
+        assert_eq!(std::fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_project_lock_excludes_other_holders() {
+        let temp = tempdir().unwrap();
+        let lock = ProjectLock::new(temp.path());
+        lock.acquire(true).unwrap();
+        // Reentrant within the same lock
+        lock.acquire(false).unwrap();
+
+        // Another open file description, as in another process, can't take it
+        let other = File::open(temp.path().join(LOCK_FILE)).unwrap();
+        assert!(matches!(other.try_lock_shared(), Err(TryLockError::WouldBlock)));
+
+        lock.release();
+        assert!(matches!(other.try_lock_shared(), Err(TryLockError::WouldBlock)));
+        lock.release();
+        other.try_lock_shared().unwrap();
+    }
+
+    #[test]
+    fn test_project_lock_is_not_upgraded() {
+        let temp = tempdir().unwrap();
+        let data_dir = temp.path().join("glow");
+
+        // Reading a project without data changes nothing on disk
+        let lock = ProjectLock::new(&data_dir);
+        lock.acquire(false).unwrap();
+        assert!(!data_dir.exists());
+        assert!(matches!(lock.acquire(true), Err(GlowError::LockNotExclusive { .. })));
+        lock.release();
+
+        lock.acquire(true).unwrap();
+        assert!(data_dir.join(LOCK_FILE).exists());
+        lock.release();
+        lock.acquire(false).unwrap();
+        assert!(matches!(lock.acquire(true), Err(GlowError::LockNotExclusive { .. })));
+        lock.release();
+    }
+}
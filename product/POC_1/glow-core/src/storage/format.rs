@@ -0,0 +1,230 @@
+//! Step data file formats
+//!
+//! Step data files are Markdown with YAML frontmatter, or plain YAML, JSON
+//! or TOML files; the extension tells which. Only Markdown files have a body.
+
+use serde_yaml::{Mapping, Value};
+
+use crate::config::StepFormat;
+use crate::error::{GlowError, Result};
+use crate::model::Step;
+
+use super::frontmatter::{split_frontmatter, FRONTMATTER_DELIMITER};
+
+/// Side file in a step's folder keeping the Markdown body of a step file in another format
+pub const NOTES_FILE: &str = "notes.md";
+
+/// Outcome of converting step data files to another format
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Conversion {
+    /// Number of step data files converted
+    pub converted: usize,
+    /// Number of Markdown bodies moved to or from `notes.md` side files
+    pub notes: usize,
+}
+
+/// Parse a step data file into its data and Markdown body
+///
+/// The data is null for a Markdown file without frontmatter, whose whole
+/// content is the body.
+pub fn parse_step_file(format: StepFormat, content: &str) -> Result<(Value, String)> {
+    let data = match format {
+        StepFormat::Markdown => {
+            return match split_frontmatter(content)? {
+                Some((_, yaml, body)) => Ok((serde_yaml::from_str(yaml)?, body.to_string())),
+                None => Ok((Value::Null, content.to_string())),
+            };
+        }
+        StepFormat::Yaml => serde_yaml::from_str(content)?,
+        StepFormat::Json => serde_json::from_str(content)?,
+        StepFormat::Toml => toml::from_str(content).map_err(|e| GlowError::TomlParseError {
+            message: e.to_string(),
+        })?,
+    };
+    Ok((data, String::new()))
+}
+
+/// Render step data, and a body for Markdown, as a step data file
+pub fn render_step_file(format: StepFormat, data: &Value, body: &str) -> Result<String> {
+    match format {
+        // Rendered from the YAML value directly, which keeps the key order
+        StepFormat::Markdown => Ok(format!(
+            "{}\n{}{}\n{}",
+            FRONTMATTER_DELIMITER,
+            serde_yaml::to_string(data)?,
+            FRONTMATTER_DELIMITER,
+            body
+        )),
+        StepFormat::Yaml => Ok(serde_yaml::to_string(data)?),
+        StepFormat::Json => Ok(format!("{}\n", serde_json::to_string_pretty(data)?)),
+        // TOML has no null; a missing key reads the same
+        StepFormat::Toml => toml::to_string(&without_nulls(data.clone())).map_err(|e| {
+            GlowError::TomlParseError {
+                message: e.to_string(),
+            }
+        }),
+    }
+}
+
+/// Render a step as a new step data file
+pub fn render_new_step(format: StepFormat, step: &Step, body: &str) -> Result<String> {
+    render_step_file(format, &serde_yaml::to_value(step)?, body)
+}
+
+/// Render a step into the existing content of its data file
+///
+/// Keeps the body, the order of the keys and any keys the model doesn't
+/// know. Keys the model read from the file but the step no longer has (e.g.
+/// a cleared due date) are removed. Comments are not kept.
+pub fn render_step_into(format: StepFormat, step: &Step, existing: &str) -> Result<String> {
+    let (written, body) = parse_step_file(format, existing)?;
+    if written.is_null() {
+        // No frontmatter yet: the whole file is the body
+        return render_new_step(format, step, &body);
+    }
+
+    // What the model reads from the file tells removed keys from unknown ones
+    let read = serde_json::to_value(&written)
+        .ok()
+        .and_then(|value| serde_json::from_value::<Step>(value).ok())
+        .map(|read| serde_yaml::to_value(&read))
+        .transpose()?;
+    let merged = merge_step_data(written, read.as_ref(), serde_yaml::to_value(step)?);
+    render_step_file(format, &merged, &body)
+}
+
+/// Get the body of a new Markdown step data file
+pub fn default_body(id: &str, purpose: Option<&str>) -> String {
+    format!("# {}\n\n{}\n", id, purpose.unwrap_or("TODO: Add description"))
+}
+
+/// Merge new step data into the data written in a file
+///
+/// `read` is what the model read from the written data (None if it
+/// couldn't), so keys missing from it are unknown and kept. List items are
+/// matched by `id`, or by position when they have none.
+fn merge_step_data(written: Value, read: Option<&Value>, new: Value) -> Value {
+    match (written, new) {
+        (Value::Mapping(written), Value::Mapping(mut new)) => {
+            let read = read.and_then(Value::as_mapping);
+            let mut merged = Mapping::new();
+            for (key, value) in written {
+                if let Some(new_value) = new.remove(&key) {
+                    let read_value = read.and_then(|r| r.get(&key));
+                    merged.insert(key, merge_step_data(value, read_value, new_value));
+                } else if read.is_none_or(|r| !r.contains_key(&key)) {
+                    merged.insert(key, value);
+                }
+            }
+            // Keys new to the file follow in the model's order
+            merged.extend(new);
+            Value::Mapping(merged)
+        }
+        (Value::Sequence(written), Value::Sequence(new)) => {
+            let read = read.and_then(Value::as_sequence);
+            let id = |item: &Value| item.get("id").cloned();
+            let items = new
+                .into_iter()
+                .enumerate()
+                .map(|(i, new_item)| {
+                    let matching = match id(&new_item) {
+                        Some(new_id) => written.iter().position(|w| id(w) == Some(new_id.clone())),
+                        None => Some(i).filter(|&i| written.get(i).is_some_and(|w| id(w).is_none())),
+                    };
+                    match matching {
+                        Some(j) => merge_step_data(
+                            written[j].clone(),
+                            read.and_then(|r| r.get(j)),
+                            new_item,
+                        ),
+                        None => new_item,
+                    }
+                })
+                .collect();
+            Value::Sequence(items)
+        }
+        (_, new) => new,
+    }
+}
+
+/// Drop the null values of mappings
+fn without_nulls(value: Value) -> Value {
+    match value {
+        Value::Mapping(mapping) => Value::Mapping(
+            mapping
+                .into_iter()
+                .filter(|(_, v)| !v.is_null())
+                .map(|(k, v)| (k, without_nulls(v)))
+                .collect(),
+        ),
+        Value::Sequence(items) => Value::Sequence(items.into_iter().map(without_nulls).collect()),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ParameterValue, StepStatus};
+
+    fn read_step(format: StepFormat, content: &str) -> Step {
+        let (data, _) = parse_step_file(format, content).unwrap();
+        serde_json::from_value(serde_json::to_value(data).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_render_step_into_keeps_order_unknown_keys_and_body() {
+        let existing = r#"---
+notes: keep me
+attr:
+  status: todo
+  id: A
+  due: 2026-03-01
+  reviewer: sam
+output:
+  - value: "42"
+    id: RESULT
+    source: manual
+---
+# A
+
+Written by hand.
+"#;
+        let mut step = read_step(StepFormat::Markdown, existing);
+        step.attr.status = StepStatus::Done;
+        step.attr.due = None;
+
+        let rendered = render_step_into(StepFormat::Markdown, &step, existing).unwrap();
+        let position = |needle: &str| rendered.find(needle).unwrap_or_else(|| panic!("{} missing", needle));
+
+        // File order first, unknown keys kept, cleared fields removed
+        assert!(position("notes: keep me") < position("attr:"));
+        assert!(position("status: done") < position("id: A"));
+        assert!(position("id: A") < position("reviewer: sam"));
+        assert!(!rendered.contains("due:"));
+        assert!(position("value: '42'") < position("id: RESULT"));
+        assert!(position("id: RESULT") < position("source: manual"));
+        assert!(rendered.ends_with("---\n# A\n\nWritten by hand.\n"));
+    }
+
+    #[test]
+    fn test_formats_round_trip() {
+        let mut step = read_step(
+            StepFormat::Yaml,
+            "attr:\n  id: A\n  status: todo\ninput:\n  - id: EMPTY\n",
+        );
+        step.output.push(ParameterValue::new("RESULT", serde_json::json!(42)));
+
+        for format in StepFormat::ALL {
+            let rendered = render_new_step(format, &step, "").unwrap();
+            let read = read_step(format, &rendered);
+            assert_eq!(read.attr.id, "A", "{}", format.as_str());
+            assert_eq!(read.input[0].value, None, "{}", format.as_str());
+            assert_eq!(read.output[0].value, Some(serde_json::json!(42)), "{}", format.as_str());
+
+            // Writing again keeps the file's key order
+            let again = render_step_into(format, &read, &rendered).unwrap();
+            assert_eq!(again, rendered, "{}", format.as_str());
+        }
+    }
+}
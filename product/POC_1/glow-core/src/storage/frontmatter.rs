@@ -3,30 +3,46 @@
 //! Handles YAML frontmatter in Markdown files.
 
 use crate::error::{GlowError, Result};
-use crate::model::Step;
 
 /// Frontmatter delimiter
-const FRONTMATTER_DELIMITER: &str = "---";
+pub(crate) const FRONTMATTER_DELIMITER: &str = "---";
 
 /// Parse frontmatter from a markdown file content
 pub fn parse_frontmatter(content: &str) -> Result<(serde_json::Value, String)> {
+    match split_frontmatter(content)? {
+        Some((_, yaml_content, remaining)) => {
+            let frontmatter: serde_json::Value = serde_yaml::from_str(yaml_content)?;
+            Ok((frontmatter, remaining.to_string()))
+        }
+        None => Ok((serde_json::Value::Null, content.to_string())),
+    }
+}
+
+/// Split a markdown file content into frontmatter YAML and body
+///
+/// Returns the line the YAML starts on (0-based), the YAML and the body, or
+/// None if the content has no frontmatter.
+pub(crate) fn split_frontmatter(content: &str) -> Result<Option<(usize, &str, &str)>> {
     let trimmed = content.trim_start();
 
     if !trimmed.starts_with(FRONTMATTER_DELIMITER) {
-        return Ok((serde_json::Value::Null, content.to_string()));
+        return Ok(None);
     }
 
     // Find the end of frontmatter
     let after_first_delimiter = &trimmed[FRONTMATTER_DELIMITER.len()..];
-    
+
     // Skip the newline after the first delimiter
-    let after_newline = if after_first_delimiter.starts_with('\n') {
-        &after_first_delimiter[1..]
-    } else if after_first_delimiter.starts_with("\r\n") {
-        &after_first_delimiter[2..]
+    let after_newline = if let Some(rest) = after_first_delimiter.strip_prefix('\n') {
+        rest
+    } else if let Some(rest) = after_first_delimiter.strip_prefix("\r\n") {
+        rest
     } else {
         after_first_delimiter
     };
+    let first_line = content[..content.len() - after_newline.len()]
+        .matches('\n')
+        .count();
 
     // Find the closing delimiter
     if let Some(end_pos) = after_newline.find(&format!("\n{}", FRONTMATTER_DELIMITER)) {
@@ -35,10 +51,10 @@ pub fn parse_frontmatter(content: &str) -> Result<(serde_json::Value, String)> {
         let remaining = if remaining_start < after_newline.len() {
             // Skip newline after closing delimiter
             let rest = &after_newline[remaining_start..];
-            if rest.starts_with('\n') {
-                &rest[1..]
-            } else if rest.starts_with("\r\n") {
-                &rest[2..]
+            if let Some(stripped) = rest.strip_prefix('\n') {
+                stripped
+            } else if let Some(stripped) = rest.strip_prefix("\r\n") {
+                stripped
             } else {
                 rest
             }
@@ -46,8 +62,7 @@ pub fn parse_frontmatter(content: &str) -> Result<(serde_json::Value, String)> {
             ""
         };
 
-        let frontmatter: serde_json::Value = serde_yaml::from_str(yaml_content)?;
-        Ok((frontmatter, remaining.to_string()))
+        Ok(Some((first_line, yaml_content, remaining)))
     } else {
         Err(GlowError::InvalidFrontmatter {
             path: std::path::PathBuf::from("<content>"),
@@ -72,26 +87,6 @@ pub fn render_frontmatter(frontmatter: &serde_json::Value, content: &str) -> Res
     ))
 }
 
-/// Parse a step from frontmatter
-pub fn parse_step_frontmatter(content: &str) -> Result<(Step, String)> {
-    let (frontmatter, body) = parse_frontmatter(content)?;
-    
-    if frontmatter.is_null() {
-        return Err(GlowError::InvalidFrontmatter {
-            path: std::path::PathBuf::from("<content>"),
-        });
-    }
-
-    let step: Step = serde_json::from_value(frontmatter)?;
-    Ok((step, body))
-}
-
-/// Render a step to markdown with frontmatter
-pub fn render_step_frontmatter(step: &Step, content: &str) -> Result<String> {
-    let frontmatter = serde_json::to_value(step)?;
-    render_frontmatter(&frontmatter, content)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
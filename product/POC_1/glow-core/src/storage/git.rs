@@ -0,0 +1,136 @@
+//! Git integration
+//!
+//! Registers the step file merge driver (`glow merge-driver`) for the data
+//! folder in `.gitattributes`, and the journal as a union merge, so branches
+//! changing different steps merge without conflicts.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::config::StepFormat;
+use crate::error::{GlowError, Result};
+
+use super::format::NOTES_FILE;
+use super::step_files::JOURNAL_FILE;
+
+/// Name of the merge driver in `.gitattributes` and the git config
+pub const MERGE_DRIVER: &str = "glow";
+
+/// Command git runs to merge a step file: base, ours, theirs and the file's path
+const MERGE_DRIVER_COMMAND: &str = "glow merge-driver %O %A %B %P";
+
+/// Outcome of installing the git integration
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitInstall {
+    /// The `.gitattributes` file
+    pub attributes_path: PathBuf,
+    /// Lines added to it (empty if it was already set up)
+    pub added: Vec<String>,
+}
+
+/// Files in the data folder with a step file extension that aren't step data files
+///
+/// Later `.gitattributes` lines win, so these take the merge driver back off.
+const OTHER_FILES: [&str; 6] = [
+    "description.md",
+    "summary.md",
+    NOTES_FILE,
+    "artifacts/**",
+    "iteration_*/**",
+    ".*",
+];
+
+/// Get the `.gitattributes` lines for a data folder relative to the project root
+pub fn attribute_lines(data_folder: &str) -> Vec<String> {
+    let folder = match data_folder.trim_end_matches('/') {
+        "" => String::new(),
+        folder => format!("{}/", folder),
+    };
+    StepFormat::ALL
+        .into_iter()
+        .map(|format| format!("{}**/*.{} merge={}", folder, format.extension(), MERGE_DRIVER))
+        .chain(OTHER_FILES.into_iter().map(|pattern| format!("{}**/{} !merge", folder, pattern)))
+        .chain(std::iter::once(format!("{}{} merge=union", folder, JOURNAL_FILE)))
+        .collect()
+}
+
+/// Register the merge driver for a project's data folder
+///
+/// Adds the missing lines to the `.gitattributes` of the project root and
+/// defines the driver in the local git config of the repository.
+pub fn install(project_root: &Path, data_folder: &str) -> Result<GitInstall> {
+    let attributes_path = project_root.join(".gitattributes");
+    let existing = match std::fs::read_to_string(&attributes_path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => {
+            return Err(GlowError::FileReadError {
+                path: attributes_path,
+                source: e,
+            })
+        }
+    };
+
+    let added: Vec<String> = attribute_lines(data_folder)
+        .into_iter()
+        .filter(|line| !existing.lines().any(|l| l.trim() == line))
+        .collect();
+    if !added.is_empty() {
+        let mut content = existing;
+        if !content.is_empty() && !content.ends_with('\n') {
+            content.push('\n');
+        }
+        content.push_str("# glow step files\n");
+        for line in &added {
+            content.push_str(line);
+            content.push('\n');
+        }
+        std::fs::write(&attributes_path, content).map_err(|e| GlowError::FileWriteError {
+            path: attributes_path.clone(),
+            source: e,
+        })?;
+    }
+
+    let driver = format!("merge.{}", MERGE_DRIVER);
+    git_config(project_root, &format!("{}.name", driver), "glow step file merge")?;
+    git_config(project_root, &format!("{}.driver", driver), MERGE_DRIVER_COMMAND)?;
+
+    Ok(GitInstall {
+        attributes_path,
+        added,
+    })
+}
+
+/// Set a key of the local git config of the repository containing a directory
+fn git_config(dir: &Path, key: &str, value: &str) -> Result<()> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(["config", "--local", key, value])
+        .output()
+        .map_err(|e| GlowError::IoError {
+            message: format!("Failed to run git: {}", e),
+            source: e,
+        })?;
+    if output.status.success() {
+        return Ok(());
+    }
+    Err(GlowError::GitError {
+        message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_attribute_lines() {
+        let lines = attribute_lines("glow/");
+        assert_eq!(lines[0], "glow/**/*.md merge=glow");
+        assert!(lines.contains(&"glow/**/description.md !merge".to_string()));
+        assert!(lines.contains(&"glow/**/artifacts/** !merge".to_string()));
+        assert!(lines.contains(&"glow/**/iteration_*/** !merge".to_string()));
+        assert_eq!(lines.last().unwrap(), "glow/journal.jsonl merge=union");
+        assert_eq!(lines.len(), StepFormat::ALL.len() + OTHER_FILES.len() + 1);
+    }
+}
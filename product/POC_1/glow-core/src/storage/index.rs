@@ -0,0 +1,302 @@
+//! Status index
+//!
+//! Caches what status queries need from each step data file, keyed by the
+//! file's modification time and content hash, so large projects don't
+//! re-read and re-check every step file.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GlowError, Result};
+use crate::model::{LinkRef, Step, StepAttributes, StepRef, StepStatus};
+
+use super::atomic::write_atomic;
+
+/// Index file in the data directory
+pub const INDEX_FILE: &str = ".index.json";
+
+/// Version of the index file format; other versions are rebuilt
+const INDEX_VERSION: u32 = 1;
+
+/// What status queries need from a step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepSummary {
+    /// Step attributes
+    pub attr: StepAttributes,
+    /// Sub-steps for step-as-process
+    #[serde(default)]
+    pub own_steps: Vec<StepRef>,
+    /// Links to other steps
+    #[serde(default)]
+    pub links: Vec<LinkRef>,
+    /// IDs of input parameters with a value
+    #[serde(default)]
+    pub inputs: Vec<String>,
+    /// IDs of output parameters with a value
+    #[serde(default)]
+    pub outputs: Vec<String>,
+}
+
+impl StepSummary {
+    /// Summarize a step
+    pub fn of(step: &Step) -> Self {
+        let with_value = |params: &[crate::model::ParameterValue]| {
+            params
+                .iter()
+                .filter(|p| p.value.is_some())
+                .map(|p| p.id.clone())
+                .collect()
+        };
+
+        Self {
+            attr: step.attr.clone(),
+            own_steps: step.own_steps.clone(),
+            links: step.links.clone(),
+            inputs: with_value(&step.input),
+            outputs: with_value(&step.output),
+        }
+    }
+
+    /// Rebuild the step the summary was taken from, without its parameter values
+    pub fn to_step(&self) -> Step {
+        Step {
+            attr: self.attr.clone(),
+            input: Vec::new(),
+            scope: Vec::new(),
+            output: Vec::new(),
+            parent: Vec::new(),
+            own_steps: self.own_steps.clone(),
+            links: self.links.clone(),
+            extra: serde_json::Map::new(),
+            revision: None,
+        }
+    }
+
+    /// Get the current status
+    pub fn status(&self) -> StepStatus {
+        self.attr.status
+    }
+
+    /// Check if an input parameter has a value
+    pub fn has_input(&self, id: &str) -> bool {
+        self.inputs.iter().any(|i| i == id)
+    }
+
+    /// Check if an output parameter has a value
+    pub fn has_output(&self, id: &str) -> bool {
+        self.outputs.iter().any(|o| o == id)
+    }
+}
+
+/// Modification time and size of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStamp {
+    /// Modification time in nanoseconds since the Unix epoch
+    modified: u64,
+    /// Size in bytes
+    len: u64,
+}
+
+impl FileStamp {
+    /// Get the stamp of a file (None if it doesn't exist)
+    pub fn of(path: &Path) -> Option<Self> {
+        let metadata = std::fs::metadata(path).ok()?;
+        if !metadata.is_file() {
+            return None;
+        }
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|m| m.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_nanos() as u64);
+        Some(Self {
+            modified,
+            len: metadata.len(),
+        })
+    }
+}
+
+/// Get the SHA-256 of some content as hex, e.g. a step file's content
+pub fn content_hash(content: impl AsRef<[u8]>) -> String {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(content.as_ref())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Indexed step file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    stamp: FileStamp,
+    hash: String,
+    summary: StepSummary,
+}
+
+/// Index file content
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexFile {
+    version: u32,
+    checker: String,
+    entries: BTreeMap<String, IndexEntry>,
+}
+
+/// Step summaries by FQID, persisted in the data directory
+#[derive(Debug)]
+pub struct StepIndex {
+    path: PathBuf,
+    checker: String,
+    entries: BTreeMap<String, IndexEntry>,
+    dirty: bool,
+}
+
+impl StepIndex {
+    /// Load the index of a data directory for steps checked by a checker
+    ///
+    /// A missing, unreadable or outdated index file, or one built with
+    /// another checker fingerprint, starts an empty index.
+    pub fn load(data_dir: &Path, checker: &str) -> Self {
+        let path = data_dir.join(INDEX_FILE);
+        let file = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<IndexFile>(&content).ok())
+            .filter(|file| file.version == INDEX_VERSION && file.checker == checker);
+
+        Self {
+            dirty: file.is_none() && path.exists(),
+            entries: file.map(|f| f.entries).unwrap_or_default(),
+            checker: checker.to_string(),
+            path,
+        }
+    }
+
+    /// Get the number of indexed steps
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Get the summary of a step whose file is unchanged since indexed
+    pub fn lookup(&self, fqid: &str, stamp: FileStamp) -> Option<&StepSummary> {
+        self.entries
+            .get(fqid)
+            .filter(|entry| entry.stamp == stamp)
+            .map(|entry| &entry.summary)
+    }
+
+    /// Get the summary of a step whose file was touched but has the same content
+    pub fn lookup_content(&mut self, fqid: &str, stamp: FileStamp, hash: &str) -> Option<StepSummary> {
+        let entry = self.entries.get_mut(fqid).filter(|entry| entry.hash == hash)?;
+        entry.stamp = stamp;
+        self.dirty = true;
+        Some(entry.summary.clone())
+    }
+
+    /// Index a step
+    pub fn insert(&mut self, fqid: &str, stamp: FileStamp, hash: String, summary: StepSummary) {
+        self.entries
+            .insert(fqid.to_string(), IndexEntry { stamp, hash, summary });
+        self.dirty = true;
+    }
+
+    /// Drop a step from the index
+    pub fn remove(&mut self, fqid: &str) {
+        if self.entries.remove(fqid).is_some() {
+            self.dirty = true;
+        }
+    }
+
+    /// Drop every step from the index
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.dirty = true;
+    }
+
+    /// Write the index file if it changed
+    pub fn save(&mut self) -> Result<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        if !self.path.parent().is_some_and(|dir| dir.exists()) {
+            return Ok(());
+        }
+
+        let file = IndexFile {
+            version: INDEX_VERSION,
+            checker: self.checker.clone(),
+            entries: std::mem::take(&mut self.entries),
+        };
+        let content = serde_json::to_string(&file);
+        self.entries = file.entries;
+        let content = content.map_err(|e| GlowError::JsonParseError {
+            message: e.to_string(),
+            source: e,
+        })?;
+
+        // Write aside and rename so readers never see a partial index
+        write_atomic(&self.path, content.as_bytes())?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn summary(status: StepStatus) -> StepSummary {
+        StepSummary {
+            attr: StepAttributes {
+                id: "A".to_string(),
+                status,
+                ..Default::default()
+            },
+            own_steps: Vec::new(),
+            links: Vec::new(),
+            inputs: Vec::new(),
+            outputs: vec!["result".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_index_keyed_by_stamp_and_hash() {
+        let temp = tempdir().unwrap();
+        let file = temp.path().join("A.md");
+        std::fs::write(&file, "content").unwrap();
+        let stamp = FileStamp::of(&file).unwrap();
+
+        let mut index = StepIndex::load(temp.path(), "checker");
+        assert_eq!(index.len(), 0);
+        index.insert("A", stamp, content_hash("content"), summary(StepStatus::Todo));
+        assert_eq!(index.lookup("A", stamp).unwrap().status(), StepStatus::Todo);
+
+        // A touched file with the same content keeps its summary
+        let touched = FileStamp { modified: stamp.modified + 1, ..stamp };
+        assert!(index.lookup("A", touched).is_none());
+        assert!(index.lookup_content("A", touched, &content_hash("other")).is_none());
+        assert!(index.lookup_content("A", touched, &content_hash("content")).is_some());
+        assert!(index.lookup("A", touched).is_some());
+    }
+
+    #[test]
+    fn test_index_persists() {
+        let temp = tempdir().unwrap();
+        let mut index = StepIndex::load(temp.path(), "checker");
+        let stamp = FileStamp { modified: 1, len: 2 };
+        index.insert("A", stamp, "hash".to_string(), summary(StepStatus::Done));
+        index.save().unwrap();
+
+        let loaded = StepIndex::load(temp.path(), "checker");
+        assert_eq!(loaded.len(), 1);
+        assert!(loaded.lookup("A", stamp).unwrap().has_output("result"));
+
+        // Another checker or an unreadable index starts over
+        assert_eq!(StepIndex::load(temp.path(), "other").len(), 0);
+        std::fs::write(temp.path().join(INDEX_FILE), "{").unwrap();
+        assert_eq!(StepIndex::load(temp.path(), "checker").len(), 0);
+    }
+}
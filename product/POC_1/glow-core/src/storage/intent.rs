@@ -0,0 +1,386 @@
+//! Write-ahead intent records
+//!
+//! An operation changing several data files (e.g. finishing a step writes the
+//! step, its summary and its parent) first records every file it is about to
+//! write, with the content before and after, in an intent file. If the
+//! process stops while applying the writes, the next load rolls the
+//! operation forward from the record, or back if that fails. Binary content,
+//! such as artifacts and archives, is kept in blob files next to the record.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GlowError, Result};
+
+use super::atomic::write_atomic;
+
+/// Intent file in the data directory
+pub const INTENT_FILE: &str = ".intent.json";
+
+/// Prefix of the blob files holding binary content, in the data directory
+const BLOB_PREFIX: &str = ".intent.";
+
+/// Extension of the blob files
+const BLOB_EXTENSION: &str = ".blob";
+
+/// Content of a file before or after an operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+enum Content {
+    /// UTF-8 content, in the record
+    Text(String),
+    /// Binary content, in a blob file of the data directory
+    Blob { blob: String },
+}
+
+/// File written by an operation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct IntentWrite {
+    /// Path relative to the data directory
+    path: PathBuf,
+    /// Content before the operation (None if the file didn't exist)
+    before: Option<Content>,
+    /// Content after the operation (None if it deletes the file)
+    after: Option<Content>,
+}
+
+/// Record of the data files an operation writes
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Intent {
+    writes: Vec<IntentWrite>,
+    /// Binary content by blob file name, until saved
+    #[serde(skip)]
+    blobs: BTreeMap<String, Vec<u8>>,
+    /// Journal file to append to
+    journal_file: PathBuf,
+    /// Length of the journal file before the operation
+    journal_len: u64,
+    /// Journal lines the operation appends
+    journal: Vec<String>,
+}
+
+impl Intent {
+    /// Record an operation on a data directory from its staged writes and journal lines
+    ///
+    /// Reads the current content of every written file, so it can be restored.
+    pub fn record(
+        data_dir: &Path,
+        writes: impl IntoIterator<Item = (PathBuf, Option<Vec<u8>>)>,
+        journal_file: &Path,
+        journal: Vec<String>,
+    ) -> Result<Self> {
+        let mut blobs = BTreeMap::new();
+        let mut content = |bytes: Vec<u8>| match String::from_utf8(bytes) {
+            Ok(text) => Content::Text(text),
+            Err(e) => {
+                let blob = format!("{}{}{}", BLOB_PREFIX, blobs.len(), BLOB_EXTENSION);
+                blobs.insert(blob.clone(), e.into_bytes());
+                Content::Blob { blob }
+            }
+        };
+
+        let mut recorded = Vec::new();
+        for (path, after) in writes {
+            let before = read_bytes(&path)?.map(&mut content);
+            let after = after.map(&mut content);
+            let path = path
+                .strip_prefix(data_dir)
+                .map(Path::to_path_buf)
+                .unwrap_or(path);
+            recorded.push(IntentWrite { path, before, after });
+        }
+        let journal_len = std::fs::metadata(data_dir.join(journal_file)).map_or(0, |m| m.len());
+
+        Ok(Self {
+            writes: recorded,
+            blobs,
+            journal_file: journal_file.to_path_buf(),
+            journal_len,
+            journal,
+        })
+    }
+
+    /// Check if the operation changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.writes.is_empty() && self.journal.is_empty()
+    }
+
+    /// Get the number of files the operation writes
+    pub fn len(&self) -> usize {
+        self.writes.len()
+    }
+
+    /// Load the intent left in a data directory (None if there is none)
+    pub fn load(data_dir: &Path) -> Result<Option<Self>> {
+        let path = data_dir.join(INTENT_FILE);
+        let Some(content) = read_existing(&path)? else {
+            return Ok(None);
+        };
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| GlowError::JsonParseError {
+                message: format!("{}: {}", path.display(), e),
+                source: e,
+            })
+    }
+
+    /// Record the intent in a data directory before applying it
+    ///
+    /// The blobs are written first, so a saved record always has its content.
+    pub fn save(&self, data_dir: &Path) -> Result<()> {
+        for (blob, content) in &self.blobs {
+            write_atomic(&data_dir.join(blob), content)?;
+        }
+        let content = serde_json::to_string(self)?;
+        write_atomic(&data_dir.join(INTENT_FILE), content.as_bytes())
+    }
+
+    /// Remove the intent record and its blobs from a data directory once applied or undone
+    pub fn clear(data_dir: &Path) -> Result<()> {
+        remove_existing(&data_dir.join(INTENT_FILE))?;
+        let blobs = std::fs::read_dir(data_dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| n.starts_with(BLOB_PREFIX) && n.ends_with(BLOB_EXTENSION))
+            });
+        for blob in blobs {
+            remove_existing(&blob)?;
+        }
+        Ok(())
+    }
+
+    /// Write the content after the operation (roll forward)
+    ///
+    /// Safe to repeat: an interrupted apply is completed by applying again.
+    pub fn apply(&self, data_dir: &Path) -> Result<()> {
+        for write in &self.writes {
+            self.put(data_dir, &write.path, write.after.as_ref())?;
+        }
+        if self.journal.is_empty() {
+            return Ok(());
+        }
+
+        let path = data_dir.join(&self.journal_file);
+        let write_error = |source| GlowError::FileWriteError {
+            path: path.clone(),
+            source,
+        };
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(write_error)?;
+        // Drop lines appended by an earlier, interrupted apply
+        file.set_len(self.journal_len).map_err(write_error)?;
+        let lines: String = self.journal.iter().map(|line| format!("{}\n", line)).collect();
+        file.write_all(lines.as_bytes()).map_err(write_error)?;
+        file.sync_all().map_err(write_error)
+    }
+
+    /// Restore the content before the operation (roll back)
+    pub fn revert(&self, data_dir: &Path) -> Result<()> {
+        for write in &self.writes {
+            self.put(data_dir, &write.path, write.before.as_ref())?;
+        }
+
+        let path = data_dir.join(&self.journal_file);
+        if path.exists() {
+            let file = std::fs::OpenOptions::new()
+                .write(true)
+                .open(&path)
+                .map_err(|e| GlowError::FileWriteError { path: path.clone(), source: e })?;
+            file.set_len(self.journal_len)
+                .map_err(|e| GlowError::FileWriteError { path, source: e })?;
+        }
+        Ok(())
+    }
+
+    /// Write a file's content, or remove it for None
+    fn put(&self, data_dir: &Path, path: &Path, content: Option<&Content>) -> Result<()> {
+        let path = data_dir.join(path);
+        match content {
+            Some(Content::Text(text)) => write_atomic(&path, text.as_bytes()),
+            Some(Content::Blob { blob }) => match self.blobs.get(blob) {
+                Some(bytes) => write_atomic(&path, bytes),
+                // Loaded records read their blobs from the data directory
+                None => {
+                    let bytes = read_bytes(&data_dir.join(blob))?.ok_or_else(|| GlowError::FileReadError {
+                        path: data_dir.join(blob),
+                        source: std::io::ErrorKind::NotFound.into(),
+                    })?;
+                    write_atomic(&path, &bytes)
+                }
+            },
+            None => remove_existing(&path),
+        }
+    }
+}
+
+/// Outcome of recovering an operation interrupted by a previous process
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// The operation's writes were completed
+    RolledForward { files: usize },
+    /// The operation's writes were undone
+    RolledBack { files: usize },
+}
+
+impl std::fmt::Display for Recovery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Recovery::RolledForward { files } => {
+                write!(f, "Completed an interrupted operation ({} file(s) written)", files)
+            }
+            Recovery::RolledBack { files } => {
+                write!(f, "Undid an interrupted operation ({} file(s) restored)", files)
+            }
+        }
+    }
+}
+
+/// Recover the operation recorded in a data directory, if any
+///
+/// Rolls it forward, or back when its writes can't be completed.
+pub fn recover(data_dir: &Path) -> Result<Option<Recovery>> {
+    let Some(intent) = Intent::load(data_dir)? else {
+        return Ok(None);
+    };
+
+    let recovery = match intent.apply(data_dir) {
+        Ok(()) => Recovery::RolledForward { files: intent.len() },
+        Err(_) => {
+            intent.revert(data_dir)?;
+            Recovery::RolledBack { files: intent.len() }
+        }
+    };
+    Intent::clear(data_dir)?;
+    Ok(Some(recovery))
+}
+
+/// Read a file (None if it doesn't exist)
+fn read_existing(path: &Path) -> Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(GlowError::FileReadError {
+            path: path.to_path_buf(),
+            source: e,
+        }),
+    }
+}
+
+/// Read a file that may be binary (None if it doesn't exist)
+fn read_bytes(path: &Path) -> Result<Option<Vec<u8>>> {
+    match std::fs::read(path) {
+        Ok(content) => Ok(Some(content)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(GlowError::FileReadError {
+            path: path.to_path_buf(),
+            source: e,
+        }),
+    }
+}
+
+/// Remove a file if it exists
+fn remove_existing(path: &Path) -> Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(GlowError::FileWriteError {
+            path: path.to_path_buf(),
+            source: e,
+        }),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn intent(data_dir: &Path) -> Intent {
+        std::fs::write(data_dir.join("A.md"), "old A").unwrap();
+        std::fs::write(data_dir.join("journal.jsonl"), "old\n").unwrap();
+        Intent::record(
+            data_dir,
+            vec![
+                (data_dir.join("A.md"), Some(b"new A".to_vec())),
+                (data_dir.join("B").join("C.md"), Some(b"new C".to_vec())),
+            ],
+            Path::new("journal.jsonl"),
+            vec!["new".to_string()],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_intent_rolls_forward_repeatably() {
+        let temp = tempdir().unwrap();
+        let intent = intent(temp.path());
+        intent.save(temp.path()).unwrap();
+
+        // An apply interrupted after the journal, then completed on recovery
+        intent.apply(temp.path()).unwrap();
+        let recovery = recover(temp.path()).unwrap();
+
+        assert_eq!(recovery, Some(Recovery::RolledForward { files: 2 }));
+        assert_eq!(std::fs::read_to_string(temp.path().join("A.md")).unwrap(), "new A");
+        assert_eq!(std::fs::read_to_string(temp.path().join("B/C.md")).unwrap(), "new C");
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("journal.jsonl")).unwrap(),
+            "old\nnew\n"
+        );
+        assert_eq!(Intent::load(temp.path()).unwrap(), None);
+        assert_eq!(recover(temp.path()).unwrap(), None);
+    }
+
+    #[test]
+    fn test_intent_reverts() {
+        let temp = tempdir().unwrap();
+        let intent = intent(temp.path());
+        intent.apply(temp.path()).unwrap();
+        intent.revert(temp.path()).unwrap();
+
+        assert_eq!(std::fs::read_to_string(temp.path().join("A.md")).unwrap(), "old A");
+        assert!(!temp.path().join("B/C.md").exists());
+        assert_eq!(
+            std::fs::read_to_string(temp.path().join("journal.jsonl")).unwrap(),
+            "old\n"
+        );
+    }
+
+    #[test]
+    fn test_intent_keeps_binary_content_in_blobs() {
+        let temp = tempdir().unwrap();
+        let logo = temp.path().join("A/artifacts/logo.png");
+        std::fs::create_dir_all(logo.parent().unwrap()).unwrap();
+        std::fs::write(&logo, [0x89, 0xff]).unwrap();
+        let intent = Intent::record(
+            temp.path(),
+            vec![(logo.clone(), Some(vec![0x89, 0xfe]))],
+            Path::new("journal.jsonl"),
+            Vec::new(),
+        )
+        .unwrap();
+        intent.save(temp.path()).unwrap();
+        assert!(temp.path().join(".intent.0.blob").exists());
+
+        // A record loaded after a crash reads its content from the blobs
+        let loaded = Intent::load(temp.path()).unwrap().unwrap();
+        loaded.apply(temp.path()).unwrap();
+        assert_eq!(std::fs::read(&logo).unwrap(), [0x89, 0xfe]);
+        loaded.revert(temp.path()).unwrap();
+        assert_eq!(std::fs::read(&logo).unwrap(), [0x89, 0xff]);
+
+        Intent::clear(temp.path()).unwrap();
+        assert!(!temp.path().join(".intent.0.blob").exists());
+        assert!(!temp.path().join(".intent.1.blob").exists());
+    }
+}
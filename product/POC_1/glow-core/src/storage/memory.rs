@@ -0,0 +1,346 @@
+//! In-memory step store
+//!
+//! Keeps step data in memory, for tests and tools embedding glow that
+//! don't want the project's data on disk.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::SystemTime;
+
+use crate::error::{GlowError, Result};
+use crate::model::{JournalEntry, Step};
+
+use super::store::StepStore;
+
+/// Step data held by a `MemoryStore`
+#[derive(Default)]
+struct MemoryData {
+    steps: BTreeMap<String, Step>,
+    documents: BTreeMap<(String, String), String>,
+    iterations: BTreeMap<String, BTreeSet<u32>>,
+    iteration_documents: BTreeMap<(String, u32, String), String>,
+    artifacts: BTreeMap<(String, String), Vec<u8>>,
+    modified: BTreeMap<String, SystemTime>,
+    journal: Vec<JournalEntry>,
+}
+
+impl MemoryData {
+    /// Record activity on a step
+    fn touch(&mut self, fqid: &str) {
+        self.modified.insert(fqid.to_string(), SystemTime::now());
+    }
+}
+
+/// Step store keeping everything in memory
+#[derive(Default)]
+pub struct MemoryStore {
+    data: RwLock<MemoryData>,
+}
+
+impl MemoryStore {
+    /// Create an empty store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, MemoryData> {
+        self.data.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, MemoryData> {
+        self.data.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Check if a step is another step or one of its sub-steps
+fn in_tree(fqid: &str, root: &str) -> bool {
+    fqid.strip_prefix(root)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Get the FQID of the step a step is listed under (None for the top level)
+fn listed_under(fqid: &str) -> Option<&str> {
+    fqid.rsplit_once('.').map(|(parent, _)| parent)
+}
+
+impl StepStore for MemoryStore {
+    fn init(&self) -> Result<()> {
+        Ok(())
+    }
+
+    fn read_step(&self, fqid: &str) -> Result<Step> {
+        self.read()
+            .steps
+            .get(fqid)
+            .cloned()
+            .ok_or_else(|| GlowError::StepNotFound {
+                fqid: fqid.to_string(),
+            })
+    }
+
+    fn write_step(&self, step: &mut Step) -> Result<()> {
+        let mut data = self.write();
+        data.steps.insert(step.fqid().to_string(), step.clone());
+        Ok(())
+    }
+
+    fn step_exists(&self, fqid: &str) -> bool {
+        self.read().steps.contains_key(fqid)
+    }
+
+    fn delete_step(&self, fqid: &str) -> Result<()> {
+        let mut data = self.write();
+        let owned = |owner: &str| in_tree(owner, fqid);
+        data.steps.retain(|key, _| !owned(key));
+        data.documents.retain(|(owner, _), _| !owned(owner));
+        data.iterations.retain(|owner, _| !owned(owner));
+        data.iteration_documents.retain(|(owner, _, _), _| !owned(owner));
+        data.artifacts.retain(|(owner, _), _| !owned(owner));
+        data.modified.retain(|owner, _| !owned(owner));
+        Ok(())
+    }
+
+    fn move_step(&self, from: &str, to: &str) -> Result<()> {
+        let mut data = self.write();
+        if data.steps.contains_key(to) {
+            return Err(GlowError::StepAlreadyExists { fqid: to.to_string() });
+        }
+
+        let moved = |owner: &str| match owner.strip_prefix(from) {
+            Some(rest) if in_tree(owner, from) => format!("{}{}", to, rest),
+            _ => owner.to_string(),
+        };
+        data.steps = std::mem::take(&mut data.steps)
+            .into_iter()
+            .map(|(key, step)| (moved(&key), step))
+            .collect();
+        data.documents = std::mem::take(&mut data.documents)
+            .into_iter()
+            .map(|((owner, name), content)| ((moved(&owner), name), content))
+            .collect();
+        data.iterations = std::mem::take(&mut data.iterations)
+            .into_iter()
+            .map(|(owner, iterations)| (moved(&owner), iterations))
+            .collect();
+        data.iteration_documents = std::mem::take(&mut data.iteration_documents)
+            .into_iter()
+            .map(|((owner, iteration, name), content)| ((moved(&owner), iteration, name), content))
+            .collect();
+        data.artifacts = std::mem::take(&mut data.artifacts)
+            .into_iter()
+            .map(|((owner, name), content)| ((moved(&owner), name), content))
+            .collect();
+        data.modified = std::mem::take(&mut data.modified)
+            .into_iter()
+            .map(|(owner, modified)| (moved(&owner), modified))
+            .collect();
+        Ok(())
+    }
+
+    fn list_steps(&self, parent_fqid: Option<&str>) -> Result<Vec<String>> {
+        Ok(self
+            .read()
+            .steps
+            .keys()
+            .filter(|fqid| listed_under(fqid) == parent_fqid)
+            .cloned()
+            .collect())
+    }
+
+    fn read_document(&self, fqid: &str, name: &str) -> Result<Option<String>> {
+        Ok(self
+            .read()
+            .documents
+            .get(&(fqid.to_string(), name.to_string()))
+            .cloned())
+    }
+
+    fn write_document(&self, fqid: &str, name: &str, content: &str) -> Result<()> {
+        let mut data = self.write();
+        data.documents
+            .insert((fqid.to_string(), name.to_string()), content.to_string());
+        data.touch(fqid);
+        Ok(())
+    }
+
+    fn create_iteration(&self, process_fqid: &str, iteration: u32) -> Result<()> {
+        let mut data = self.write();
+        data.iterations
+            .entry(process_fqid.to_string())
+            .or_default()
+            .insert(iteration);
+        data.iteration_documents
+            .entry((process_fqid.to_string(), iteration, "summary.md".to_string()))
+            .or_insert_with(|| format!("# Iteration {} Summary\n\nIn progress.\n", iteration));
+        Ok(())
+    }
+
+    fn current_iteration(&self, process_fqid: &str) -> Option<u32> {
+        self.read()
+            .iterations
+            .get(process_fqid)
+            .and_then(|iterations| iterations.last().copied())
+    }
+
+    fn write_iteration_document(
+        &self,
+        process_fqid: &str,
+        iteration: u32,
+        name: &str,
+        content: &str,
+    ) -> Result<()> {
+        let mut data = self.write();
+        data.iterations
+            .entry(process_fqid.to_string())
+            .or_default()
+            .insert(iteration);
+        data.iteration_documents.insert(
+            (process_fqid.to_string(), iteration, name.to_string()),
+            content.to_string(),
+        );
+        Ok(())
+    }
+
+    fn read_iteration_document(
+        &self,
+        process_fqid: &str,
+        iteration: u32,
+        name: &str,
+    ) -> Result<Option<String>> {
+        Ok(self
+            .read()
+            .iteration_documents
+            .get(&(process_fqid.to_string(), iteration, name.to_string()))
+            .cloned())
+    }
+
+    fn list_artifacts(&self, fqid: &str) -> Result<Vec<String>> {
+        Ok(self
+            .read()
+            .artifacts
+            .keys()
+            .filter(|(owner, _)| owner == fqid)
+            .map(|(_, name)| name.clone())
+            .collect())
+    }
+
+    fn read_artifact(&self, fqid: &str, name: &str) -> Result<Vec<u8>> {
+        self.read()
+            .artifacts
+            .get(&(fqid.to_string(), name.to_string()))
+            .cloned()
+            .ok_or_else(|| GlowError::ArtifactNotFound {
+                fqid: fqid.to_string(),
+                name: name.to_string(),
+            })
+    }
+
+    fn write_artifact(&self, fqid: &str, name: &str, content: &[u8]) -> Result<()> {
+        let mut data = self.write();
+        data.artifacts
+            .insert((fqid.to_string(), name.to_string()), content.to_vec());
+        data.touch(fqid);
+        Ok(())
+    }
+
+    fn delete_artifact(&self, fqid: &str, name: &str) -> Result<()> {
+        let mut data = self.write();
+        if data
+            .artifacts
+            .remove(&(fqid.to_string(), name.to_string()))
+            .is_none()
+        {
+            return Err(GlowError::ArtifactNotFound {
+                fqid: fqid.to_string(),
+                name: name.to_string(),
+            });
+        }
+        data.touch(fqid);
+        Ok(())
+    }
+
+    fn last_modified(&self, fqid: &str) -> Option<SystemTime> {
+        self.read().modified.get(fqid).copied()
+    }
+
+    fn append_journal(&self, entry: &JournalEntry) -> Result<()> {
+        self.write().journal.push(entry.clone());
+        Ok(())
+    }
+
+    fn read_journal(&self) -> Result<Vec<JournalEntry>> {
+        Ok(self.read().journal.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{StepAttributes, StepStatus};
+
+    fn step(fqid: &str) -> Step {
+        let id = fqid.rsplit('.').next().unwrap_or(fqid);
+        Step {
+            attr: StepAttributes {
+                id: id.to_string(),
+                fqid: Some(fqid.to_string()),
+                status: StepStatus::Todo,
+                ..Default::default()
+            },
+            input: Vec::new(),
+            scope: Vec::new(),
+            output: Vec::new(),
+            parent: Vec::new(),
+            own_steps: Vec::new(),
+            links: Vec::new(),
+            extra: serde_json::Map::new(),
+            revision: None,
+        }
+    }
+
+    #[test]
+    fn test_steps_round_trip_and_list() {
+        let store = MemoryStore::new();
+        store.write_step(&mut step("FEAT")).unwrap();
+        store.write_step(&mut step("FEAT.REQ")).unwrap();
+        store.write_step(&mut step("FEAT.REQ.TASK")).unwrap();
+
+        assert_eq!(store.read_step("FEAT.REQ").unwrap().attr.id, "REQ");
+        assert_eq!(store.list_steps(None).unwrap(), vec!["FEAT"]);
+        assert_eq!(store.list_steps(Some("FEAT")).unwrap(), vec!["FEAT.REQ"]);
+        assert!(store.last_modified("FEAT.REQ").is_none());
+
+        store.write_artifact("FEAT.REQ.TASK", "notes.md", b"progress").unwrap();
+        assert!(store.last_modified("FEAT.REQ.TASK").is_some());
+        store.move_step("FEAT.REQ", "FEAT.SPEC").unwrap();
+        assert!(store.step_exists("FEAT.SPEC.TASK"));
+        assert_eq!(store.read_artifact("FEAT.SPEC.TASK", "notes.md").unwrap(), b"progress");
+        assert!(matches!(
+            store.move_step("FEAT.SPEC", "FEAT"),
+            Err(GlowError::StepAlreadyExists { .. })
+        ));
+
+        store.delete_step("FEAT.SPEC").unwrap();
+        assert!(!store.step_exists("FEAT.SPEC.TASK"));
+        assert!(store.list_artifacts("FEAT.SPEC.TASK").unwrap().is_empty());
+        assert!(matches!(
+            store.read_step("FEAT.SPEC"),
+            Err(GlowError::StepNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_iterations_and_artifacts() {
+        let store = MemoryStore::new();
+        assert_eq!(store.current_iteration("ROOT"), None);
+        store.create_iteration("ROOT", 1).unwrap();
+        store.create_iteration("ROOT", 2).unwrap();
+        assert_eq!(store.current_iteration("ROOT"), Some(2));
+
+        store.write_artifact("A", "notes.md", b"progress").unwrap();
+        assert_eq!(store.list_artifacts("A").unwrap(), vec!["notes.md"]);
+        assert_eq!(store.read_artifact("A", "notes.md").unwrap(), b"progress");
+        store.delete_artifact("A", "notes.md").unwrap();
+        assert!(store.delete_artifact("A", "notes.md").is_err());
+    }
+}
@@ -0,0 +1,264 @@
+//! Three-way merge of step data files
+//!
+//! Merges the versions of a step data file from two branches against their
+//! common base key by key instead of line by line, so two branches changing
+//! different steps of a parent's `own_steps` list, or different keys of a
+//! step, merge cleanly. Statuses copied from other steps (`own_steps[].status`
+//! and `links[].step_status`) are derived data: when both branches changed
+//! them, the furthest status wins and glow recomputes them later.
+
+use serde_yaml::{Mapping, Value};
+
+use crate::config::StepFormat;
+use crate::error::Result;
+use crate::model::StepStatus;
+
+use super::format::{parse_step_file, render_step_file};
+
+/// Outcome of merging a step data file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepMerge {
+    /// Merged file content; conflicting values are taken from our side
+    pub content: String,
+    /// Keys changed differently on both sides, such as `attr.due` or `body`
+    pub conflicts: Vec<String>,
+}
+
+impl StepMerge {
+    /// Check if the merge is clean
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+/// Merge our and their version of a step data file against their base
+///
+/// A conflicting Markdown body gets conflict markers; conflicting data
+/// keeps our value and is listed in the conflicts.
+pub fn merge_step_files(format: StepFormat, base: &str, ours: &str, theirs: &str) -> Result<StepMerge> {
+    let clean = |content: &str| StepMerge {
+        content: content.to_string(),
+        conflicts: Vec::new(),
+    };
+    if ours == theirs || theirs == base {
+        return Ok(clean(ours));
+    }
+    if ours == base {
+        return Ok(clean(theirs));
+    }
+
+    let (base_data, base_body) = parse_step_file(format, base)?;
+    let (our_data, our_body) = parse_step_file(format, ours)?;
+    let (their_data, their_body) = parse_step_file(format, theirs)?;
+
+    let mut conflicts = Vec::new();
+    let data = merge_value("", Some(&base_data), Some(our_data), Some(their_data), &mut conflicts)
+        .unwrap_or(Value::Null);
+    let body = merge_body(&base_body, our_body, their_body, &mut conflicts);
+
+    let content = if data.is_null() {
+        body
+    } else {
+        render_step_file(format, &data, &body)?
+    };
+    Ok(StepMerge { content, conflicts })
+}
+
+/// Merge a value of our and their side against the base (None if absent or removed)
+fn merge_value(
+    path: &str,
+    base: Option<&Value>,
+    ours: Option<Value>,
+    theirs: Option<Value>,
+    conflicts: &mut Vec<String>,
+) -> Option<Value> {
+    if ours == theirs || theirs.as_ref() == base {
+        return ours;
+    }
+    if ours.as_ref() == base {
+        return theirs;
+    }
+
+    match (ours, theirs) {
+        (Some(Value::Mapping(ours)), Some(Value::Mapping(theirs))) => Some(Value::Mapping(
+            merge_mappings(path, base.and_then(Value::as_mapping), ours, theirs, conflicts),
+        )),
+        (Some(Value::Sequence(ours)), Some(Value::Sequence(theirs)))
+            if has_ids(&ours) && has_ids(&theirs) =>
+        {
+            let base = base.and_then(Value::as_sequence).filter(|base| has_ids(base));
+            Some(Value::Sequence(merge_lists(path, base, ours, theirs, conflicts)))
+        }
+        (Some(ours), Some(theirs)) if is_derived(path) => {
+            Some(if rank(&theirs) > rank(&ours) { theirs } else { ours })
+        }
+        (ours, theirs) => {
+            conflicts.push(path.to_string());
+            ours.or(theirs)
+        }
+    }
+}
+
+/// Merge two mappings key by key, in our key order followed by keys only they added
+fn merge_mappings(
+    path: &str,
+    base: Option<&Mapping>,
+    ours: Mapping,
+    mut theirs: Mapping,
+    conflicts: &mut Vec<String>,
+) -> Mapping {
+    let child = |key: &Value| match key.as_str() {
+        Some(key) if path.is_empty() => key.to_string(),
+        Some(key) => format!("{}.{}", path, key),
+        None => path.to_string(),
+    };
+
+    let mut merged = Mapping::new();
+    for (key, value) in ours {
+        let their_value = theirs.remove(&key);
+        let base_value = base.and_then(|b| b.get(&key));
+        if let Some(value) = merge_value(&child(&key), base_value, Some(value), their_value, conflicts) {
+            merged.insert(key, value);
+        }
+    }
+    for (key, value) in theirs {
+        let base_value = base.and_then(|b| b.get(&key));
+        if let Some(value) = merge_value(&child(&key), base_value, None, Some(value), conflicts) {
+            merged.insert(key, value);
+        }
+    }
+    merged
+}
+
+/// Merge two lists of items with an `id`, matching the items by `id`
+fn merge_lists(
+    path: &str,
+    base: Option<&Vec<Value>>,
+    ours: Vec<Value>,
+    mut theirs: Vec<Value>,
+    conflicts: &mut Vec<String>,
+) -> Vec<Value> {
+    let path = format!("{}[]", path);
+    let base_item = |id: &Value| base.and_then(|b| b.iter().find(|item| item_id(item) == Some(id)));
+    let take_theirs = |id: &Value, theirs: &mut Vec<Value>| {
+        let position = theirs.iter().position(|item| item_id(item) == Some(id))?;
+        Some(theirs.remove(position))
+    };
+
+    let mut merged = Vec::new();
+    for item in ours {
+        let Some(id) = item_id(&item).cloned() else {
+            continue;
+        };
+        let their_item = take_theirs(&id, &mut theirs);
+        if let Some(item) = merge_value(&path, base_item(&id), Some(item), their_item, conflicts) {
+            merged.push(item);
+        }
+    }
+    for item in theirs {
+        let Some(id) = item_id(&item).cloned() else {
+            continue;
+        };
+        if let Some(item) = merge_value(&path, base_item(&id), None, Some(item), conflicts) {
+            merged.push(item);
+        }
+    }
+    merged
+}
+
+/// Merge Markdown bodies, with conflict markers if both sides changed them
+fn merge_body(base: &str, ours: String, theirs: String, conflicts: &mut Vec<String>) -> String {
+    if ours == theirs || theirs == base {
+        return ours;
+    }
+    if ours == base {
+        return theirs;
+    }
+
+    conflicts.push("body".to_string());
+    format!(
+        "<<<<<<< ours\n{}{}=======\n{}{}>>>>>>> theirs\n",
+        ours,
+        if ours.ends_with('\n') { "" } else { "\n" },
+        theirs,
+        if theirs.ends_with('\n') { "" } else { "\n" }
+    )
+}
+
+/// Get the `id` of a list item
+fn item_id(item: &Value) -> Option<&Value> {
+    item.get("id")
+}
+
+/// Check if every item of a list has an `id`
+fn has_ids(items: &[Value]) -> bool {
+    items.iter().all(|item| item_id(item).is_some())
+}
+
+/// Check if a value is a status copied from another step
+fn is_derived(path: &str) -> bool {
+    matches!(path, "own_steps[].status" | "links[].step_status")
+}
+
+/// Get how far a status value is along the lifecycle (unknown values first)
+fn rank(status: &Value) -> Option<u8> {
+    let status: StepStatus = serde_yaml::from_value(status.clone()).ok()?;
+    Some(match status {
+        StepStatus::Wait => 0,
+        StepStatus::Todo => 1,
+        StepStatus::InProgress => 2,
+        StepStatus::Done => 3,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BASE: &str = r#"---
+attr:
+  id: PROC
+  status: in-progress
+own_steps:
+- id: A
+  status: todo
+- id: B
+  status: todo
+- id: C
+  status: wait
+---
+# PROC
+"#;
+
+    #[test]
+    fn test_sibling_steps_merge_cleanly() {
+        // Each branch finished one sibling, and both made C ready
+        let ours = BASE
+            .replacen("id: A\n  status: todo", "id: A\n  status: done", 1)
+            .replace("status: wait", "status: todo");
+        let theirs = BASE
+            .replacen("id: B\n  status: todo", "id: B\n  status: done", 1)
+            .replace("status: wait", "status: in-progress");
+
+        let merge = merge_step_files(StepFormat::Markdown, BASE, &ours, &theirs).unwrap();
+
+        assert!(merge.is_clean(), "{:?}", merge.conflicts);
+        assert!(merge.content.contains("id: A\n  status: done"));
+        assert!(merge.content.contains("id: B\n  status: done"));
+        // Derived statuses changed on both sides take the furthest one
+        assert!(merge.content.contains("id: C\n  status: in-progress"));
+        assert!(merge.content.ends_with("---\n# PROC\n"));
+    }
+
+    #[test]
+    fn test_conflicts_keep_ours() {
+        let ours = BASE.replace("status: in-progress", "status: done").replace("# PROC", "# Ours");
+        let theirs = BASE.replace("status: in-progress", "status: todo").replace("# PROC", "# Theirs");
+
+        let merge = merge_step_files(StepFormat::Markdown, BASE, &ours, &theirs).unwrap();
+
+        assert_eq!(merge.conflicts, vec!["attr.status", "body"]);
+        assert!(merge.content.contains("status: done"));
+        assert!(merge.content.contains("<<<<<<< ours\n# Ours\n=======\n# Theirs\n>>>>>>> theirs\n"));
+    }
+}
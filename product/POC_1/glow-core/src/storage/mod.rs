@@ -1,53 +1,114 @@
 //! Storage layer
 //!
-//! Handles file system operations for step data files.
+//! Handles step data through a pluggable `StepStore`: step data files in the
+//! data folder by default, or memory.
 
+mod archive;
+mod atomic;
+mod format;
 mod frontmatter;
+mod git;
+mod index;
+mod intent;
+mod memory;
+mod merge;
+mod snapshot;
 mod step_files;
+mod store;
+mod validation;
 
+pub use archive::{ArchiveManifest, ArchiveStub, ArchivedFile, ARCHIVE_FILE, ARCHIVE_STUB};
+pub use atomic::{write_atomic, LOCK_FILE};
+pub use format::{Conversion, NOTES_FILE};
 pub use frontmatter::{parse_frontmatter, render_frontmatter};
-pub use step_files::StepFileManager;
+pub use git::{attribute_lines, GitInstall, MERGE_DRIVER};
+pub use index::{StepSummary, INDEX_FILE};
+pub use intent::{Recovery, INTENT_FILE};
+pub use merge::{merge_step_files, StepMerge};
+pub use memory::MemoryStore;
+pub use snapshot::{
+    ParameterChange, Snapshot, SnapshotDiff, SnapshotInfo, SnapshotRestore, StepDiff, StepDiffKind,
+    CURRENT_DATA, SNAPSHOT_DIR,
+};
+pub use step_files::{StepFileManager, JOURNAL_FILE};
+pub use store::StepStore;
+pub use validation::StepChecker;
 
 use std::path::{Path, PathBuf};
 
-use crate::config::Config;
-use crate::error::Result;
+use crate::config::{Config, ProcessConfig, StepFormat, StepValidation};
+use crate::error::{GlowError, Result};
 use crate::model::{Process, Step};
 
 /// Main storage interface
 pub struct Storage {
     /// Project root path
     project_root: PathBuf,
-    /// Data directory path
-    data_dir: PathBuf,
     /// Config directory path
     config_dir: PathBuf,
-    /// Step file manager
-    step_files: StepFileManager,
+    /// Checks step data files on read
+    checker: StepChecker,
+    /// Format of new step data files
+    step_format: StepFormat,
+    /// Backend holding the step data
+    store: Box<dyn StepStore>,
 }
 
 impl Storage {
-    /// Create a new storage instance
+    /// Create a new storage instance keeping step data files in `data_folder`
     pub fn new(project_root: PathBuf, config: &Config, config_dir: PathBuf) -> Self {
         let data_dir = config.data_dir(&project_root);
-        let step_files = StepFileManager::new(data_dir.clone());
+        let checker = StepChecker::new(None, config.step_validation);
+        let store = StepFileManager::new(data_dir)
+            .with_format(config.step_format)
+            .with_checker(checker.clone());
 
         Self {
             project_root,
-            data_dir,
             config_dir,
-            step_files,
+            checker,
+            step_format: config.step_format,
+            store: Box::new(store),
         }
     }
 
+    /// Type the parameter values of step files from the process definition
+    pub fn with_process_config(mut self, process_config: &ProcessConfig) -> Self {
+        self.set_process_config(process_config);
+        self
+    }
+
+    /// Type the parameter values of step files from a changed process definition
+    pub fn set_process_config(&mut self, process_config: &ProcessConfig) {
+        self.checker = StepChecker::new(Some(process_config), self.validation());
+        self.store.set_checker(self.checker.clone());
+    }
+
+    /// Keep step data files in another directory than `data_folder`
+    pub fn with_data_dir(mut self, data_dir: PathBuf) -> Self {
+        let store = StepFileManager::new(data_dir)
+            .with_format(self.step_format)
+            .with_checker(self.checker.clone());
+        self.store = Box::new(store);
+        self
+    }
+
+    /// Keep step data in another store
+    pub fn with_store(mut self, mut store: Box<dyn StepStore>) -> Self {
+        store.set_checker(self.checker.clone());
+        store.set_step_format(self.step_format);
+        self.store = store;
+        self
+    }
+
     /// Get the project root path
     pub fn project_root(&self) -> &Path {
         &self.project_root
     }
 
-    /// Get the data directory path
-    pub fn data_dir(&self) -> &Path {
-        &self.data_dir
+    /// Get the data directory path (None if the store doesn't use files)
+    pub fn data_dir(&self) -> Option<&Path> {
+        self.store.data_dir()
     }
 
     /// Get the config directory path
@@ -55,78 +116,119 @@ impl Storage {
         &self.config_dir
     }
 
+    /// Get the format of new step data files
+    pub fn step_format(&self) -> StepFormat {
+        self.step_format
+    }
+
+    /// Write new step data files in a format
+    pub fn set_step_format(&mut self, format: StepFormat) {
+        self.step_format = format;
+        self.store.set_step_format(format);
+    }
+
+    /// Get how step data files are checked when read
+    pub fn validation(&self) -> StepValidation {
+        self.checker.validation()
+    }
+
     /// Initialize the data directory structure
     pub fn init_data_dir(&self) -> Result<()> {
-        std::fs::create_dir_all(&self.data_dir)?;
-        
-        // Create description.md placeholder
-        let description_path = self.data_dir.join("description.md");
-        if !description_path.exists() {
-            std::fs::write(&description_path, "# Project Description\n\nTODO: Add project description.\n")?;
-        }
+        self.store.init()
+    }
 
-        // Create summary.md placeholder
-        let summary_path = self.data_dir.join("summary.md");
-        if !summary_path.exists() {
-            std::fs::write(&summary_path, "# Process Summary\n\nNo iterations yet.\n")?;
-        }
+    /// Register the step file merge driver for the data folder with git
+    ///
+    /// Fails if the step data isn't kept in files under the project root.
+    pub fn install_git(&self) -> Result<GitInstall> {
+        let data_dir = self.data_dir().ok_or_else(|| GlowError::ValidationError {
+            message: "The step data isn't kept in files, so there's nothing for git to merge".to_string(),
+        })?;
+        let data_folder = data_dir.strip_prefix(&self.project_root).map_err(|_| GlowError::ValidationError {
+            message: format!(
+                "The data folder {} is outside the project root {}; .gitattributes can't name it",
+                data_dir.display(),
+                self.project_root.display()
+            ),
+        })?;
+        git::install(&self.project_root, &data_folder.to_string_lossy().replace('\\', "/"))
+    }
 
-        Ok(())
+    /// Get the store holding the step data
+    pub fn store(&self) -> &dyn StepStore {
+        self.store.as_ref()
     }
 
-    /// Get step file manager
-    pub fn step_files(&self) -> &StepFileManager {
-        &self.step_files
+    /// Start an operation: take the project lock and hold back its writes
+    ///
+    /// End it with `commit` to apply the writes together, or `abort` to
+    /// discard them.
+    pub fn begin(&self) -> Result<()> {
+        self.store.lock(true)?;
+        self.store.begin().inspect_err(|_| self.store.unlock())
     }
 
-    /// Get mutable step file manager
-    pub fn step_files_mut(&mut self) -> &mut StepFileManager {
-        &mut self.step_files
+    /// Apply the writes of the operation and release the project lock
+    pub fn commit(&self) -> Result<()> {
+        let result = self.store.commit();
+        self.store.unlock();
+        result
+    }
+
+    /// Discard the writes of the operation and release the project lock
+    pub fn abort(&self) {
+        self.store.abort();
+        self.store.unlock();
+    }
+
+    /// Complete or undo an operation a previous process was interrupted in
+    pub fn recover(&self) -> Result<Option<Recovery>> {
+        self.store.recover()
     }
 
     /// Read a step from its data file
     pub fn read_step(&self, fqid: &str) -> Result<Step> {
-        self.step_files.read_step(fqid)
+        self.store.read_step(fqid)
     }
 
-    /// Write a step to its data file
-    pub fn write_step(&self, step: &Step) -> Result<()> {
-        self.step_files.write_step(step)
+    /// Write a step to its data file, refreshing its revision
+    pub fn write_step(&self, step: &mut Step) -> Result<()> {
+        self.store.write_step(step)
     }
 
-    /// Create iteration folder
-    pub fn create_iteration_folder(&self, process_fqid: &str, iteration: u32) -> Result<PathBuf> {
-        let iteration_folder = format!("iteration_{:06}", iteration);
-        let path = if process_fqid == "ROOT" {
-            self.data_dir.join(&iteration_folder)
-        } else {
-            let step_folder = self.step_files.step_folder_path(process_fqid);
-            step_folder.join(&iteration_folder)
-        };
+    /// Read what status queries need from a step
+    pub fn read_summary(&self, fqid: &str) -> Result<StepSummary> {
+        self.store.read_summary(fqid)
+    }
 
-        std::fs::create_dir_all(&path)?;
+    /// Check if a step exists
+    pub fn step_exists(&self, fqid: &str) -> bool {
+        self.store.step_exists(fqid)
+    }
 
-        // Create iteration summary
-        let summary_path = path.join("summary.md");
-        if !summary_path.exists() {
-            std::fs::write(
-                &summary_path,
-                format!("# Iteration {} Summary\n\nIn progress.\n", iteration),
-            )?;
-        }
+    /// Create an iteration of a process
+    pub fn create_iteration(&self, process_fqid: &str, iteration: u32) -> Result<()> {
+        self.store.create_iteration(process_fqid, iteration)
+    }
 
-        Ok(path)
+    /// Get the latest iteration number of a process
+    pub fn current_iteration(&self, process_fqid: &str) -> Option<u32> {
+        self.store.current_iteration(process_fqid)
     }
 
-    /// Get iteration folder path
-    pub fn iteration_folder_path(&self, process_fqid: &str, iteration: u32) -> PathBuf {
-        let iteration_folder = format!("iteration_{:06}", iteration);
-        if process_fqid == "ROOT" {
-            self.data_dir.join(&iteration_folder)
-        } else {
-            let step_folder = self.step_files.step_folder_path(process_fqid);
-            step_folder.join(&iteration_folder)
-        }
+    /// Save a snapshot in the config directory, returning its path
+    pub fn save_snapshot(&self, snapshot: &mut Snapshot) -> Result<PathBuf> {
+        snapshot::save(&self.config_dir.join(SNAPSHOT_DIR), snapshot)
+    }
+
+    /// List the saved snapshots, oldest first
+    pub fn snapshots(&self) -> Result<Vec<Snapshot>> {
+        snapshot::load_all(&self.config_dir.join(SNAPSHOT_DIR))
+    }
+
+    /// Find a saved snapshot by ID, or the latest one with a label
+    pub fn find_snapshot(&self, reference: &str) -> Result<Snapshot> {
+        snapshot::find(&self.config_dir.join(SNAPSHOT_DIR), reference)
     }
 
     /// Load process state from files
@@ -138,12 +240,12 @@ impl Storage {
     /// Save process state to files
     pub fn save_process(&self, process: &Process) -> Result<()> {
         // Save main step file
-        self.write_step(&process.step)?;
+        self.write_step(&mut process.step.clone())?;
 
         // Save iteration data
         if let Some(iteration) = process.current_iteration_data() {
             for step in &iteration.steps {
-                self.write_step(step)?;
+                self.write_step(&mut step.clone())?;
             }
         }
 
@@ -165,8 +267,47 @@ mod tests {
         let storage = Storage::new(temp.path().to_path_buf(), &config, config_dir);
         storage.init_data_dir().unwrap();
 
-        assert!(storage.data_dir().exists());
-        assert!(storage.data_dir().join("description.md").exists());
-        assert!(storage.data_dir().join("summary.md").exists());
+        let data_dir = storage.data_dir().unwrap();
+        assert!(data_dir.exists());
+        assert!(data_dir.join("description.md").exists());
+        assert!(data_dir.join("summary.md").exists());
+
+        // Files for the local machine stay out of version control
+        let gitignore = std::fs::read_to_string(data_dir.join(".gitignore")).unwrap();
+        for ignored in [INDEX_FILE, LOCK_FILE, INTENT_FILE] {
+            assert!(gitignore.lines().any(|line| line == ignored));
+        }
+    }
+
+    #[test]
+    fn test_current_iteration() {
+        let temp = tempdir().unwrap();
+        let config = Config::default();
+        let config_dir = temp.path().join(".glow");
+
+        let storage = Storage::new(temp.path().to_path_buf(), &config, config_dir);
+        storage.init_data_dir().unwrap();
+        assert_eq!(storage.current_iteration("ROOT"), None);
+
+        storage.create_iteration("ROOT", 1).unwrap();
+        storage.create_iteration("ROOT", 2).unwrap();
+        storage.create_iteration("FEAT", 1).unwrap();
+
+        assert_eq!(storage.current_iteration("ROOT"), Some(2));
+        assert_eq!(storage.current_iteration("FEAT"), Some(1));
+        assert_eq!(storage.current_iteration("OTHER"), None);
+    }
+
+    #[test]
+    fn test_memory_store() {
+        let config = Config::default();
+        let storage = Storage::new(PathBuf::from("/nonexistent"), &config, PathBuf::from(".glow"))
+            .with_store(Box::new(MemoryStore::new()));
+        storage.init_data_dir().unwrap();
+
+        assert_eq!(storage.data_dir(), None);
+        storage.create_iteration("ROOT", 1).unwrap();
+        assert_eq!(storage.current_iteration("ROOT"), Some(1));
+        assert!(!Path::new("/nonexistent").exists());
     }
 }
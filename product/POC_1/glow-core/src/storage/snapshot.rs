@@ -0,0 +1,382 @@
+//! Snapshots of step data
+//!
+//! A snapshot keeps the data of every step, without documents or
+//! artifacts, under a label in the config directory. Comparing two
+//! snapshots, or a snapshot and the current data, tells what changed in the
+//! process between them without relying on version control.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{GlowError, Result};
+use crate::model::{ParameterValue, Step, StepStatus};
+
+use super::atomic::write_atomic;
+
+/// Snapshot folder in the config directory
+pub const SNAPSHOT_DIR: &str = "snapshots";
+
+/// Reference to the current step data in place of a snapshot
+pub const CURRENT_DATA: &str = "now";
+
+/// Step data captured at a point in time
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// Unique ID, from the time and label
+    pub id: String,
+    /// Label given when the snapshot was created
+    pub label: String,
+    /// When the snapshot was created
+    pub created_at: DateTime<Utc>,
+    /// Step data by FQID
+    pub steps: BTreeMap<String, Step>,
+}
+
+impl Snapshot {
+    /// Capture steps under a label, with an ID from the time and label
+    pub fn new(label: &str, created_at: DateTime<Utc>, steps: Vec<Step>) -> Self {
+        let slug: String = label
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '-' })
+            .collect();
+        let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+        let mut id = created_at.format("%Y%m%d-%H%M%S").to_string();
+        if !slug.is_empty() {
+            id = format!("{}-{}", id, slug);
+        }
+
+        Self {
+            id,
+            label: label.to_string(),
+            created_at,
+            steps: steps.into_iter().map(|step| (step.fqid().to_string(), step)).collect(),
+        }
+    }
+
+    /// Get the listing of the snapshot, without its step data
+    pub fn info(&self) -> SnapshotInfo {
+        SnapshotInfo {
+            id: self.id.clone(),
+            label: self.label.clone(),
+            created_at: self.created_at,
+            steps: self.steps.len(),
+        }
+    }
+}
+
+/// Listing of a snapshot
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotInfo {
+    /// Unique ID
+    pub id: String,
+    /// Label given when the snapshot was created
+    pub label: String,
+    /// When the snapshot was created
+    pub created_at: DateTime<Utc>,
+    /// Number of captured steps
+    pub steps: usize,
+}
+
+/// Save a snapshot in a snapshot folder, returning its path
+///
+/// A snapshot taken in the same second under the same label gets a suffix.
+pub fn save(dir: &Path, snapshot: &mut Snapshot) -> Result<PathBuf> {
+    let base = snapshot.id.clone();
+    let mut suffix = 1;
+    while dir.join(format!("{}.json", snapshot.id)).exists() {
+        suffix += 1;
+        snapshot.id = format!("{}-{}", base, suffix);
+    }
+
+    let path = dir.join(format!("{}.json", snapshot.id));
+    write_atomic(&path, serde_json::to_string_pretty(snapshot)?.as_bytes())?;
+    Ok(path)
+}
+
+/// Load the snapshots in a snapshot folder, oldest first
+pub fn load_all(dir: &Path) -> Result<Vec<Snapshot>> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut snapshots = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json") {
+            let content = std::fs::read_to_string(&path).map_err(|source| GlowError::FileReadError {
+                path: path.clone(),
+                source,
+            })?;
+            snapshots.push(serde_json::from_str::<Snapshot>(&content)?);
+        }
+    }
+    snapshots.sort_by(|a, b| (a.created_at, &a.id).cmp(&(b.created_at, &b.id)));
+    Ok(snapshots)
+}
+
+/// Find a snapshot by ID, or the latest one with a label
+pub fn find(dir: &Path, reference: &str) -> Result<Snapshot> {
+    let snapshots = load_all(dir)?;
+    if let Some(snapshot) = snapshots.iter().find(|s| s.id == reference) {
+        return Ok(snapshot.clone());
+    }
+    snapshots
+        .into_iter()
+        .rev()
+        .find(|s| s.label == reference)
+        .ok_or_else(|| GlowError::SnapshotNotFound {
+            reference: reference.to_string(),
+        })
+}
+
+/// Outcome of restoring a snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub struct SnapshotRestore {
+    /// Restored snapshot
+    pub snapshot: SnapshotInfo,
+    /// Snapshot of the data as it was before the restore
+    pub backup: SnapshotInfo,
+    /// Changes made by the restore
+    pub changes: SnapshotDiff,
+}
+
+/// Kind of change of a step between two snapshots
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StepDiffKind {
+    /// The step is new
+    Added,
+    /// The step is gone
+    Removed,
+    /// The step's status or parameter values changed
+    Changed,
+}
+
+/// Change of a parameter value
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ParameterChange {
+    /// Kind of parameter: input, scope or output
+    pub kind: String,
+    /// Parameter ID
+    pub id: String,
+    /// Value before (None if unset)
+    pub from: Option<serde_json::Value>,
+    /// Value after (None if unset)
+    pub to: Option<serde_json::Value>,
+}
+
+/// Change of a step between two snapshots
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StepDiff {
+    /// FQID of the step
+    pub fqid: String,
+    /// Kind of change
+    pub kind: StepDiffKind,
+    /// Status before (None for an added step)
+    pub status_from: Option<StepStatus>,
+    /// Status after (None for a removed step)
+    pub status_to: Option<StepStatus>,
+    /// Changed parameter values
+    pub parameters: Vec<ParameterChange>,
+}
+
+impl StepDiff {
+    /// Check if the step's status changed
+    pub fn status_changed(&self) -> bool {
+        self.status_from != self.status_to
+    }
+}
+
+/// Changes between two snapshots, by FQID
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDiff {
+    /// Snapshot compared from
+    pub from: String,
+    /// Snapshot compared to, or `now` for the current data
+    pub to: String,
+    /// Changed steps, ordered by FQID
+    pub steps: Vec<StepDiff>,
+}
+
+impl SnapshotDiff {
+    /// Compare the steps of two snapshots
+    pub fn between(from: &Snapshot, to: &Snapshot) -> Self {
+        let mut fqids: Vec<&String> = from.steps.keys().chain(to.steps.keys()).collect();
+        fqids.sort();
+        fqids.dedup();
+
+        let steps = fqids
+            .into_iter()
+            .filter_map(|fqid| {
+                let before = from.steps.get(fqid);
+                let after = to.steps.get(fqid);
+                let kind = match (before, after) {
+                    (None, _) => StepDiffKind::Added,
+                    (_, None) => StepDiffKind::Removed,
+                    _ => StepDiffKind::Changed,
+                };
+                let diff = StepDiff {
+                    fqid: fqid.clone(),
+                    kind,
+                    status_from: before.map(Step::status),
+                    status_to: after.map(Step::status),
+                    parameters: parameter_changes(before, after),
+                };
+                let changed = kind != StepDiffKind::Changed || diff.status_changed() || !diff.parameters.is_empty();
+                changed.then_some(diff)
+            })
+            .collect();
+
+        Self {
+            from: from.id.clone(),
+            to: to.id.clone(),
+            steps,
+        }
+    }
+
+    /// Check if nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+/// Get the parameter values that differ between two versions of a step
+fn parameter_changes(before: Option<&Step>, after: Option<&Step>) -> Vec<ParameterChange> {
+    let mut changes = Vec::new();
+    for kind in ["input", "scope", "output"] {
+        let before = before.map_or(&[][..], |step| parameters_of(step, kind));
+        let after = after.map_or(&[][..], |step| parameters_of(step, kind));
+        let value = |params: &[ParameterValue], id: &str| {
+            params.iter().find(|p| p.id == id).and_then(|p| p.value.clone())
+        };
+
+        let mut ids: Vec<&str> = Vec::new();
+        for param in before.iter().chain(after) {
+            if !ids.contains(&param.id.as_str()) {
+                ids.push(&param.id);
+            }
+        }
+        for id in ids {
+            let (from, to) = (value(before, id), value(after, id));
+            if from != to {
+                changes.push(ParameterChange {
+                    kind: kind.to_string(),
+                    id: id.to_string(),
+                    from,
+                    to,
+                });
+            }
+        }
+    }
+    changes
+}
+
+/// Get the parameters of a step of a kind: input, scope or output
+fn parameters_of<'a>(step: &'a Step, kind: &str) -> &'a [ParameterValue] {
+    match kind {
+        "input" => &step.input,
+        "scope" => &step.scope,
+        _ => &step.output,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::StepAttributes;
+
+    fn step(fqid: &str, status: StepStatus, output: Option<&str>) -> Step {
+        Step {
+            attr: StepAttributes {
+                id: fqid.rsplit('.').next().unwrap_or(fqid).to_string(),
+                fqid: Some(fqid.to_string()),
+                status,
+                ..Default::default()
+            },
+            input: Vec::new(),
+            scope: Vec::new(),
+            output: vec![match output {
+                Some(value) => ParameterValue::new("DOC", value.into()),
+                None => ParameterValue::empty("DOC"),
+            }],
+            parent: Vec::new(),
+            own_steps: Vec::new(),
+            links: Vec::new(),
+            extra: serde_json::Map::new(),
+            revision: None,
+        }
+    }
+
+    #[test]
+    fn test_diff_reports_status_steps_and_parameters() {
+        let at = Utc::now();
+        let before = Snapshot::new(
+            "Monday",
+            at,
+            vec![
+                step("FEAT", StepStatus::InProgress, None),
+                step("FEAT.REQ", StepStatus::Todo, None),
+                step("FEAT.OLD", StepStatus::Todo, None),
+                step("OPS", StepStatus::Wait, None),
+            ],
+        );
+        let after = Snapshot::new(
+            "Friday review",
+            at,
+            vec![
+                step("FEAT", StepStatus::InProgress, None),
+                step("FEAT.REQ", StepStatus::Done, Some("docs/req.md")),
+                step("FEAT.NEW", StepStatus::Todo, None),
+                step("OPS", StepStatus::Wait, None),
+            ],
+        );
+        assert!(after.id.ends_with("-friday-review"));
+
+        let diff = SnapshotDiff::between(&before, &after);
+        let fqids: Vec<_> = diff.steps.iter().map(|s| s.fqid.as_str()).collect();
+        assert_eq!(fqids, vec!["FEAT.NEW", "FEAT.OLD", "FEAT.REQ"]);
+
+        assert_eq!(diff.steps[0].kind, StepDiffKind::Added);
+        assert_eq!(diff.steps[1].kind, StepDiffKind::Removed);
+        let req = &diff.steps[2];
+        assert_eq!(req.kind, StepDiffKind::Changed);
+        assert_eq!((req.status_from, req.status_to), (Some(StepStatus::Todo), Some(StepStatus::Done)));
+        assert_eq!(
+            req.parameters,
+            vec![ParameterChange {
+                kind: "output".to_string(),
+                id: "DOC".to_string(),
+                from: None,
+                to: Some("docs/req.md".into()),
+            }]
+        );
+
+        assert!(SnapshotDiff::between(&after, &after).is_empty());
+    }
+
+    #[test]
+    fn test_save_find_and_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let at = Utc::now();
+        let mut first = Snapshot::new("monday", at, vec![step("FEAT", StepStatus::Todo, None)]);
+        let mut second = Snapshot::new("monday", at, Vec::new());
+        save(dir.path(), &mut first).unwrap();
+        save(dir.path(), &mut second).unwrap();
+        assert_eq!(second.id, format!("{}-2", first.id));
+
+        let listed: Vec<_> = load_all(dir.path()).unwrap().iter().map(Snapshot::info).collect();
+        assert_eq!(listed.len(), 2);
+        assert_eq!(listed[0].steps, 1);
+        assert_eq!(find(dir.path(), &first.id).unwrap().steps.len(), 1);
+        assert_eq!(find(dir.path(), "monday").unwrap().id, second.id);
+        assert!(matches!(
+            find(dir.path(), "tuesday"),
+            Err(GlowError::SnapshotNotFound { .. })
+        ));
+    }
+}
@@ -2,23 +2,255 @@
 //!
 //! Handles reading and writing step data files.
 
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use std::time::SystemTime;
 
+use chrono::{DateTime, Utc};
+
+use crate::config::StepFormat;
 use crate::error::{GlowError, Result};
-use crate::model::Step;
+use crate::model::{JournalEntry, Step};
+
+use super::archive::{self, ArchiveStub, ARCHIVE_FILE, ARCHIVE_STUB};
+use super::atomic::{write_atomic, ProjectLock, LOCK_FILE};
+use super::format::{
+    default_body, parse_step_file, render_new_step, render_step_file, render_step_into,
+    Conversion, NOTES_FILE,
+};
+use super::index::{content_hash, FileStamp, StepIndex, StepSummary, INDEX_FILE};
+use super::intent::{Intent, Recovery, INTENT_FILE};
+use super::store::StepStore;
+use super::validation::StepChecker;
+
+/// Journal file in the data directory, one JSON entry per line
+pub const JOURNAL_FILE: &str = "journal.jsonl";
 
-use super::frontmatter::{parse_step_frontmatter, render_step_frontmatter};
+/// Names of the Markdown side files of a step, which are not step files
+const SIDE_FILES: &[&str] = &["description", "summary", "notes"];
+
+/// Writes held back by the operation in progress
+#[derive(Default)]
+struct Operation {
+    /// Number of nested `begin` calls
+    depth: usize,
+    /// Content to write by path (None to delete the file)
+    writes: BTreeMap<PathBuf, Option<Vec<u8>>>,
+    /// Journal lines to append
+    journal: Vec<String>,
+}
 
 /// Manages step data files
 pub struct StepFileManager {
     /// Data directory root
     data_dir: PathBuf,
+    /// Checks step data files on read
+    checker: StepChecker,
+    /// Format of new step data files
+    format: StepFormat,
+    /// Status index, loaded on first use
+    index: Mutex<Option<StepIndex>>,
+    /// Lock on the data directory shared with other processes
+    lock: ProjectLock,
+    /// Operation in progress
+    operation: Mutex<Operation>,
 }
 
 impl StepFileManager {
     /// Create a new step file manager
     pub fn new(data_dir: PathBuf) -> Self {
-        Self { data_dir }
+        Self {
+            lock: ProjectLock::new(&data_dir),
+            data_dir,
+            checker: StepChecker::default(),
+            format: StepFormat::default(),
+            index: Mutex::new(None),
+            operation: Mutex::new(Operation::default()),
+        }
+    }
+
+    /// Use a checker, e.g. one typed from the project's parameter types
+    pub fn with_checker(mut self, checker: StepChecker) -> Self {
+        self.set_checker(checker);
+        self
+    }
+
+    /// Write new step data files in a format
+    pub fn with_format(mut self, format: StepFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Get the format of new step data files
+    pub fn format(&self) -> StepFormat {
+        self.format
+    }
+
+    /// Get the checker used on read
+    pub fn checker(&self) -> &StepChecker {
+        &self.checker
+    }
+
+    /// Replace the checker used on read
+    pub fn set_checker(&mut self, checker: StepChecker) {
+        self.checker = checker;
+        // Reload the index for the new checker's fingerprint
+        *self.index.get_mut().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// Use the status index, loading it first if needed
+    fn with_index<T>(&self, f: impl FnOnce(&mut StepIndex) -> T) -> T {
+        let mut index = self.index.lock().unwrap_or_else(|e| e.into_inner());
+        f(index.get_or_insert_with(|| StepIndex::load(&self.data_dir, self.checker.fingerprint())))
+    }
+
+    fn operation(&self) -> MutexGuard<'_, Operation> {
+        self.operation.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Get the content an operation in progress writes to a file, if it does
+    ///
+    /// Some(None) means the operation deletes the file.
+    fn staged(&self, path: &Path) -> Option<Option<Vec<u8>>> {
+        let operation = self.operation();
+        if operation.depth == 0 {
+            return None;
+        }
+        operation.writes.get(path).cloned()
+    }
+
+    /// Read a data file, as written by the operation in progress (None if missing)
+    fn read_file(&self, path: &Path) -> Result<Option<String>> {
+        let Some(content) = self.read_bytes(path)? else {
+            return Ok(None);
+        };
+        String::from_utf8(content)
+            .map(Some)
+            .map_err(|e| GlowError::FileReadError {
+                path: path.to_path_buf(),
+                source: std::io::Error::new(std::io::ErrorKind::InvalidData, e),
+            })
+    }
+
+    /// Check if a data file exists, as written by the operation in progress
+    fn file_exists(&self, path: &Path) -> bool {
+        match self.staged(path) {
+            Some(content) => content.is_some(),
+            None => path.exists(),
+        }
+    }
+
+    /// Write a data file, held back until the operation in progress is committed
+    fn write_file(&self, path: &Path, content: String) -> Result<()> {
+        self.write_bytes(path, content.into_bytes())
+    }
+
+    /// Delete a data file, held back until the operation in progress is committed
+    fn remove_file(&self, path: &Path) -> Result<()> {
+        let mut operation = self.operation();
+        if operation.depth > 0 {
+            operation.writes.insert(path.to_path_buf(), None);
+            return Ok(());
+        }
+        drop(operation);
+        if path.exists() {
+            std::fs::remove_file(path)?;
+            self.remove_empty_dirs(path);
+        }
+        Ok(())
+    }
+
+    /// Move a data file, held back until the operation in progress is committed
+    fn move_file(&self, from: &Path, to: &Path) -> Result<()> {
+        let Some(content) = self.read_bytes(from)? else {
+            return Ok(());
+        };
+        self.write_bytes(to, content)?;
+        self.remove_file(from)
+    }
+
+    /// Read a data file that may be binary, as written by the operation in progress (None if missing)
+    fn read_bytes(&self, path: &Path) -> Result<Option<Vec<u8>>> {
+        match self.staged(path) {
+            Some(content) => Ok(content),
+            None => match std::fs::read(path) {
+                Ok(content) => Ok(Some(content)),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+                Err(e) => Err(GlowError::FileReadError {
+                    path: path.to_path_buf(),
+                    source: e,
+                }),
+            },
+        }
+    }
+
+    /// Write a data file that may be binary, held back until the operation in progress is committed
+    fn write_bytes(&self, path: &Path, content: Vec<u8>) -> Result<()> {
+        let mut operation = self.operation();
+        if operation.depth > 0 {
+            operation.writes.insert(path.to_path_buf(), Some(content));
+            return Ok(());
+        }
+        drop(operation);
+        write_atomic(path, &content)
+    }
+
+    /// Remove the folders left empty by removing a file, up to the data directory
+    fn remove_empty_dirs(&self, path: &Path) {
+        let mut dir = path.parent();
+        while let Some(folder) = dir.filter(|d| d.starts_with(&self.data_dir) && *d != self.data_dir) {
+            // Fails, and stops, at the first folder that isn't empty
+            if std::fs::remove_dir(folder).is_err() {
+                break;
+            }
+            dir = folder.parent();
+        }
+    }
+
+    /// Get the data files in a folder and its sub-folders, leaving out hidden files
+    fn folder_files(&self, folder: &Path) -> Vec<PathBuf> {
+        walkdir::WalkDir::new(folder)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter(|entry| !entry.file_name().to_string_lossy().starts_with('.'))
+            .map(|entry| entry.into_path())
+            .collect()
+    }
+
+    /// Get the data files of a step: its step file and everything in its folder
+    fn step_tree_files(&self, fqid: &str) -> Vec<PathBuf> {
+        let mut files = self.folder_files(&self.step_folder_path(fqid));
+        let file = self.step_file_path(fqid);
+        if self.file_exists(&file) {
+            files.push(file);
+        }
+        files.sort();
+        files
+    }
+
+    /// Get the FQID of a step data file in the data directory
+    ///
+    /// Documents, artifacts and files in iteration folders are not step files.
+    pub fn step_fqid(&self, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(&self.data_dir).ok()?;
+        StepFormat::of_path(relative)?;
+        let name = relative.file_stem()?.to_str()?;
+        if SIDE_FILES.contains(&name) || name.starts_with('.') {
+            return None;
+        }
+
+        let mut parts = Vec::new();
+        for folder in relative.parent()?.components() {
+            let folder = folder.as_os_str().to_str()?;
+            if folder == "artifacts" || folder.starts_with("iteration_") {
+                return None;
+            }
+            parts.push(folder);
+        }
+        parts.push(name);
+        Some(parts.join("."))
     }
 
     /// Get the data directory
@@ -27,28 +259,26 @@ impl StepFileManager {
     }
 
     /// Convert FQID to file path
+    ///
+    /// Finds the step's data file in any format, or gives the path of a new
+    /// file in the configured format.
     pub fn step_file_path(&self, fqid: &str) -> PathBuf {
-        let parts: Vec<&str> = fqid.split('.').collect();
-        let mut path = self.data_dir.clone();
-
-        // Build path from FQID parts
-        for (i, part) in parts.iter().enumerate() {
-            if i == parts.len() - 1 {
-                // Last part is the file name
-                path.push(format!("{}.md", part));
-            } else {
-                // Intermediate parts are folders
-                path.push(part);
-            }
-        }
+        let folder = self.step_folder_path(fqid);
+        let formats = std::iter::once(self.format)
+            .chain(StepFormat::ALL.into_iter().filter(|f| *f != self.format));
 
-        path
+        formats
+            .map(|format| folder.with_extension(format.extension()))
+            .find(|path| self.file_exists(path))
+            .unwrap_or_else(|| folder.with_extension(self.format.extension()))
     }
 
     /// Get the folder path for a step (for artifacts, sub-steps)
     pub fn step_folder_path(&self, fqid: &str) -> PathBuf {
-        let file_path = self.step_file_path(fqid);
-        file_path.with_extension("")
+        // Intermediate FQID parts are folders, the last one names the step's file and folder
+        let mut path = self.data_dir.clone();
+        path.extend(fqid.split('.'));
+        path
     }
 
     /// Get the artifacts folder path for a step
@@ -61,6 +291,16 @@ impl StepFileManager {
         self.step_folder_path(fqid).join("description.md")
     }
 
+    /// Get the folder path of a process iteration
+    pub fn iteration_folder_path(&self, process_fqid: &str, iteration: u32) -> PathBuf {
+        let iteration_folder = format!("iteration_{:06}", iteration);
+        if process_fqid == "ROOT" {
+            self.data_dir.join(&iteration_folder)
+        } else {
+            self.step_folder_path(process_fqid).join(&iteration_folder)
+        }
+    }
+
     /// Read a step from its data file
     pub fn read_step(&self, fqid: &str) -> Result<Step> {
         let path = self.step_file_path(fqid);
@@ -69,64 +309,104 @@ impl StepFileManager {
 
     /// Read a step from a specific path
     pub fn read_step_from_path(&self, path: &Path, fqid: &str) -> Result<Step> {
-        if !path.exists() {
+        let Some(content) = self.read_file(path)? else {
+            return Err(GlowError::StepNotFound {
+                fqid: fqid.to_string(),
+            });
+        };
+
+        let (mut step, _body) = self.checker.read(path, &content)?;
+        step.revision = Some(content_hash(&content));
+        Ok(step)
+    }
+
+    /// Read what status queries need from a step, through the status index
+    ///
+    /// Steps archived with an iteration are read from the archive's stub.
+    pub fn read_summary(&self, fqid: &str) -> Result<StepSummary> {
+        match self.read_file_summary(fqid) {
+            Err(GlowError::StepNotFound { .. }) => self
+                .archived_summary(fqid)
+                .ok_or_else(|| GlowError::StepNotFound { fqid: fqid.to_string() }),
+            result => result,
+        }
+    }
+
+    /// Read what status queries need from a step's data file, through the status index
+    fn read_file_summary(&self, fqid: &str) -> Result<StepSummary> {
+        let path = self.step_file_path(fqid);
+        if self.staged(&path).is_some() {
+            // Written by the operation in progress, so not indexed yet
+            return self.read_step_from_path(&path, fqid).map(|step| StepSummary::of(&step));
+        }
+        let Some(stamp) = FileStamp::of(&path) else {
             return Err(GlowError::StepNotFound {
                 fqid: fqid.to_string(),
             });
+        };
+        if let Some(summary) = self.with_index(|index| index.lookup(fqid, stamp).cloned()) {
+            return Ok(summary);
         }
 
-        let content = std::fs::read_to_string(path).map_err(|e| GlowError::FileReadError {
-            path: path.to_path_buf(),
+        let content = std::fs::read_to_string(&path).map_err(|e| GlowError::FileReadError {
+            path: path.clone(),
             source: e,
         })?;
+        let hash = content_hash(&content);
+        if let Some(summary) = self.with_index(|index| index.lookup_content(fqid, stamp, &hash)) {
+            return Ok(summary);
+        }
 
-        let (step, _body) = parse_step_frontmatter(&content)?;
-        Ok(step)
+        let (step, _body) = self.checker.read(&path, &content)?;
+        let summary = StepSummary::of(&step);
+        self.with_index(|index| index.insert(fqid, stamp, hash, summary.clone()));
+        Ok(summary)
     }
 
     /// Write a step to its data file
-    pub fn write_step(&self, step: &Step) -> Result<()> {
+    pub fn write_step(&self, step: &mut Step) -> Result<()> {
         let path = self.step_file_path(step.fqid());
-        self.write_step_to_path(step, &path)
+        self.write_step_to_path(step, &path)?;
+        self.with_index(|index| index.remove(step.fqid()));
+        Ok(())
     }
 
     /// Write a step to a specific path
-    pub fn write_step_to_path(&self, step: &Step, path: &Path) -> Result<()> {
-        // Ensure parent directory exists
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent).map_err(|e| GlowError::FileWriteError {
-                path: parent.to_path_buf(),
-                source: e,
-            })?;
-        }
-
-        // Read existing content if file exists
-        let existing_content = if path.exists() {
-            let content = std::fs::read_to_string(path).ok();
-            content.and_then(|c| {
-                super::frontmatter::parse_frontmatter(&c)
-                    .ok()
-                    .map(|(_, body)| body)
-            })
-        } else {
-            None
-        };
+    ///
+    /// Keeps the body and unknown frontmatter keys of an existing file, and
+    /// fails with a conflict if the file changed since the step was read or
+    /// last written. The step's revision is set to the written content.
+    pub fn write_step_to_path(&self, step: &mut Step, path: &Path) -> Result<()> {
+        let existing = self.read_file(path)?;
 
-        let body = existing_content.unwrap_or_else(|| {
-            // Default content template
-            format!(
-                "# {}\n\n{}\n",
-                step.attr.id,
-                step.attr.purpose.as_deref().unwrap_or("TODO: Add description")
-            )
-        });
+        if let Some(revision) = &step.revision {
+            if existing.as_deref().map(content_hash).as_ref() != Some(revision) {
+                return Err(GlowError::StepConflict {
+                    fqid: step.fqid().to_string(),
+                    path: path.to_path_buf(),
+                });
+            }
+        }
 
-        let content = render_step_frontmatter(step, &body)?;
+        let format = StepFormat::of_path(path).unwrap_or(self.format);
+        let content = match existing {
+            Some(existing) => render_step_into(format, step, &existing).map_err(|e| match e {
+                GlowError::InvalidFrontmatter { .. } => GlowError::InvalidFrontmatter {
+                    path: path.to_path_buf(),
+                },
+                e => e,
+            })?,
+            None if format == StepFormat::Markdown => {
+                let body = default_body(&step.attr.id, step.attr.purpose.as_deref());
+                render_new_step(format, step, &body)?
+            }
+            None => render_new_step(format, step, "")?,
+        };
 
-        std::fs::write(path, content).map_err(|e| GlowError::FileWriteError {
-            path: path.to_path_buf(),
-            source: e,
-        })
+        let revision = content_hash(&content);
+        self.write_file(path, content)?;
+        step.revision = Some(revision);
+        Ok(())
     }
 
     /// Create step folder structure
@@ -143,7 +423,26 @@ impl StepFileManager {
 
     /// Check if a step file exists
     pub fn step_exists(&self, fqid: &str) -> bool {
-        self.step_file_path(fqid).exists()
+        self.file_exists(&self.step_file_path(fqid))
+    }
+
+    /// Get the latest modification time of a step's documents and artifacts
+    ///
+    /// The step data file, which glow rewrites itself, and the folders of the
+    /// sub-steps are left out.
+    pub fn last_modified(&self, fqid: &str) -> Option<SystemTime> {
+        let folder = self.step_folder_path(fqid);
+        let documents = SIDE_FILES
+            .iter()
+            .filter_map(|name| std::fs::metadata(folder.join(format!("{}.md", name))).ok()?.modified().ok());
+
+        let artifacts = walkdir::WalkDir::new(self.artifacts_folder_path(fqid))
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .filter_map(|entry| entry.metadata().ok()?.modified().ok());
+
+        documents.chain(artifacts).max()
     }
 
     /// List all step files in a directory
@@ -156,39 +455,648 @@ impl StepFileManager {
         for entry in std::fs::read_dir(dir)? {
             let entry = entry?;
             let path = entry.path();
-            if path.is_file() && path.extension().map_or(false, |e| e == "md") {
-                // Skip special files
+            if path.is_file() && StepFormat::of_path(&path).is_some() {
+                // Skip side files and hidden files
                 let name = path.file_stem().and_then(|n| n.to_str()).unwrap_or("");
-                if !matches!(name, "description" | "summary") {
+                if !SIDE_FILES.contains(&name) && !name.starts_with('.') {
                     steps.push(path);
                 }
             }
         }
 
-        // Sort by file name for consistent ordering
+        // Sort by file name for consistent ordering, one file per step
         steps.sort();
+        steps.dedup_by(|a, b| a.file_stem() == b.file_stem());
         Ok(steps)
     }
 
-    /// Delete a step file
+    /// Convert every step data file to another format
+    ///
+    /// The Markdown body of a step file moves to `notes.md` in the step's
+    /// folder unless it's the default one, and moves back when converting to
+    /// Markdown.
+    pub fn convert_steps(&self, to: StepFormat) -> Result<Conversion> {
+        let mut paths: Vec<PathBuf> = walkdir::WalkDir::new(&self.data_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+            .map(|entry| entry.into_path())
+            .filter(|path| self.step_fqid(path).is_some())
+            .collect();
+        paths.sort();
+
+        let mut conversion = Conversion::default();
+        for path in paths {
+            let Some(from) = StepFormat::of_path(&path).filter(|from| *from != to) else {
+                continue;
+            };
+            let content = self.read_file(&path)?.unwrap_or_default();
+            let (data, body) = parse_step_file(from, &content)?;
+            if data.is_null() {
+                return Err(GlowError::InvalidFrontmatter { path });
+            }
+
+            let attr = |key: &str| data.get("attr")?.get(key)?.as_str().map(String::from);
+            let default = default_body(&attr("id").unwrap_or_default(), attr("purpose").as_deref());
+            let notes_path = path.with_extension("").join(NOTES_FILE);
+            let body = if to == StepFormat::Markdown {
+                match self.read_file(&notes_path)? {
+                    Some(notes) => {
+                        self.remove_file(&notes_path)?;
+                        conversion.notes += 1;
+                        notes
+                    }
+                    None => default,
+                }
+            } else {
+                if !body.trim().is_empty() && body.trim() != default.trim() {
+                    self.write_file(&notes_path, body)?;
+                    conversion.notes += 1;
+                }
+                String::new()
+            };
+
+            let content = render_step_file(to, &data, &body)?;
+            self.write_file(&path.with_extension(to.extension()), content)?;
+            self.remove_file(&path)?;
+            conversion.converted += 1;
+        }
+        Ok(conversion)
+    }
+
+    /// Delete a step file with the step's folder
     pub fn delete_step(&self, fqid: &str) -> Result<()> {
-        let path = self.step_file_path(fqid);
-        if path.exists() {
-            std::fs::remove_file(&path)?;
+        for path in self.step_tree_files(fqid) {
+            self.remove_file(&path)?;
+        }
+        self.with_index(|index| index.remove(fqid));
+        Ok(())
+    }
+
+    /// Move a step file with the step's folder to another FQID
+    ///
+    /// Refused while the step or its sub-steps have archived iterations:
+    /// the archives hold the FQIDs and paths the steps had when archived.
+    pub fn move_step(&self, from: &str, to: &str) -> Result<()> {
+        if self.step_exists(to) {
+            return Err(GlowError::StepAlreadyExists { fqid: to.to_string() });
         }
 
-        // Also remove folder if empty
-        let folder_path = self.step_folder_path(fqid);
-        if folder_path.exists() {
-            if let Ok(entries) = std::fs::read_dir(&folder_path) {
-                if entries.count() == 0 {
-                    std::fs::remove_dir(&folder_path)?;
+        let files = self.step_tree_files(from);
+        let archived: Vec<String> = files
+            .iter()
+            .filter(|path| path.file_name().is_some_and(|n| n == ARCHIVE_STUB))
+            .filter_map(|path| path.parent()?.strip_prefix(&self.data_dir).ok())
+            .map(|folder| folder.to_string_lossy().replace('\\', "/"))
+            .collect();
+        if !archived.is_empty() {
+            return Err(GlowError::ValidationError {
+                message: format!(
+                    "'{}' has archived iterations ({}); restore them before moving it",
+                    from,
+                    archived.join(", ")
+                ),
+            });
+        }
+
+        let from_folder = self.step_folder_path(from);
+        let to_folder = self.step_folder_path(to);
+        for path in files {
+            let target = match path.strip_prefix(&from_folder) {
+                Ok(relative) => to_folder.join(relative),
+                // The step file next to the folder keeps its format
+                Err(_) => to_folder.with_extension(path.extension().unwrap_or_default()),
+            };
+            self.move_file(&path, &target)?;
+        }
+        self.with_index(|index| index.remove(from));
+        Ok(())
+    }
+
+    /// Pack a process iteration, with the data of the given sub-steps, into an archive
+    ///
+    /// The archive, its stub and the removal of the packed files are written
+    /// with the operation.
+    pub fn archive_iteration(
+        &self,
+        process_fqid: &str,
+        iteration: u32,
+        steps: &[String],
+        archived_at: DateTime<Utc>,
+    ) -> Result<ArchiveStub> {
+        let folder = self.iteration_folder_path(process_fqid, iteration);
+        if !folder.is_dir() {
+            return Err(GlowError::IterationNotFound {
+                fqid: process_fqid.to_string(),
+                iteration,
+            });
+        }
+        let stub_path = folder.join(ARCHIVE_STUB);
+        if self.file_exists(&stub_path) {
+            return Err(GlowError::ValidationError {
+                message: format!("Iteration {} of '{}' is already archived", iteration, process_fqid),
+            });
+        }
+
+        let mut paths = self.folder_files(&folder);
+        let mut summaries = Vec::new();
+        for fqid in steps {
+            summaries.push(self.read_summary(fqid)?);
+            paths.extend(self.step_tree_files(fqid));
+        }
+        let mut files = Vec::new();
+        for path in &paths {
+            let relative = path.strip_prefix(&self.data_dir).unwrap_or(path);
+            let content = self.read_bytes(path)?.unwrap_or_default();
+            files.push((relative.to_string_lossy().replace('\\', "/"), content));
+        }
+        let (archive, manifest) = archive::pack(process_fqid, iteration, archived_at, steps.to_vec(), &files)?;
+        let sha256 = content_hash(&archive);
+        self.write_bytes(&folder.join(ARCHIVE_FILE), archive)?;
+
+        for path in &paths {
+            self.remove_file(path)?;
+        }
+        for fqid in steps {
+            self.with_index(|index| index.remove(fqid));
+        }
+        let stub = ArchiveStub {
+            process: process_fqid.to_string(),
+            iteration,
+            archived_at,
+            archive: ARCHIVE_FILE.to_string(),
+            sha256,
+            files: manifest.files.len(),
+            steps: summaries,
+        };
+        self.write_file(&stub_path, format!("{}\n", serde_json::to_string_pretty(&stub)?))?;
+        Ok(stub)
+    }
+
+    /// Unpack an archived iteration back in place, after verifying it
+    pub fn restore_iteration(&self, process_fqid: &str, iteration: u32) -> Result<ArchiveStub> {
+        let (stub, archive_path, archive) = self.read_archive(process_fqid, iteration)?;
+        let problems = archive::verify(&stub, &archive);
+        if !problems.is_empty() {
+            return Err(GlowError::ArchiveCorrupt {
+                path: archive_path,
+                problems,
+            });
+        }
+
+        let (manifest, files) = archive::unpack(&archive)?;
+        if let Some(fqid) = manifest.steps.iter().find(|fqid| self.step_exists(fqid)) {
+            return Err(GlowError::StepAlreadyExists { fqid: fqid.clone() });
+        }
+        for (path, content) in files {
+            self.write_bytes(&self.data_dir.join(path), content)?;
+        }
+        self.remove_file(&archive_path.with_file_name(ARCHIVE_STUB))?;
+        self.remove_file(&archive_path)?;
+        Ok(stub)
+    }
+
+    /// Check an archived iteration against its checksums, returning the problems found
+    pub fn verify_archive(&self, process_fqid: &str, iteration: u32) -> Result<Vec<String>> {
+        let (stub, _, archive) = self.read_archive(process_fqid, iteration)?;
+        Ok(archive::verify(&stub, &archive))
+    }
+
+    /// List the stubs of the archived iterations of a process, oldest first
+    pub fn archived_iterations(&self, process_fqid: &str) -> Result<Vec<ArchiveStub>> {
+        let dir = match process_fqid {
+            "ROOT" => self.data_dir.clone(),
+            fqid => self.step_folder_path(fqid),
+        };
+        let mut folders: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter(|path| path.file_name().and_then(|n| n.to_str()).is_some_and(|n| n.starts_with("iteration_")))
+            .collect();
+        folders.sort();
+
+        let mut stubs = Vec::new();
+        for folder in folders {
+            if let Some(content) = self.read_file(&folder.join(ARCHIVE_STUB))? {
+                stubs.push(serde_json::from_str(&content)?);
+            }
+        }
+        Ok(stubs)
+    }
+
+    /// Get the summary of a step archived with an iteration of its parent, the latest one
+    fn archived_summary(&self, fqid: &str) -> Option<StepSummary> {
+        let parent_fqid = fqid.rsplit_once('.').map_or("ROOT", |(parent, _)| parent);
+        self.archived_iterations(parent_fqid)
+            .ok()?
+            .iter()
+            .rev()
+            .find_map(|stub| stub.step(fqid).cloned())
+    }
+
+    /// Read the stub, path and content of the archive of an iteration
+    fn read_archive(&self, process_fqid: &str, iteration: u32) -> Result<(ArchiveStub, PathBuf, Vec<u8>)> {
+        let folder = self.iteration_folder_path(process_fqid, iteration);
+        let Some(content) = self.read_file(&folder.join(ARCHIVE_STUB))? else {
+            return Err(GlowError::ValidationError {
+                message: format!("Iteration {} of '{}' is not archived", iteration, process_fqid),
+            });
+        };
+        let stub: ArchiveStub = serde_json::from_str(&content)?;
+        let archive_path = folder.join(&stub.archive);
+        let archive = self.read_bytes(&archive_path)?.ok_or_else(|| GlowError::ArchiveCorrupt {
+            path: archive_path.clone(),
+            problems: vec![format!("{} is missing", stub.archive)],
+        })?;
+        Ok((stub, archive_path, archive))
+    }
+}
+
+impl StepStore for StepFileManager {
+    fn data_dir(&self) -> Option<&Path> {
+        Some(&self.data_dir)
+    }
+
+    fn set_checker(&mut self, checker: StepChecker) {
+        StepFileManager::set_checker(self, checker)
+    }
+
+    fn set_step_format(&mut self, format: StepFormat) {
+        self.format = format;
+    }
+
+    fn init(&self) -> Result<()> {
+        std::fs::create_dir_all(&self.data_dir)?;
+
+        // Create description.md placeholder
+        let description_path = self.data_dir.join("description.md");
+        if !self.file_exists(&description_path) {
+            self.write_file(
+                &description_path,
+                "# Project Description\n\nTODO: Add project description.\n".to_string(),
+            )?;
+        }
+
+        // Create summary.md placeholder
+        let summary_path = self.data_dir.join("summary.md");
+        if !self.file_exists(&summary_path) {
+            self.write_file(&summary_path, "# Process Summary\n\nNo iterations yet.\n".to_string())?;
+        }
+
+        // Keep the status index, lock and intent record out of version control
+        let gitignore_path = self.data_dir.join(".gitignore");
+        if !self.file_exists(&gitignore_path) {
+            self.write_file(
+                &gitignore_path,
+                format!("{}\n{}\n{}\n", INDEX_FILE, LOCK_FILE, INTENT_FILE),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn read_step(&self, fqid: &str) -> Result<Step> {
+        StepFileManager::read_step(self, fqid)
+    }
+
+    fn read_summary(&self, fqid: &str) -> Result<StepSummary> {
+        StepFileManager::read_summary(self, fqid)
+    }
+
+    fn write_step(&self, step: &mut Step) -> Result<()> {
+        StepFileManager::write_step(self, step)
+    }
+
+    fn step_exists(&self, fqid: &str) -> bool {
+        StepFileManager::step_exists(self, fqid)
+    }
+
+    fn delete_step(&self, fqid: &str) -> Result<()> {
+        StepFileManager::delete_step(self, fqid)
+    }
+
+    fn move_step(&self, from: &str, to: &str) -> Result<()> {
+        StepFileManager::move_step(self, from, to)
+    }
+
+    fn list_steps(&self, parent_fqid: Option<&str>) -> Result<Vec<String>> {
+        let dir = match parent_fqid {
+            Some(parent) => self.step_folder_path(parent),
+            None => self.data_dir.clone(),
+        };
+        let steps = self
+            .list_steps_in_dir(&dir)?
+            .iter()
+            .filter_map(|path| path.file_stem().and_then(|n| n.to_str()))
+            .map(|id| match parent_fqid {
+                Some(parent) => format!("{}.{}", parent, id),
+                None => id.to_string(),
+            })
+            .collect();
+        Ok(steps)
+    }
+
+    fn read_document(&self, fqid: &str, name: &str) -> Result<Option<String>> {
+        self.read_file(&self.step_folder_path(fqid).join(name))
+    }
+
+    fn write_document(&self, fqid: &str, name: &str, content: &str) -> Result<()> {
+        self.write_file(&self.step_folder_path(fqid).join(name), content.to_string())
+    }
+
+    fn create_iteration(&self, process_fqid: &str, iteration: u32) -> Result<()> {
+        let path = self.iteration_folder_path(process_fqid, iteration);
+
+        // Create iteration summary
+        let summary_path = path.join("summary.md");
+        if !self.file_exists(&summary_path) {
+            self.write_file(
+                &summary_path,
+                format!("# Iteration {} Summary\n\nIn progress.\n", iteration),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn current_iteration(&self, process_fqid: &str) -> Option<u32> {
+        let dir = if process_fqid == "ROOT" {
+            self.data_dir.clone()
+        } else {
+            self.step_folder_path(process_fqid)
+        };
+
+        let iteration_of = |folder: &Path| {
+            folder
+                .file_name()?
+                .to_str()?
+                .strip_prefix("iteration_")?
+                .parse::<u32>()
+                .ok()
+        };
+
+        // Iterations created by the operation in progress have no folder yet
+        let staged = {
+            let operation = self.operation();
+            operation
+                .writes
+                .keys()
+                .filter_map(|path| path.parent())
+                .filter(|folder| folder.parent() == Some(dir.as_path()))
+                .filter_map(iteration_of)
+                .max()
+        };
+
+        let existing = std::fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.is_dir())
+            .filter_map(|path| iteration_of(&path))
+            .max();
+
+        existing.max(staged)
+    }
+
+    fn write_iteration_document(
+        &self,
+        process_fqid: &str,
+        iteration: u32,
+        name: &str,
+        content: &str,
+    ) -> Result<()> {
+        let path = self.iteration_folder_path(process_fqid, iteration).join(name);
+        self.write_file(&path, content.to_string())
+    }
+
+    fn read_iteration_document(
+        &self,
+        process_fqid: &str,
+        iteration: u32,
+        name: &str,
+    ) -> Result<Option<String>> {
+        self.read_file(&self.iteration_folder_path(process_fqid, iteration).join(name))
+    }
+
+    fn list_artifacts(&self, fqid: &str) -> Result<Vec<String>> {
+        let dir = self.artifacts_folder_path(fqid);
+        let mut names = BTreeMap::new();
+        if dir.exists() {
+            for entry in std::fs::read_dir(&dir)? {
+                let entry = entry?;
+                if entry.path().is_file() {
+                    names.extend(entry.file_name().to_str().map(|name| (name.to_string(), true)));
                 }
             }
         }
 
+        // Artifacts written or deleted by the operation in progress
+        let operation = self.operation();
+        let staged = operation.writes.iter().filter(|(path, _)| path.parent() == Some(dir.as_path()));
+        for (path, content) in staged {
+            names.extend(path.file_name().and_then(|n| n.to_str()).map(|name| (name.to_string(), content.is_some())));
+        }
+        Ok(names.into_iter().filter(|(_, exists)| *exists).map(|(name, _)| name).collect())
+    }
+
+    fn read_artifact(&self, fqid: &str, name: &str) -> Result<Vec<u8>> {
+        let path = self.artifacts_folder_path(fqid).join(name);
+        self.read_bytes(&path)?.ok_or_else(|| GlowError::ArtifactNotFound {
+            fqid: fqid.to_string(),
+            name: name.to_string(),
+        })
+    }
+
+    fn write_artifact(&self, fqid: &str, name: &str, content: &[u8]) -> Result<()> {
+        self.write_bytes(&self.artifacts_folder_path(fqid).join(name), content.to_vec())
+    }
+
+    fn delete_artifact(&self, fqid: &str, name: &str) -> Result<()> {
+        let path = self.artifacts_folder_path(fqid).join(name);
+        if !self.file_exists(&path) {
+            return Err(GlowError::ArtifactNotFound {
+                fqid: fqid.to_string(),
+                name: name.to_string(),
+            });
+        }
+        self.remove_file(&path)
+    }
+
+    fn last_modified(&self, fqid: &str) -> Option<SystemTime> {
+        StepFileManager::last_modified(self, fqid)
+    }
+
+    fn append_journal(&self, entry: &JournalEntry) -> Result<()> {
+        use std::io::Write;
+
+        let path = self.data_dir.join(JOURNAL_FILE);
+        let line = serde_json::to_string(entry).map_err(|e| GlowError::JsonParseError {
+            message: e.to_string(),
+            source: e,
+        })?;
+        let mut operation = self.operation();
+        if operation.depth > 0 {
+            operation.journal.push(line);
+            return Ok(());
+        }
+        drop(operation);
+
+        std::fs::create_dir_all(&self.data_dir)?;
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| GlowError::FileWriteError { path: path.clone(), source: e })?;
+        writeln!(file, "{}", line).map_err(|e| GlowError::FileWriteError { path, source: e })
+    }
+
+    fn read_journal(&self) -> Result<Vec<JournalEntry>> {
+        let path = self.data_dir.join(JOURNAL_FILE);
+        let content = if path.exists() {
+            std::fs::read_to_string(&path)
+                .map_err(|e| GlowError::FileReadError { path: path.clone(), source: e })?
+        } else {
+            String::new()
+        };
+        let staged = self.operation().journal.clone();
+        content
+            .lines()
+            .chain(staged.iter().map(String::as_str))
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                serde_json::from_str(line).map_err(|e| GlowError::JsonParseError {
+                    message: format!("{}: {}", path.display(), e),
+                    source: e,
+                })
+            })
+            .collect()
+    }
+
+    fn lock(&self, exclusive: bool) -> Result<()> {
+        self.lock.acquire(exclusive)
+    }
+
+    fn unlock(&self) {
+        self.lock.release()
+    }
+
+    fn begin(&self) -> Result<()> {
+        self.operation().depth += 1;
+        Ok(())
+    }
+
+    fn commit(&self) -> Result<()> {
+        let (writes, journal) = {
+            let mut operation = self.operation();
+            operation.depth = operation.depth.saturating_sub(1);
+            if operation.depth > 0 {
+                return Ok(());
+            }
+            (
+                std::mem::take(&mut operation.writes),
+                std::mem::take(&mut operation.journal),
+            )
+        };
+
+        let written: Vec<String> = writes.keys().filter_map(|path| self.step_fqid(path)).collect();
+        let removed: Vec<PathBuf> = writes
+            .iter()
+            .filter(|(_, content)| content.is_none())
+            .map(|(path, _)| path.clone())
+            .collect();
+        let intent = Intent::record(&self.data_dir, writes, Path::new(JOURNAL_FILE), journal)?;
+        if intent.is_empty() {
+            return Ok(());
+        }
+
+        // Record the intent first, so an interrupted apply can be recovered
+        intent.save(&self.data_dir)?;
+        let applied = intent.apply(&self.data_dir);
+        self.with_index(|index| written.iter().for_each(|fqid| index.remove(fqid)));
+        if let Err(e) = applied {
+            // Leave the intent for recovery on the next load if undoing fails too
+            if intent.revert(&self.data_dir).is_ok() {
+                Intent::clear(&self.data_dir)?;
+            }
+            return Err(e);
+        }
+        Intent::clear(&self.data_dir)?;
+        removed.iter().for_each(|path| self.remove_empty_dirs(path));
         Ok(())
     }
+
+    fn abort(&self) {
+        let mut operation = self.operation();
+        operation.depth = operation.depth.saturating_sub(1);
+        if operation.depth == 0 {
+            operation.writes.clear();
+            operation.journal.clear();
+        }
+    }
+
+    fn recover(&self) -> Result<Option<Recovery>> {
+        self.lock.acquire(true)?;
+        let recovery = super::intent::recover(&self.data_dir);
+        self.lock.release();
+        let recovery = recovery?;
+        if recovery.is_some() {
+            self.with_index(|index| index.clear());
+        }
+        Ok(recovery)
+    }
+
+    fn convert_steps(&self, to: StepFormat) -> Result<Conversion> {
+        StepFileManager::convert_steps(self, to)
+    }
+
+    fn archive_iteration(
+        &self,
+        process_fqid: &str,
+        iteration: u32,
+        steps: &[String],
+        archived_at: DateTime<Utc>,
+    ) -> Result<ArchiveStub> {
+        StepFileManager::archive_iteration(self, process_fqid, iteration, steps, archived_at)
+    }
+
+    fn restore_iteration(&self, process_fqid: &str, iteration: u32) -> Result<ArchiveStub> {
+        StepFileManager::restore_iteration(self, process_fqid, iteration)
+    }
+
+    fn verify_archive(&self, process_fqid: &str, iteration: u32) -> Result<Vec<String>> {
+        StepFileManager::verify_archive(self, process_fqid, iteration)
+    }
+
+    fn archived_iterations(&self, process_fqid: &str) -> Result<Vec<ArchiveStub>> {
+        StepFileManager::archived_iterations(self, process_fqid)
+    }
+
+    fn flush(&self) -> Result<()> {
+        self.with_index(|index| index.save())
+    }
+
+    fn rebuild_index(&self) -> Result<usize> {
+        self.with_index(|index| index.clear());
+
+        for entry in walkdir::WalkDir::new(&self.data_dir)
+            .into_iter()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().is_file())
+        {
+            let Some(fqid) = self.step_fqid(entry.path()) else {
+                continue;
+            };
+            // Invalid step files are reported when read
+            let _ = self.read_summary(&fqid);
+        }
+
+        self.flush()?;
+        Ok(self.with_index(|index| index.len()))
+    }
 }
 
 #[cfg(test)]
@@ -206,6 +1114,7 @@ mod tests {
                 purpose: Some("Test step".to_string()),
                 expectations: None,
                 status: StepStatus::Wait,
+                ..Default::default()
             },
             input: Vec::new(),
             scope: Vec::new(),
@@ -213,6 +1122,8 @@ mod tests {
             parent: Vec::new(),
             own_steps: Vec::new(),
             links: Vec::new(),
+            extra: serde_json::Map::new(),
+            revision: None,
         }
     }
 
@@ -245,8 +1156,8 @@ mod tests {
         let temp = tempdir().unwrap();
         let manager = StepFileManager::new(temp.path().to_path_buf());
 
-        let step = create_test_step("FEAT-001", "FEAT-001");
-        manager.write_step(&step).unwrap();
+        let mut step = create_test_step("FEAT-001", "FEAT-001");
+        manager.write_step(&mut step).unwrap();
 
         assert!(manager.step_exists("FEAT-001"));
 
@@ -255,6 +1166,28 @@ mod tests {
         assert_eq!(loaded.attr.status, StepStatus::Wait);
     }
 
+    #[test]
+    fn test_steps_in_any_format() {
+        let temp = tempdir().unwrap();
+        let manager = StepFileManager::new(temp.path().to_path_buf()).with_format(StepFormat::Toml);
+
+        // New steps use the configured format, existing ones are found in any format
+        manager.write_step(&mut create_test_step("A", "A")).unwrap();
+        std::fs::write(
+            temp.path().join("B.json"),
+            r#"{"attr": {"id": "B", "status": "todo"}}"#,
+        )
+        .unwrap();
+        assert_eq!(manager.step_file_path("A"), temp.path().join("A.toml"));
+        assert_eq!(manager.read_step("B").unwrap().attr.status, StepStatus::Todo);
+
+        let conversion = manager.convert_steps(StepFormat::Yaml).unwrap();
+        assert_eq!(conversion, Conversion { converted: 2, notes: 0 });
+        assert_eq!(manager.step_file_path("B"), temp.path().join("B.yaml"));
+        assert_eq!(manager.read_step("B").unwrap().attr.id, "B");
+        assert!(!temp.path().join("A.toml").exists());
+    }
+
     #[test]
     fn test_nested_step() {
         let temp = tempdir().unwrap();
@@ -263,10 +1196,142 @@ mod tests {
         // Create parent folder first
         std::fs::create_dir_all(temp.path().join("FEAT-001")).unwrap();
 
-        let step = create_test_step("REQ-001", "FEAT-001.REQ-001");
-        manager.write_step(&step).unwrap();
+        let mut step = create_test_step("REQ-001", "FEAT-001.REQ-001");
+        manager.write_step(&mut step).unwrap();
 
         let loaded = manager.read_step("FEAT-001.REQ-001").unwrap();
         assert_eq!(loaded.attr.id, "REQ-001");
     }
+
+    #[test]
+    fn test_step_fqid() {
+        let temp = tempdir().unwrap();
+        let manager = StepFileManager::new(temp.path().to_path_buf());
+
+        for fqid in ["ROOT", "FEAT-001", "FEAT-001.REQ-001.TASK-001"] {
+            assert_eq!(manager.step_fqid(&manager.step_file_path(fqid)).as_deref(), Some(fqid));
+        }
+        for skipped in [
+            "description.md",
+            "FEAT-001/summary.md",
+            "FEAT-001/artifacts/notes.md",
+            "iteration_000001/summary.md",
+            "journal.jsonl",
+        ] {
+            assert_eq!(manager.step_fqid(&temp.path().join(skipped)), None);
+        }
+    }
+
+    #[test]
+    fn test_operation_applies_writes_together() {
+        let temp = tempdir().unwrap();
+        let manager = StepFileManager::new(temp.path().to_path_buf());
+
+        manager.begin().unwrap();
+        manager.write_step(&mut create_test_step("A", "A")).unwrap();
+        manager.write_document("A", "summary.md", "Done").unwrap();
+        manager.create_iteration("ROOT", 1).unwrap();
+
+        // Reads in the operation see its writes; the files wait for the commit
+        assert!(manager.step_exists("A"));
+        assert_eq!(manager.read_summary("A").unwrap().status(), StepStatus::Wait);
+        assert_eq!(manager.current_iteration("ROOT"), Some(1));
+        assert!(!temp.path().join("A.md").exists());
+
+        manager.commit().unwrap();
+        assert_eq!(manager.read_step("A").unwrap().attr.id, "A");
+        assert_eq!(manager.read_document("A", "summary.md").unwrap().as_deref(), Some("Done"));
+        assert!(!temp.path().join(INTENT_FILE).exists());
+
+        // An aborted operation writes nothing
+        manager.begin().unwrap();
+        manager.write_step(&mut create_test_step("B", "B")).unwrap();
+        manager.abort();
+        assert!(!manager.step_exists("B"));
+    }
+
+    #[test]
+    fn test_operation_stages_artifacts() {
+        let temp = tempdir().unwrap();
+        let manager = StepFileManager::new(temp.path().to_path_buf());
+        let logo = vec![0x89, 0x50, 0x4e, 0x47, 0x00, 0xff];
+        manager.write_artifact("A", "old.png", &logo).unwrap();
+
+        manager.begin().unwrap();
+        manager.write_artifact("A", "logo.png", &logo).unwrap();
+        manager.delete_artifact("A", "old.png").unwrap();
+        assert_eq!(manager.list_artifacts("A").unwrap(), vec!["logo.png"]);
+        assert_eq!(manager.read_artifact("A", "logo.png").unwrap(), logo);
+        assert!(!temp.path().join("A/artifacts/logo.png").exists());
+
+        // An aborted operation leaves the artifacts as they were
+        manager.abort();
+        assert_eq!(manager.list_artifacts("A").unwrap(), vec!["old.png"]);
+
+        manager.begin().unwrap();
+        manager.write_artifact("A", "logo.png", &logo).unwrap();
+        manager.delete_artifact("A", "old.png").unwrap();
+        manager.commit().unwrap();
+        assert_eq!(manager.list_artifacts("A").unwrap(), vec!["logo.png"]);
+        assert_eq!(std::fs::read(temp.path().join("A/artifacts/logo.png")).unwrap(), logo);
+        assert!(!temp.path().join(".intent.0.blob").exists());
+    }
+
+    #[test]
+    fn test_recover_interrupted_operation() {
+        let temp = tempdir().unwrap();
+        let manager = StepFileManager::new(temp.path().to_path_buf());
+        manager.write_step(&mut create_test_step("A", "A")).unwrap();
+
+        // A process recorded its intent, then stopped before writing the files
+        let mut done = create_test_step("A", "A");
+        done.attr.status = StepStatus::Done;
+        let content = render_new_step(StepFormat::Markdown, &done, "# A\n").unwrap();
+        let intent = Intent::record(
+            temp.path(),
+            vec![(manager.step_file_path("A"), Some(content.into_bytes()))],
+            Path::new(JOURNAL_FILE),
+            Vec::new(),
+        )
+        .unwrap();
+        intent.save(temp.path()).unwrap();
+
+        let manager = StepFileManager::new(temp.path().to_path_buf());
+        assert_eq!(manager.recover().unwrap(), Some(Recovery::RolledForward { files: 1 }));
+        assert_eq!(manager.read_step("A").unwrap().attr.status, StepStatus::Done);
+        assert_eq!(manager.recover().unwrap(), None);
+    }
+
+    #[test]
+    fn test_write_step_detects_conflicting_edit() {
+        let temp = tempdir().unwrap();
+        let manager = StepFileManager::new(temp.path().to_path_buf());
+        manager.write_step(&mut create_test_step("A", "A")).unwrap();
+
+        // Writing again what was read, even twice, is no conflict
+        let mut step = manager.read_step("A").unwrap();
+        let mut stale = step.clone();
+        step.attr.status = StepStatus::Todo;
+        manager.write_step(&mut step).unwrap();
+        manager.write_step(&mut step).unwrap();
+        assert_eq!(step.revision, manager.read_step("A").unwrap().revision);
+
+        // A copy read before another write is stale
+        stale.attr.status = StepStatus::Done;
+        assert!(matches!(
+            manager.write_step(&mut stale),
+            Err(GlowError::StepConflict { .. })
+        ));
+
+        // An edit made in between is not overwritten
+        let mut step = manager.read_step("A").unwrap();
+        let path = manager.step_file_path("A");
+        let edited = std::fs::read_to_string(&path).unwrap().replace("Test step", "Edited");
+        std::fs::write(&path, &edited).unwrap();
+        assert!(matches!(
+            manager.write_step(&mut step),
+            Err(GlowError::StepConflict { .. })
+        ));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), edited);
+    }
 }
@@ -0,0 +1,203 @@
+//! Step store
+//!
+//! Backend-neutral access to a project's step data: steps, their documents
+//! and artifacts, process iterations and the journal.
+
+use std::path::Path;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Utc};
+
+use crate::config::StepFormat;
+use crate::error::{GlowError, Result};
+use crate::model::{JournalEntry, Step};
+
+use super::archive::ArchiveStub;
+use super::format::Conversion;
+use super::index::StepSummary;
+use super::intent::Recovery;
+use super::validation::StepChecker;
+
+/// Backend holding a project's step data
+///
+/// Steps are addressed by FQID. Documents (description, summary) and
+/// artifacts belong to a step; iterations belong to a process step, where
+/// `ROOT` stands for the project itself.
+pub trait StepStore: Send + Sync {
+    /// Get the directory holding the data, if the store keeps it in files
+    fn data_dir(&self) -> Option<&Path> {
+        None
+    }
+
+    /// Check steps read from their files (stores not parsing files ignore it)
+    fn set_checker(&mut self, _checker: StepChecker) {}
+
+    /// Write new step data files in a format (stores not keeping files ignore it)
+    fn set_step_format(&mut self, _format: StepFormat) {}
+
+    /// Create the project-level data structure
+    fn init(&self) -> Result<()>;
+
+    /// Read a step
+    fn read_step(&self, fqid: &str) -> Result<Step>;
+
+    /// Read what status queries need from a step
+    fn read_summary(&self, fqid: &str) -> Result<StepSummary> {
+        self.read_step(fqid).map(|step| StepSummary::of(&step))
+    }
+
+    /// Write a step, refreshing its revision
+    fn write_step(&self, step: &mut Step) -> Result<()>;
+
+    /// Check if a step exists
+    fn step_exists(&self, fqid: &str) -> bool;
+
+    /// Delete a step with its documents, artifacts, iterations and sub-steps
+    fn delete_step(&self, fqid: &str) -> Result<()>;
+
+    /// Move a step with its documents, artifacts, iterations and sub-steps to another FQID
+    ///
+    /// The step data is moved as is; the caller rewrites the FQIDs in it.
+    fn move_step(&self, from: &str, to: &str) -> Result<()>;
+
+    /// List the FQIDs of the steps directly under a step (None for the top level)
+    fn list_steps(&self, parent_fqid: Option<&str>) -> Result<Vec<String>>;
+
+    /// Read a document of a step (None if missing)
+    fn read_document(&self, fqid: &str, name: &str) -> Result<Option<String>>;
+
+    /// Write a document of a step
+    fn write_document(&self, fqid: &str, name: &str, content: &str) -> Result<()>;
+
+    /// Create an iteration of a process with a placeholder summary
+    fn create_iteration(&self, process_fqid: &str, iteration: u32) -> Result<()>;
+
+    /// Get the latest iteration number of a process
+    fn current_iteration(&self, process_fqid: &str) -> Option<u32>;
+
+    /// Write a document of a process iteration
+    fn write_iteration_document(
+        &self,
+        process_fqid: &str,
+        iteration: u32,
+        name: &str,
+        content: &str,
+    ) -> Result<()>;
+
+    /// Read a document of a process iteration (None if missing)
+    fn read_iteration_document(
+        &self,
+        process_fqid: &str,
+        iteration: u32,
+        name: &str,
+    ) -> Result<Option<String>>;
+
+    /// List the artifact names of a step
+    fn list_artifacts(&self, fqid: &str) -> Result<Vec<String>>;
+
+    /// Read an artifact of a step
+    fn read_artifact(&self, fqid: &str, name: &str) -> Result<Vec<u8>>;
+
+    /// Write an artifact of a step
+    fn write_artifact(&self, fqid: &str, name: &str, content: &[u8]) -> Result<()>;
+
+    /// Delete an artifact of a step
+    fn delete_artifact(&self, fqid: &str, name: &str) -> Result<()>;
+
+    /// Get the latest change to a step's documents or artifacts
+    fn last_modified(&self, fqid: &str) -> Option<SystemTime>;
+
+    /// Append an entry to the journal
+    fn append_journal(&self, entry: &JournalEntry) -> Result<()>;
+
+    /// Read the journal, oldest entry first
+    fn read_journal(&self) -> Result<Vec<JournalEntry>>;
+
+    /// Take the project lock, shared for reading or exclusive for changes
+    ///
+    /// Waits while another process holds it. Nested calls only count; pair
+    /// each call with `unlock`. Stores not shared between processes ignore it.
+    fn lock(&self, _exclusive: bool) -> Result<()> {
+        Ok(())
+    }
+
+    /// Release the project lock taken by `lock`
+    fn unlock(&self) {}
+
+    /// Start an operation whose writes are applied together by `commit`
+    ///
+    /// Reads during the operation see its writes. Nested calls join the
+    /// outermost operation. Stores writing at once ignore it.
+    fn begin(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Apply the writes of the operation started by `begin`
+    fn commit(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Discard the writes of the operation started by `begin`
+    fn abort(&self) {}
+
+    /// Complete or undo an operation a previous process was interrupted in
+    fn recover(&self) -> Result<Option<Recovery>> {
+        Ok(None)
+    }
+
+    /// Convert every step data file to another format
+    ///
+    /// Stores not keeping step data files have nothing to convert.
+    fn convert_steps(&self, _to: StepFormat) -> Result<Conversion> {
+        Ok(Conversion::default())
+    }
+
+    /// Pack a process iteration, with the data of the given sub-steps, into an archive
+    ///
+    /// The iteration folder keeps the archive and a stub through which the
+    /// archived sub-steps still count. Stores not keeping step data files
+    /// have nothing to archive.
+    fn archive_iteration(
+        &self,
+        process_fqid: &str,
+        _iteration: u32,
+        _steps: &[String],
+        _archived_at: DateTime<Utc>,
+    ) -> Result<ArchiveStub> {
+        Err(no_archives(process_fqid))
+    }
+
+    /// Unpack an archived iteration back in place, after verifying it
+    fn restore_iteration(&self, process_fqid: &str, _iteration: u32) -> Result<ArchiveStub> {
+        Err(no_archives(process_fqid))
+    }
+
+    /// Check an archived iteration against its checksums, returning the problems found
+    fn verify_archive(&self, process_fqid: &str, _iteration: u32) -> Result<Vec<String>> {
+        Err(no_archives(process_fqid))
+    }
+
+    /// List the stubs of the archived iterations of a process
+    fn archived_iterations(&self, _process_fqid: &str) -> Result<Vec<ArchiveStub>> {
+        Ok(Vec::new())
+    }
+
+    /// Persist cached data, such as a status index
+    fn flush(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Rebuild the status index from scratch, returning the number of indexed steps
+    ///
+    /// Stores without an index have nothing to rebuild.
+    fn rebuild_index(&self) -> Result<usize> {
+        Ok(0)
+    }
+}
+
+/// Error for archiving in a store without step data files
+fn no_archives(process_fqid: &str) -> GlowError {
+    GlowError::ValidationError {
+        message: format!("Iterations of '{}' are not kept in files and can't be archived", process_fqid),
+    }
+}
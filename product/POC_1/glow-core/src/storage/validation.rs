@@ -0,0 +1,293 @@
+//! Step data file validation
+//!
+//! Checks the data of step data files against the step data schema, typed
+//! from the project's parameter types, and reports every problem with its
+//! key path, and its line and column in Markdown and YAML files.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use jsonschema::error::ValidationErrorKind;
+use jsonschema::JSONSchema;
+use serde_json::Value;
+
+use crate::config::diagnostics::ConfigDiagnostic;
+use crate::config::schema::{project_step_data_schema, step_data_schema};
+use crate::config::{ProcessConfig, SourceMap, SourcePosition, StepFormat, StepValidation};
+use crate::error::{GlowError, Result};
+use crate::model::Step;
+
+use super::frontmatter::split_frontmatter;
+
+/// Checks step data files on read
+#[derive(Clone)]
+pub struct StepChecker {
+    schema: Arc<JSONSchema>,
+    validation: StepValidation,
+    fingerprint: String,
+}
+
+impl Default for StepChecker {
+    fn default() -> Self {
+        Self::new(None, StepValidation::default())
+    }
+}
+
+impl StepChecker {
+    /// Create a checker for a project's process definition
+    ///
+    /// Without a process config, parameter values are not typed.
+    pub fn new(process_config: Option<&ProcessConfig>, validation: StepValidation) -> Self {
+        let schema = match process_config {
+            Some(process_config) => project_step_data_schema(process_config),
+            None => step_data_schema(),
+        };
+        let compiled = JSONSchema::compile(&schema).expect("generated step data schema compiles");
+        let fingerprint = super::index::content_hash(format!("{}{:?}", schema, validation));
+        Self {
+            schema: Arc::new(compiled),
+            validation,
+            fingerprint,
+        }
+    }
+
+    /// Get a hash of the schema and validation mode, identifying what passes the checks
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Get the validation mode
+    pub fn validation(&self) -> StepValidation {
+        self.validation
+    }
+
+    /// Parse and check a step data file, returning the step and the body
+    pub fn read(&self, file: &Path, content: &str) -> Result<(Step, String)> {
+        match self.diagnose(file, content) {
+            (Some(step), diagnostics) if diagnostics.is_empty() => Ok(step),
+            (_, diagnostics) => Err(GlowError::InvalidStepFile {
+                diagnostics: diagnostics.iter().map(|d| d.to_string()).collect(),
+            }),
+        }
+    }
+
+    /// Parse and check a step data file, collecting every problem found
+    pub fn diagnose(
+        &self,
+        file: &Path,
+        content: &str,
+    ) -> (Option<(Step, String)>, Vec<ConfigDiagnostic>) {
+        let diagnostic = |path: &str, position: SourcePosition, message: String| ConfigDiagnostic {
+            file: file.to_path_buf(),
+            position,
+            path: path.to_string(),
+            message,
+        };
+
+        let format = StepFormat::of_path(file).unwrap_or_default();
+        let (first_line, data, body) = match format {
+            StepFormat::Markdown => match split_frontmatter(content) {
+                Ok(Some(parts)) => parts,
+                Ok(None) => {
+                    let message = "missing frontmatter (expected a '---' block)".to_string();
+                    return (None, vec![diagnostic("", SourcePosition::START, message)]);
+                }
+                Err(_) => {
+                    let message = "frontmatter is not closed with '---'".to_string();
+                    return (None, vec![diagnostic("", SourcePosition::START, message)]);
+                }
+            },
+            _ => (0, content, ""),
+        };
+
+        let parsed = match format {
+            StepFormat::Markdown | StepFormat::Yaml => serde_yaml::from_str::<Value>(data).map_err(|e| {
+                let position = e.location().map(|l| SourcePosition {
+                    line: first_line + l.line(),
+                    column: l.column(),
+                });
+                (position, e.to_string())
+            }),
+            StepFormat::Json => serde_json::from_str::<Value>(data).map_err(|e| {
+                let position = SourcePosition {
+                    line: e.line(),
+                    column: e.column(),
+                };
+                (Some(position), e.to_string())
+            }),
+            StepFormat::Toml => toml::from_str::<Value>(data).map_err(|e| {
+                let position = e.span().map(|span| position_of_offset(data, span.start));
+                (position, e.message().to_string())
+            }),
+        };
+        let value = match parsed {
+            Ok(value) => value,
+            Err((position, message)) => {
+                let position = position.unwrap_or(SourcePosition::START);
+                return (None, vec![diagnostic("", position, message)]);
+            }
+        };
+
+        // Pad the outline so positions count from the start of the file; only
+        // YAML is outlined, so keys in JSON and TOML files are not located
+        let map = matches!(format, StepFormat::Markdown | StepFormat::Yaml)
+            .then(|| SourceMap::new(&format!("{}{}", "\n".repeat(first_line), data)));
+        let locate = |pointer: &str| {
+            map.as_ref()
+                .map_or(SourcePosition::START, |map| map.locate_pointer(pointer))
+        };
+        let mut diagnostics = Vec::new();
+        if let Err(errors) = self.schema.validate(&value) {
+            for error in errors {
+                let pointer = error.instance_path.to_string();
+                match &error.kind {
+                    ValidationErrorKind::AdditionalProperties { unexpected } => {
+                        if self.validation == StepValidation::Lenient
+                            && (pointer.is_empty() || pointer == "/attr")
+                        {
+                            continue;
+                        }
+                        for key in unexpected {
+                            let pointer = format!("{}/{}", pointer, key);
+                            let path = key_path(&pointer);
+                            diagnostics.push(diagnostic(
+                                &path,
+                                locate(&pointer),
+                                format!("{}: unknown key", path),
+                            ));
+                        }
+                    }
+                    _ => {
+                        let path = key_path(&pointer);
+                        let message = if path.is_empty() {
+                            error.to_string()
+                        } else {
+                            format!("{}: {}", path, error)
+                        };
+                        diagnostics.push(diagnostic(&path, locate(&pointer), message));
+                    }
+                }
+            }
+        }
+        if !diagnostics.is_empty() {
+            return (None, diagnostics);
+        }
+
+        match serde_json::from_value::<Step>(value) {
+            Ok(step) => (Some((step, body.to_string())), diagnostics),
+            Err(e) => {
+                diagnostics.push(diagnostic(
+                    "",
+                    SourcePosition {
+                        line: first_line + 1,
+                        column: 1,
+                    },
+                    e.to_string(),
+                ));
+                (None, diagnostics)
+            }
+        }
+    }
+}
+
+/// Get the line and column of a byte offset in a file
+fn position_of_offset(content: &str, offset: usize) -> SourcePosition {
+    let before = &content[..offset.min(content.len())];
+    let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+    SourcePosition {
+        line: before.matches('\n').count() + 1,
+        column: before[line_start..].chars().count() + 1,
+    }
+}
+
+/// Convert a JSON pointer to a key path (e.g., "/output/0/value" to "output[0].value")
+fn key_path(pointer: &str) -> String {
+    let mut path = String::new();
+    for segment in pointer.split('/').filter(|s| !s.is_empty()) {
+        if segment.parse::<usize>().is_ok() {
+            path.push_str(&format!("[{}]", segment));
+        } else {
+            if !path.is_empty() {
+                path.push('.');
+            }
+            path.push_str(segment);
+        }
+    }
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process_config() -> ProcessConfig {
+        serde_yaml::from_str(
+            r#"
+version: "0.2.0"
+parameter_types:
+  - id: EFFORT
+    data_type: INT
+root_process:
+  id: ROOT
+  steps:
+    - id: TASK
+      outputs:
+        - id: EFFORT
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_diagnostics_give_position_path_and_expected_value() {
+        let checker = StepChecker::new(Some(&process_config()), StepValidation::Strict);
+        let content = "---\nattr:\n  id: TASK\n  fqid: TASK\n  status: complete\noutput:\n  - id: EFFORT\n    value: lots\n---\n\n# TASK\n";
+
+        let (step, diagnostics) = checker.diagnose(Path::new("glow/TASK.md"), content);
+        assert!(step.is_none());
+        assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+
+        let status = diagnostics
+            .iter()
+            .find(|d| d.path == "attr.status")
+            .unwrap();
+        assert_eq!(status.position, SourcePosition { line: 5, column: 3 });
+        assert!(
+            status.message.contains("\"in-progress\""),
+            "{}",
+            status.message
+        );
+        assert!(status
+            .to_string()
+            .starts_with("glow/TASK.md:5:3: attr.status: \"complete\""));
+
+        let value = diagnostics
+            .iter()
+            .find(|d| d.path == "output[0].value")
+            .unwrap();
+        assert_eq!(value.position.line, 8);
+    }
+
+    #[test]
+    fn test_unknown_keys_strict_and_lenient() {
+        let content = "---\nattr:\n  id: TASK\n  owner: ana\nreviewed: true\n---\n";
+
+        let strict = StepChecker::new(None, StepValidation::Strict);
+        let err = strict.read(Path::new("TASK.md"), content).unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("TASK.md:4:3: attr.owner: unknown key"));
+        assert!(err
+            .to_string()
+            .contains("TASK.md:5:1: reviewed: unknown key"));
+
+        let lenient = StepChecker::new(None, StepValidation::Lenient);
+        let (step, _) = lenient.read(Path::new("TASK.md"), content).unwrap();
+        assert_eq!(step.attr.extra["owner"], "ana");
+        assert_eq!(step.extra["reviewed"], true);
+
+        let rendered = super::super::format::render_new_step(StepFormat::Markdown, &step, "").unwrap();
+        assert!(rendered.contains("owner: ana"));
+        assert!(rendered.contains("reviewed: true"));
+    }
+}
@@ -156,6 +156,8 @@ pub struct TemplateEngine {
     handlebars: Handlebars<'static>,
     /// Templates directory
     templates_dir: PathBuf,
+    /// Directory searched when a template is not in `templates_dir`
+    fallback_dir: Option<PathBuf>,
     /// Loaded templates cache
     loaded_templates: HashMap<String, bool>,
 }
@@ -175,18 +177,28 @@ impl TemplateEngine {
         Self {
             handlebars,
             templates_dir,
+            fallback_dir: None,
             loaded_templates: HashMap::new(),
         }
     }
 
+    /// Also look for templates in another directory (e.g., the user's)
+    pub fn with_fallback_dir(mut self, dir: Option<PathBuf>) -> Self {
+        self.fallback_dir = dir;
+        self
+    }
+
     /// Load a template from file
     pub fn load_template(&mut self, name: &str) -> Result<()> {
         if self.loaded_templates.contains_key(name) {
             return Ok(());
         }
 
-        let template_path = self.templates_dir.join(name);
-        if template_path.exists() {
+        let template_path = std::iter::once(&self.templates_dir)
+            .chain(self.fallback_dir.as_ref())
+            .map(|dir| dir.join(name))
+            .find(|path| path.exists());
+        if let Some(template_path) = template_path {
             let content =
                 std::fs::read_to_string(&template_path).map_err(|e| GlowError::FileReadError {
                     path: template_path.clone(),
@@ -310,6 +322,7 @@ mod tests {
                 purpose: Some("Test feature".to_string()),
                 expectations: Some("Feature works".to_string()),
                 status: StepStatus::Todo,
+                ..Default::default()
             },
             input: vec![ParameterValue::new(
                 "REQUIREMENT",
@@ -323,6 +336,8 @@ mod tests {
             parent: Vec::new(),
             own_steps: Vec::new(),
             links: Vec::new(),
+            extra: serde_json::Map::new(),
+            revision: None,
         }
     }
 
@@ -52,14 +52,14 @@ impl SubsetPicker {
         let parts: Vec<&str> = selector_str.split(':').filter(|s| !s.is_empty()).collect();
 
         for part in parts {
-            if part.starts_with('!') {
+            if let Some(excluded) = part.strip_prefix('!') {
                 // Exclusion selector
-                if let Some(level) = Self::parse_level_selector(&part[1..]) {
+                if let Some(level) = Self::parse_level_selector(excluded) {
                     selectors.push(Selector::Exclusion(level));
                 }
-            } else if part.starts_with('+') {
+            } else if let Some(added) = part.strip_prefix('+') {
                 // Additive selector
-                if let Some(level) = Self::parse_level_selector(&part[1..]) {
+                if let Some(level) = Self::parse_level_selector(added) {
                     selectors.push(Selector::Additive(level));
                 }
             } else {
@@ -80,10 +80,10 @@ impl SubsetPicker {
         }
 
         // Check for regex pattern
-        if s.starts_with('/') {
-            if let Some(end_slash) = s[1..].find('/') {
-                let pattern = &s[1..end_slash + 1];
-                let flags = &s[end_slash + 2..];
+        if let Some(body) = s.strip_prefix('/') {
+            if let Some(end_slash) = body.find('/') {
+                let pattern = &body[..end_slash];
+                let flags = &body[end_slash + 1..];
                 return Some(LevelSelector::Regex(pattern.to_string(), flags.to_string()));
             }
         }
@@ -280,7 +280,7 @@ impl SubsetPicker {
                     if let Some(sub) = sub_selector {
                         // Apply sub-selector to children
                         if item_code.starts_with(&format!("{}.", code)) {
-                            let matches = self.apply_level_selector(&[v.clone()], sub);
+                            let matches = self.apply_level_selector(std::slice::from_ref(v), sub);
                             result.extend(matches);
                         } else {
                             result.push(v.clone());
@@ -1,5 +1,7 @@
 //! Common test utilities
 
+#![allow(dead_code)]
+
 use std::fs;
 use std::path::{Path, PathBuf};
 use tempfile::TempDir;
@@ -0,0 +1,102 @@
+//! Status index benchmark
+//!
+//! Generates a 5,000-step project and compares status queries reading every
+//! step file with queries served from the status index. The benchmark is
+//! ignored by default; run it with `cargo test -- --ignored`.
+
+mod common;
+
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use common::TestProject;
+use glow_core::engine::operations::ProcessEngine;
+use glow_core::model::StepStatus;
+use glow_core::storage::INDEX_FILE;
+
+/// Create a project of `processes` processes with `tasks` chained tasks each
+fn generate_project(processes: usize, tasks: usize) -> TestProject {
+    let project = TestProject::new("benchmark");
+    project.write_file(
+        ".glow/config.yaml",
+        "version: \"0.1.0\"\nproject_name: \"Benchmark\"\ndata_folder: \"glow\"\n",
+    );
+
+    let mut process_config = String::from("version: \"0.1.0\"\nroot_process:\n  id: ROOT\n  steps:\n");
+    for p in 0..processes {
+        writeln!(process_config, "    - id: P{:02}\n      steps:", p).unwrap();
+        for t in 0..tasks {
+            writeln!(process_config, "        - id: T{:03}", t).unwrap();
+        }
+        process_config.push_str("      links:\n");
+        for t in 1..tasks {
+            writeln!(
+                process_config,
+                "        - {{ type: dependency, from: T{:03}, to: T{:03} }}",
+                t,
+                t - 1
+            )
+            .unwrap();
+        }
+    }
+    project.write_file(".glow/process_config.yaml", &process_config);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf()).expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    for p in 0..processes {
+        engine
+            .init_step(&format!("P{:02}", p), vec![], false)
+            .expect("Failed to init process");
+    }
+
+    project
+}
+
+/// Time a status query on a freshly loaded engine
+fn time_status(project: &TestProject) -> (Duration, usize) {
+    let engine = ProcessEngine::new(project.path().to_path_buf()).expect("Failed to load project");
+    let started = Instant::now();
+    let tree = engine.get_status_tree().expect("Failed to get status tree");
+    let progress = engine.get_progress().expect("Failed to get progress");
+    engine.get_next_actions().expect("Failed to get next actions");
+    let elapsed = started.elapsed();
+    assert_eq!(progress.total, tree.flatten().len());
+    (elapsed, progress.total)
+}
+
+#[test]
+#[ignore = "benchmark; run with --ignored"]
+fn test_status_index_benchmark() {
+    let project = generate_project(50, 100);
+    let index = project.path().join("glow").join(INDEX_FILE);
+
+    // Cold: every step file is read, checked and indexed
+    std::fs::remove_file(&index).ok();
+    let (cold, total) = time_status(&project);
+    assert_eq!(total, 1 + 50 * (1 + 100));
+    assert!(index.exists());
+
+    // Warm: statuses come from the index
+    let (warm, _) = time_status(&project);
+    assert!(warm < cold, "indexed queries ({:?}) should beat reading every file ({:?})", warm, cold);
+}
+
+#[test]
+fn test_status_index_rereads_edited_files() {
+    let project = generate_project(3, 5);
+    let (_, total) = time_status(&project);
+    assert_eq!(total, 1 + 3 * (1 + 5));
+    assert!(project.path().join("glow").join(INDEX_FILE).exists());
+
+    // A hand-edited step file is re-read
+    let file = project.path().join("glow/P01/T000.md");
+    let content = std::fs::read_to_string(&file).unwrap();
+    std::fs::write(&file, content.replace("status: todo", "status: in-progress")).unwrap();
+    let engine = ProcessEngine::new(project.path().to_path_buf()).unwrap();
+    let tree = engine.get_status_tree().unwrap();
+    let node = tree.flatten().into_iter().find(|n| n.fqid == "P01.T000").unwrap();
+    assert_eq!(node.status, StepStatus::InProgress);
+
+    // Rebuilding indexes every step file
+    assert_eq!(engine.rebuild_index().unwrap(), total);
+}
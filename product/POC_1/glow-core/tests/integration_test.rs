@@ -6,8 +6,13 @@
 mod common;
 
 use common::TestProject;
+use glow_core::config::Preset;
+use chrono::TimeZone;
 use glow_core::engine::operations::ProcessEngine;
+use glow_core::engine::FixedClock;
+use glow_core::error::GlowError;
 use glow_core::model::StepStatus;
+use glow_core::storage::{MemoryStore, Recovery, StepFileManager, INTENT_FILE};
 
 #[test]
 fn test_project_initialization() {
@@ -23,6 +28,27 @@ fn test_project_initialization() {
     assert!(project.path().join("glow").exists());
 }
 
+#[test]
+fn test_project_initialization_from_preset() {
+    let project = TestProject::empty();
+    let preset = Preset::find("research-spike").expect("Failed to find preset");
+
+    let mut engine = ProcessEngine::init_project_with_preset(project.path().to_path_buf(), None, Some(&preset))
+        .expect("Failed to initialize project");
+    assert_eq!(engine.process_config().root_process.steps[0].id, "SPIKE");
+    assert!(project.path().join(".glow/templates/spike.md").exists());
+
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.init_step("SPIKE", vec![], false).expect("Failed to init SPIKE");
+    let description = std::fs::read_to_string(project.path().join("glow/SPIKE/description.md"))
+        .expect("Failed to read description");
+    assert!(description.contains("## Time Box"));
+
+    // The written process config loads like any hand-written one
+    let reloaded = ProcessEngine::new(project.path().to_path_buf()).expect("Failed to load project");
+    assert_eq!(reloaded.process_config().parameter_types.len(), 5);
+}
+
 #[test]
 fn test_root_step_initialization() {
     let project = TestProject::with_tasktrack_config();
@@ -167,3 +193,939 @@ fn test_validation() {
     // Initial state may have low completeness
     assert!(report.completeness >= 0.0);
 }
+
+#[test]
+fn test_estimate_weighted_progress_and_forecast() {
+    use glow_core::model::{Estimate, ParameterValue};
+
+    let project = TestProject::with_tasktrack_config();
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.start_step("ROOT").expect("Failed to start ROOT");
+    engine.init_step(
+        "FEAT",
+        vec![
+            ParameterValue::new("FEATURE_ID", serde_json::json!("001")),
+            ParameterValue::new("FEATURE_NAME", serde_json::json!("User Management")),
+        ],
+        false,
+    ).expect("Failed to init FEAT");
+    engine.start_step("FEAT").expect("Failed to start FEAT");
+
+    // No history yet: forecast has no dates
+    let forecasts = engine.get_forecast().expect("Failed to get forecast");
+    assert!(forecasts.iter().any(|f| f.fqid == "ROOT"));
+    assert!(forecasts.iter().all(|f| f.expected.is_none()));
+
+    // REQ is big, the rest are small
+    engine.set_estimate("FEAT.REQ", Some(Estimate::Duration("2d".to_string())))
+        .expect("Failed to set estimate");
+    for fqid in ["FEAT.DESIGN", "FEAT.IMPL", "FEAT.TEST"] {
+        engine.set_estimate(fqid, Some(Estimate::Duration("0h".to_string())))
+            .expect("Failed to set estimate");
+    }
+
+    // Points and durations can't be added up
+    let mixed = engine.set_estimate("FEAT.TEST", Some(Estimate::Points(1.0)));
+    assert!(matches!(mixed, Err(GlowError::ValidationError { .. })));
+
+    engine.start_step("FEAT.REQ").expect("Failed to start FEAT.REQ");
+    let req = engine.finish_step("FEAT.REQ", vec![], None)
+        .expect("Failed to finish FEAT.REQ");
+    assert!(req.attr.started_at.is_some());
+    assert!(req.attr.finished_at.is_some());
+
+    let progress = engine.get_progress().expect("Failed to get progress");
+    assert_eq!(progress.estimated, progress.leaves);
+    assert_eq!(progress.completion_percentage(), 100.0);
+
+    // History now exists: forecast has dates for the feature process
+    let forecasts = engine.get_forecast().expect("Failed to get forecast");
+    let feat = forecasts.iter().find(|f| f.fqid == "FEAT").expect("No FEAT forecast");
+    assert_eq!(feat.iteration, Some(1));
+    assert!(feat.expected.is_some());
+    assert!(feat.earliest <= feat.expected && feat.expected <= feat.latest);
+}
+
+#[test]
+fn test_next_actions_ranked_with_explanation() {
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(".glow/process_config.yaml", r#"
+version: "0.1.0"
+classifications:
+  - id: priority
+    values:
+      - key: Must
+      - key: Should
+      - key: Could
+root_process:
+  id: ROOT
+  steps:
+    - id: LOW
+      purpose: "Nice to have"
+      classification: "Could"
+    - id: CORE
+      purpose: "Core service"
+      classification: "Should"
+    - id: UI
+      purpose: "User interface"
+      classification: "Must"
+    - id: DOCS
+      purpose: "Documentation"
+      classification: "Could"
+      due: "2020-01-01"
+  links:
+    - type: dependency
+      from: UI
+      to: CORE
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.start_step("ROOT").expect("Failed to start ROOT");
+
+    let actions = engine.get_next_actions().expect("Failed to get next actions");
+    let order: Vec<&str> = actions.iter().map(|a| a.fqid.as_str()).collect();
+    assert_eq!(order, vec!["DOCS", "CORE", "LOW"]);
+
+    assert!(actions[0].why.starts_with("overdue by"));
+    assert!(actions[1].why.contains("on critical path"));
+    assert!(actions[1].why.contains("unblocks 1"));
+    assert!(actions.windows(2).all(|w| w[0].score >= w[1].score));
+}
+
+#[test]
+fn test_overdue_and_iteration_close_carries_over() {
+    use chrono::NaiveDate;
+    use glow_core::engine::OverdueKind;
+    use glow_core::model::{Estimate, ParameterValue, Timebox};
+
+    let project = TestProject::with_tasktrack_config();
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.start_step("ROOT").expect("Failed to start ROOT");
+    engine.init_step(
+        "FEAT",
+        vec![
+            ParameterValue::new("FEATURE_ID", serde_json::json!("001")),
+            ParameterValue::new("FEATURE_NAME", serde_json::json!("User Management")),
+        ],
+        false,
+    ).expect("Failed to init FEAT");
+    engine.start_step("FEAT").expect("Failed to start FEAT");
+
+    let date = |s: &str| s.parse::<NaiveDate>().unwrap();
+    engine.set_timebox("FEAT", Some(Timebox::new(date("2020-01-01"), date("2020-01-14"))))
+        .expect("Failed to set time box");
+    engine.set_due("FEAT.DESIGN", Some(date("2020-01-10"))).expect("Failed to set due date");
+    engine.set_estimate("FEAT.DESIGN", Some(Estimate::Points(5.0)))
+        .expect("Failed to set estimate");
+
+    // Time boxes only apply to processes
+    assert!(engine.set_timebox("FEAT.REQ", None).is_err());
+
+    let overdue = engine.get_overdue().expect("Failed to get overdue");
+    assert!(overdue.iter().any(|o| o.fqid == "FEAT"
+        && matches!(o.kind, OverdueKind::Iteration { number: Some(1), .. })));
+    assert!(overdue.iter().any(|o| o.fqid == "FEAT.DESIGN"
+        && matches!(o.kind, OverdueKind::Step { .. })));
+
+    let report = engine.validate(None).expect("Failed to validate");
+    assert!(report.warnings.iter().any(|w| w.contains("FEAT.DESIGN: overdue by")));
+
+    engine.start_step("FEAT.REQ").expect("Failed to start FEAT.REQ");
+    engine.finish_step("FEAT.REQ", vec![], None).expect("Failed to finish FEAT.REQ");
+
+    let close = engine.close_iteration("FEAT", None).expect("Failed to close iteration");
+    assert_eq!(close.closed, 1);
+    assert_eq!(close.opened, 2);
+    assert_eq!(close.done, vec!["FEAT.REQ"]);
+    assert_eq!(close.carried_over, vec!["FEAT.DESIGN", "FEAT.IMPL", "FEAT.TEST"]);
+    assert_eq!(
+        close.timebox,
+        Some(Timebox::new(date("2020-01-15"), date("2020-01-28")))
+    );
+
+    // Carried steps keep their data
+    let design = engine.show_step("FEAT.DESIGN").expect("Failed to read FEAT.DESIGN");
+    assert_eq!(design.status(), StepStatus::Todo);
+    assert_eq!(design.attr.estimate, Some(Estimate::Points(5.0)));
+    assert_eq!(design.attr.due, Some(date("2020-01-10")));
+    assert_eq!(design.attr.carried_over, 1);
+
+    // The finished sub-step is recorded with the iteration it was done in
+    let feat = engine.show_step("FEAT").expect("Failed to read FEAT");
+    assert_eq!(feat.attr.iteration, Some(2));
+    let iterations: Vec<(&str, Option<u32>)> =
+        feat.own_steps.iter().map(|s| (s.id.as_str(), s.iteration)).collect();
+    assert_eq!(iterations, vec![("REQ", Some(1)), ("DESIGN", None), ("IMPL", None), ("TEST", None)]);
+
+    let summary = project.read_file("glow/FEAT/iteration_000001/summary.md");
+    assert!(summary.contains("- FEAT.REQ"));
+    assert!(summary.contains("## Carried over to iteration 2"));
+    assert!(project.file_exists("glow/FEAT/iteration_000002"));
+
+    // The next close only lists what was done in the new iteration
+    let close = engine.close_iteration("FEAT", None).expect("Failed to close iteration 2");
+    assert_eq!(close.closed, 2);
+    assert!(close.done.is_empty());
+    assert_eq!(close.carried_over, vec!["FEAT.DESIGN", "FEAT.IMPL", "FEAT.TEST"]);
+
+    // Initializing the process again opens an iteration after the existing ones
+    let feat = engine.init_step("FEAT", vec![], true).expect("Failed to init FEAT again");
+    assert_eq!(feat.attr.iteration, Some(4));
+    assert!(project.file_exists("glow/FEAT/iteration_000004"));
+}
+
+#[test]
+fn test_wip_limits_block_starts_and_prefer_finishing() {
+    use glow_core::engine::operations::ActionType;
+    use glow_core::engine::StartOptions;
+    use glow_core::error::GlowError;
+
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(".glow/process_config.yaml", r#"
+version: "0.1.0"
+wip_limits:
+  - process: ROOT
+    max: 1
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+      purpose: "First task"
+    - id: B
+      purpose: "Second task"
+    - id: C
+      purpose: "Third task"
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.start_step("ROOT").expect("Failed to start ROOT");
+    engine.start_step("A").expect("Failed to start A");
+
+    let err = engine.start_step("B").expect_err("WIP limit should block B");
+    match err {
+        GlowError::WipLimitExceeded { step_id, active, .. } => {
+            assert_eq!(step_id, "B");
+            assert_eq!(active, vec!["A"]);
+        }
+        other => panic!("Unexpected error: {}", other),
+    }
+
+    // Finishing comes before starting more
+    let actions = engine.get_next_actions().expect("Failed to get next actions");
+    assert_eq!(actions[0].fqid, "A");
+    assert!(matches!(actions[0].action_type, ActionType::Finish));
+    assert!(actions[1..].iter().all(|a| a.why.starts_with("WIP limit reached")));
+
+    // An override starts anyway
+    engine.start_step_with("B", StartOptions { override_wip_limits: true })
+        .expect("Override should start B");
+    assert_eq!(engine.active_steps().expect("Failed to get active steps"), vec!["ROOT", "A", "B"]);
+
+    engine.finish_step("A", vec![], None).expect("Failed to finish A");
+    engine.finish_step("B", vec![], None).expect("Failed to finish B");
+    engine.start_step("C").expect("C should start once the limit frees up");
+}
+
+/// Move a step's last activity into the past
+fn age_step(engine: &ProcessEngine, fqid: &str, days: i64) {
+    let then = chrono::Utc::now() - chrono::Duration::days(days);
+    let mut step = engine.storage().read_step(fqid).expect("Failed to read step");
+    step.attr.status_since = Some(then);
+    engine.storage().write_step(&mut step).expect("Failed to write step");
+
+    let data_dir = engine.storage().data_dir().expect("Steps are kept in files");
+    let step_files = StepFileManager::new(data_dir.to_path_buf());
+    let folder_files = std::fs::read_dir(step_files.step_folder_path(fqid))
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file());
+    for path in std::iter::once(step_files.step_file_path(fqid)).chain(folder_files) {
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(then.into()))
+            .expect("Failed to set modification time");
+    }
+}
+
+#[test]
+fn test_stale_work_detection() {
+    use glow_core::engine::StaleKind;
+
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(".glow/process_config.yaml", r#"
+version: "0.1.0"
+staleness:
+  in_progress_days: 5
+  wait_days: 5
+  todo_days: 30
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+      purpose: "Task in progress"
+    - id: B
+      purpose: "Fresh task"
+    - id: P
+      purpose: "Sub-process"
+      steps:
+        - id: X
+    - id: W
+      purpose: "Waits on P"
+  links:
+    - from: W
+      to: P
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.start_step("ROOT").expect("Failed to start ROOT");
+    engine.start_step("A").expect("Failed to start A");
+
+    assert!(engine.get_stale_work().expect("Failed to get stale work").is_empty());
+
+    age_step(&engine, "ROOT", 10);
+    age_step(&engine, "A", 10);
+    age_step(&engine, "B", 10);
+    age_step(&engine, "W", 10);
+
+    // Glow rewriting a step's data file is no activity
+    let mut a = engine.storage().read_step("A").expect("Failed to read A");
+    engine.storage().write_step(&mut a).expect("Failed to write A");
+
+    let stale = engine.get_stale_work().expect("Failed to get stale work");
+    let fqids: Vec<&str> = stale.iter().map(|s| s.fqid.as_str()).collect();
+    assert_eq!(fqids, vec!["ROOT", "A", "W"]);
+    assert_eq!(stale[1].kind, StaleKind::InProgressIdle);
+    assert!(stale[1].remediation.contains("glow finish A"));
+    assert_eq!(
+        stale[2].kind,
+        StaleKind::WaitingOnUninitialized { dependencies: vec!["P".to_string()] }
+    );
+    assert!(stale[2].remediation.contains("glow init P"));
+
+    let report = engine.validate(None).expect("Failed to validate");
+    assert!(report.warnings.iter().any(|w| w.starts_with("Stale: A:")));
+
+    // Working on a step's artifacts counts as activity
+    engine
+        .storage()
+        .store()
+        .write_artifact("A", "notes.md", b"progress")
+        .expect("Failed to write artifact");
+    assert!(project.file_exists("glow/A/artifacts/notes.md"));
+    let stale = engine.get_stale_work().expect("Failed to get stale work");
+    assert!(stale.iter().all(|s| s.fqid != "A"));
+}
+
+#[test]
+fn test_check_config_of_tasktrack_process() {
+    let project = TestProject::with_tasktrack_config();
+    let engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+
+    let errors: Vec<_> = engine
+        .check_config()
+        .into_iter()
+        .filter(|f| f.kind.is_error())
+        .collect();
+    assert!(errors.is_empty(), "Unexpected findings: {:?}", errors);
+}
+
+#[test]
+fn test_invalid_process_config_reports_all_problems() {
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(
+        ".glow/process_config.yaml",
+        r#"version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+      inputs:
+        - id: SPEC
+          type_ref: UNDECLARED
+    - id: A
+  links:
+    - type: blocks
+      from: A
+      to: A
+"#,
+    );
+
+    let error = match ProcessEngine::new(project.path().to_path_buf()) {
+        Err(GlowError::InvalidProcessConfig { diagnostics }) => diagnostics,
+        other => panic!("Expected invalid process config, got {:?}", other.err()),
+    };
+    assert_eq!(error.len(), 4, "{:?}", error);
+    assert!(error[0].ends_with("process_config.yaml:8:11: parameter 'SPEC' refers to undeclared parameter type 'UNDECLARED'"));
+    assert!(error[1].ends_with("process_config.yaml:9:7: duplicate step ID 'A'"));
+    assert!(error[2].ends_with("process_config.yaml:11:5: circular dependency: A -> A"));
+    assert!(error[3].ends_with("process_config.yaml:11:7: unknown link type 'blocks'"));
+}
+
+#[test]
+fn test_config_apply_reconciles_in_flight_iterations() {
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(".glow/process_config.yaml", r#"
+version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+    - id: B
+    - id: C
+    - id: P
+      steps:
+        - id: X
+  links:
+    - from: B
+      to: A
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.init_step("P", vec![], false).expect("Failed to init P");
+    engine.start_step("P.X").expect("Failed to start P.X");
+    engine.finish_step("P.X", vec![], None).expect("Failed to finish P.X");
+    let v1 = engine.show_step("ROOT").unwrap().attr.definition_version;
+    assert!(v1.is_some());
+
+    // Replace C by D, make B depend on D and extend the completed process P
+    project.write_file(".glow/process_config.yaml", r#"
+version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+    - id: B
+    - id: D
+    - id: P
+      steps:
+        - id: X
+        - id: Y
+  links:
+    - from: B
+      to: D
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    let plan = engine.plan_config_apply().expect("Failed to plan");
+    assert_eq!(plan.completed, vec!["P"]);
+    assert_eq!(plan.iterations.len(), 1);
+    assert_eq!(plan.iterations[0].fqid, "ROOT");
+    assert_eq!(plan.iterations[0].from_version, v1);
+    let changes: Vec<String> = plan.iterations[0].changes.iter().map(|c| c.to_string()).collect();
+    assert_eq!(
+        changes,
+        vec![
+            "~ B: relinked from A (dependency) to D (dependency)",
+            "+ D: added",
+            "- C: orphaned (data kept)",
+        ]
+    );
+
+    // The plan alone changes nothing
+    assert!(!project.file_exists("glow/D.md"));
+
+    let applied = engine.apply_config().expect("Failed to apply");
+    assert_eq!(applied.change_count(), 3);
+
+    let root = engine.show_step("ROOT").unwrap();
+    let own: Vec<&str> = root.own_steps.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(own, vec!["A", "B", "P", "D"]);
+    assert_eq!(root.attr.definition_version, Some(plan.iterations[0].to_version.clone()));
+    assert_eq!(engine.show_step("D").unwrap().status(), StepStatus::Todo);
+    assert_eq!(engine.show_step("B").unwrap().links[0].step_id, "D");
+    assert!(project.file_exists("glow/C.md"));
+
+    // Completed iterations are untouched
+    let p = engine.show_step("P").unwrap();
+    assert_eq!(p.own_steps.len(), 1);
+    assert!(!project.file_exists("glow/P/Y.md"));
+
+    assert!(engine.plan_config_apply().unwrap().is_empty());
+}
+
+#[test]
+fn test_builder_honors_directory_overrides() {
+    let project = TestProject::empty();
+    let config_dir = project.path().join("glow-config");
+    let data_dir = project.path().join("work");
+
+    let mut engine = ProcessEngine::builder(project.path())
+        .config_dir(&config_dir)
+        .data_dir(&data_dir)
+        .user_templates_dir(None)
+        .init(Some("Atlas".to_string()), None)
+        .expect("Failed to initialize project");
+    assert!(config_dir.join("config.yaml").exists());
+    assert!(!project.path().join(".glow").exists());
+
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    assert!(data_dir.join("description.md").exists());
+    assert!(!project.path().join("glow").exists());
+
+    // The defaults don't find the project elsewhere
+    assert!(ProcessEngine::new(project.path().to_path_buf()).is_err());
+    let reloaded = ProcessEngine::builder(project.path())
+        .config_dir(&config_dir)
+        .data_dir(&data_dir)
+        .build()
+        .expect("Failed to load project");
+    assert_eq!(reloaded.config().project_name.as_deref(), Some("Atlas"));
+    assert_eq!(reloaded.show_step("ROOT").unwrap().attr.id, "ROOT");
+}
+
+#[test]
+fn test_builder_clock_and_actor() {
+    let project = TestProject::with_tasktrack_config();
+    let now = chrono::Utc.with_ymd_and_hms(2030, 1, 2, 3, 4, 5).unwrap();
+
+    let mut engine = ProcessEngine::builder(project.path())
+        .clock(FixedClock(now))
+        .actor("ci-bot")
+        .build()
+        .expect("Failed to load project");
+    assert_eq!(engine.actor(), Some("ci-bot"));
+
+    let root = engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    assert_eq!(root.attr.status_since, Some(now));
+}
+
+#[test]
+fn test_memory_store_keeps_step_data_off_disk() {
+    let project = TestProject::with_tasktrack_config();
+
+    let mut engine = ProcessEngine::builder(project.path())
+        .storage(MemoryStore::new())
+        .actor("ci-bot")
+        .build()
+        .expect("Failed to load project");
+    assert_eq!(engine.storage().data_dir(), None);
+
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    let next = engine.get_next_actions().expect("Failed to get next actions");
+    let fqid = next[0].fqid.clone();
+    engine.start_step(&fqid).expect("Failed to start step");
+    engine
+        .finish_step(&fqid, vec![], Some("Done in memory".to_string()))
+        .expect("Failed to finish step");
+
+    assert_eq!(engine.show_step(&fqid).unwrap().status(), StepStatus::Done);
+    assert_eq!(
+        engine.storage().store().read_document(&fqid, "summary.md").unwrap().as_deref(),
+        Some("# Summary\n\nDone in memory\n")
+    );
+    assert!(!project.file_exists(&format!("glow/{}.md", fqid)));
+
+    let journal = engine.get_journal().expect("Failed to read journal");
+    let statuses: Vec<StepStatus> = journal
+        .iter()
+        .filter(|e| e.fqid == fqid)
+        .map(|e| e.status)
+        .collect();
+    assert_eq!(statuses, vec![StepStatus::Todo, StepStatus::InProgress, StepStatus::Done]);
+    assert!(journal.iter().all(|e| e.actor.as_deref() == Some("ci-bot")));
+}
+
+#[test]
+fn test_operations_are_atomic_and_recovered() {
+    let project = TestProject::with_tasktrack_config();
+    let mut engine = ProcessEngine::new(project.path().to_path_buf()).expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    let fqid = engine.get_next_actions().expect("Failed to get next actions")[0].fqid.clone();
+    engine.start_step(&fqid).expect("Failed to start step");
+    engine
+        .finish_step(&fqid, vec![], Some("Done".to_string()))
+        .expect("Failed to finish step");
+
+    // Completed operations leave neither an intent record nor temporary files
+    assert!(!project.file_exists(&format!("glow/{}", INTENT_FILE)));
+    let leftovers: Vec<_> = walkdir::WalkDir::new(project.path().join("glow"))
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_name().to_string_lossy().ends_with(".tmp"))
+        .collect();
+    assert!(leftovers.is_empty(), "temporary files left: {:?}", leftovers);
+
+    // A process stopped after recording its intent is completed on the next load
+    let journal = std::fs::metadata(project.path().join("glow/journal.jsonl")).unwrap().len();
+    project.write_file(
+        &format!("glow/{}", INTENT_FILE),
+        &serde_json::json!({
+            "writes": [{
+                "path": format!("{}/summary.md", fqid),
+                "before": "# Summary\n\nDone\n",
+                "after": "# Summary\n\nRecovered\n",
+            }],
+            "journal_file": "journal.jsonl",
+            "journal_len": journal,
+            "journal": [],
+        })
+        .to_string(),
+    );
+
+    let engine = ProcessEngine::new(project.path().to_path_buf()).expect("Failed to load project");
+    assert_eq!(engine.recovery(), Some(&Recovery::RolledForward { files: 1 }));
+    assert_eq!(
+        engine.storage().store().read_document(&fqid, "summary.md").unwrap().as_deref(),
+        Some("# Summary\n\nRecovered\n")
+    );
+    assert!(!project.file_exists(&format!("glow/{}", INTENT_FILE)));
+    assert_eq!(
+        std::fs::metadata(project.path().join("glow/journal.jsonl")).unwrap().len(),
+        journal
+    );
+}
+
+#[test]
+fn test_step_writes_keep_hand_edits() {
+    let project = TestProject::with_tasktrack_config();
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf()).expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    let fqid = engine.get_next_actions().expect("Failed to get next actions")[0].fqid.clone();
+    engine.start_step(&fqid).expect("Failed to start step");
+
+    // Add a key glow doesn't know at the top and replace the body in an editor
+    let file = format!("glow/{}.md", fqid);
+    let content = project.read_file(&file);
+    let frontmatter_end = content.rfind("\n---\n").unwrap() + "\n---\n".len();
+    project.write_file(
+        &file,
+        &format!("---\nreviewed_by: sam\n{}Written by hand.\n", &content[4..frontmatter_end]),
+    );
+
+    engine
+        .finish_step(&fqid, vec![], Some("Done".to_string()))
+        .expect("Failed to finish step");
+
+    let content = project.read_file(&file);
+    assert!(content.starts_with("---\nreviewed_by: sam\nattr:\n"), "{}", content);
+    assert!(content.ends_with("---\nWritten by hand.\n"));
+    assert_eq!(engine.show_step(&fqid).unwrap().status(), StepStatus::Done);
+}
+
+#[test]
+fn test_derived_statuses_follow_sub_steps() {
+    let project = TestProject::with_tasktrack_config();
+    let mut engine = ProcessEngine::new(project.path().to_path_buf()).expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.start_step("ROOT").expect("Failed to start ROOT");
+    engine.start_step("FEAT").expect("Failed to start FEAT");
+
+    // The parent's copy of the sub-step status is behind, as after a merge
+    let parent = project.read_file("glow/ROOT.md");
+    assert!(parent.contains("id: FEAT\n  status: todo"), "{}", parent);
+
+    let root = engine.show_step("ROOT").expect("Failed to show ROOT");
+    let own_step = root.own_steps.iter().find(|s| s.id == "FEAT").unwrap();
+    assert_eq!(own_step.status, StepStatus::InProgress);
+}
+
+#[test]
+fn test_move_and_remove_steps() {
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(".glow/process_config.yaml", r#"
+# Delivery process
+version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    # Gather notes first
+    - id: A   # analysis
+    - id: B
+      inputs:
+        - id: NOTES
+          mapping: 'links.A.output.NOTES'
+    - id: C
+    - id: P
+      steps:
+        - id: X
+  links:
+    - from: B
+      to: A
+    - from: C
+      to: B
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.init_step("P", vec![], false).expect("Failed to init P");
+    project.write_file("glow/A/description.md", "# A\n");
+
+    // Renaming keeps the links and mappings pointing at the step
+    let moved = engine.move_step("A", "FIRST").expect("Failed to rename A");
+    assert_eq!(moved.steps, 1);
+    assert!(moved.definition_moved);
+    assert!(!project.file_exists("glow/A.md"));
+    assert_eq!(project.read_file("glow/FIRST/description.md"), "# A\n");
+    assert_eq!(engine.show_step("FIRST").unwrap().attr.id, "FIRST");
+    assert_eq!(engine.show_step("B").unwrap().links[0].step_id, "FIRST");
+    let config = project.read_file(".glow/process_config.yaml");
+    assert!(config.contains("'links.FIRST.output.NOTES'"), "{}", config);
+
+    // Comments and formatting written by hand stay
+    assert!(config.starts_with("\n# Delivery process\nversion: \"0.1.0\"\n"), "{}", config);
+    assert!(config.contains("    # Gather notes first\n    - id: FIRST   # analysis\n"), "{}", config);
+
+    // Re-parenting drops the links with the former siblings
+    let moved = engine.move_step("C", "P.C").expect("Failed to move C");
+    assert_eq!(moved.dropped_links, vec!["C -> B"]);
+    assert!(engine.show_step("P.C").unwrap().links.is_empty());
+    let p = engine.show_step("P").unwrap();
+    assert!(p.own_steps.iter().any(|s| s.id == "C"));
+
+    let root = engine.show_step("ROOT").unwrap();
+    let own: Vec<&str> = root.own_steps.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(own, vec!["FIRST", "B", "P"]);
+    assert!(engine.plan_config_apply().unwrap().is_empty());
+
+    // A step others depend on is only removed with them
+    let err = engine.remove_step("FIRST", false).unwrap_err();
+    assert!(matches!(err, GlowError::StepHasDependents { ref dependents, .. } if dependents == &["B"]));
+    assert!(project.file_exists("glow/FIRST.md"));
+
+    let removal = engine.remove_step("FIRST", true).expect("Failed to remove FIRST");
+    assert_eq!(removal.removed, vec!["FIRST", "B"]);
+    assert!(!project.file_exists("glow/FIRST.md"));
+    assert!(!project.file_exists("glow/FIRST"));
+    assert!(!project.file_exists("glow/B.md"));
+    let root = engine.show_step("ROOT").unwrap();
+    assert_eq!(root.own_steps.len(), 1);
+    assert!(engine.plan_config_apply().unwrap().is_empty());
+
+    // A definition that can't be edited leaves the step data as it was
+    let config = project.read_file(".glow/process_config.yaml");
+    project.write_file(".glow/process_config.yaml", "root_process: [\n");
+    assert!(engine.remove_step("P", false).is_err());
+    assert!(project.file_exists("glow/P.md"));
+    assert!(project.file_exists("glow/P/X.md"));
+    project.write_file(".glow/process_config.yaml", &config);
+
+    // Sub-steps are journaled with the step they are removed with
+    engine.remove_step("P", false).expect("Failed to remove P");
+    assert!(!project.file_exists("glow/P.md"));
+
+    let journal: Vec<String> = engine
+        .get_journal()
+        .unwrap()
+        .iter()
+        .filter(|e| e.change.is_some())
+        .map(|e| e.to_string()[17..].to_string())
+        .collect();
+    assert_eq!(
+        journal,
+        vec!["A moved to FIRST", "C moved to P.C", "FIRST removed", "B removed", "P removed", "P.C removed", "P.X removed"]
+    );
+}
+
+#[test]
+fn test_archive_and_restore_iteration() {
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(".glow/process_config.yaml", r#"
+version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: P
+      steps:
+        - id: X
+        - id: Y
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.init_step("P", vec![], false).expect("Failed to init P");
+    engine.start_step("P.X").expect("Failed to start P.X");
+    engine.finish_step("P.X", vec![], None).expect("Failed to finish P.X");
+    let logo = vec![0x89, 0x50, 0x4e, 0x47, 0x00, 0xff];
+    engine.storage().store().write_artifact("P.X", "logo.png", &logo).unwrap();
+    engine.close_iteration("P", None).expect("Failed to close iteration");
+    let progress = engine.get_progress().expect("Failed to get progress");
+
+    // The open iteration can't be archived yet
+    assert!(engine.archive_iteration("P", 2).is_err());
+
+    // An aborted archive leaves no archive behind
+    let store = engine.storage().store();
+    store.begin().unwrap();
+    store.archive_iteration("P", 1, &["P.X".to_string()], chrono::Utc::now()).unwrap();
+    store.abort();
+    assert!(!project.file_exists("glow/P/iteration_000001/archive.tar.gz"));
+    assert!(project.file_exists("glow/P/X.md"));
+
+    let stub = engine.archive_iteration("P", 1).expect("Failed to archive");
+    assert_eq!(stub.steps.len(), 1);
+    assert_eq!(stub.files, 4);
+    assert!(!project.file_exists("glow/P/X.md"));
+    assert!(!project.file_exists("glow/P/X"));
+    assert!(!project.file_exists("glow/P/iteration_000001/summary.md"));
+    assert!(project.file_exists("glow/P/iteration_000001/archive.tar.gz"));
+    assert!(project.file_exists("glow/P/Y.md"));
+
+    // Archived work still counts
+    let archived = engine.get_progress().expect("Failed to get progress");
+    assert_eq!((archived.total, archived.done), (progress.total, progress.done));
+    let p = engine.show_step("P").unwrap();
+    assert_eq!(p.own_steps[0].status, StepStatus::Done);
+    assert!(engine.verify_archive("P", 1).unwrap().is_empty());
+    assert_eq!(engine.archived_iterations("P").unwrap().len(), 1);
+
+    // Archives keep the FQIDs and paths their steps had, so the process stays in place
+    let err = engine.move_step("P", "Q").unwrap_err();
+    assert!(err.to_string().contains("restore them before moving it"), "{}", err);
+    assert!(project.file_exists("glow/P/iteration_000001/archived.json"));
+    assert!(!project.file_exists("glow/Q"));
+
+    let restored = engine.restore_iteration("P", 1).expect("Failed to restore");
+    assert_eq!(restored.steps.len(), 1);
+    assert_eq!(engine.show_step("P.X").unwrap().status(), StepStatus::Done);
+    assert_eq!(engine.storage().store().read_artifact("P.X", "logo.png").unwrap(), logo);
+
+    // Once restored, the process moves with the restored steps
+    engine.move_step("P", "Q").expect("Failed to move P");
+    assert_eq!(engine.show_step("Q.X").unwrap().status(), StepStatus::Done);
+    assert_eq!(engine.storage().store().read_artifact("Q.X", "logo.png").unwrap(), logo);
+    engine.move_step("Q", "P").expect("Failed to move Q back");
+    assert!(project.file_exists("glow/P/iteration_000001/summary.md"));
+    assert!(!project.file_exists("glow/P/iteration_000001/archived.json"));
+    assert!(!project.file_exists("glow/P/iteration_000001/archive.tar.gz"));
+
+    // A damaged archive is reported and not restored
+    engine.archive_iteration("P", 1).expect("Failed to archive again");
+    let archive_path = project.path().join("glow/P/iteration_000001/archive.tar.gz");
+    let mut archive = std::fs::read(&archive_path).unwrap();
+    let middle = archive.len() / 2;
+    archive[middle] ^= 0xff;
+    std::fs::write(&archive_path, archive).unwrap();
+    assert!(!engine.verify_archive("P", 1).unwrap().is_empty());
+    assert!(matches!(
+        engine.restore_iteration("P", 1),
+        Err(GlowError::ArchiveCorrupt { .. })
+    ));
+    assert!(!project.file_exists("glow/P/X.md"));
+}
+
+#[test]
+fn test_carried_over_step_starts_after_dependency_is_archived() {
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(".glow/process_config.yaml", r#"
+version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: P
+      steps:
+        - id: X
+        - id: Y
+      links:
+        - type: dependency
+          from: Y
+          to: X
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    engine.init_step("P", vec![], false).expect("Failed to init P");
+    engine.start_step("P.X").expect("Failed to start P.X");
+    engine.finish_step("P.X", vec![], None).expect("Failed to finish P.X");
+    engine.close_iteration("P", None).expect("Failed to close iteration");
+    engine.archive_iteration("P", 1).expect("Failed to archive");
+    assert!(!project.file_exists("glow/P/X.md"));
+
+    // The archived dependency is read from the archive's stub
+    assert_eq!(engine.show_step("P.X").unwrap().status(), StepStatus::Done);
+    assert!(engine.plan_config_apply().unwrap().is_empty());
+    engine.start_step("P.Y").expect("Failed to start P.Y after archiving P.X");
+    assert_eq!(engine.show_step("P.Y").unwrap().status(), StepStatus::InProgress);
+}
+
+#[test]
+fn test_snapshot_diff_and_restore() {
+    use glow_core::model::ParameterValue;
+    use glow_core::storage::StepDiffKind;
+
+    let project = TestProject::with_tasktrack_config();
+    project.write_file(".glow/process_config.yaml", r#"
+version: "0.1.0"
+root_process:
+  id: ROOT
+  steps:
+    - id: A
+      outputs:
+        - id: DOC
+    - id: P
+      steps:
+        - id: X
+"#);
+
+    let mut engine = ProcessEngine::new(project.path().to_path_buf())
+        .expect("Failed to load project");
+    engine.init_step("ROOT", vec![], false).expect("Failed to init ROOT");
+    let monday = engine.create_snapshot("monday").expect("Failed to create snapshot");
+    assert!(monday.id.ends_with("-monday"));
+    assert!(project.file_exists(&format!(".glow/snapshots/{}.json", monday.id)));
+
+    engine.start_step("A").expect("Failed to start A");
+    engine
+        .finish_step("A", vec![ParameterValue::new("DOC", "docs/a.md".into())], None)
+        .expect("Failed to finish A");
+    engine.init_step("P", vec![], false).expect("Failed to init P");
+
+    // Snapshots are found by ID or label, and compared with the current data
+    let diff = engine.diff_snapshots("monday", "now").expect("Failed to diff");
+    assert_eq!(diff.to, "now");
+    let a = diff.steps.iter().find(|s| s.fqid == "A").expect("A changed");
+    assert_eq!((a.status_from, a.status_to), (Some(StepStatus::Todo), Some(StepStatus::Done)));
+    assert_eq!(a.parameters[0].id, "DOC");
+    assert_eq!(a.parameters[0].to, Some("docs/a.md".into()));
+    assert!(diff.steps.iter().any(|s| s.fqid == "P.X" && s.kind == StepDiffKind::Added));
+    assert!(engine.diff_snapshots(&monday.id, "monday").unwrap().is_empty());
+    assert!(matches!(
+        engine.diff_snapshots("tuesday", "now"),
+        Err(GlowError::SnapshotNotFound { .. })
+    ));
+
+    // Steps the restore would remove keep their artifacts unless forced
+    engine.storage().store().write_artifact("P.X", "notes.txt", b"draft").unwrap();
+    assert!(matches!(
+        engine.restore_snapshot("monday", false),
+        Err(GlowError::RestoreRemovesWork { ref steps, .. }) if steps == &["P.X"]
+    ));
+    assert_eq!(engine.list_snapshots().unwrap().len(), 1);
+
+    // Restoring puts the data back and keeps what it replaced
+    let restore = engine.restore_snapshot("monday", true).expect("Failed to restore");
+    assert_eq!(restore.snapshot, monday);
+    assert!(restore.changes.steps.iter().any(|s| s.fqid == "P.X" && s.kind == StepDiffKind::Removed));
+    assert_eq!(engine.show_step("A").unwrap().status(), StepStatus::Todo);
+    assert!(!project.file_exists("glow/P/X.md"));
+    assert!(engine.diff_snapshots("monday", "now").unwrap().is_empty());
+
+    let snapshots = engine.list_snapshots().unwrap();
+    assert_eq!(snapshots.len(), 2);
+    assert_eq!(snapshots[1], restore.backup);
+    let undo = engine.diff_snapshots("now", &restore.backup.id).unwrap();
+    assert!(undo.steps.iter().any(|s| s.fqid == "P.X" && s.kind == StepDiffKind::Added));
+}
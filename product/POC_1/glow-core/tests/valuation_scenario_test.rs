@@ -252,7 +252,7 @@ fn test_phase3_feat001_complete_workflow() {
     println!("FEAT-001 initial status: {:?}", feat001.status());
 
     // Init FEAT-001 with scope parameter
-    let feat001 = engine
+    let _feat001 = engine
         .init_step(
             "FEAT-001",
             vec![ParameterValue::new(
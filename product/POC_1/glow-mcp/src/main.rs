@@ -14,6 +14,7 @@ use std::sync::Arc;
 use anyhow::Result;
 use clap::Parser;
 use glow_core::engine::operations::ProcessEngine;
+use glow_core::engine::ProcessEngineBuilder;
 use tokio::sync::RwLock;
 
 /// MCP Server for Dev-Glow
@@ -25,6 +26,10 @@ struct Args {
     #[arg(long, default_value = ".")]
     project_dir: PathBuf,
 
+    /// Configuration directory path (overrides default .glow/)
+    #[arg(long, env = "DEV_GLOW_CONFIG_DIR")]
+    config_dir: Option<PathBuf>,
+
     /// Enable verbose logging
     #[arg(short, long)]
     verbose: bool,
@@ -34,20 +39,35 @@ struct Args {
 pub struct ServerState {
     pub engine: RwLock<Option<ProcessEngine>>,
     pub project_dir: PathBuf,
+    pub config_dir: Option<PathBuf>,
 }
 
 impl ServerState {
-    pub fn new(project_dir: PathBuf) -> Self {
+    pub fn new(project_dir: PathBuf, config_dir: Option<PathBuf>) -> Self {
         Self {
             engine: RwLock::new(None),
             project_dir,
+            config_dir,
+        }
+    }
+
+    /// Start building the project's engine with the server's overrides
+    pub fn engine_builder(&self) -> ProcessEngineBuilder {
+        let builder = ProcessEngine::builder(&self.project_dir);
+        match &self.config_dir {
+            Some(config_dir) => builder.config_dir(config_dir),
+            None => builder,
         }
     }
 
     pub async fn ensure_engine(&self) -> Result<()> {
         let mut engine = self.engine.write().await;
         if engine.is_none() {
-            *engine = Some(ProcessEngine::new(self.project_dir.clone())?);
+            let loaded = self.engine_builder().build()?;
+            if let Some(recovery) = loaded.recovery() {
+                tracing::warn!("{}", recovery);
+            }
+            *engine = Some(loaded);
         }
         Ok(())
     }
@@ -73,7 +93,7 @@ fn main() -> Result<()> {
     let rt = tokio::runtime::Runtime::new()?;
     rt.block_on(async {
         let project_dir = std::fs::canonicalize(&args.project_dir)?;
-        let state = Arc::new(ServerState::new(project_dir));
+        let state = Arc::new(ServerState::new(project_dir, args.config_dir));
 
         // Run the stdio-based JSON-RPC server
         run_stdio_server(state).await
@@ -5,10 +5,16 @@
 //! - glow://step/{fqid} - Specific step context
 //! - glow://next - Recommended next actions
 //! - glow://progress - Progress metrics
+//! - glow://stale - Stale steps with suggested remediations
+//! - glow://config/diagnostics - Process config problems with source positions
+//! - glow://snapshots - Saved snapshots of the step data
+//! - glow://diff/{snapshot}[/{snapshot}] - Changes since a snapshot, or between two
 
 use std::sync::Arc;
 
 use glow_core::engine::operations::{ActionType, ProcessEngine};
+use glow_core::engine::StaleKind;
+use glow_core::storage::CURRENT_DATA;
 use serde_json::{json, Value};
 
 use crate::protocol::{Resource, ResourceContent, RpcError};
@@ -32,13 +38,31 @@ pub async fn list_resources(state: &Arc<ServerState>) -> Result<Value, RpcError>
         Resource {
             uri: "glow://progress".to_string(),
             name: "Progress Metrics".to_string(),
-            description: "Overall progress metrics".to_string(),
+            description: "Overall progress metrics and completion forecast".to_string(),
+            mime_type: "application/json".to_string(),
+        },
+        Resource {
+            uri: "glow://stale".to_string(),
+            name: "Stale Work".to_string(),
+            description: "Steps stuck in progress, waiting or untouched, with suggested remediations".to_string(),
+            mime_type: "application/json".to_string(),
+        },
+        Resource {
+            uri: "glow://config/diagnostics".to_string(),
+            name: "Config Diagnostics".to_string(),
+            description: "Process config problems with file, line and column".to_string(),
+            mime_type: "application/json".to_string(),
+        },
+        Resource {
+            uri: "glow://snapshots".to_string(),
+            name: "Snapshots".to_string(),
+            description: "Saved snapshots of the step data, oldest first".to_string(),
             mime_type: "application/json".to_string(),
         },
     ];
 
     // Add dynamic step resources if engine is loaded
-    if let Err(_) = state.ensure_engine().await {
+    if state.ensure_engine().await.is_err() {
         return Ok(json!({ "resources": resources }));
     }
 
@@ -47,6 +71,17 @@ pub async fn list_resources(state: &Arc<ServerState>) -> Result<Value, RpcError>
         if let Ok(status) = engine.get_status_tree() {
             add_step_resources(&mut resources, &status, "".to_string());
         }
+        if let Ok(snapshots) = engine.list_snapshots() {
+            resources.extend(snapshots.into_iter().map(|snapshot| Resource {
+                uri: format!("glow://diff/{}", snapshot.id),
+                name: format!("Changes since: {}", snapshot.label),
+                description: format!(
+                    "Status changes, new and removed steps and changed parameter values since {}",
+                    snapshot.created_at.format("%Y-%m-%d %H:%M")
+                ),
+                mime_type: "application/json".to_string(),
+            }));
+        }
     }
 
     Ok(json!({ "resources": resources }))
@@ -86,6 +121,12 @@ pub async fn read_resource(state: &Arc<ServerState>, params: &Value) -> Result<V
             data: None,
         })?;
 
+    // Diagnostics must be readable when the config prevents loading
+    if uri == "glow://config/diagnostics" {
+        let content = read_config_diagnostics(state)?;
+        return Ok(json!({ "contents": [content] }));
+    }
+
     // Ensure engine is loaded
     state.ensure_engine().await.map_err(|e| RpcError {
         code: -32603,
@@ -106,6 +147,13 @@ pub async fn read_resource(state: &Arc<ServerState>, params: &Value) -> Result<V
         read_next_actions(engine)?
     } else if uri == "glow://progress" {
         read_progress(engine)?
+    } else if uri == "glow://stale" {
+        read_stale_work(engine)?
+    } else if uri == "glow://snapshots" {
+        read_snapshots(engine)?
+    } else if let Some(snapshots) = uri.strip_prefix("glow://diff/") {
+        let (from, to) = snapshots.split_once('/').unwrap_or((snapshots, CURRENT_DATA));
+        read_diff(engine, uri, from, to)?
     } else if uri.starts_with("glow://step/") {
         let fqid = uri.strip_prefix("glow://step/").unwrap();
         read_step(engine, fqid)?
@@ -167,7 +215,9 @@ fn read_next_actions(engine: &ProcessEngine) -> Result<ResourceContent, RpcError
                     ActionType::Review => "review",
                     ActionType::Finish => "finish",
                 },
-                "description": a.description
+                "description": a.description,
+                "score": a.score,
+                "why": a.why
             })
         })
         .collect();
@@ -192,13 +242,35 @@ fn read_progress(engine: &ProcessEngine) -> Result<ResourceContent, RpcError> {
         data: None,
     })?;
 
+    let forecasts = engine.get_forecast().map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
     let json = serde_json::to_string_pretty(&json!({
         "total": progress.total,
         "wait": progress.wait,
         "todo": progress.todo,
         "in_progress": progress.in_progress,
         "done": progress.done,
-        "completion_percentage": progress.completion_percentage()
+        "leaves": progress.leaves,
+        "estimated": progress.estimated,
+        "total_weight": progress.total_weight,
+        "remaining_weight": progress.remaining_weight(),
+        "completion_percentage": progress.completion_percentage(),
+        "warning": progress.warning,
+        "forecast": forecasts.iter().map(|f| {
+            json!({
+                "fqid": f.fqid,
+                "iteration": f.iteration,
+                "total_weight": f.total_weight,
+                "remaining_weight": f.remaining_weight,
+                "expected": f.expected.map(|d| d.to_rfc3339()),
+                "earliest": f.earliest.map(|d| d.to_rfc3339()),
+                "latest": f.latest.map(|d| d.to_rfc3339())
+            })
+        }).collect::<Vec<_>>()
     }))
     .map_err(|e| RpcError {
         code: -32603,
@@ -213,6 +285,144 @@ fn read_progress(engine: &ProcessEngine) -> Result<ResourceContent, RpcError> {
     })
 }
 
+fn read_stale_work(engine: &ProcessEngine) -> Result<ResourceContent, RpcError> {
+    let stale = engine.get_stale_work().map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    let stale_json: Vec<Value> = stale
+        .iter()
+        .map(|s| {
+            let (kind, dependencies) = match &s.kind {
+                StaleKind::InProgressIdle => ("in_progress_idle", Vec::new()),
+                StaleKind::WaitingOnUninitialized { dependencies } => {
+                    ("waiting_on_uninitialized", dependencies.clone())
+                }
+                StaleKind::TodoUntouched => ("todo_untouched", Vec::new()),
+            };
+            json!({
+                "fqid": s.fqid,
+                "kind": kind,
+                "dependencies": dependencies,
+                "days": s.days,
+                "threshold_days": s.threshold,
+                "remediation": s.remediation
+            })
+        })
+        .collect();
+
+    let json = serde_json::to_string_pretty(&stale_json).map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    Ok(ResourceContent {
+        uri: "glow://stale".to_string(),
+        mime_type: "application/json".to_string(),
+        text: json,
+    })
+}
+
+fn read_snapshots(engine: &ProcessEngine) -> Result<ResourceContent, RpcError> {
+    let snapshots = engine.list_snapshots().map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    let json = serde_json::to_string_pretty(&snapshots).map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    Ok(ResourceContent {
+        uri: "glow://snapshots".to_string(),
+        mime_type: "application/json".to_string(),
+        text: json,
+    })
+}
+
+fn read_diff(engine: &ProcessEngine, uri: &str, from: &str, to: &str) -> Result<ResourceContent, RpcError> {
+    let diff = engine.diff_snapshots(from, to).map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    let json = serde_json::to_string_pretty(&diff).map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    Ok(ResourceContent {
+        uri: uri.to_string(),
+        mime_type: "application/json".to_string(),
+        text: json,
+    })
+}
+
+fn read_config_diagnostics(state: &ServerState) -> Result<ResourceContent, RpcError> {
+    let check = state.engine_builder().check().map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    let diagnostics_json: Vec<Value> = check
+        .diagnostics
+        .iter()
+        .map(|d| {
+            json!({
+                "file": d.file.display().to_string(),
+                "line": d.position.line,
+                "column": d.position.column,
+                "path": d.path,
+                "message": d.message
+            })
+        })
+        .collect();
+
+    let findings_json: Vec<Value> = check
+        .findings
+        .iter()
+        .map(|f| {
+            let position = check.finding_position(f);
+            json!({
+                "kind": f.kind.to_string(),
+                "error": f.kind.is_error(),
+                "subject": f.subject,
+                "file": check.file.display().to_string(),
+                "line": position.line,
+                "column": position.column,
+                "path": f.location,
+                "message": f.message
+            })
+        })
+        .collect();
+
+    let json = serde_json::to_string_pretty(&json!({
+        "valid": !check.has_errors(),
+        "diagnostics": diagnostics_json,
+        "findings": findings_json
+    }))
+    .map_err(|e| RpcError {
+        code: -32603,
+        message: e.to_string(),
+        data: None,
+    })?;
+
+    Ok(ResourceContent {
+        uri: "glow://config/diagnostics".to_string(),
+        mime_type: "application/json".to_string(),
+        text: json,
+    })
+}
+
 fn read_step(engine: &ProcessEngine, fqid: &str) -> Result<ResourceContent, RpcError> {
     let step = engine.show_step(fqid).map_err(|e| RpcError {
         code: -32603,
@@ -225,6 +435,13 @@ fn read_step(engine: &ProcessEngine, fqid: &str) -> Result<ResourceContent, RpcE
         "fqid": step.fqid(),
         "status": format!("{:?}", step.status()),
         "classification": step.attr.classification,
+        "estimate": step.attr.estimate.as_ref().map(|e| e.to_string()),
+        "started_at": step.attr.started_at.map(|d| d.to_rfc3339()),
+        "finished_at": step.attr.finished_at.map(|d| d.to_rfc3339()),
+        "due": step.attr.due.map(|d| d.to_string()),
+        "iteration": step.attr.iteration,
+        "timebox": step.attr.timebox,
+        "carried_over": step.attr.carried_over,
         "purpose": step.attr.purpose,
         "expectations": step.attr.expectations,
         "input": step.input.iter().map(|p| {
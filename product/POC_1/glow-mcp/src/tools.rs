@@ -8,10 +8,12 @@
 //! - glow_finish_step - Finish a step
 //! - glow_progress - Get progress metrics
 //! - glow_validate - Validate context quality
+//! - glow_config_check - Check the process definition
 
 use std::sync::Arc;
 
 use glow_core::engine::operations::ActionType;
+use glow_core::engine::{Overdue, StaleWork, StartOptions};
 use glow_core::model::ParameterValue;
 use serde_json::{json, Value};
 
@@ -32,10 +34,16 @@ pub async fn list_tools() -> Result<Value, RpcError> {
         },
         Tool {
             name: "glow_next".to_string(),
-            description: "Get recommended next actions based on current project state".to_string(),
+            description: "Get recommended next actions ranked by priority, critical path, age, due date and unblocked dependents".to_string(),
             input_schema: json!({
                 "type": "object",
-                "properties": {}
+                "properties": {
+                    "limit": {
+                        "type": "integer",
+                        "minimum": 1,
+                        "description": "Return only the top N actions"
+                    }
+                }
             }),
         },
         Tool {
@@ -55,7 +63,7 @@ pub async fn list_tools() -> Result<Value, RpcError> {
         },
         Tool {
             name: "glow_start_step".to_string(),
-            description: "Start working on a step, transitioning it to in-progress state"
+            description: "Start working on a step, transitioning it to in-progress state. Refused when a work-in-progress limit would be exceeded"
                 .to_string(),
             input_schema: json!({
                 "type": "object",
@@ -63,6 +71,11 @@ pub async fn list_tools() -> Result<Value, RpcError> {
                     "fqid": {
                         "type": "string",
                         "description": "Step FQID to start"
+                    },
+                    "override_wip_limit": {
+                        "type": "boolean",
+                        "description": "Start even if a work-in-progress limit would be exceeded",
+                        "default": false
                     }
                 },
                 "required": ["fqid"]
@@ -92,7 +105,8 @@ pub async fn list_tools() -> Result<Value, RpcError> {
         },
         Tool {
             name: "glow_progress".to_string(),
-            description: "Get progress metrics for the project".to_string(),
+            description: "Get progress metrics and completion forecast for the project"
+                .to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {}
@@ -111,6 +125,15 @@ pub async fn list_tools() -> Result<Value, RpcError> {
                 }
             }),
         },
+        Tool {
+            name: "glow_config_check".to_string(),
+            description: "Check the process definition and report problems with file, line and column"
+                .to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": {}
+            }),
+        },
     ];
 
     Ok(json!({ "tools": tools }))
@@ -129,6 +152,15 @@ pub async fn call_tool(state: &Arc<ServerState>, params: &Value) -> Result<Value
 
     let arguments = params.get("arguments").cloned().unwrap_or(json!({}));
 
+    // Config problems must be reportable when they prevent loading
+    if name == "glow_config_check" {
+        return serde_json::to_value(tool_config_check(state)).map_err(|e| RpcError {
+            code: -32603,
+            message: e.to_string(),
+            data: None,
+        });
+    }
+
     // Ensure engine is loaded
     state.ensure_engine().await.map_err(|e| RpcError {
         code: -32603,
@@ -138,7 +170,7 @@ pub async fn call_tool(state: &Arc<ServerState>, params: &Value) -> Result<Value
 
     let result = match name {
         "glow_status" => tool_status(state).await,
-        "glow_next" => tool_next(state).await,
+        "glow_next" => tool_next(state, &arguments).await,
         "glow_show_step" => tool_show_step(state, &arguments).await,
         "glow_start_step" => tool_start_step(state, &arguments).await,
         "glow_finish_step" => tool_finish_step(state, &arguments).await,
@@ -154,6 +186,42 @@ pub async fn call_tool(state: &Arc<ServerState>, params: &Value) -> Result<Value
     })
 }
 
+fn tool_config_check(state: &ServerState) -> ToolResult {
+    let check = match state.engine_builder().check() {
+        Ok(check) => check,
+        Err(e) => return ToolResult::error(e.to_string()),
+    };
+
+    if check.diagnostics.is_empty() && check.findings.is_empty() {
+        return ToolResult::text(
+            "Process definition is consistent: every step can be reached".to_string(),
+        );
+    }
+
+    let mut output = String::new();
+    for diagnostic in &check.diagnostics {
+        output.push_str(&format!("[error] {}\n", diagnostic));
+    }
+    for finding in &check.findings {
+        let position = check.finding_position(finding);
+        output.push_str(&format!(
+            "[{}] {}:{}:{}: {}: {}\n",
+            finding.kind,
+            check.file.display(),
+            position.line,
+            position.column,
+            finding.location,
+            finding.message
+        ));
+    }
+
+    if check.has_errors() {
+        ToolResult::error(output)
+    } else {
+        ToolResult::text(output)
+    }
+}
+
 async fn tool_status(state: &Arc<ServerState>) -> ToolResult {
     let engine = state.engine.read().await;
     let engine = match engine.as_ref() {
@@ -163,13 +231,42 @@ async fn tool_status(state: &Arc<ServerState>) -> ToolResult {
 
     match engine.get_status_tree() {
         Ok(status) => {
-            let text = format_status_tree(&status, "");
+            let mut text = format_status_tree(&status, "");
+            if let Ok(overdue) = engine.get_overdue() {
+                text.push_str(&format_overdue(&overdue));
+            }
             ToolResult::text(text)
         }
         Err(e) => ToolResult::error(e.to_string()),
     }
 }
 
+/// Format overdue steps and iterations as a warning section
+fn format_overdue(overdue: &[Overdue]) -> String {
+    if overdue.is_empty() {
+        return String::new();
+    }
+
+    let mut text = "\nOverdue:\n".to_string();
+    for item in overdue {
+        text.push_str(&format!("⚠ {}\n", item));
+    }
+    text
+}
+
+/// Format stale steps with their remediations as a warning section
+fn format_stale(stale: &[StaleWork]) -> String {
+    if stale.is_empty() {
+        return String::new();
+    }
+
+    let mut text = "\nStale:\n".to_string();
+    for item in stale {
+        text.push_str(&format!("⚠ {}\n", item));
+    }
+    text
+}
+
 fn format_status_tree(tree: &glow_core::engine::operations::StatusTree, indent: &str) -> String {
     let status_icon = match tree.status {
         glow_core::model::StepStatus::Wait => "○",
@@ -192,29 +289,51 @@ fn format_status_tree(tree: &glow_core::engine::operations::StatusTree, indent:
     result
 }
 
-async fn tool_next(state: &Arc<ServerState>) -> ToolResult {
+async fn tool_next(state: &Arc<ServerState>, args: &Value) -> ToolResult {
+    let limit = args
+        .get("limit")
+        .and_then(|v| v.as_u64())
+        .map(|n| n as usize)
+        .unwrap_or(usize::MAX);
+
     let engine = state.engine.read().await;
     let engine = match engine.as_ref() {
         Some(e) => e,
         None => return ToolResult::error("Engine not initialized".to_string()),
     };
 
+    let mut warnings = match engine.get_overdue() {
+        Ok(overdue) => format_overdue(&overdue),
+        Err(e) => return ToolResult::error(e.to_string()),
+    };
+    match engine.get_stale_work() {
+        Ok(stale) => warnings.push_str(&format_stale(&stale)),
+        Err(e) => return ToolResult::error(e.to_string()),
+    };
+
     match engine.get_next_actions() {
         Ok(actions) => {
             if actions.is_empty() {
-                return ToolResult::text("No pending actions. All steps are complete or waiting.".to_string());
+                return ToolResult::text(format!(
+                    "No pending actions. All steps are complete or waiting.\n{}",
+                    warnings
+                ));
             }
 
             let mut text = "Recommended Next Actions:\n\n".to_string();
-            for action in &actions {
+            for action in actions.iter().take(limit) {
                 let cmd = match action.action_type {
                     ActionType::Init => format!("glow init {}", action.fqid),
                     ActionType::Start => format!("glow start {}", action.fqid),
                     ActionType::Review => format!("glow show {}", action.fqid),
                     ActionType::Finish => format!("glow finish {}", action.fqid),
                 };
-                text.push_str(&format!("→ {}\n  Command: {}\n\n", action.description, cmd));
+                text.push_str(&format!(
+                    "→ {} (score {:.1})\n  Why: {}\n  Command: {}\n\n",
+                    action.description, action.score, action.why, cmd
+                ));
             }
+            text.push_str(&warnings);
             ToolResult::text(text)
         }
         Err(e) => ToolResult::error(e.to_string()),
@@ -301,13 +420,20 @@ async fn tool_start_step(state: &Arc<ServerState>, args: &Value) -> ToolResult {
         None => return ToolResult::error("Missing 'fqid' argument".to_string()),
     };
 
+    let options = StartOptions {
+        override_wip_limits: args
+            .get("override_wip_limit")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false),
+    };
+
     let mut engine = state.engine.write().await;
     let engine = match engine.as_mut() {
         Some(e) => e,
         None => return ToolResult::error("Engine not initialized".to_string()),
     };
 
-    match engine.start_step(fqid) {
+    match engine.start_step_with(fqid, options) {
         Ok(step) => {
             let mut text = format!("Started step: {}\n\n", fqid);
 
@@ -379,7 +505,7 @@ async fn tool_progress(state: &Arc<ServerState>) -> ToolResult {
         Ok(progress) => {
             let pct = progress.completion_percentage();
             let mut text = "Progress Report\n".to_string();
-            text.push_str(&format!("═══════════════════════\n\n"));
+            text.push_str("═══════════════════════\n\n");
             text.push_str(&format!("Completion: {:.1}%\n\n", pct));
             text.push_str(&format!("Total Steps: {}\n", progress.total));
             text.push_str(&format!("  ● Done: {}\n", progress.done));
@@ -387,6 +513,38 @@ async fn tool_progress(state: &Arc<ServerState>) -> ToolResult {
             text.push_str(&format!("  ◐ Todo: {}\n", progress.todo));
             text.push_str(&format!("  ○ Wait: {}\n", progress.wait));
 
+            if progress.estimated > 0 {
+                text.push_str(&format!(
+                    "\nWeighted by estimate: {} of {} leaf steps estimated, {:.1} remaining\n",
+                    progress.estimated,
+                    progress.leaves,
+                    progress.remaining_weight()
+                ));
+            }
+            if let Some(warning) = &progress.warning {
+                text.push_str(&format!("\nWarning: {}\n", warning));
+            }
+
+            if let Ok(forecasts) = engine.get_forecast() {
+                text.push_str("\nForecast:\n");
+                for forecast in &forecasts {
+                    let projection = match (forecast.expected, forecast.earliest, forecast.latest) {
+                        _ if forecast.is_complete() => "complete".to_string(),
+                        (Some(expected), Some(earliest), Some(latest)) => format!(
+                            "{} (range {} – {})",
+                            expected.format("%Y-%m-%d"),
+                            earliest.format("%Y-%m-%d"),
+                            latest.format("%Y-%m-%d")
+                        ),
+                        _ => "no throughput history yet".to_string(),
+                    };
+                    text.push_str(&format!(
+                        "  {}: {:.1}/{:.1} remaining → {}\n",
+                        forecast.fqid, forecast.remaining_weight, forecast.total_weight, projection
+                    ));
+                }
+            }
+
             ToolResult::text(text)
         }
         Err(e) => ToolResult::error(e.to_string()),
@@ -405,7 +563,7 @@ async fn tool_validate(state: &Arc<ServerState>, args: &Value) -> ToolResult {
     match engine.validate(fqid) {
         Ok(report) => {
             let mut text = "Context Quality Report\n".to_string();
-            text.push_str(&format!("═══════════════════════\n\n"));
+            text.push_str("═══════════════════════\n\n");
 
             if report.is_valid {
                 text.push_str(&format!("✓ Completeness: {:.1}%\n", report.completeness));
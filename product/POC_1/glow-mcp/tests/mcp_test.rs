@@ -19,7 +19,7 @@ fn setup_test_project() -> TempDir {
 
     // Write config
     let config = r#"
-version: "1.0"
+version: "0.1.0"
 project_id: "TEST"
 project_name: "Test Project"
 process: "simple"
@@ -29,7 +29,7 @@ glow_dir: "glow"
 
     // Write process config
     let process_config = r#"
-version: "1.0"
+version: "0.1.0"
 
 root_process:
   id: ROOT
@@ -460,6 +460,43 @@ mod engine_integration_tests {
         let progress = engine.get_progress();
         assert!(progress.is_ok());
     }
+
+    /// Test engine snapshot diff for MCP resources
+    #[test]
+    fn test_engine_snapshot_diff() {
+        let project = setup_test_project();
+        let mut engine = ProcessEngine::new(project.path().to_path_buf()).unwrap();
+
+        engine.init_step("ROOT", vec![], false).unwrap();
+        let snapshot = engine.create_snapshot("monday").unwrap();
+        assert_eq!(engine.list_snapshots().unwrap(), vec![snapshot.clone()]);
+
+        // Read as glow://diff/{snapshot}, compared with the current data
+        let diff = engine.diff_snapshots(&snapshot.id, "now").unwrap();
+        let json = serde_json::to_value(&diff).unwrap();
+        assert_eq!(json["from"], snapshot.id);
+        assert_eq!(json["steps"], serde_json::json!([]));
+    }
+
+    /// Test config diagnostics are available when the engine cannot load
+    #[test]
+    fn test_engine_config_diagnostics() {
+        let project = setup_test_project();
+        let check = ProcessEngine::check_project(project.path().to_path_buf()).unwrap();
+        assert!(!check.has_errors());
+
+        fs::write(
+            project.path().join(".glow/process_config.yaml"),
+            "version: \"0.1.0\"\nroot_process:\n  id: ROOT\n  steps:\n    - id: A\n    - id: A\n",
+        )
+        .unwrap();
+
+        assert!(ProcessEngine::new(project.path().to_path_buf()).is_err());
+        let check = ProcessEngine::check_project(project.path().to_path_buf()).unwrap();
+        assert_eq!(check.diagnostics.len(), 1);
+        assert_eq!(check.diagnostics[0].position.line, 6);
+        assert_eq!(check.diagnostics[0].path, "root_process.steps[1].id");
+    }
 }
 
 mod error_code_tests {